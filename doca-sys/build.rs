@@ -2,6 +2,11 @@ use std::env::{self, consts};
 use std::path::{Path, PathBuf};
 
 fn main() {
+    // The mock implements the functions in Rust, so it neither links nor binds DOCA
+    if env::var("CARGO_FEATURE_MOCK").is_ok() {
+        return;
+    }
+
     let arch = consts::ARCH;
     println!(
         "cargo:rustc-link-search=native=/opt/mellanox/doca/lib/{}-linux-gnu",
@@ -23,15 +28,22 @@ fn main() {
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
 
     // generate bindings based on the wrapper header
-    let bindings = bindgen::Builder::default()
+    let builder = bindgen::Builder::default()
         .header("wrapper.h")
         .clang_arg("-I/opt/mellanox/doca/include")
         .generate_comments(false)
+        // DOCA_ERROR part
+        .whitelist_function("doca_get_error_.*")
         .whitelist_function("doca_dev_.*")
         .whitelist_function("doca_devinfo_.*")
         // DOCA_DEV part
         .whitelist_type("doca_dev")
         .whitelist_type("doca_devinfo")
+        // the link state, the firmware version and the maximum MTU of a device
+        .whitelist_function("doca_devinfo_get_link_state")
+        .whitelist_function("doca_devinfo_get_fw_version")
+        .whitelist_function("doca_devinfo_get_max_mtu")
+        .whitelist_type("doca_devinfo_link_state")
         // DOCA_MMAP part
         .whitelist_function("doca_mmap_.*")
         .whitelist_type("doca_mmap")
@@ -39,27 +51,76 @@ fn main() {
         .whitelist_type("doca_buf_inventory")
         .whitelist_function("doca_buf_inventory_.*")
         // DOCA_CTX part
-        .whitelist_type("doca_event")
         .whitelist_type("doca_ctx")
-        .whitelist_type("doca_workq_.*")
-        .whitelist_type("doca_job_.*")
-        .whitelist_function("doca_workq_.*")
         .whitelist_function("doca_ctx_.*")
         // DOCA_BUF part
         .whitelist_type("doca_buf")
         .whitelist_function("doca_buf_.*")
-        // DOCA_DMA part
-        .whitelist_type("doca_dma_.*")
-        .whitelist_function("doca_dma_.*")
+        .whitelist_type("doca_dev_rep_filter")
         .whitelist_type("doca_pci_bdf")
         .derive_default(true)
         .derive_debug(true)
         .prepend_enum_name(false)
         .size_t_is_usize(true)
         // .constified_enum_module("doca_error")
-        .rustified_enum("doca_error")
-        .generate()
-        .expect("Unable to generate bindings");
+        .rustified_enum("doca_error");
+
+    // DOCA_PE, DOCA_EC, DOCA_AES_GCM, DOCA_RDMA and DOCA_COMCH part, which only exist in DOCA 2.x,
+    // where the DMA jobs become tasks
+    let builder = if env::var("CARGO_FEATURE_DOCA2").is_ok() {
+        println!("cargo:rustc-link-lib=doca_erasure_coding");
+        println!("cargo:rustc-link-lib=doca_aes_gcm");
+        println!("cargo:rustc-link-lib=doca_rdma");
+        println!("cargo:rustc-link-lib=doca_comch");
+        builder
+            .clang_arg("-DRUST_DOCA2")
+            .whitelist_type("doca_dma_task_.*")
+            .whitelist_function("doca_dma_task_.*")
+            .whitelist_function("doca_dma_cap_task_.*")
+            .whitelist_type("doca_pe")
+            .whitelist_function("doca_pe_.*")
+            .whitelist_type("doca_task")
+            .whitelist_function("doca_task_.*")
+            .whitelist_type("doca_ec")
+            .whitelist_type("doca_ec_.*")
+            .whitelist_function("doca_ec_.*")
+            .whitelist_type("doca_aes_gcm")
+            .whitelist_type("doca_aes_gcm_.*")
+            .whitelist_function("doca_aes_gcm_.*")
+            .whitelist_type("doca_rdma")
+            .whitelist_type("doca_rdma_.*")
+            .whitelist_function("doca_rdma_.*")
+            .whitelist_type("doca_access_flag")
+            .whitelist_type("doca_comch_.*")
+            .whitelist_function("doca_comch_.*")
+    } else {
+        // DOCA_WORKQ, DOCA_DMA jobs, DOCA_COMPRESS, DOCA_REGEX, DOCA_SHA, DOCA_TELEMETRY
+        // and DOCA_COMM_CHANNEL part, which only exist in DOCA 1.5
+        println!("cargo:rustc-link-lib=doca_compress");
+        println!("cargo:rustc-link-lib=doca_regex");
+        println!("cargo:rustc-link-lib=doca_sha");
+        println!("cargo:rustc-link-lib=doca_telemetry");
+        builder
+            .whitelist_type("doca_event")
+            .whitelist_type("doca_workq_.*")
+            .whitelist_type("doca_job_.*")
+            .whitelist_function("doca_workq_.*")
+            .whitelist_type("doca_dma_.*")
+            .whitelist_function("doca_dma_.*")
+            .whitelist_type("doca_compress_.*")
+            .whitelist_function("doca_compress_.*")
+            .whitelist_type("doca_regex.*")
+            .whitelist_function("doca_regex_.*")
+            .whitelist_type("doca_sha.*")
+            .whitelist_function("doca_sha_.*")
+            .whitelist_type("doca_telemetry.*")
+            .whitelist_function("doca_telemetry_.*")
+            .whitelist_type("doca_comm_channel_.*")
+            .whitelist_function("doca_comm_channel_.*")
+            .whitelist_type("doca_cc_msg_flag")
+    };
+
+    let bindings = builder.generate().expect("Unable to generate bindings");
 
     bindings
         .write_to_file(out_path.join("bindings.rs"))
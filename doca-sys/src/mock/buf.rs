@@ -0,0 +1,347 @@
+//! The buffer inventories and the buffers.
+
+use std::os::raw::c_void;
+use std::ptr;
+
+use super::mmap::mmap;
+use super::types::*;
+
+pub(crate) struct MockInventory {
+    capacity: usize,
+    free: usize,
+    started: bool,
+}
+
+pub(crate) struct MockBuf {
+    inventory: *mut MockInventory,
+    pub(crate) head: usize,
+    pub(crate) len: usize,
+    pub(crate) data: usize,
+    pub(crate) data_len: usize,
+    pub(crate) next: *mut MockBuf,
+    // whether the buffer is chained after another one
+    chained: bool,
+    refcount: u16,
+}
+
+impl MockBuf {
+    // Iterate the buffers of the list starting at the buffer
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &MockBuf> {
+        let mut cur = self as *const MockBuf;
+        std::iter::from_fn(move || {
+            let buf = unsafe { cur.as_ref()? };
+            cur = buf.next;
+            Some(buf)
+        })
+    }
+}
+
+unsafe fn inventory<'a>(inventory: *const doca_buf_inventory) -> Option<&'a mut MockInventory> {
+    (inventory as *mut MockInventory).as_mut()
+}
+
+pub(crate) unsafe fn buf<'a>(buf: *const doca_buf) -> Option<&'a mut MockBuf> {
+    (buf as *mut MockBuf).as_mut()
+}
+
+pub unsafe extern "C" fn doca_buf_inventory_create(
+    _user_data: *const doca_data,
+    num_elements: usize,
+    _extensions: u32,
+    buf_inventory: *mut *mut doca_buf_inventory,
+) -> doca_error_t {
+    if num_elements == 0 || buf_inventory.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    *buf_inventory = Box::into_raw(Box::new(MockInventory {
+        capacity: num_elements,
+        free: num_elements,
+        started: false,
+    })) as *mut doca_buf_inventory;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_buf_inventory_destroy(
+    inventory: *mut doca_buf_inventory,
+) -> doca_error_t {
+    match self::inventory(inventory) {
+        Some(inv) if inv.free != inv.capacity => doca_error::DOCA_ERROR_IN_USE,
+        Some(_) => {
+            drop(Box::from_raw(inventory as *mut MockInventory));
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_inventory_start(
+    inventory: *mut doca_buf_inventory,
+) -> doca_error_t {
+    match self::inventory(inventory) {
+        Some(inv) if inv.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(inv) => {
+            inv.started = true;
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_inventory_get_num_elements(
+    inventory: *const doca_buf_inventory,
+    num_of_elements: *mut u32,
+) -> doca_error_t {
+    match self::inventory(inventory) {
+        Some(inv) if !num_of_elements.is_null() => {
+            *num_of_elements = inv.capacity as u32;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_inventory_get_num_free_elements(
+    inventory: *const doca_buf_inventory,
+    num_of_free_elements: *mut u32,
+) -> doca_error_t {
+    match self::inventory(inventory) {
+        Some(inv) if !num_of_free_elements.is_null() => {
+            *num_of_free_elements = inv.free as u32;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_inventory_buf_by_args(
+    inventory: *mut doca_buf_inventory,
+    mmap: *mut doca_mmap,
+    head: *mut c_void,
+    len: usize,
+    data: *mut c_void,
+    data_len: usize,
+    buf: *mut *mut doca_buf,
+) -> doca_error_t {
+    let (inv, mmap) = match (self::inventory(inventory), self::mmap(mmap)) {
+        (Some(inv), Some(mmap)) if !buf.is_null() => (inv, mmap),
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    if !inv.started {
+        return doca_error::DOCA_ERROR_BAD_STATE;
+    }
+
+    // the head region must be populated, and the data must be in the head region
+    let (head, data) = (head as usize, data as usize);
+    let data_in_head = data >= head
+        && data
+            .checked_add(data_len)
+            .is_some_and(|end| end <= head + len);
+    if !mmap.contains(head, len) || !data_in_head {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    if inv.free == 0 {
+        return doca_error::DOCA_ERROR_NO_MEMORY;
+    }
+
+    inv.free -= 1;
+    *buf = Box::into_raw(Box::new(MockBuf {
+        inventory: inv,
+        head,
+        len,
+        data,
+        data_len,
+        next: ptr::null_mut(),
+        chained: false,
+        refcount: 1,
+    })) as *mut doca_buf;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_buf_refcount_rm(
+    buf: *mut doca_buf,
+    refcount: *mut u16,
+) -> doca_error_t {
+    let b = match self::buf(buf) {
+        Some(b) => b,
+        None => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+
+    b.refcount -= 1;
+    if !refcount.is_null() {
+        *refcount = b.refcount;
+    }
+    if b.refcount == 0 {
+        // the buffer returns to its inventory
+        let b = Box::from_raw(buf as *mut MockBuf);
+        (*b.inventory).free += 1;
+    }
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_buf_get_head(
+    buf: *const doca_buf,
+    head: *mut *mut c_void,
+) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !head.is_null() => {
+            *head = b.head as *mut c_void;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_get_len(buf: *const doca_buf, len: *mut usize) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !len.is_null() => {
+            *len = b.len;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_get_data(
+    buf: *const doca_buf,
+    data: *mut *mut c_void,
+) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !data.is_null() => {
+            *data = b.data as *mut c_void;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_get_data_len(
+    buf: *const doca_buf,
+    data_len: *mut usize,
+) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !data_len.is_null() => {
+            *data_len = b.data_len;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_set_data(
+    buf: *mut doca_buf,
+    data: *mut c_void,
+    data_len: usize,
+) -> doca_error_t {
+    let b = match self::buf(buf) {
+        Some(b) => b,
+        None => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+
+    let data = data as usize;
+    let in_head = data >= b.head
+        && data
+            .checked_add(data_len)
+            .is_some_and(|end| end <= b.head + b.len);
+    if !in_head {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+
+    b.data = data;
+    b.data_len = data_len;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_buf_list_chain(
+    list1: *mut doca_buf,
+    list2: *mut doca_buf,
+) -> doca_error_t {
+    let (l1, l2) = match (self::buf(list1), self::buf(list2)) {
+        (Some(l1), Some(l2)) if list1 != list2 => (l1, l2),
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    if l2.chained {
+        return doca_error::DOCA_ERROR_NOT_PERMITTED;
+    }
+
+    let mut last = l1 as *mut MockBuf;
+    while !(*last).next.is_null() {
+        last = (*last).next;
+    }
+    (*last).next = l2;
+    l2.chained = true;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_buf_list_unchain(
+    list1: *mut doca_buf,
+    list2: *mut doca_buf,
+) -> doca_error_t {
+    let l1 = match self::buf(list1) {
+        Some(l1) if !list2.is_null() => l1,
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+
+    // cut the list before `list2`
+    let mut prev = l1 as *mut MockBuf;
+    while !(*prev).next.is_null() {
+        if (*prev).next == list2 as *mut MockBuf {
+            (*prev).next = ptr::null_mut();
+            (*(list2 as *mut MockBuf)).chained = false;
+            return doca_error::DOCA_SUCCESS;
+        }
+        prev = (*prev).next;
+    }
+    doca_error::DOCA_ERROR_NOT_FOUND
+}
+
+pub unsafe extern "C" fn doca_buf_get_list_len(
+    buf: *mut doca_buf,
+    num_elements: *mut u32,
+) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !num_elements.is_null() => {
+            *num_elements = b.iter().count() as u32;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_is_last_in_list(
+    buf: *const doca_buf,
+    is_last: *mut u8,
+) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !is_last.is_null() => {
+            *is_last = b.next.is_null() as u8;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_get_next_in_list(
+    buf: *mut doca_buf,
+    next_buf: *mut *mut doca_buf,
+) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !next_buf.is_null() => {
+            *next_buf = b.next as *mut doca_buf;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_buf_get_last_in_list(
+    buf: *mut doca_buf,
+    last_buf: *mut *mut doca_buf,
+) -> doca_error_t {
+    match self::buf(buf) {
+        Some(b) if !last_buf.is_null() => {
+            *last_buf = b.iter().last().unwrap() as *const MockBuf as *mut doca_buf;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
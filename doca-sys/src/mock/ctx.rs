@@ -0,0 +1,384 @@
+//! The contexts, the DMA engine and the work queues.
+//!
+//! A DMA memcpy job is done by `memcpy` when it is submitted, and its event waits in the
+//! work queue until it is retrieved, so a work queue holds at most `depth` unretrieved events.
+
+use std::collections::VecDeque;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+
+use super::buf::{buf, MockBuf};
+use super::device::MockDev;
+use super::jobs::*;
+use super::types::*;
+
+pub(crate) struct MockCtx {
+    devs: Vec<*const MockDev>,
+    workqs: Vec<*const MockWorkq>,
+    started: bool,
+}
+
+impl MockCtx {
+    fn new() -> Self {
+        Self {
+            devs: Vec::new(),
+            workqs: Vec::new(),
+            started: false,
+        }
+    }
+}
+
+// The context is the first field, so the engine and its context share the address
+#[repr(C)]
+pub(crate) struct MockDma {
+    ctx: MockCtx,
+}
+
+pub(crate) struct MockWorkq {
+    depth: usize,
+    ctxs: Vec<*const MockCtx>,
+    done: VecDeque<doca_event>,
+    event_driven: bool,
+    armed: bool,
+    event_fd: c_int,
+}
+
+impl MockWorkq {
+    fn push_event(&mut self, event: doca_event) {
+        self.done.push_back(event);
+        if self.armed {
+            self.armed = false;
+            notify(self.event_fd);
+        }
+    }
+}
+
+impl MockBuf {
+    // The free bytes after the data, to which a DMA job appends
+    fn tail_room(&self) -> usize {
+        self.head + self.len - (self.data + self.data_len)
+    }
+}
+
+unsafe fn ctx<'a>(ctx: *mut doca_ctx) -> Option<&'a mut MockCtx> {
+    (ctx as *mut MockCtx).as_mut()
+}
+
+unsafe fn workq<'a>(workq: *mut doca_workq) -> Option<&'a mut MockWorkq> {
+    (workq as *mut MockWorkq).as_mut()
+}
+
+fn notify(event_fd: c_int) {
+    let one = 1u64;
+    unsafe { libc::write(event_fd, &one as *const u64 as *const c_void, 8) };
+}
+
+pub unsafe extern "C" fn doca_dma_create(dma: *mut *mut doca_dma) -> doca_error_t {
+    if dma.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    *dma = Box::into_raw(Box::new(MockDma {
+        ctx: MockCtx::new(),
+    })) as *mut doca_dma;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_dma_destroy(dma: *mut doca_dma) -> doca_error_t {
+    let engine = match (dma as *mut MockDma).as_ref() {
+        Some(engine) => engine,
+        None => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    if engine.ctx.started || !engine.ctx.workqs.is_empty() {
+        return doca_error::DOCA_ERROR_IN_USE;
+    }
+    drop(Box::from_raw(dma as *mut MockDma));
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_dma_as_ctx(dma: *mut doca_dma) -> *mut doca_ctx {
+    match (dma as *mut MockDma).as_mut() {
+        Some(engine) => &mut engine.ctx as *mut MockCtx as *mut doca_ctx,
+        None => ptr::null_mut(),
+    }
+}
+
+pub unsafe extern "C" fn doca_ctx_start(ctx: *mut doca_ctx) -> doca_error_t {
+    match self::ctx(ctx) {
+        Some(ctx) if ctx.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(ctx) => {
+            ctx.started = true;
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_ctx_stop(ctx: *mut doca_ctx) -> doca_error_t {
+    match self::ctx(ctx) {
+        Some(ctx) if !ctx.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(ctx) => {
+            ctx.started = false;
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_ctx_dev_add(ctx: *mut doca_ctx, dev: *mut doca_dev) -> doca_error_t {
+    let dev = dev as *const MockDev;
+    match self::ctx(ctx) {
+        Some(_) if dev.is_null() => doca_error::DOCA_ERROR_INVALID_VALUE,
+        Some(ctx) if ctx.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(ctx) if ctx.devs.contains(&dev) => doca_error::DOCA_ERROR_IN_USE,
+        Some(ctx) => {
+            ctx.devs.push(dev);
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_ctx_dev_rm(ctx: *mut doca_ctx, dev: *mut doca_dev) -> doca_error_t {
+    let dev = dev as *const MockDev;
+    match self::ctx(ctx) {
+        Some(ctx) if ctx.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(ctx) => match ctx.devs.iter().position(|d| *d == dev) {
+            Some(idx) => {
+                ctx.devs.remove(idx);
+                doca_error::DOCA_SUCCESS
+            }
+            None => doca_error::DOCA_ERROR_NOT_FOUND,
+        },
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_ctx_workq_add(
+    ctx: *mut doca_ctx,
+    workq: *mut doca_workq,
+) -> doca_error_t {
+    let (c, w) = match (self::ctx(ctx), self::workq(workq)) {
+        (Some(c), Some(w)) => (c, w),
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    let (ctx, workq) = (ctx as *const MockCtx, workq as *const MockWorkq);
+    if c.workqs.contains(&workq) {
+        return doca_error::DOCA_ERROR_IN_USE;
+    }
+
+    c.workqs.push(workq);
+    w.ctxs.push(ctx);
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_ctx_workq_rm(
+    ctx: *mut doca_ctx,
+    workq: *mut doca_workq,
+) -> doca_error_t {
+    let (c, w) = match (self::ctx(ctx), self::workq(workq)) {
+        (Some(c), Some(w)) => (c, w),
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    let (ctx, workq) = (ctx as *const MockCtx, workq as *const MockWorkq);
+    match c.workqs.iter().position(|q| *q == workq) {
+        Some(idx) => {
+            c.workqs.remove(idx);
+            w.ctxs.retain(|c| *c != ctx);
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_NOT_FOUND,
+    }
+}
+
+pub unsafe extern "C" fn doca_workq_create(
+    depth: u32,
+    workq: *mut *mut doca_workq,
+) -> doca_error_t {
+    if depth == 0 || workq.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    *workq = Box::into_raw(Box::new(MockWorkq {
+        depth: depth as usize,
+        ctxs: Vec::new(),
+        done: VecDeque::with_capacity(depth as usize),
+        event_driven: false,
+        armed: false,
+        event_fd: -1,
+    })) as *mut doca_workq;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_workq_destroy(workq: *mut doca_workq) -> doca_error_t {
+    match self::workq(workq) {
+        Some(w) if !w.ctxs.is_empty() => doca_error::DOCA_ERROR_IN_USE,
+        Some(w) => {
+            if w.event_fd >= 0 {
+                libc::close(w.event_fd);
+            }
+            drop(Box::from_raw(workq as *mut MockWorkq));
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_workq_submit(
+    workq: *mut doca_workq,
+    job: *const doca_job,
+) -> doca_error_t {
+    let (w, job) = match (self::workq(workq), job.as_ref()) {
+        (Some(w), Some(job)) => (w, job),
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    if !w.ctxs.contains(&(job.ctx as *const MockCtx)) {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    if !(*(job.ctx as *const MockCtx)).started {
+        return doca_error::DOCA_ERROR_BAD_STATE;
+    }
+    if w.done.len() >= w.depth {
+        return doca_error::DOCA_ERROR_NO_MEMORY;
+    }
+    if job.type_ as u32 != DOCA_DMA_JOB_MEMCPY {
+        return doca_error::DOCA_ERROR_NOT_SUPPORTED;
+    }
+
+    let memcpy = &*(job as *const doca_job as *const doca_dma_job_memcpy);
+    let result = match (buf(memcpy.src_buff), buf(memcpy.dst_buff)) {
+        (Some(src), Some(dst)) => dma_memcpy(src, dst),
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+
+    w.push_event(doca_event {
+        type_: job.type_,
+        user_data: job.user_data,
+        result: doca_data { u64: result as u64 },
+    });
+    doca_error::DOCA_SUCCESS
+}
+
+// Append the data of the source list to the data of the destination list,
+// failing as the hardware does if the destination has no room for all of it
+unsafe fn dma_memcpy(src: &MockBuf, dst: &mut MockBuf) -> doca_error_t {
+    let total: usize = src.iter().map(|b| b.data_len).sum();
+    let room: usize = dst.iter().map(|b| b.tail_room()).sum();
+    if total > room {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+
+    let mut src_bufs = src.iter().map(|b| (b.data, b.data_len));
+    let (mut from, mut left) = (0, 0);
+    let mut to = dst as *mut MockBuf;
+    while let Some(d) = to.as_mut() {
+        let mut room = d.tail_room();
+        while room > 0 {
+            if left == 0 {
+                match src_bufs.next() {
+                    Some((data, len)) => (from, left) = (data, len),
+                    None => return doca_error::DOCA_SUCCESS,
+                }
+                continue;
+            }
+
+            let n = room.min(left);
+            // the source and the destination may overlap, as `memmove` allows
+            ptr::copy(from as *const u8, (d.data + d.data_len) as *mut u8, n);
+            d.data_len += n;
+            (from, left, room) = (from + n, left - n, room - n);
+        }
+        to = d.next;
+    }
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_workq_progress_retrieve(
+    workq: *mut doca_workq,
+    ev: *mut doca_event,
+    _flags: c_int,
+) -> doca_error_t {
+    let w = match self::workq(workq) {
+        Some(w) if !ev.is_null() => w,
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    // the events wait until the work queue is added to a context again
+    if w.ctxs.is_empty() {
+        return doca_error::DOCA_ERROR_BAD_STATE;
+    }
+
+    match w.done.pop_front() {
+        Some(event) => {
+            *ev = event;
+            if event.result.u64 == 0 {
+                doca_error::DOCA_SUCCESS
+            } else {
+                doca_error::DOCA_ERROR_IO_FAILED
+            }
+        }
+        None => doca_error::DOCA_ERROR_AGAIN,
+    }
+}
+
+pub unsafe extern "C" fn doca_workq_set_event_driven_enable(
+    workq: *mut doca_workq,
+    enabled: u8,
+) -> doca_error_t {
+    let w = match self::workq(workq) {
+        Some(w) => w,
+        None => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    if !w.ctxs.is_empty() {
+        return doca_error::DOCA_ERROR_BAD_STATE;
+    }
+
+    if enabled != 0 && w.event_fd < 0 {
+        let fd = libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC);
+        if fd < 0 {
+            return doca_error::DOCA_ERROR_OPERATING_SYSTEM;
+        }
+        w.event_fd = fd;
+    }
+    w.event_driven = enabled != 0;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_workq_get_event_handle(
+    workq: *mut doca_workq,
+    handle: *mut doca_event_handle_t,
+) -> doca_error_t {
+    match self::workq(workq) {
+        Some(w) if !w.event_driven => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(w) if !handle.is_null() => {
+            *handle = w.event_fd;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_workq_event_handle_arm(workq: *mut doca_workq) -> doca_error_t {
+    match self::workq(workq) {
+        Some(w) if !w.event_driven => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(w) => {
+            // notify at once if an event is already waiting
+            if w.done.is_empty() {
+                w.armed = true;
+            } else {
+                notify(w.event_fd);
+            }
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_workq_event_handle_clear(workq: *mut doca_workq) -> doca_error_t {
+    match self::workq(workq) {
+        Some(w) if !w.event_driven => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(w) => {
+            let mut count = 0u64;
+            libc::read(w.event_fd, &mut count as *mut u64 as *mut c_void, 8);
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
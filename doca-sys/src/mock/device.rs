@@ -0,0 +1,350 @@
+//! The fake devices.
+//!
+//! The devices are named by their PCIe addresses, read from the comma-separated
+//! `DOCA_MOCK_DEVICES` environment variable, e.g., `DOCA_MOCK_DEVICES=03:00.0,03:00.1`,
+//! or set by [`set_devices`]. Without both, a single device at `03:00.0` is found.
+//!
+//! Each device supports the DMA memcpy jobs only. With `doca2`, it reports the capability
+//! of the DMA memcpy tasks, which can't run without a progress engine though.
+//! Its link is always up, with the firmware version and the maximum MTU of a ConnectX-6 Dx.
+
+use std::os::raw::c_char;
+use std::ptr;
+use std::sync::Mutex;
+
+#[cfg(not(feature = "doca2"))]
+use super::jobs::*;
+use super::types::*;
+
+const DEFAULT_DEVICES: &str = "03:00.0";
+
+// The size of the buffers of the maximum DMA job
+const MAX_DMA_BUF_SIZE: u64 = 1 << 30;
+
+// The firmware version and the maximum MTU of a ConnectX-6 Dx
+const FW_VERSION: &str = "22.35.1012";
+const MAX_MTU: u32 = 9978;
+
+pub(crate) struct MockDevInfo {
+    pci_addr: u16,
+    ibdev_name: String,
+    iface_name: String,
+    mac_addr: [u8; 6],
+}
+
+pub(crate) struct MockDev {
+    pub(crate) devinfo: &'static MockDevInfo,
+}
+
+// The device infos are leaked, so they outlive the lists and the opened devices
+static DEVICES: Mutex<Option<Vec<&'static MockDevInfo>>> = Mutex::new(None);
+
+/// Set the PCIe addresses, e.g., `"03:00.0"`, of the fake devices found by the
+/// later `doca_devinfo_list_create`, in place of the `DOCA_MOCK_DEVICES` environment variable.
+///
+/// Return `DOCA_ERROR_INVALID_VALUE` if an address is malformed, which keeps the former devices.
+pub fn set_devices(pci_addrs: &[&str]) -> doca_error_t {
+    match parse_devices(pci_addrs.iter().copied()) {
+        Some(devs) => {
+            *DEVICES.lock().unwrap() = Some(devs);
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+fn parse_devices<'a>(
+    pci_addrs: impl Iterator<Item = &'a str>,
+) -> Option<Vec<&'static MockDevInfo>> {
+    pci_addrs
+        .enumerate()
+        .map(|(i, addr)| {
+            let pci_addr = parse_pci_addr(addr.trim())?;
+            Some(&*Box::leak(Box::new(MockDevInfo {
+                pci_addr,
+                ibdev_name: format!("mlx5_{}", i),
+                iface_name: format!("p{}", i),
+                mac_addr: [0x02, 0, 0, 0, 0, i as u8],
+            })))
+        })
+        .collect()
+}
+
+// Parse "BUS:DEVICE.FUNCTION" in hex into the raw `doca_pci_bdf`
+fn parse_pci_addr(addr: &str) -> Option<u16> {
+    let (bus, rest) = addr.split_once(':')?;
+    let (device, function) = rest.split_once('.')?;
+    let bus = u16::from_str_radix(bus, 16).ok().filter(|b| *b <= 0xff)?;
+    let device = u16::from_str_radix(device, 16)
+        .ok()
+        .filter(|d| *d <= 0x1f)?;
+    let function = u16::from_str_radix(function, 16)
+        .ok()
+        .filter(|f| *f <= 0x7)?;
+
+    Some(bus << 8 | device << 3 | function)
+}
+
+fn current_devices() -> Vec<&'static MockDevInfo> {
+    let mut devices = DEVICES.lock().unwrap();
+    devices
+        .get_or_insert_with(|| {
+            let addrs =
+                std::env::var("DOCA_MOCK_DEVICES").unwrap_or_else(|_| DEFAULT_DEVICES.to_string());
+            parse_devices(addrs.split(',').filter(|a| !a.trim().is_empty()))
+                .expect("DOCA_MOCK_DEVICES should be a list of PCIe addresses, e.g., 03:00.0")
+        })
+        .clone()
+}
+
+pub(crate) unsafe fn devinfo<'a>(devinfo: *const doca_devinfo) -> Option<&'a MockDevInfo> {
+    (devinfo as *const MockDevInfo).as_ref()
+}
+
+pub(crate) unsafe fn dev<'a>(dev: *const doca_dev) -> Option<&'a MockDev> {
+    (dev as *const MockDev).as_ref()
+}
+
+// Copy the string with the trailing NUL into the `size` bytes of `dst`
+unsafe fn copy_name(name: &str, dst: *mut c_char, size: u32) -> doca_error_t {
+    if dst.is_null() || name.len() >= size as usize {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    ptr::copy_nonoverlapping(name.as_ptr() as *const c_char, dst, name.len());
+    *dst.add(name.len()) = 0;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_devinfo_list_create(
+    dev_list: *mut *mut *mut doca_devinfo,
+    nb_devs: *mut u32,
+) -> doca_error_t {
+    if dev_list.is_null() || nb_devs.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+
+    // the list is NULL-terminated, so it can be freed without its length
+    let mut list: Vec<*mut doca_devinfo> = current_devices()
+        .into_iter()
+        .map(|d| d as *const MockDevInfo as *mut doca_devinfo)
+        .collect();
+    let n = list.len() as u32;
+    list.push(ptr::null_mut());
+
+    *dev_list = Box::into_raw(list.into_boxed_slice()) as *mut *mut doca_devinfo;
+    *nb_devs = n;
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_devinfo_list_destroy(
+    dev_list: *mut *mut doca_devinfo,
+) -> doca_error_t {
+    if dev_list.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+
+    let mut len = 1;
+    while !(*dev_list.add(len - 1)).is_null() {
+        len += 1;
+    }
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(dev_list, len)));
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_devinfo_get_pci_addr(
+    devinfo: *const doca_devinfo,
+    pci_addr: *mut doca_pci_bdf,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(info) if !pci_addr.is_null() => {
+            (*pci_addr).__bindgen_anon_1.raw = info.pci_addr;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_devinfo_get_ibdev_name(
+    devinfo: *const doca_devinfo,
+    ibdev_name: *mut c_char,
+    size: u32,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(info) => copy_name(&info.ibdev_name, ibdev_name, size),
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_devinfo_get_iface_name(
+    devinfo: *const doca_devinfo,
+    iface_name: *mut c_char,
+    size: u32,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(info) => copy_name(&info.iface_name, iface_name, size),
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_devinfo_get_mac_addr(
+    devinfo: *const doca_devinfo,
+    mac_addr: *mut u8,
+    size: u32,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(info) if !mac_addr.is_null() && size as usize >= info.mac_addr.len() => {
+            ptr::copy_nonoverlapping(info.mac_addr.as_ptr(), mac_addr, info.mac_addr.len());
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_devinfo_get_link_state(
+    devinfo: *const doca_devinfo,
+    state: *mut doca_devinfo_link_state,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(_) if !state.is_null() => {
+            *state = DOCA_DEVINFO_LINK_STATE_UP;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_devinfo_get_fw_version(
+    devinfo: *const doca_devinfo,
+    fw_version: *mut c_char,
+    size: u32,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(_) => copy_name(FW_VERSION, fw_version, size),
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_devinfo_get_max_mtu(
+    devinfo: *const doca_devinfo,
+    max_mtu: *mut u32,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(_) if !max_mtu.is_null() => {
+            *max_mtu = MAX_MTU;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_dev_open(
+    devinfo: *mut doca_devinfo,
+    dev: *mut *mut doca_dev,
+) -> doca_error_t {
+    match self::devinfo(devinfo) {
+        Some(info) if !dev.is_null() => {
+            *dev = Box::into_raw(Box::new(MockDev { devinfo: info })) as *mut doca_dev;
+            doca_error::DOCA_SUCCESS
+        }
+        _ => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_dev_close(dev: *mut doca_dev) -> doca_error_t {
+    if dev.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    drop(Box::from_raw(dev as *mut MockDev));
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_dev_as_devinfo(dev: *const doca_dev) -> *mut doca_devinfo {
+    match self::dev(dev) {
+        Some(dev) => dev.devinfo as *const MockDevInfo as *mut doca_devinfo,
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(not(feature = "doca2"))]
+pub unsafe extern "C" fn doca_dma_get_max_buf_size(
+    devinfo: *const doca_devinfo,
+    max_buf_size: *mut u64,
+) -> doca_error_t {
+    if self::devinfo(devinfo).is_none() || max_buf_size.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    *max_buf_size = MAX_DMA_BUF_SIZE;
+    doca_error::DOCA_SUCCESS
+}
+
+#[cfg(not(feature = "doca2"))]
+pub unsafe extern "C" fn doca_dma_job_get_supported(
+    devinfo: *mut doca_devinfo,
+    job_type: doca_dma_job_types,
+) -> doca_error_t {
+    if self::devinfo(devinfo).is_none() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    if job_type == DOCA_DMA_JOB_MEMCPY {
+        doca_error::DOCA_SUCCESS
+    } else {
+        doca_error::DOCA_ERROR_NOT_SUPPORTED
+    }
+}
+
+#[cfg(feature = "doca2")]
+pub unsafe extern "C" fn doca_dma_cap_task_memcpy_get_max_buf_size(
+    devinfo: *const doca_devinfo,
+    max_buf_size: *mut u64,
+) -> doca_error_t {
+    if self::devinfo(devinfo).is_none() || max_buf_size.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    *max_buf_size = MAX_DMA_BUF_SIZE;
+    doca_error::DOCA_SUCCESS
+}
+
+#[cfg(feature = "doca2")]
+pub unsafe extern "C" fn doca_dma_cap_task_memcpy_is_supported(
+    devinfo: *const doca_devinfo,
+) -> doca_error_t {
+    if self::devinfo(devinfo).is_none() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    doca_error::DOCA_SUCCESS
+}
+
+// The fake devices have no representors
+
+pub unsafe extern "C" fn doca_devinfo_rep_list_create(
+    _dev: *mut doca_dev,
+    _filter: std::os::raw::c_int,
+    _list: *mut *mut *mut doca_devinfo_rep,
+    _n: *mut u32,
+) -> doca_error_t {
+    doca_error::DOCA_ERROR_NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_devinfo_rep_list_destroy(
+    _list: *mut *mut doca_devinfo_rep,
+) -> doca_error_t {
+    doca_error::DOCA_ERROR_NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_devinfo_rep_get_pci_addr(
+    _rep: *const doca_devinfo_rep,
+    _pci_addr: *mut doca_pci_bdf,
+) -> doca_error_t {
+    doca_error::DOCA_ERROR_NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_dev_rep_open(
+    _rep: *mut doca_devinfo_rep,
+    _dev_rep: *mut *mut doca_dev_rep,
+) -> doca_error_t {
+    doca_error::DOCA_ERROR_NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_dev_rep_close(_dev_rep: *mut doca_dev_rep) -> doca_error_t {
+    doca_error::DOCA_ERROR_NOT_SUPPORTED
+}
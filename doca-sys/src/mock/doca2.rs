@@ -0,0 +1,661 @@
+//! The progress engine, erasure coding, AES-GCM, RDMA and Comch of DOCA 2.x.
+//!
+//! The fake devices don't support them, so the engines can't be created.
+
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+use super::types::*;
+
+const NOT_SUPPORTED: doca_error_t = doca_error::DOCA_ERROR_NOT_SUPPORTED;
+
+// No context is created, since the engines below can't be
+pub unsafe extern "C" fn doca_ctx_start(_ctx: *mut doca_ctx) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ctx_stop(_ctx: *mut doca_ctx) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub type doca_ctx_states = u32;
+pub const DOCA_CTX_STATE_IDLE: doca_ctx_states = 0;
+pub const DOCA_CTX_STATE_STARTING: doca_ctx_states = 1;
+pub const DOCA_CTX_STATE_RUNNING: doca_ctx_states = 2;
+pub const DOCA_CTX_STATE_STOPPING: doca_ctx_states = 3;
+pub unsafe extern "C" fn doca_ctx_get_state(
+    _ctx: *const doca_ctx,
+    _state: *mut doca_ctx_states,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+opaque!(doca_pe, doca_task, doca_dma_task_memcpy);
+pub unsafe extern "C" fn doca_pe_create(_pe: *mut *mut doca_pe) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_pe_destroy(_pe: *mut doca_pe) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_pe_progress(_pe: *mut doca_pe) -> u8 {
+    0
+}
+
+pub unsafe extern "C" fn doca_pe_connect_ctx(
+    _pe: *mut doca_pe,
+    _ctx: *mut doca_ctx,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_pe_get_num_inflight_tasks(
+    _pe: *const doca_pe,
+    _n: *mut usize,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_task_submit(_task: *mut doca_task) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_task_free(_task: *mut doca_task) {}
+
+pub unsafe extern "C" fn doca_task_get_status(_task: *const doca_task) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_task_set_user_data(_task: *mut doca_task, _user_data: doca_data) {}
+
+pub unsafe extern "C" fn doca_task_get_user_data(_task: *const doca_task) -> doca_data {
+    doca_data::default()
+}
+
+pub unsafe extern "C" fn doca_dma_task_memcpy_as_task(
+    _task: *mut doca_dma_task_memcpy,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+opaque!(
+    doca_ec,
+    doca_ec_matrix,
+    doca_ec_task_create,
+    doca_ec_task_recover,
+    doca_ec_task_update
+);
+pub type doca_ec_matrix_type = u32;
+pub const DOCA_EC_MATRIX_TYPE_CAUCHY: doca_ec_matrix_type = 1;
+pub const DOCA_EC_MATRIX_TYPE_VANDERMONDE: doca_ec_matrix_type = 2;
+pub type doca_ec_task_create_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_ec_task_create, doca_data, doca_data)>;
+pub type doca_ec_task_recover_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_ec_task_recover, doca_data, doca_data)>;
+pub type doca_ec_task_update_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_ec_task_update, doca_data, doca_data)>;
+pub unsafe extern "C" fn doca_ec_create(
+    _dev: *mut doca_dev,
+    _ec: *mut *mut doca_ec,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_destroy(_ec: *mut doca_ec) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_as_ctx(_ec: *mut doca_ec) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_ec_cap_task_create_is_supported(
+    _devinfo: *const doca_devinfo,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_matrix_create(
+    _ec: *mut doca_ec,
+    _ty: doca_ec_matrix_type,
+    _data_block_count: usize,
+    _rdnc_block_count: usize,
+    _matrix: *mut *mut doca_ec_matrix,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_matrix_create_recover(
+    _ec: *mut doca_ec,
+    _coding_matrix: *const doca_ec_matrix,
+    _missing_indices: *mut u32,
+    _n_missing: usize,
+    _matrix: *mut *mut doca_ec_matrix,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_matrix_create_update(
+    _ec: *mut doca_ec,
+    _coding_matrix: *const doca_ec_matrix,
+    _update_indices: *mut u32,
+    _n_updates: usize,
+    _matrix: *mut *mut doca_ec_matrix,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_matrix_destroy(_matrix: *mut doca_ec_matrix) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_task_create_set_conf(
+    _ec: *mut doca_ec,
+    _ok: doca_ec_task_create_completion_cb_t,
+    _err: doca_ec_task_create_completion_cb_t,
+    _num_tasks: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_task_recover_set_conf(
+    _ec: *mut doca_ec,
+    _ok: doca_ec_task_recover_completion_cb_t,
+    _err: doca_ec_task_recover_completion_cb_t,
+    _num_tasks: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_task_update_set_conf(
+    _ec: *mut doca_ec,
+    _ok: doca_ec_task_update_completion_cb_t,
+    _err: doca_ec_task_update_completion_cb_t,
+    _num_tasks: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_task_create_allocate_init(
+    _ec: *mut doca_ec,
+    _coding_matrix: *const doca_ec_matrix,
+    _original_data_blocks: *const doca_buf,
+    _rdnc_blocks: *mut doca_buf,
+    _user_data: doca_data,
+    _task: *mut *mut doca_ec_task_create,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_task_recover_allocate_init(
+    _ec: *mut doca_ec,
+    _recover_matrix: *const doca_ec_matrix,
+    _available_blocks: *const doca_buf,
+    _recovered_data_blocks: *mut doca_buf,
+    _user_data: doca_data,
+    _task: *mut *mut doca_ec_task_recover,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_task_update_allocate_init(
+    _ec: *mut doca_ec,
+    _update_matrix: *const doca_ec_matrix,
+    _original_updated_and_rdnc_blocks: *const doca_buf,
+    _updated_rdnc_blocks: *mut doca_buf,
+    _user_data: doca_data,
+    _task: *mut *mut doca_ec_task_update,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_ec_task_create_as_task(
+    _task: *mut doca_ec_task_create,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_ec_task_recover_as_task(
+    _task: *mut doca_ec_task_recover,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_ec_task_update_as_task(
+    _task: *mut doca_ec_task_update,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+opaque!(
+    doca_aes_gcm,
+    doca_aes_gcm_key,
+    doca_aes_gcm_task_encrypt,
+    doca_aes_gcm_task_decrypt
+);
+pub type doca_aes_gcm_key_type = u32;
+pub const DOCA_AES_GCM_KEY_128: doca_aes_gcm_key_type = 0;
+pub const DOCA_AES_GCM_KEY_256: doca_aes_gcm_key_type = 1;
+pub type doca_aes_gcm_task_encrypt_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_aes_gcm_task_encrypt, doca_data, doca_data)>;
+pub type doca_aes_gcm_task_decrypt_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_aes_gcm_task_decrypt, doca_data, doca_data)>;
+pub unsafe extern "C" fn doca_aes_gcm_create(
+    _dev: *mut doca_dev,
+    _aes_gcm: *mut *mut doca_aes_gcm,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_destroy(_aes_gcm: *mut doca_aes_gcm) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_as_ctx(_aes_gcm: *mut doca_aes_gcm) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_cap_task_encrypt_is_supported(
+    _devinfo: *const doca_devinfo,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_key_create(
+    _aes_gcm: *mut doca_aes_gcm,
+    _raw_key: *const c_void,
+    _ty: doca_aes_gcm_key_type,
+    _key: *mut *mut doca_aes_gcm_key,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_key_destroy(_key: *mut doca_aes_gcm_key) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_task_encrypt_set_conf(
+    _aes_gcm: *mut doca_aes_gcm,
+    _ok: doca_aes_gcm_task_encrypt_completion_cb_t,
+    _err: doca_aes_gcm_task_encrypt_completion_cb_t,
+    _num_tasks: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_task_decrypt_set_conf(
+    _aes_gcm: *mut doca_aes_gcm,
+    _ok: doca_aes_gcm_task_decrypt_completion_cb_t,
+    _err: doca_aes_gcm_task_decrypt_completion_cb_t,
+    _num_tasks: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_task_encrypt_alloc_init(
+    _aes_gcm: *mut doca_aes_gcm,
+    _src: *const doca_buf,
+    _dst: *mut doca_buf,
+    _key: *mut doca_aes_gcm_key,
+    _iv: *const u8,
+    _iv_length: u32,
+    _tag_size: u32,
+    _aad_size: u32,
+    _user_data: doca_data,
+    _task: *mut *mut doca_aes_gcm_task_encrypt,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_task_decrypt_alloc_init(
+    _aes_gcm: *mut doca_aes_gcm,
+    _src: *const doca_buf,
+    _dst: *mut doca_buf,
+    _key: *mut doca_aes_gcm_key,
+    _iv: *const u8,
+    _iv_length: u32,
+    _tag_size: u32,
+    _aad_size: u32,
+    _user_data: doca_data,
+    _task: *mut *mut doca_aes_gcm_task_decrypt,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_task_encrypt_as_task(
+    _task: *mut doca_aes_gcm_task_encrypt,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_aes_gcm_task_decrypt_as_task(
+    _task: *mut doca_aes_gcm_task_decrypt,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+opaque!(
+    doca_rdma,
+    doca_rdma_task_send,
+    doca_rdma_task_receive,
+    doca_rdma_task_read,
+    doca_rdma_task_write
+);
+pub type doca_access_flag = u32;
+pub const DOCA_ACCESS_FLAG_LOCAL_READ_WRITE: doca_access_flag = 1;
+pub const DOCA_ACCESS_FLAG_RDMA_READ: doca_access_flag = 2;
+pub const DOCA_ACCESS_FLAG_RDMA_WRITE: doca_access_flag = 4;
+pub type doca_rdma_task_send_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_rdma_task_send, doca_data, doca_data)>;
+pub type doca_rdma_task_receive_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_rdma_task_receive, doca_data, doca_data)>;
+pub type doca_rdma_task_read_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_rdma_task_read, doca_data, doca_data)>;
+pub type doca_rdma_task_write_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_rdma_task_write, doca_data, doca_data)>;
+pub unsafe extern "C" fn doca_rdma_create(
+    _dev: *mut doca_dev,
+    _rdma: *mut *mut doca_rdma,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_destroy(_rdma: *mut doca_rdma) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_as_ctx(_rdma: *mut doca_rdma) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_rdma_cap_task_send_is_supported(
+    _devinfo: *const doca_devinfo,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_set_permissions(
+    _rdma: *mut doca_rdma,
+    _permissions: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_export(
+    _rdma: *const doca_rdma,
+    _details: *mut *const c_void,
+    _size: *mut usize,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_connect(
+    _rdma: *mut doca_rdma,
+    _details: *const c_void,
+    _size: usize,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_send_set_conf(
+    _rdma: *mut doca_rdma,
+    _ok: doca_rdma_task_send_completion_cb_t,
+    _err: doca_rdma_task_send_completion_cb_t,
+    _num: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_receive_set_conf(
+    _rdma: *mut doca_rdma,
+    _ok: doca_rdma_task_receive_completion_cb_t,
+    _err: doca_rdma_task_receive_completion_cb_t,
+    _num: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_read_set_conf(
+    _rdma: *mut doca_rdma,
+    _ok: doca_rdma_task_read_completion_cb_t,
+    _err: doca_rdma_task_read_completion_cb_t,
+    _num: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_write_set_conf(
+    _rdma: *mut doca_rdma,
+    _ok: doca_rdma_task_write_completion_cb_t,
+    _err: doca_rdma_task_write_completion_cb_t,
+    _num: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_send_allocate_init(
+    _rdma: *mut doca_rdma,
+    _src: *const doca_buf,
+    _user_data: doca_data,
+    _task: *mut *mut doca_rdma_task_send,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_receive_allocate_init(
+    _rdma: *mut doca_rdma,
+    _dst: *mut doca_buf,
+    _user_data: doca_data,
+    _task: *mut *mut doca_rdma_task_receive,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_read_allocate_init(
+    _rdma: *mut doca_rdma,
+    _src: *const doca_buf,
+    _dst: *mut doca_buf,
+    _user_data: doca_data,
+    _task: *mut *mut doca_rdma_task_read,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_write_allocate_init(
+    _rdma: *mut doca_rdma,
+    _src: *const doca_buf,
+    _dst: *mut doca_buf,
+    _user_data: doca_data,
+    _task: *mut *mut doca_rdma_task_write,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_rdma_task_send_as_task(
+    _task: *mut doca_rdma_task_send,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_rdma_task_receive_as_task(
+    _task: *mut doca_rdma_task_receive,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_rdma_task_read_as_task(
+    _task: *mut doca_rdma_task_read,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_rdma_task_write_as_task(
+    _task: *mut doca_rdma_task_write,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+opaque!(
+    doca_comch_client,
+    doca_comch_connection,
+    doca_comch_producer,
+    doca_comch_producer_task_send,
+    doca_comch_consumer,
+    doca_comch_consumer_task_post_recv
+);
+pub type doca_comch_producer_task_send_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_comch_producer_task_send, doca_data, doca_data)>;
+pub type doca_comch_consumer_task_post_recv_completion_cb_t =
+    Option<unsafe extern "C" fn(*mut doca_comch_consumer_task_post_recv, doca_data, doca_data)>;
+pub unsafe extern "C" fn doca_comch_client_create(
+    _dev: *mut doca_dev,
+    _name: *const c_char,
+    _client: *mut *mut doca_comch_client,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_client_destroy(
+    _client: *mut doca_comch_client,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_client_as_ctx(
+    _client: *mut doca_comch_client,
+) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_comch_client_get_connection(
+    _client: *const doca_comch_client,
+    _connection: *mut *mut doca_comch_connection,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_producer_cap_is_supported(
+    _devinfo: *const doca_devinfo,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_producer_create(
+    _connection: *mut doca_comch_connection,
+    _producer: *mut *mut doca_comch_producer,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_producer_destroy(
+    _producer: *mut doca_comch_producer,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_producer_as_ctx(
+    _producer: *mut doca_comch_producer,
+) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_comch_producer_task_send_set_conf(
+    _producer: *mut doca_comch_producer,
+    _ok: doca_comch_producer_task_send_completion_cb_t,
+    _err: doca_comch_producer_task_send_completion_cb_t,
+    _num_tasks: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_producer_task_send_alloc_init(
+    _producer: *mut doca_comch_producer,
+    _buf: *const doca_buf,
+    _imm_data: *const u8,
+    _imm_data_len: u32,
+    _consumer_id: u32,
+    _task: *mut *mut doca_comch_producer_task_send,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_producer_task_send_as_task(
+    _task: *mut doca_comch_producer_task_send,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_cap_is_supported(
+    _devinfo: *const doca_devinfo,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_create(
+    _connection: *mut doca_comch_connection,
+    _buf_mmap: *mut doca_mmap,
+    _consumer: *mut *mut doca_comch_consumer,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_destroy(
+    _consumer: *mut doca_comch_consumer,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_as_ctx(
+    _consumer: *mut doca_comch_consumer,
+) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_get_id(
+    _consumer: *const doca_comch_consumer,
+    _id: *mut u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_task_post_recv_set_conf(
+    _consumer: *mut doca_comch_consumer,
+    _ok: doca_comch_consumer_task_post_recv_completion_cb_t,
+    _err: doca_comch_consumer_task_post_recv_completion_cb_t,
+    _num_tasks: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_task_post_recv_alloc_init(
+    _consumer: *mut doca_comch_consumer,
+    _buf: *mut doca_buf,
+    _task: *mut *mut doca_comch_consumer_task_post_recv,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_task_post_recv_get_imm_data(
+    _task: *const doca_comch_consumer_task_post_recv,
+) -> *const u8 {
+    ptr::null()
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_task_post_recv_get_imm_data_len(
+    _task: *const doca_comch_consumer_task_post_recv,
+) -> u32 {
+    0
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_task_post_recv_get_producer_id(
+    _task: *const doca_comch_consumer_task_post_recv,
+) -> u32 {
+    0
+}
+
+pub unsafe extern "C" fn doca_comch_consumer_task_post_recv_as_task(
+    _task: *mut doca_comch_consumer_task_post_recv,
+) -> *mut doca_task {
+    ptr::null_mut()
+}
@@ -0,0 +1,71 @@
+//! The names and descriptions of the errors.
+
+use std::os::raw::c_char;
+
+use super::types::*;
+
+macro_rules! c_str {
+    ($s:expr) => {
+        concat!($s, "\0").as_ptr() as *const c_char
+    };
+}
+
+pub unsafe extern "C" fn doca_get_error_string(error: doca_error_t) -> *const c_char {
+    match error {
+        doca_error::DOCA_SUCCESS => c_str!("Success"),
+        doca_error::DOCA_ERROR_UNKNOWN => c_str!("Unknown error"),
+        doca_error::DOCA_ERROR_NOT_PERMITTED => c_str!("Operation not permitted"),
+        doca_error::DOCA_ERROR_IN_USE => c_str!("Resource already in use"),
+        doca_error::DOCA_ERROR_NOT_SUPPORTED => c_str!("Operation not supported"),
+        doca_error::DOCA_ERROR_AGAIN => c_str!("Resource temporarily unavailable, try again"),
+        doca_error::DOCA_ERROR_INVALID_VALUE => c_str!("Invalid input"),
+        doca_error::DOCA_ERROR_NO_MEMORY => c_str!("Memory allocation failure"),
+        doca_error::DOCA_ERROR_INITIALIZATION => c_str!("Resource initialization failure"),
+        doca_error::DOCA_ERROR_TIME_OUT => c_str!("Timer expired waiting for resource"),
+        doca_error::DOCA_ERROR_SHUTDOWN => c_str!("Shut down in process or completed"),
+        doca_error::DOCA_ERROR_CONNECTION_RESET => c_str!("Connection reset by peer"),
+        doca_error::DOCA_ERROR_CONNECTION_ABORTED => c_str!("Connection aborted"),
+        doca_error::DOCA_ERROR_CONNECTION_INPROGRESS => c_str!("Connection in progress"),
+        doca_error::DOCA_ERROR_NOT_CONNECTED => c_str!("Not connected"),
+        doca_error::DOCA_ERROR_NO_LOCK => c_str!("Unable to acquire required lock"),
+        doca_error::DOCA_ERROR_NOT_FOUND => c_str!("Resource not found"),
+        doca_error::DOCA_ERROR_IO_FAILED => c_str!("Input/Output operation failed"),
+        doca_error::DOCA_ERROR_BAD_STATE => c_str!("Bad state"),
+        doca_error::DOCA_ERROR_UNSUPPORTED_VERSION => c_str!("Unsupported version"),
+        doca_error::DOCA_ERROR_OPERATING_SYSTEM => c_str!("Operating system call failure"),
+        doca_error::DOCA_ERROR_DRIVER => c_str!("DOCA Driver call failure"),
+        doca_error::DOCA_ERROR_UNEXPECTED => c_str!("Unexpected result"),
+        #[cfg(feature = "doca2")]
+        doca_error::DOCA_ERROR_IN_PROGRESS => c_str!("Operation in progress"),
+    }
+}
+
+pub unsafe extern "C" fn doca_get_error_name(error: doca_error_t) -> *const c_char {
+    match error {
+        doca_error::DOCA_SUCCESS => c_str!("DOCA_SUCCESS"),
+        doca_error::DOCA_ERROR_UNKNOWN => c_str!("DOCA_ERROR_UNKNOWN"),
+        doca_error::DOCA_ERROR_NOT_PERMITTED => c_str!("DOCA_ERROR_NOT_PERMITTED"),
+        doca_error::DOCA_ERROR_IN_USE => c_str!("DOCA_ERROR_IN_USE"),
+        doca_error::DOCA_ERROR_NOT_SUPPORTED => c_str!("DOCA_ERROR_NOT_SUPPORTED"),
+        doca_error::DOCA_ERROR_AGAIN => c_str!("DOCA_ERROR_AGAIN"),
+        doca_error::DOCA_ERROR_INVALID_VALUE => c_str!("DOCA_ERROR_INVALID_VALUE"),
+        doca_error::DOCA_ERROR_NO_MEMORY => c_str!("DOCA_ERROR_NO_MEMORY"),
+        doca_error::DOCA_ERROR_INITIALIZATION => c_str!("DOCA_ERROR_INITIALIZATION"),
+        doca_error::DOCA_ERROR_TIME_OUT => c_str!("DOCA_ERROR_TIME_OUT"),
+        doca_error::DOCA_ERROR_SHUTDOWN => c_str!("DOCA_ERROR_SHUTDOWN"),
+        doca_error::DOCA_ERROR_CONNECTION_RESET => c_str!("DOCA_ERROR_CONNECTION_RESET"),
+        doca_error::DOCA_ERROR_CONNECTION_ABORTED => c_str!("DOCA_ERROR_CONNECTION_ABORTED"),
+        doca_error::DOCA_ERROR_CONNECTION_INPROGRESS => c_str!("DOCA_ERROR_CONNECTION_INPROGRESS"),
+        doca_error::DOCA_ERROR_NOT_CONNECTED => c_str!("DOCA_ERROR_NOT_CONNECTED"),
+        doca_error::DOCA_ERROR_NO_LOCK => c_str!("DOCA_ERROR_NO_LOCK"),
+        doca_error::DOCA_ERROR_NOT_FOUND => c_str!("DOCA_ERROR_NOT_FOUND"),
+        doca_error::DOCA_ERROR_IO_FAILED => c_str!("DOCA_ERROR_IO_FAILED"),
+        doca_error::DOCA_ERROR_BAD_STATE => c_str!("DOCA_ERROR_BAD_STATE"),
+        doca_error::DOCA_ERROR_UNSUPPORTED_VERSION => c_str!("DOCA_ERROR_UNSUPPORTED_VERSION"),
+        doca_error::DOCA_ERROR_OPERATING_SYSTEM => c_str!("DOCA_ERROR_OPERATING_SYSTEM"),
+        doca_error::DOCA_ERROR_DRIVER => c_str!("DOCA_ERROR_DRIVER"),
+        doca_error::DOCA_ERROR_UNEXPECTED => c_str!("DOCA_ERROR_UNEXPECTED"),
+        #[cfg(feature = "doca2")]
+        doca_error::DOCA_ERROR_IN_PROGRESS => c_str!("DOCA_ERROR_IN_PROGRESS"),
+    }
+}
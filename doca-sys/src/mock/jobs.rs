@@ -0,0 +1,171 @@
+//! The types of the jobs and the work queues of DOCA 1.5, which are replaced by
+//! the tasks and the progress engine in DOCA 2.x.
+
+use std::os::raw::c_int;
+
+use super::types::*;
+
+opaque!(
+    doca_workq,
+    doca_dma,
+    doca_compress,
+    doca_regex,
+    doca_regex_mempool,
+    doca_sha,
+    doca_comm_channel_ep_t,
+    doca_comm_channel_addr_t,
+);
+
+// DOCA_CTX part
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct doca_job {
+    pub type_: c_int,
+    pub flags: c_int,
+    pub ctx: *mut doca_ctx,
+    pub user_data: doca_data,
+}
+
+impl Default for doca_job {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct doca_event {
+    pub type_: c_int,
+    pub user_data: doca_data,
+    pub result: doca_data,
+}
+
+impl Default for doca_event {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+pub type doca_job_flags = u32;
+pub const DOCA_JOB_FLAGS_NONE: doca_job_flags = 0;
+pub type doca_workq_retrieve_flags = u32;
+pub const DOCA_WORKQ_RETRIEVE_FLAGS_NONE: doca_workq_retrieve_flags = 0;
+pub type doca_event_handle_t = c_int;
+
+// DOCA_DMA part
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct doca_dma_job_memcpy {
+    pub base: doca_job,
+    pub dst_buff: *mut doca_buf,
+    pub src_buff: *const doca_buf,
+}
+
+impl Default for doca_dma_job_memcpy {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+pub type doca_dma_job_types = u32;
+pub const DOCA_DMA_JOB_MEMCPY: doca_dma_job_types = 1;
+
+// DOCA_COMPRESS part
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct doca_compress_deflate_job {
+    pub base: doca_job,
+    pub dst_buff: *mut doca_buf,
+    pub src_buff: *const doca_buf,
+    pub output_chksum: *mut u64,
+}
+
+impl Default for doca_compress_deflate_job {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+pub type doca_compress_job_types = u32;
+pub const DOCA_COMPRESS_DEFLATE_JOB: doca_compress_job_types = 257;
+pub const DOCA_DECOMPRESS_DEFLATE_JOB: doca_compress_job_types = 258;
+
+// DOCA_REGEX part
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct doca_regex_match {
+    pub next: *mut doca_regex_match,
+    pub rule_id: u32,
+    pub match_start: u32,
+    pub length: u32,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct doca_regex_search_result {
+    pub detected_matches: u64,
+    pub num_matches: u64,
+    pub matches: *mut doca_regex_match,
+    pub matches_mempool: *mut doca_regex_mempool,
+    pub status_flags: u8,
+}
+
+impl Default for doca_regex_search_result {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct doca_regex_job_search {
+    pub base: doca_job,
+    pub rule_group_ids: [u16; 4],
+    pub buffer: *const doca_buf,
+    pub result: *mut doca_regex_search_result,
+    pub allow_batching: u8,
+}
+
+impl Default for doca_regex_job_search {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+pub type doca_regex_job_types = u32;
+pub const DOCA_REGEX_JOB_SEARCH: doca_regex_job_types = 513;
+pub type doca_regex_status_flag = u32;
+pub const DOCA_REGEX_STATUS_SEARCH_FAILED: doca_regex_status_flag = 1;
+
+// DOCA_SHA part
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct doca_sha_job {
+    pub base: doca_job,
+    pub resp_buf: *mut doca_buf,
+    pub req_buf: *const doca_buf,
+    pub flags: u64,
+}
+
+impl Default for doca_sha_job {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+pub type doca_sha_job_type = u32;
+pub const DOCA_SHA_JOB_SHA1: doca_sha_job_type = 769;
+pub const DOCA_SHA_JOB_SHA256: doca_sha_job_type = 770;
+pub const DOCA_SHA_JOB_SHA512: doca_sha_job_type = 771;
+pub type doca_sha_job_flags = u32;
+pub const DOCA_SHA_JOB_FLAGS_NONE: doca_sha_job_flags = 0;
+
+// DOCA_COMM_CHANNEL part
+
+pub type doca_cc_msg_flag = u32;
+pub const DOCA_CC_MSG_FLAG_NONE: doca_cc_msg_flag = 0;
@@ -0,0 +1,268 @@
+//! The memory maps.
+//!
+//! The export descriptor lists the populated ranges after a magic header. Since the "remote"
+//! memory is in the same process, the mmap created from the export just checks the buffers
+//! against the listed ranges, and the DMA jobs access the memory directly.
+
+use std::os::raw::c_void;
+use std::{mem, ptr, slice};
+
+use super::device::MockDev;
+use super::types::*;
+
+const EXPORT_MAGIC: &[u8; 8] = b"MOCKMMAP";
+
+struct MemRange {
+    addr: usize,
+    len: usize,
+    free_cb: doca_mmap_memrange_free_cb_t,
+    opaque: *mut c_void,
+}
+
+pub(crate) struct MockMmap {
+    ranges: Vec<MemRange>,
+    devs: Vec<*const MockDev>,
+    max_chunks: u32,
+    started: bool,
+    imported: bool,
+}
+
+impl MockMmap {
+    // Check whether the memory of `len` bytes at `addr` is in a populated range
+    pub(crate) fn contains(&self, addr: usize, len: usize) -> bool {
+        self.ranges.iter().any(|r| {
+            addr >= r.addr
+                && addr
+                    .checked_add(len)
+                    .is_some_and(|end| end <= r.addr + r.len)
+        })
+    }
+}
+
+pub(crate) unsafe fn mmap<'a>(mmap: *const doca_mmap) -> Option<&'a mut MockMmap> {
+    (mmap as *mut MockMmap).as_mut()
+}
+
+fn new_mmap(mmap: MockMmap) -> *mut doca_mmap {
+    Box::into_raw(Box::new(mmap)) as *mut doca_mmap
+}
+
+pub unsafe extern "C" fn doca_mmap_create(
+    _user_data: *const doca_data,
+    mmap: *mut *mut doca_mmap,
+) -> doca_error_t {
+    if mmap.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    *mmap = new_mmap(MockMmap {
+        ranges: Vec::new(),
+        devs: Vec::new(),
+        max_chunks: 1,
+        started: false,
+        imported: false,
+    });
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_mmap_destroy(mmap: *mut doca_mmap) -> doca_error_t {
+    if mmap.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+    let mmap = Box::from_raw(mmap as *mut MockMmap);
+    for range in mmap.ranges.iter() {
+        if let Some(free_cb) = range.free_cb {
+            free_cb(range.addr as *mut c_void, range.len, range.opaque);
+        }
+    }
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_mmap_start(mmap: *mut doca_mmap) -> doca_error_t {
+    match self::mmap(mmap) {
+        Some(mmap) if mmap.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(mmap) => {
+            mmap.started = true;
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_mmap_set_max_num_chunks(
+    mmap: *mut doca_mmap,
+    max_num_chunks: u32,
+) -> doca_error_t {
+    match self::mmap(mmap) {
+        Some(mmap) if mmap.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(mmap) => {
+            mmap.max_chunks = max_num_chunks;
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_mmap_set_permissions(
+    mmap: *mut doca_mmap,
+    _access_mask: u32,
+) -> doca_error_t {
+    // every access is permitted to the memory in the same process
+    match self::mmap(mmap) {
+        Some(mmap) if mmap.started => doca_error::DOCA_ERROR_BAD_STATE,
+        Some(_) => doca_error::DOCA_SUCCESS,
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_mmap_dev_add(
+    mmap: *mut doca_mmap,
+    dev: *mut doca_dev,
+) -> doca_error_t {
+    let dev = dev as *const MockDev;
+    match self::mmap(mmap) {
+        Some(_) if dev.is_null() => doca_error::DOCA_ERROR_INVALID_VALUE,
+        Some(mmap) if mmap.imported => doca_error::DOCA_ERROR_NOT_PERMITTED,
+        Some(mmap) if mmap.devs.contains(&dev) => doca_error::DOCA_ERROR_IN_USE,
+        Some(mmap) => {
+            mmap.devs.push(dev);
+            doca_error::DOCA_SUCCESS
+        }
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_mmap_dev_rm(
+    mmap: *mut doca_mmap,
+    dev: *mut doca_dev,
+) -> doca_error_t {
+    let dev = dev as *const MockDev;
+    match self::mmap(mmap) {
+        Some(mmap) => match mmap.devs.iter().position(|d| *d == dev) {
+            Some(idx) => {
+                mmap.devs.remove(idx);
+                doca_error::DOCA_SUCCESS
+            }
+            None => doca_error::DOCA_ERROR_NOT_FOUND,
+        },
+        None => doca_error::DOCA_ERROR_INVALID_VALUE,
+    }
+}
+
+pub unsafe extern "C" fn doca_mmap_populate(
+    mmap: *mut doca_mmap,
+    addr: *mut c_void,
+    len: usize,
+    _pg_sz: usize,
+    free_cb: doca_mmap_memrange_free_cb_t,
+    opaque: *mut c_void,
+) -> doca_error_t {
+    let mmap = match self::mmap(mmap) {
+        Some(mmap) if !addr.is_null() && len > 0 => mmap,
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    if mmap.imported {
+        return doca_error::DOCA_ERROR_NOT_PERMITTED;
+    }
+    if mmap.ranges.len() >= mmap.max_chunks as usize {
+        return doca_error::DOCA_ERROR_NO_MEMORY;
+    }
+
+    mmap.ranges.push(MemRange {
+        addr: addr as usize,
+        len,
+        free_cb,
+        opaque,
+    });
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_mmap_export(
+    mmap: *const doca_mmap,
+    dev: *const doca_dev,
+    export_desc: *mut *mut c_void,
+    export_desc_len: *mut usize,
+) -> doca_error_t {
+    let mmap = match self::mmap(mmap) {
+        Some(mmap) if !export_desc.is_null() && !export_desc_len.is_null() => mmap,
+        _ => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+    if mmap.imported || !mmap.started {
+        return doca_error::DOCA_ERROR_NOT_PERMITTED;
+    }
+    if !mmap.devs.contains(&(dev as *const MockDev)) {
+        return doca_error::DOCA_ERROR_NOT_FOUND;
+    }
+
+    // the magic, the number of the ranges, and then the address and the length of each range
+    let mut desc = EXPORT_MAGIC.to_vec();
+    desc.extend_from_slice(&(mmap.ranges.len() as u64).to_le_bytes());
+    for range in mmap.ranges.iter() {
+        desc.extend_from_slice(&(range.addr as u64).to_le_bytes());
+        desc.extend_from_slice(&(range.len as u64).to_le_bytes());
+    }
+
+    // the caller frees the descriptor with `free`, as the one of the SDK
+    let buf = libc::malloc(desc.len()) as *mut u8;
+    if buf.is_null() {
+        return doca_error::DOCA_ERROR_NO_MEMORY;
+    }
+    ptr::copy_nonoverlapping(desc.as_ptr(), buf, desc.len());
+
+    *export_desc = buf as *mut c_void;
+    *export_desc_len = desc.len();
+    doca_error::DOCA_SUCCESS
+}
+
+pub unsafe extern "C" fn doca_mmap_create_from_export(
+    _user_data: *const doca_data,
+    export_desc: *const c_void,
+    export_desc_len: usize,
+    dev: *mut doca_dev,
+    mmap: *mut *mut doca_mmap,
+) -> doca_error_t {
+    if export_desc.is_null() || dev.is_null() || mmap.is_null() {
+        return doca_error::DOCA_ERROR_INVALID_VALUE;
+    }
+
+    let desc = slice::from_raw_parts(export_desc as *const u8, export_desc_len);
+    let ranges = match parse_export(desc) {
+        Some(ranges) => ranges,
+        None => return doca_error::DOCA_ERROR_INVALID_VALUE,
+    };
+
+    *mmap = new_mmap(MockMmap {
+        max_chunks: ranges.len() as u32,
+        ranges,
+        devs: vec![dev as *const MockDev],
+        started: true,
+        imported: true,
+    });
+    doca_error::DOCA_SUCCESS
+}
+
+fn parse_export(desc: &[u8]) -> Option<Vec<MemRange>> {
+    let read_u64 = |off: usize| -> Option<u64> {
+        let bytes = desc.get(off..off + mem::size_of::<u64>())?;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    };
+
+    if desc.get(..EXPORT_MAGIC.len())? != EXPORT_MAGIC {
+        return None;
+    }
+    let count = read_u64(EXPORT_MAGIC.len())? as usize;
+    if desc.len() != EXPORT_MAGIC.len() + 8 + count.checked_mul(16)? {
+        return None;
+    }
+
+    (0..count)
+        .map(|i| {
+            let off = EXPORT_MAGIC.len() + 8 + i * 16;
+            Some(MemRange {
+                addr: read_u64(off)? as usize,
+                len: read_u64(off + 8)? as usize,
+                free_cb: None,
+                opaque: ptr::null_mut(),
+            })
+        })
+        .collect()
+}
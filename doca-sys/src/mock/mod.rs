@@ -0,0 +1,58 @@
+//! An in-process software implementation of the bound DOCA functions,
+//! built with the `mock` feature in place of the bindings of the SDK.
+//!
+//! It lets the crates over the bindings run their tests on a machine without a BlueField:
+//!
+//! - The devices are fake ones named by their PCIe addresses, see [`set_devices`].
+//! - The memory maps and the buffers check their ranges as the SDK does,
+//!   and the export descriptor lists the populated memory of this process.
+//! - A DMA memcpy job is done by `memcpy` when it is submitted,
+//!   and its completion is retrieved from the work queue as usual.
+//! - The other libraries, e.g., compress and RegEx, return `DOCA_ERROR_NOT_SUPPORTED`.
+//!
+//! With the `doca2` feature, the work queues and the libraries of DOCA 1.5 are left out
+//! as the bindings do, and the tasks of DOCA 2.x are not supported either.
+
+#![allow(clippy::missing_safety_doc)]
+
+macro_rules! opaque {
+    ($($name:ident),* $(,)?) => { $(
+        #[repr(C)]
+        #[derive(Debug, Default, Copy, Clone)]
+        pub struct $name {
+            _unused: [u8; 0],
+        }
+    )* };
+}
+
+mod buf;
+mod device;
+mod error;
+mod mmap;
+mod types;
+
+#[cfg(not(feature = "doca2"))]
+mod ctx;
+#[cfg(not(feature = "doca2"))]
+mod jobs;
+#[cfg(not(feature = "doca2"))]
+mod unsupported;
+
+#[cfg(feature = "doca2")]
+mod doca2;
+
+pub use buf::*;
+pub use device::*;
+pub use error::*;
+pub use mmap::*;
+pub use types::*;
+
+#[cfg(not(feature = "doca2"))]
+pub use ctx::*;
+#[cfg(not(feature = "doca2"))]
+pub use jobs::*;
+#[cfg(not(feature = "doca2"))]
+pub use unsupported::*;
+
+#[cfg(feature = "doca2")]
+pub use doca2::*;
@@ -0,0 +1,122 @@
+//! The types of the SDK, laid out as bindgen generates them from the DOCA headers.
+//!
+//! The handles are opaque, the mock objects behind them are defined in the other modules.
+
+use std::os::raw::c_void;
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum doca_error {
+    DOCA_SUCCESS = 0,
+    DOCA_ERROR_UNKNOWN = 1,
+    DOCA_ERROR_NOT_PERMITTED = 2,
+    DOCA_ERROR_IN_USE = 3,
+    DOCA_ERROR_NOT_SUPPORTED = 4,
+    DOCA_ERROR_AGAIN = 5,
+    DOCA_ERROR_INVALID_VALUE = 6,
+    DOCA_ERROR_NO_MEMORY = 7,
+    DOCA_ERROR_INITIALIZATION = 8,
+    DOCA_ERROR_TIME_OUT = 9,
+    DOCA_ERROR_SHUTDOWN = 10,
+    DOCA_ERROR_CONNECTION_RESET = 11,
+    DOCA_ERROR_CONNECTION_ABORTED = 12,
+    DOCA_ERROR_CONNECTION_INPROGRESS = 13,
+    DOCA_ERROR_NOT_CONNECTED = 14,
+    DOCA_ERROR_NO_LOCK = 15,
+    DOCA_ERROR_NOT_FOUND = 16,
+    DOCA_ERROR_IO_FAILED = 17,
+    DOCA_ERROR_BAD_STATE = 18,
+    DOCA_ERROR_UNSUPPORTED_VERSION = 19,
+    DOCA_ERROR_OPERATING_SYSTEM = 20,
+    DOCA_ERROR_DRIVER = 21,
+    DOCA_ERROR_UNEXPECTED = 22,
+    // the asynchronous start of the contexts of DOCA 2.x
+    #[cfg(feature = "doca2")]
+    DOCA_ERROR_IN_PROGRESS = 26,
+}
+pub type doca_error_t = doca_error;
+
+pub type doca_devinfo_link_state = u32;
+pub const DOCA_DEVINFO_LINK_STATE_UNKNOWN: doca_devinfo_link_state = 0;
+pub const DOCA_DEVINFO_LINK_STATE_DOWN: doca_devinfo_link_state = 1;
+pub const DOCA_DEVINFO_LINK_STATE_UP: doca_devinfo_link_state = 2;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union doca_data {
+    pub ptr: *mut c_void,
+    pub u64: u64,
+}
+
+impl Default for doca_data {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+impl std::fmt::Debug for doca_data {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "doca_data({:#x})", unsafe { self.u64 })
+    }
+}
+
+opaque!(
+    doca_devinfo,
+    doca_dev,
+    doca_devinfo_rep,
+    doca_dev_rep,
+    doca_mmap,
+    doca_buf_inventory,
+    doca_buf,
+    doca_ctx,
+);
+
+// DOCA_DEV part
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct doca_pci_bdf__bindgen_ty_1__bindgen_ty_1 {
+    pub _bitfield_1: u16,
+}
+
+impl doca_pci_bdf__bindgen_ty_1__bindgen_ty_1 {
+    pub fn function(&self) -> u16 {
+        self._bitfield_1 & 0x7
+    }
+
+    pub fn device(&self) -> u16 {
+        (self._bitfield_1 >> 3) & 0x1f
+    }
+
+    pub fn bus(&self) -> u16 {
+        self._bitfield_1 >> 8
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union doca_pci_bdf__bindgen_ty_1 {
+    pub raw: u16,
+    pub __bindgen_anon_1: doca_pci_bdf__bindgen_ty_1__bindgen_ty_1,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct doca_pci_bdf {
+    pub __bindgen_anon_1: doca_pci_bdf__bindgen_ty_1,
+}
+
+impl Default for doca_pci_bdf {
+    fn default() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+pub type doca_dev_rep_filter = u32;
+pub const DOCA_DEV_REP_FILTER_ALL: doca_dev_rep_filter = 0;
+pub const DOCA_DEV_REP_FILTER_NET: doca_dev_rep_filter = 2;
+
+// DOCA_MMAP part
+
+pub type doca_mmap_memrange_free_cb_t =
+    Option<unsafe extern "C" fn(addr: *mut c_void, len: usize, opaque: *mut c_void)>;
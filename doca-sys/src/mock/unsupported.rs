@@ -0,0 +1,372 @@
+//! The libraries the fake devices don't support, i.e., compress, RegEx, SHA, Telemetry
+//! and Comm Channel.
+//!
+//! The engines can't be created, and the capability checks fail, so the callers
+//! take the same path as on a device without them.
+//! Telemetry has no service to report to, so its schemas can't be created either.
+
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use super::jobs::*;
+use super::types::*;
+
+const NOT_SUPPORTED: doca_error_t = doca_error::DOCA_ERROR_NOT_SUPPORTED;
+
+// DOCA_COMPRESS part
+
+pub unsafe extern "C" fn doca_compress_create(_compress: *mut *mut doca_compress) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_compress_destroy(_compress: *mut doca_compress) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_compress_as_ctx(_compress: *mut doca_compress) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_compress_job_get_supported(
+    _devinfo: *mut doca_devinfo,
+    _job_type: doca_compress_job_types,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_compress_get_max_buf_size(
+    _devinfo: *const doca_devinfo,
+    _job_type: doca_compress_job_types,
+    _max_buf_size: *mut u64,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+// DOCA_REGEX part
+
+pub unsafe extern "C" fn doca_regex_create(_regex: *mut *mut doca_regex) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_regex_destroy(_regex: *mut doca_regex) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_regex_as_ctx(_regex: *mut doca_regex) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_regex_job_get_supported(
+    _devinfo: *mut doca_devinfo,
+    _job_type: doca_regex_job_types,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_regex_get_maximum_job_size(
+    _devinfo: *const doca_devinfo,
+    _max_job_len: *mut u64,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_regex_set_hardware_compiled_rules(
+    _regex: *mut doca_regex,
+    _rules_data: *const c_void,
+    _rules_data_size: usize,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_regex_set_workq_matches_memory_pool_size(
+    _regex: *mut doca_regex,
+    _nb_matches: u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_regex_mempool_put_obj(
+    _pool: *mut doca_regex_mempool,
+    _obj: *mut c_void,
+) {
+}
+
+// DOCA_SHA part
+
+pub unsafe extern "C" fn doca_sha_create(_sha: *mut *mut doca_sha) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_sha_destroy(_sha: *mut doca_sha) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_sha_as_ctx(_sha: *mut doca_sha) -> *mut doca_ctx {
+    ptr::null_mut()
+}
+
+pub unsafe extern "C" fn doca_sha_job_get_supported(
+    _devinfo: *mut doca_devinfo,
+    _job_type: doca_sha_job_type,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_sha_get_max_src_buffer_size(
+    _devinfo: *mut doca_devinfo,
+    _max_buffer_size: *mut u64,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_sha_get_min_dst_buffer_size(
+    _devinfo: *mut doca_devinfo,
+    _job_type: doca_sha_job_type,
+    _min_buffer_size: *mut u32,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+// DOCA_TELEMETRY part
+
+opaque!(
+    doca_telemetry_schema,
+    doca_telemetry_type,
+    doca_telemetry_field,
+    doca_telemetry_source,
+);
+
+pub type doca_telemetry_type_index_t = u8;
+pub type doca_telemetry_timestamp_t = u64;
+
+pub unsafe extern "C" fn doca_telemetry_schema_init(
+    _schema_name: *const c_char,
+    _doca_schema: *mut *mut doca_telemetry_schema,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_schema_start(
+    _doca_schema: *mut doca_telemetry_schema,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_schema_destroy(
+    _doca_schema: *mut doca_telemetry_schema,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_schema_add_type(
+    _doca_schema: *mut doca_telemetry_schema,
+    _new_type_name: *const c_char,
+    _fields: *mut doca_telemetry_type,
+    _type_index: *mut doca_telemetry_type_index_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_type_create(
+    _doca_type: *mut *mut doca_telemetry_type,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_type_destroy(
+    _doca_type: *mut doca_telemetry_type,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_type_add_field(
+    _doca_type: *mut doca_telemetry_type,
+    _field: *mut doca_telemetry_field,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_field_create(
+    _field: *mut *mut doca_telemetry_field,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_field_destroy(
+    _field: *mut doca_telemetry_field,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_field_set_name(
+    _field: *mut doca_telemetry_field,
+    _name: *const c_char,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_field_set_description(
+    _field: *mut doca_telemetry_field,
+    _desc: *const c_char,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_field_set_type_name(
+    _field: *mut doca_telemetry_field,
+    _type: *const c_char,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_field_set_array_length(
+    _field: *mut doca_telemetry_field,
+    _array_len: u16,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_source_create(
+    _doca_schema: *mut doca_telemetry_schema,
+    _doca_source: *mut *mut doca_telemetry_source,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_source_destroy(
+    _doca_source: *mut doca_telemetry_source,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_source_set_id(
+    _doca_source: *mut doca_telemetry_source,
+    _source_id: *const c_char,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_source_set_tag(
+    _doca_source: *mut doca_telemetry_source,
+    _source_tag: *const c_char,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_source_start(
+    _doca_source: *mut doca_telemetry_source,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_source_report(
+    _doca_source: *mut doca_telemetry_source,
+    _index: doca_telemetry_type_index_t,
+    _data: *mut c_void,
+    _count: c_int,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_source_flush(
+    _doca_source: *mut doca_telemetry_source,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_telemetry_get_timestamp(
+    _timestamp: *mut doca_telemetry_timestamp_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+// DOCA_COMM_CHANNEL part
+
+pub unsafe extern "C" fn doca_comm_channel_ep_create(
+    _ep: *mut *mut doca_comm_channel_ep_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_destroy(
+    _ep: *mut doca_comm_channel_ep_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_set_device(
+    _ep: *mut doca_comm_channel_ep_t,
+    _dev: *mut doca_dev,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_set_device_rep(
+    _ep: *mut doca_comm_channel_ep_t,
+    _dev_rep: *mut doca_dev_rep,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_set_max_msg_size(
+    _ep: *mut doca_comm_channel_ep_t,
+    _max_msg_size: u16,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_set_send_queue_size(
+    _ep: *mut doca_comm_channel_ep_t,
+    _send_queue_size: u16,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_set_recv_queue_size(
+    _ep: *mut doca_comm_channel_ep_t,
+    _recv_queue_size: u16,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_listen(
+    _ep: *mut doca_comm_channel_ep_t,
+    _name: *const c_char,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_connect(
+    _ep: *mut doca_comm_channel_ep_t,
+    _name: *const c_char,
+    _peer_addr: *mut *mut doca_comm_channel_addr_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_sendto(
+    _ep: *mut doca_comm_channel_ep_t,
+    _msg: *const c_void,
+    _len: usize,
+    _flags: c_int,
+    _peer_addr: *mut doca_comm_channel_addr_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_recvfrom(
+    _ep: *mut doca_comm_channel_ep_t,
+    _msg: *mut c_void,
+    _len: *mut usize,
+    _flags: c_int,
+    _peer_addr: *mut *mut doca_comm_channel_addr_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
+
+pub unsafe extern "C" fn doca_comm_channel_ep_disconnect(
+    _ep: *mut doca_comm_channel_ep_t,
+    _peer_addr: *mut doca_comm_channel_addr_t,
+) -> doca_error_t {
+    NOT_SUPPORTED
+}
@@ -1,6 +1,24 @@
+#include <doca_error.h>
 #include <doca_dev.h>
 #include <doca_mmap.h>
 #include <doca_ctx.h>
 #include <doca_buf_inventory.h>
 #include <doca_buf.h>
-#include <doca_dma.h>
\ No newline at end of file
+#include <doca_dma.h>
+
+#ifdef RUST_DOCA2
+#include <doca_pe.h>
+#include <doca_erasure_coding.h>
+#include <doca_aes_gcm.h>
+#include <doca_rdma.h>
+#include <doca_comch.h>
+#include <doca_comch_producer.h>
+#include <doca_comch_consumer.h>
+#else
+#include <doca_compress.h>
+#include <doca_regex.h>
+#include <doca_regex_mempool.h>
+#include <doca_sha.h>
+#include <doca_telemetry.h>
+#include <doca_comm_channel.h>
+#endif
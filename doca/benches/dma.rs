@@ -0,0 +1,50 @@
+//! Sweep the DMA copy sizes, queue depths and batch sizes with [`dma_bench`],
+//! printing a JSON line per configuration.
+//!
+//! The device is chosen by the `DOCA_BENCH_PCI` environment variable:
+//! ``` shell
+//! $ DOCA_BENCH_PCI="03:00.0" cargo bench --bench dma
+//! ```
+
+#![cfg_attr(feature = "doca2", allow(dead_code, unused_imports))]
+
+#[cfg(not(feature = "doca2"))]
+use doca::samples::{dma_bench, BenchConfig};
+
+const SIZES: [usize; 4] = [64, 4096, 64 * 1024, 1024 * 1024];
+const DEPTHS: [u32; 3] = [1, 16, 64];
+const BATCHES: [usize; 3] = [1, 8, 16];
+
+// enough to move about 1 GiB for the large copies, while keeping the small ones short
+fn iterations(size: usize) -> usize {
+    ((1usize << 30) / size).clamp(1_000, 100_000)
+}
+
+#[cfg(not(feature = "doca2"))]
+fn main() {
+    let pci_addr = std::env::var("DOCA_BENCH_PCI").unwrap_or_else(|_| "03:00.0".to_string());
+
+    for size in SIZES {
+        for depth in DEPTHS {
+            for batch in BATCHES.iter().copied().filter(|b| *b <= depth as usize) {
+                let config = BenchConfig {
+                    pci_addr: pci_addr.clone(),
+                    size,
+                    depth,
+                    batch,
+                    iterations: iterations(size),
+                };
+                match dma_bench(&config) {
+                    Ok(report) => println!("{}", report.to_json()),
+                    Err(e) => eprintln!("{:?} failed: {}", config, e),
+                }
+            }
+        }
+    }
+}
+
+// The DMA jobs run on the work queue of DOCA 1.5, which is left out with `doca2`
+#[cfg(feature = "doca2")]
+fn main() {
+    eprintln!("The bench runs the DMA jobs of DOCA 1.5, build it without the `doca2` feature");
+}
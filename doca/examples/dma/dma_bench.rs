@@ -0,0 +1,39 @@
+#![cfg_attr(feature = "doca2", allow(dead_code, unused_imports))]
+
+use clap::{arg, App, AppSettings};
+#[cfg(not(feature = "doca2"))]
+use doca::samples::{dma_bench, BenchConfig};
+
+#[cfg(not(feature = "doca2"))]
+fn main() {
+    let matches = App::new("doca dma bench")
+        .version("0.1")
+        .about("Measure the throughput and latency of the local DMA copies")
+        .setting(AppSettings::AllArgsOverrideSelf)
+        .args(&[
+            arg!(--pci <DEV_PCI> "DOCA DMA Device PCI address"),
+            arg!(--size [SIZE] "The bytes copied by each job"),
+            arg!(--depth [DEPTH] "The depth of the work queue, at most 64"),
+            arg!(--batch [BATCH] "The number of the jobs submitted together"),
+            arg!(--iters [ITERS] "The number of the jobs to run"),
+        ])
+        .get_matches();
+
+    let config = BenchConfig {
+        pci_addr: matches.value_of("pci").unwrap_or("03:00.0").to_string(),
+        size: matches.value_of_t("size").unwrap_or(4096),
+        depth: matches.value_of_t("depth").unwrap_or(32),
+        batch: matches.value_of_t("batch").unwrap_or(8),
+        iterations: matches.value_of_t("iters").unwrap_or(100_000),
+    };
+
+    // one JSON line per run, so the output can be collected by scripts
+    let report = dma_bench(&config).expect("Bench failed!");
+    println!("{}", report.to_json());
+}
+
+// The DMA jobs run on the work queue of DOCA 1.5, which is left out with `doca2`
+#[cfg(feature = "doca2")]
+fn main() {
+    eprintln!("The example runs the DMA jobs of DOCA 1.5, build it without the `doca2` feature");
+}
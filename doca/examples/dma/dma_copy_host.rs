@@ -1,9 +1,5 @@
-#![feature(get_mut_unchecked)]
-
-use std::{ptr::NonNull, sync::Arc};
-
 use clap::{arg, App, AppSettings};
-use doca::*;
+use doca::samples::{host_export_loop, FileTransport};
 
 fn main() {
     let matches = App::new("doca remote copy")
@@ -43,40 +39,21 @@ fn main() {
     let str = String::from_utf8(src_buffer.to_vec()).unwrap();
     println!("src_buffer check: {}", str);
 
-    // Open device
-    let device = doca::device::open_device_with_pci(pci_addr).unwrap();
-
-    let mut local_mmap = Arc::new(DOCAMmap::new().unwrap());
-    let local_mmap_ref = unsafe { Arc::get_mut_unchecked(&mut local_mmap) };
-
-    let dev_idx = local_mmap_ref.add_device(&device).unwrap();
-
-    let src_raw = RawPointer {
-        inner: NonNull::new(src_buffer.as_mut_ptr() as *mut _).unwrap(),
-        payload: length,
-    };
-
-    // populate the buffer into the mmap
-    local_mmap_ref.populate(src_raw).unwrap();
-
-    // and export it into memory so later we can store it into a file
-    let export = local_mmap_ref.export(dev_idx).unwrap();
-    doca::save_config(export, src_raw, export_file, buffer_file).unwrap();
-    println!(
-        "Please copy {} and {} to the DPU and run DMA Copy DPU sample before closing",
-        export_file, buffer_file
-    );
-
     let r = running.clone();
     ctrlc::set_handler(move || {
         r.store(false, std::sync::atomic::Ordering::SeqCst);
     })
     .expect("Error setting Ctrl-C handler");
 
-    while running.load(std::sync::atomic::Ordering::SeqCst) {
-        // Your program's code goes here
-        std::thread::sleep(std::time::Duration::from_millis(1000));
-    }
+    let mut transport = FileTransport::new(export_file, buffer_file);
+    println!(
+        "Please copy {} and {} to the DPU and run DMA Copy DPU sample before closing",
+        export_file, buffer_file
+    );
+    host_export_loop(pci_addr, &mut src_buffer, &mut transport, || {
+        running.load(std::sync::atomic::Ordering::SeqCst)
+    })
+    .unwrap();
 
     println!("Server is down!");
 }
@@ -0,0 +1,157 @@
+#![cfg_attr(feature = "doca2", allow(dead_code, unused_imports))]
+
+use std::time::Duration;
+
+use clap::{arg, App, AppSettings};
+use doca::load_config;
+use doca::prelude::*;
+
+// The layout of a slot, which must match `dma_echo_host.rs`:
+// the sequence number, the length of the message, and then the message itself
+const SEQ_OFF: usize = 0;
+const LEN_OFF: usize = 8;
+const DATA_OFF: usize = 16;
+const MAX_MSG: usize = 4096;
+const SLOT_SIZE: usize = DATA_OFF + MAX_MSG;
+
+// The sequence number telling the DPU to stop
+const STOP: u64 = u64::MAX;
+
+#[cfg(not(feature = "doca2"))]
+fn main() {
+    let matches = App::new("doca dma echo")
+        .version("0.1")
+        .about("The doca dma echo service on DPU Side")
+        .setting(AppSettings::AllArgsOverrideSelf)
+        .args(&[
+            arg!(--pci <DEV_PCI> "DOCA DMA Device PCI address"),
+            arg!(--timeout [MILLIS] "How long to wait for each DMA job"),
+            arg!(--export [FILE_PATH] "export descriptor file path"),
+            arg!(--buffer [FILE_PATH] "buffer info file path"),
+        ])
+        .get_matches();
+
+    let pci_addr = matches.value_of("pci").unwrap_or("03:00.0");
+    let timeout = Duration::from_millis(matches.value_of_t("timeout").unwrap_or(1000));
+    let export_file = matches.value_of("export").unwrap_or("/tmp/export.txt");
+    let buffer_file = matches.value_of("buffer").unwrap_or("/tmp/buffer.txt");
+
+    // the host saves the request slot and then the response slot
+    let remote_configs = load_config(export_file, buffer_file).unwrap();
+    let (remote_request, remote_response) = match remote_configs.remote_addrs[..] {
+        [request, response] if request.payload >= SLOT_SIZE && response.payload >= SLOT_SIZE => {
+            (request, response)
+        }
+        _ => panic!("The buffer info file should hold the request and the response slots"),
+    };
+
+    let device = open_device_with_pci(pci_addr).unwrap();
+    let dma = DMAEngine::new().unwrap();
+    let ctx = DOCAContext::new(&dma, vec![device.clone()]).unwrap();
+    let mut workq = DOCAWorkQueue::new(2, &ctx).unwrap();
+
+    let remote_mmap = RemoteMmap::from_export_on_dpu(&remote_configs.export_desc, &device).unwrap();
+    let local_mmap = LocalMmap::new().unwrap();
+    local_mmap.add_device(&device).unwrap();
+
+    // the local copies of the slots
+    let mut request = vec![0u8; SLOT_SIZE];
+    let mut response = vec![0u8; SLOT_SIZE];
+
+    // one job for each direction, reused for all the copies
+    let inv = BufferInventory::new(4).unwrap();
+    let remote_buf = DOCARegisteredMemory::new_from_remote(&remote_mmap, remote_request)
+        .unwrap()
+        .to_buffer(&inv)
+        .unwrap();
+    let local_buf = DOCARegisteredMemory::new(&local_mmap, MemRegion::new(&mut request).unwrap())
+        .unwrap()
+        .to_buffer(&inv)
+        .unwrap();
+    let mut read_job = workq.create_dma_job(remote_buf, local_buf);
+
+    let local_buf = DOCARegisteredMemory::new(&local_mmap, MemRegion::new(&mut response).unwrap())
+        .unwrap()
+        .to_buffer(&inv)
+        .unwrap();
+    let remote_buf = DOCARegisteredMemory::new_from_remote(&remote_mmap, remote_response)
+        .unwrap()
+        .to_buffer(&inv)
+        .unwrap();
+    let mut write_job = workq.create_dma_job(local_buf, remote_buf);
+
+    println!("Waiting for the requests of the host");
+    let mut last_seq = 0;
+    loop {
+        // poll the doorbell, i.e., the header of the request slot
+        read_job
+            .set_src_data(SEQ_OFF, DATA_OFF)
+            .set_dst_offset(SEQ_OFF);
+        run(&mut workq, &mut read_job, timeout);
+
+        let seq = read_u64(&request, SEQ_OFF);
+        if seq == STOP {
+            break;
+        }
+        if seq == last_seq {
+            std::thread::sleep(Duration::from_micros(100));
+            continue;
+        }
+        last_seq = seq;
+
+        // fetch the message, and echo it back in upper case
+        let len = (read_u64(&request, LEN_OFF) as usize).min(MAX_MSG);
+        if len > 0 {
+            read_job
+                .set_src_data(DATA_OFF, len)
+                .set_dst_offset(DATA_OFF);
+            run(&mut workq, &mut read_job, timeout);
+        }
+        let message = &request[DATA_OFF..DATA_OFF + len];
+        println!("[{}] {}", seq, String::from_utf8_lossy(message));
+
+        let reply = message.to_ascii_uppercase();
+        response[DATA_OFF..DATA_OFF + len].copy_from_slice(&reply);
+        response[LEN_OFF..DATA_OFF].copy_from_slice(&(len as u64).to_le_bytes());
+        write_job
+            .set_src_data(LEN_OFF, DATA_OFF - LEN_OFF + len)
+            .set_dst_offset(LEN_OFF);
+        run(&mut workq, &mut write_job, timeout);
+
+        // publish the sequence number only after the message has landed on the host
+        response[SEQ_OFF..LEN_OFF].copy_from_slice(&seq.to_le_bytes());
+        write_job
+            .set_src_data(SEQ_OFF, LEN_OFF)
+            .set_dst_offset(SEQ_OFF);
+        run(&mut workq, &mut write_job, timeout);
+    }
+
+    println!("Echo finished after {} requests!", last_seq);
+}
+
+// Submit the job, and wait for its completion, which must be the one of this submission
+#[cfg(not(feature = "doca2"))]
+fn run(workq: &mut DOCAWorkQueue<DMAEngine>, job: &mut DOCADMAJob, timeout: Duration) {
+    let handle = workq.submit(job).expect("Failed to submit the DMA job");
+    let completion = workq
+        .poll_completion_timeout(timeout)
+        .expect("The DMA job didn't finish in time");
+    assert_eq!(completion.handle, handle, "Unexpected completion");
+
+    let result = job.dma_result(&completion);
+    if !result.is_success() {
+        panic!("The DMA job failed: {:?}", result.status);
+    }
+}
+
+fn read_u64(slot: &[u8], off: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&slot[off..off + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+// The DMA jobs run on the work queue of DOCA 1.5, which is left out with `doca2`
+#[cfg(feature = "doca2")]
+fn main() {
+    eprintln!("The example runs the DMA jobs of DOCA 1.5, build it without the `doca2` feature");
+}
@@ -0,0 +1,118 @@
+use std::ptr;
+use std::sync::atomic::{fence, Ordering};
+use std::time::{Duration, Instant};
+
+use clap::{arg, App, AppSettings};
+use doca::memory::allocator::HugePageBuffer;
+use doca::prelude::*;
+use doca::{save_config_regions, MmapPermissions};
+
+// The layout of a slot, which must match `dma_echo_dpu.rs`:
+// the sequence number, the length of the message, and then the message itself
+const SEQ_OFF: usize = 0;
+const LEN_OFF: usize = 8;
+const DATA_OFF: usize = 16;
+const MAX_MSG: usize = 4096;
+const SLOT_SIZE: usize = DATA_OFF + MAX_MSG;
+
+// The sequence number telling the DPU to stop
+const STOP: u64 = u64::MAX;
+
+fn main() {
+    let matches = App::new("doca dma echo")
+        .version("0.1")
+        .about("The doca dma echo service on Host Side")
+        .setting(AppSettings::AllArgsOverrideSelf)
+        .args(&[
+            arg!(--pci <DEV_PCI> "DOCA DMA Device PCI address"),
+            arg!(--txt [ECHO_TEXT] "The text of the requests"),
+            arg!(--count [COUNT] "The number of the requests"),
+            arg!(--timeout [SECONDS] "How long to wait for each response"),
+            arg!(--export [FILE_PATH] "export descriptor file path"),
+            arg!(--buffer [FILE_PATH] "buffer info file path"),
+        ])
+        .get_matches();
+
+    let pci_addr = matches.value_of("pci").unwrap_or("17:00.0");
+    let txt = matches.value_of("txt").unwrap_or("Hello DPU");
+    let count: u64 = matches.value_of_t("count").unwrap_or(10);
+    let timeout = Duration::from_secs(matches.value_of_t("timeout").unwrap_or(60));
+    let export_file = matches.value_of("export").unwrap_or("/tmp/export.txt");
+    let buffer_file = matches.value_of("buffer").unwrap_or("/tmp/buffer.txt");
+
+    // The request slot followed by the response slot, in page-aligned pinned memory.
+    // The DPU reads the request and writes the response, so the PCI side may write the memory.
+    let mut region = HugePageBuffer::new_or_pages(2 * SLOT_SIZE).unwrap();
+    let raw = RawPointer::from(&region);
+
+    let device = open_device_with_pci(pci_addr).unwrap();
+    let mmap = LocalMmap::builder()
+        .permissions(MmapPermissions::LOCAL_READ_WRITE | MmapPermissions::PCI_READ_WRITE)
+        .build()
+        .unwrap();
+    let dev_idx = mmap.add_device(&device).unwrap();
+    mmap.populate(raw).unwrap();
+
+    // publish both slots, so the DPU finds them in `LoadedInfo::remote_addrs`
+    let (request, response) = (
+        raw.slice(0, SLOT_SIZE).unwrap(),
+        raw.slice(SLOT_SIZE, SLOT_SIZE).unwrap(),
+    );
+    let export = mmap.export_from_host(dev_idx).unwrap();
+    save_config_regions(&export, &[request, response], export_file, buffer_file).unwrap();
+    println!(
+        "Please copy {} and {} to the DPU and run the DMA echo DPU sample",
+        export_file, buffer_file
+    );
+
+    let base = region.as_mut_slice().as_mut_ptr();
+    let (request, response) = (base, unsafe { base.add(SLOT_SIZE) });
+
+    for seq in 1..=count {
+        let msg = format!("{} #{}", txt, seq);
+        let msg = &msg.as_bytes()[..msg.len().min(MAX_MSG)];
+
+        // fill the request, and then ring the doorbell by bumping its sequence number
+        unsafe {
+            ptr::copy_nonoverlapping(msg.as_ptr(), request.add(DATA_OFF), msg.len());
+            write_u64(request.add(LEN_OFF), msg.len() as u64);
+            fence(Ordering::Release);
+            write_u64(request.add(SEQ_OFF), seq);
+        }
+
+        // the DPU writes the sequence number of the response after its message
+        let deadline = Instant::now() + timeout;
+        while unsafe { read_u64(response.add(SEQ_OFF)) } != seq {
+            if Instant::now() > deadline {
+                eprintln!("No response to request {} in {:?}", seq, timeout);
+                std::process::exit(1);
+            }
+            std::thread::sleep(Duration::from_micros(100));
+        }
+        fence(Ordering::Acquire);
+
+        let reply = unsafe {
+            let len = (read_u64(response.add(LEN_OFF)) as usize).min(MAX_MSG);
+            std::slice::from_raw_parts(response.add(DATA_OFF), len).to_vec()
+        };
+        println!("[{}] {}", seq, String::from_utf8_lossy(&reply));
+    }
+
+    unsafe { write_u64(request.add(SEQ_OFF), STOP) };
+    println!("Echo finished!");
+
+    // keep the memory registered until the DPU has seen the stop request
+    std::thread::sleep(Duration::from_secs(1));
+    drop(mmap);
+    drop(region);
+}
+
+// The slots are accessed by the DPU concurrently, so read and write them volatilely.
+// The fields are aligned, since the slots start at a page and have a size of a multiple of 8.
+unsafe fn read_u64(ptr: *const u8) -> u64 {
+    u64::from_le(ptr::read_volatile(ptr as *const u64))
+}
+
+unsafe fn write_u64(ptr: *mut u8, value: u64) {
+    ptr::write_volatile(ptr as *mut u64, value.to_le())
+}
@@ -1,10 +1,10 @@
-#![feature(get_mut_unchecked)]
+#![cfg_attr(feature = "doca2", allow(dead_code, unused_imports))]
 
 use clap::{arg, App, AppSettings};
-use doca::{dma::DOCAContext, *};
-
-use std::sync::Arc;
+#[cfg(not(feature = "doca2"))]
+use doca::samples::local_copy;
 
+#[cfg(not(feature = "doca2"))]
 fn main() {
     let matches = App::new("doca dma local copy")
         .version("0.1")
@@ -14,6 +14,7 @@ fn main() {
         .args(&[
             arg!(--pci <DEV_PCI> "DOCA DMA Device PCI address"),
             arg!(--txt [COPY_TEXT] "The text to be delivered"),
+            arg!(--verify "Verify the copied data against the source checksum"),
         ])
         .get_matches();
 
@@ -22,6 +23,8 @@ fn main() {
         .value_of("txt")
         .unwrap_or("This is a sample copy text");
 
+    let verify = matches.is_present("verify");
+
     let length = cpy_txt.as_bytes().len();
 
     println!(
@@ -30,7 +33,6 @@ fn main() {
     );
 
     // first malloc the destination buffer
-    #[allow(unused_mut)]
     let mut dst_buffer = vec![0u8; length].into_boxed_slice();
     let mut src_buffer = vec![0u8; length].into_boxed_slice();
 
@@ -43,62 +45,22 @@ fn main() {
     );
 
     /* ********** The main test body ********** */
-
-    // Create a DMA_ENGINE;
-    let device = crate::open_device_with_pci(pci_addr).unwrap();
-
-    let dma = DMAEngine::new().unwrap();
-
-    let ctx = DOCAContext::new(&dma, vec![device.clone()]).unwrap();
-
-    let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
-
-    let mut doca_mmap = Arc::new(DOCAMmap::new().unwrap());
-    unsafe {
-        Arc::get_mut_unchecked(&mut doca_mmap)
-            .add_device(&device)
-            .unwrap()
-    };
-
-    let inv = BufferInventory::new(1024).unwrap();
-    let mut dma_src_buf =
-        DOCARegisteredMemory::new(&doca_mmap, unsafe { RawPointer::from_box(&src_buffer) })
-            .unwrap()
-            .to_buffer(&inv)
-            .unwrap();
-    unsafe { dma_src_buf.set_data(0, length).unwrap() };
-
-    let dma_dst_buf =
-        DOCARegisteredMemory::new(&doca_mmap, unsafe { RawPointer::from_box(&dst_buffer) })
-            .unwrap()
-            .to_buffer(&inv)
-            .unwrap();
-
-    /* Start to submit the DMA job!  */
-    let job = workq.create_dma_job(dma_src_buf, dma_dst_buf);
-    workq.submit(&job).expect("failed to submit the job");
-
-    loop {
-        let event = workq.poll_completion();
-        match event {
-            Ok(_e) => {
-                println!("Job finished!");
-                break;
-            }
-            Err(e) => {
-                if e == DOCAError::DOCA_ERROR_AGAIN {
-                    continue;
-                } else {
-                    panic!("Job failed! {:?}", e);
-                }
-            }
-        }
-    }
+    local_copy(pci_addr, &mut src_buffer, &mut dst_buffer, verify).expect("Job failed!");
+    println!("Job finished!");
 
     /* ------- Finalize check ---------- */
+    if verify {
+        println!("[After] dst_buffer verified");
+    }
     println!(
         "[After] src_buffer and dst_buffer check: {} || {}",
         String::from_utf8(src_buffer.to_vec()).unwrap(),
         String::from_utf8(dst_buffer.to_vec()).unwrap()
     );
 }
+
+// The DMA jobs run on the work queue of DOCA 1.5, which is left out with `doca2`
+#[cfg(feature = "doca2")]
+fn main() {
+    eprintln!("The example runs the DMA jobs of DOCA 1.5, build it without the `doca2` feature");
+}
@@ -0,0 +1,98 @@
+//! Unwind-safe shims for Rust closures called by DOCA.
+//!
+//! Unwinding across the C boundary is undefined behavior, so every Rust closure
+//! handed to the SDK (e.g., the free callback of [`LocalMmap::populate_with_free_cb`])
+//! is invoked through [`guard`], which catches the panic instead of letting it
+//! escape into the SDK. The callbacks invoked by the wrapper itself, e.g.,
+//! [`DOCAContext::on_state_change`], are guarded the same way.
+//!
+//! A caught panic is reported as a `tracing` event with the `tracing` feature and recorded,
+//! so the application can check whether one of its callbacks failed:
+//!
+//! ```
+//! for p in doca::callback::take_panics() {
+//!     println!("{}", p);
+//! }
+//! ```
+//!
+//! [`LocalMmap::populate_with_free_cb`]: crate::LocalMmap::populate_with_free_cb
+//! [`DOCAContext::on_state_change`]: crate::context::DOCAContext::on_state_change
+
+use std::any::Any;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Mutex;
+
+/// A panic caught in a user callback
+#[derive(Clone, Debug)]
+pub struct CallbackPanic {
+    /// The callback that panicked
+    pub callback: &'static str,
+    /// The panic message, if it is a string
+    pub message: String,
+}
+
+impl fmt::Display for CallbackPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} panicked: {}", self.callback, self.message)
+    }
+}
+
+static PANICS: Mutex<Vec<CallbackPanic>> = Mutex::new(Vec::new());
+
+/// Run the user callback `f`, catching the panic inside it.
+/// Return `None` if the callback panicked.
+pub(crate) fn guard<R, F: FnOnce() -> R>(callback: &'static str, f: F) -> Option<R> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(res) => Some(res),
+        Err(payload) => {
+            let panic = CallbackPanic {
+                callback,
+                message: panic_message(payload.as_ref()),
+            };
+            debug_event!(callback, message = %panic.message, "callback panicked");
+            lock(&PANICS).push(panic);
+            None
+        }
+    }
+}
+
+/// Take the callback panics caught so far
+pub fn take_panics() -> Vec<CallbackPanic> {
+    std::mem::take(&mut *lock(&PANICS))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        String::from("<non-string panic payload>")
+    }
+}
+
+// The shims should keep working even if a panic happened while holding the lock
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_catches_panic() {
+        assert_eq!(guard("test ok callback", || 1), Some(1));
+
+        let res: Option<()> = guard("test panic callback", || panic!("boom {}", 42));
+        assert!(res.is_none());
+
+        let panics: Vec<_> = take_panics()
+            .into_iter()
+            .filter(|p| p.callback == "test panic callback")
+            .collect();
+        assert_eq!(panics.len(), 1);
+        assert_eq!(panics[0].message, "boom 42");
+    }
+}
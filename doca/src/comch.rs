@@ -0,0 +1,525 @@
+//! Wrapper for the Comch library of DOCA 2.x, whose producers and consumers pass
+//! [`DOCABuffer`]s between the host and the DPU on the fast path, at message rates beyond
+//! the endpoints of the legacy comm channel of DOCA 1.5.
+//!
+//! - [`ComchClient`] connects to the Comch server of the given name on the DPU.
+//! It is connected to a [`ProgressEngine`] on creation, and the connection is established
+//! while the engine progresses, see [`ComchClient::is_running`].
+//!
+//! - [`ComchProducer`] sends buffers with immediate data to a consumer of the peer,
+//! which is named by the id returned by [`ComchConsumer::id`] of the peer,
+//! e.g., exchanged with a message of the connection.
+//!
+//! - [`ComchConsumer`] posts the buffers of a local mmap to receive the buffers sent by
+//! the producers of the peer. It registers itself to the peer while the engine progresses.
+//!
+//! - [`ComchSendJob`] and [`ComchRecvJob`] are a send of a producer and a receive of a
+//! consumer. They own their buffers until they complete, and give them back to the closures
+//! passed to their `submit`.
+//!
+//! The module is only built with the `doca2` feature, since the library doesn't exist in DOCA 1.5.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::comch::{ComchClient, ComchConsumer, ComchProducer};
+//! use doca::progress_engine::ProgressEngine;
+//!
+//! # fn run(mmap: &std::sync::Arc<doca::DOCAMmap>, local: doca::DOCABuffer, received: doca::DOCABuffer, peer_id: u32) {
+//! let device = doca::open_device_with_pci("03:00.0").unwrap();
+//! let mut pe = ProgressEngine::new().unwrap();
+//! let client = unsafe { ComchClient::new(&device, "server", &mut pe) }.unwrap();
+//! while !client.is_running() {
+//!     pe.progress();
+//! }
+//!
+//! let producer = unsafe { ComchProducer::new(&client, &mut pe, 16) }.unwrap();
+//! let consumer = unsafe { ComchConsumer::new(&client, mmap, &mut pe, 16) }.unwrap();
+//! while !consumer.is_running() {
+//!     pe.progress();
+//! }
+//!
+//! let recv = consumer.recv(received).unwrap();
+//! recv.submit(&mut pe, |_buf, res| println!("received {:?}", res.unwrap().imm_data))
+//!     .unwrap();
+//! let send = producer.send(local, b"hello", peer_id).unwrap();
+//! send.submit(&mut pe, |_buf, res| res.unwrap()).unwrap();
+//!
+//! while pe.inflight().unwrap() > 0 {
+//!     pe.progress();
+//! }
+//! # }
+//! ```
+//!
+
+use std::ffi::CString;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use ffi::doca_error;
+
+use crate::error::check;
+use crate::progress_engine::{completion_trampoline, ProgressEngine, RawTask, Task};
+use crate::{DOCABuffer, DOCAMmap, DOCAResult, DevContext, Device, DocaError};
+
+impl RawTask for ffi::doca_comch_producer_task_send {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_comch_producer_task_send_as_task(task)
+    }
+}
+
+impl RawTask for ffi::doca_comch_consumer_task_post_recv {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_comch_consumer_task_post_recv_as_task(task)
+    }
+}
+
+// Start a context connected to a progress engine.
+// The client and the consumer connect to the peer asynchronously, so their start is in progress
+// until the engine progresses.
+unsafe fn start(ctx: *mut ffi::doca_ctx) -> DOCAResult<()> {
+    match ffi::doca_ctx_start(ctx) {
+        doca_error::DOCA_ERROR_IN_PROGRESS => Ok(()),
+        ret => check(ret),
+    }
+}
+
+// Check whether a context has finished starting
+unsafe fn is_running(ctx: *mut ffi::doca_ctx) -> bool {
+    let mut state: ffi::doca_ctx_states = ffi::DOCA_CTX_STATE_IDLE;
+    let ret = ffi::doca_ctx_get_state(ctx, &mut state as *mut _);
+    ret == doca_error::DOCA_SUCCESS && state == ffi::DOCA_CTX_STATE_RUNNING
+}
+
+// The name of a Comch server, which can't be empty
+fn server_name(name: &str) -> DOCAResult<CString> {
+    if name.is_empty() {
+        return Err(DocaError::InvalidValue);
+    }
+    CString::new(name).map_err(|_e| DocaError::InvalidValue)
+}
+
+/// The client of a Comch server, whose connection carries the producers and the consumers
+pub struct ComchClient {
+    inner: NonNull<ffi::doca_comch_client>,
+    // the device should be closed after the client is destroyed
+    #[allow(dead_code)]
+    dev: Arc<DevContext>,
+}
+
+impl Drop for ComchClient {
+    fn drop(&mut self) {
+        // the producers and the consumers hold the client, so they are destroyed already
+        let _ = unsafe { ffi::doca_ctx_stop(self.ctx_ptr()) };
+
+        let ret = unsafe { ffi::doca_comch_client_destroy(self.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the Comch client: {:?}", ret);
+        }
+
+        dropped!("Comch Client is dropped!");
+    }
+}
+
+impl ComchClient {
+    /// Create a client of the server named `name` on the device, connect it to the
+    /// progress engine, and start connecting to the server.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `name` is empty or contains a nul byte.
+    ///
+    /// # Safety
+    ///
+    /// The client must outlive the progress engine, see [`ProgressEngine::connect_ctx`].
+    pub unsafe fn new(
+        dev: &Arc<DevContext>,
+        name: &str,
+        pe: &mut ProgressEngine,
+    ) -> DOCAResult<Arc<Self>> {
+        let name = server_name(name)?;
+        let mut client: *mut ffi::doca_comch_client = std::ptr::null_mut();
+        let ret =
+            ffi::doca_comch_client_create(dev.inner_ptr(), name.as_ptr(), &mut client as *mut _);
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let res = Self {
+            inner: NonNull::new(client).ok_or(DocaError::InvalidValue)?,
+            dev: dev.clone(),
+        };
+
+        pe.connect_ctx(res.ctx_ptr())?;
+        start(res.ctx_ptr())?;
+        Ok(Arc::new(res))
+    }
+
+    /// Check whether the client is connected to the server
+    pub fn is_running(&self) -> bool {
+        unsafe { is_running(self.ctx_ptr()) }
+    }
+
+    /// Return the inner pointer of the client.
+    ///
+    /// # Safety
+    ///
+    /// The client is destroyed when it is dropped, so the pointer should not
+    /// outlive the client, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_comch_client {
+        self.inner.as_ptr()
+    }
+
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_comch_client_as_ctx(self.inner_ptr())
+    }
+
+    // The connection to the server, which exists once the client is running
+    fn connection(&self) -> DOCAResult<*mut ffi::doca_comch_connection> {
+        let mut connection: *mut ffi::doca_comch_connection = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_comch_client_get_connection(self.inner_ptr(), &mut connection as *mut _)
+        })?;
+
+        if connection.is_null() {
+            return Err(DocaError::NotConnected);
+        }
+        Ok(connection)
+    }
+}
+
+/// The producer of a Comch connection, which sends buffers to the consumers of the peer
+pub struct ComchProducer {
+    inner: NonNull<ffi::doca_comch_producer>,
+    // the connection belongs to the client
+    #[allow(dead_code)]
+    client: Arc<ComchClient>,
+}
+
+impl Drop for ComchProducer {
+    fn drop(&mut self) {
+        // the jobs hold the producer, so none is in flight
+        let _ = unsafe { ffi::doca_ctx_stop(self.ctx_ptr()) };
+
+        let ret = unsafe { ffi::doca_comch_producer_destroy(self.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the Comch producer: {:?}", ret);
+        }
+
+        dropped!("Comch Producer is dropped!");
+    }
+}
+
+impl ComchProducer {
+    /// Check whether the device supports the producers
+    pub fn is_supported(dev: &Device) -> bool {
+        let ret = unsafe { ffi::doca_comch_producer_cap_is_supported(dev.inner_ptr()) };
+        ret == doca_error::DOCA_SUCCESS
+    }
+
+    /// Create a producer on the connection of the running client, connect it to the
+    /// progress engine, and start it with room for `num_tasks` sends.
+    ///
+    /// # Safety
+    ///
+    /// The producer must outlive the progress engine, see [`ProgressEngine::connect_ctx`].
+    pub unsafe fn new(
+        client: &Arc<ComchClient>,
+        pe: &mut ProgressEngine,
+        num_tasks: u32,
+    ) -> DOCAResult<Arc<Self>> {
+        let mut producer: *mut ffi::doca_comch_producer = std::ptr::null_mut();
+        check(ffi::doca_comch_producer_create(
+            client.connection()?,
+            &mut producer as *mut _,
+        ))?;
+
+        let res = Self {
+            inner: NonNull::new(producer).ok_or(DocaError::InvalidValue)?,
+            client: client.clone(),
+        };
+
+        let send_cb = Some(completion_trampoline::<ffi::doca_comch_producer_task_send> as _);
+        check(ffi::doca_comch_producer_task_send_set_conf(
+            res.inner_ptr(),
+            send_cb,
+            send_cb,
+            num_tasks,
+        ))?;
+
+        pe.connect_ctx(res.ctx_ptr())?;
+        start(res.ctx_ptr())?;
+        Ok(Arc::new(res))
+    }
+
+    /// Create a job sending the data of `src` with the immediate data `imm_data`
+    /// to the consumer of the peer with the id `consumer_id`
+    pub fn send(
+        self: &Arc<Self>,
+        src: DOCABuffer,
+        imm_data: &[u8],
+        consumer_id: u32,
+    ) -> DOCAResult<ComchSendJob> {
+        let imm_data = imm_data.to_vec();
+        let imm_len = u32::try_from(imm_data.len()).map_err(|_e| DocaError::InvalidValue)?;
+
+        let mut task: *mut ffi::doca_comch_producer_task_send = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_comch_producer_task_send_alloc_init(
+                self.inner_ptr(),
+                src.inner_ptr(),
+                imm_data.as_ptr(),
+                imm_len,
+                consumer_id,
+                &mut task as *mut _,
+            )
+        })?;
+
+        Ok(ComchSendJob {
+            task: unsafe { Task::from_raw(task)? },
+            producer: self.clone(),
+            src,
+            imm_data,
+        })
+    }
+
+    /// Return the inner pointer of the producer.
+    ///
+    /// # Safety
+    ///
+    /// The producer is destroyed when it is dropped, so the pointer should not
+    /// outlive the producer, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_comch_producer {
+        self.inner.as_ptr()
+    }
+
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_comch_producer_as_ctx(self.inner_ptr())
+    }
+}
+
+/// The consumer of a Comch connection, which receives the buffers sent by the producers
+/// of the peer into the buffers of a local mmap
+pub struct ComchConsumer {
+    inner: NonNull<ffi::doca_comch_consumer>,
+    // the connection belongs to the client
+    #[allow(dead_code)]
+    client: Arc<ComchClient>,
+    // the received buffers are in the mmap
+    #[allow(dead_code)]
+    mmap: Arc<DOCAMmap>,
+}
+
+impl Drop for ComchConsumer {
+    fn drop(&mut self) {
+        // the jobs hold the consumer, so none is in flight
+        let _ = unsafe { ffi::doca_ctx_stop(self.ctx_ptr()) };
+
+        let ret = unsafe { ffi::doca_comch_consumer_destroy(self.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the Comch consumer: {:?}", ret);
+        }
+
+        dropped!("Comch Consumer is dropped!");
+    }
+}
+
+impl ComchConsumer {
+    /// Check whether the device supports the consumers
+    pub fn is_supported(dev: &Device) -> bool {
+        let ret = unsafe { ffi::doca_comch_consumer_cap_is_supported(dev.inner_ptr()) };
+        ret == doca_error::DOCA_SUCCESS
+    }
+
+    /// Create a consumer receiving into the buffers of `mmap` on the connection of the
+    /// running client, connect it to the progress engine, and start registering it to the
+    /// peer with room for `num_tasks` receives.
+    ///
+    /// # Safety
+    ///
+    /// The consumer must outlive the progress engine, see [`ProgressEngine::connect_ctx`].
+    pub unsafe fn new(
+        client: &Arc<ComchClient>,
+        mmap: &Arc<DOCAMmap>,
+        pe: &mut ProgressEngine,
+        num_tasks: u32,
+    ) -> DOCAResult<Arc<Self>> {
+        let mut consumer: *mut ffi::doca_comch_consumer = std::ptr::null_mut();
+        check(ffi::doca_comch_consumer_create(
+            client.connection()?,
+            mmap.inner_ptr(),
+            &mut consumer as *mut _,
+        ))?;
+
+        let res = Self {
+            inner: NonNull::new(consumer).ok_or(DocaError::InvalidValue)?,
+            client: client.clone(),
+            mmap: mmap.clone(),
+        };
+
+        let recv_cb = Some(completion_trampoline::<ffi::doca_comch_consumer_task_post_recv> as _);
+        check(ffi::doca_comch_consumer_task_post_recv_set_conf(
+            res.inner_ptr(),
+            recv_cb,
+            recv_cb,
+            num_tasks,
+        ))?;
+
+        pe.connect_ctx(res.ctx_ptr())?;
+        start(res.ctx_ptr())?;
+        Ok(Arc::new(res))
+    }
+
+    /// Check whether the consumer is registered to the peer
+    pub fn is_running(&self) -> bool {
+        unsafe { is_running(self.ctx_ptr()) }
+    }
+
+    /// Get the id of the consumer, which the producers of the peer send to
+    pub fn id(&self) -> DOCAResult<u32> {
+        let mut id: u32 = 0;
+        check(unsafe { ffi::doca_comch_consumer_get_id(self.inner_ptr(), &mut id as *mut _) })?;
+        Ok(id)
+    }
+
+    /// Create a job receiving a buffer sent by a producer of the peer into `dst`
+    pub fn recv(self: &Arc<Self>, dst: DOCABuffer) -> DOCAResult<ComchRecvJob> {
+        let mut task: *mut ffi::doca_comch_consumer_task_post_recv = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_comch_consumer_task_post_recv_alloc_init(
+                self.inner_ptr(),
+                dst.inner_ptr(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        Ok(ComchRecvJob {
+            raw: task,
+            task: unsafe { Task::from_raw(task)? },
+            consumer: self.clone(),
+            dst,
+        })
+    }
+
+    /// Return the inner pointer of the consumer.
+    ///
+    /// # Safety
+    ///
+    /// The consumer is destroyed when it is dropped, so the pointer should not
+    /// outlive the consumer, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_comch_consumer {
+        self.inner.as_ptr()
+    }
+
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_comch_consumer_as_ctx(self.inner_ptr())
+    }
+}
+
+/// A send of a [`ComchProducer`], see [`ComchProducer::send`]
+pub struct ComchSendJob {
+    task: Task,
+    producer: Arc<ComchProducer>,
+    src: DOCABuffer,
+    // the immediate data should live until the send completes
+    imm_data: Vec<u8>,
+}
+
+impl ComchSendJob {
+    /// Submit the job to the progress engine the producer is connected to,
+    /// and call `on_done` with the source buffer and the result when it finishes.
+    ///
+    /// The buffer is dropped with the job if the submission fails.
+    pub fn submit<F>(self, pe: &mut ProgressEngine, on_done: F) -> DOCAResult<()>
+    where
+        F: FnOnce(DOCABuffer, DOCAResult<()>) + 'static,
+    {
+        let ComchSendJob {
+            task,
+            producer,
+            src,
+            imm_data,
+        } = self;
+
+        pe.submit(task, move |task, res| {
+            // free the task before the producer it is allocated from
+            drop(task);
+            drop(imm_data);
+            drop(producer);
+            on_done(src, res);
+        })
+    }
+}
+
+/// What a [`ComchRecvJob`] receives besides the buffer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComchReceived {
+    /// The id of the producer of the peer which sent the buffer
+    pub producer_id: u32,
+    /// The immediate data sent with the buffer
+    pub imm_data: Vec<u8>,
+}
+
+/// A receive of a [`ComchConsumer`], see [`ComchConsumer::recv`]
+pub struct ComchRecvJob {
+    // the typed task owned by `task`, whose received data is read on completion
+    raw: *mut ffi::doca_comch_consumer_task_post_recv,
+    task: Task,
+    consumer: Arc<ComchConsumer>,
+    dst: DOCABuffer,
+}
+
+impl ComchRecvJob {
+    /// Submit the job to the progress engine the consumer is connected to,
+    /// and call `on_done` with the destination buffer and what is received with it
+    /// when it finishes.
+    ///
+    /// The buffer is dropped with the job if the submission fails.
+    pub fn submit<F>(self, pe: &mut ProgressEngine, on_done: F) -> DOCAResult<()>
+    where
+        F: FnOnce(DOCABuffer, DOCAResult<ComchReceived>) + 'static,
+    {
+        let ComchRecvJob {
+            raw,
+            task,
+            consumer,
+            dst,
+        } = self;
+
+        pe.submit(task, move |task, res| {
+            // the received data is owned by the task, so it is copied out before the task is freed
+            let res = res.map(|_| unsafe { received(raw) });
+            drop(task);
+            drop(consumer);
+            on_done(dst, res);
+        })
+    }
+}
+
+// Copy out what a finished receive got besides the buffer
+unsafe fn received(task: *mut ffi::doca_comch_consumer_task_post_recv) -> ComchReceived {
+    let data = ffi::doca_comch_consumer_task_post_recv_get_imm_data(task);
+    let len = ffi::doca_comch_consumer_task_post_recv_get_imm_data_len(task) as usize;
+
+    ComchReceived {
+        producer_id: ffi::doca_comch_consumer_task_post_recv_get_producer_id(task),
+        imm_data: if data.is_null() {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(data, len).to_vec()
+        },
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_server_name() {
+        use super::*;
+
+        assert_eq!(
+            server_name("server").unwrap().as_bytes(),
+            b"server".as_slice()
+        );
+        assert_eq!(server_name("").err(), Some(DocaError::InvalidValue));
+        assert_eq!(server_name("ser\0ver").err(), Some(DocaError::InvalidValue));
+    }
+}
@@ -0,0 +1,319 @@
+//! Wrapper for DOCA Comm Channel, the management channel between the host and the DPU.
+//!
+//! It contains two endpoints:
+//! - [`CommChannelServer`] listens on the DPU, with the representor of the host PF
+//! it serves.
+//!
+//! - [`CommChannelClient`] connects to the server from the host.
+//!
+//! Both endpoints implement [`Transport`], so the export descriptor and the
+//! exported region can be exchanged over the channel directly, instead of copying the
+//! config files between the host and the DPU.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::comm_channel::CommChannelServer;
+//! use doca::samples::{dpu_copy_once, Transport};
+//!
+//! // on the DPU
+//! let device = doca::open_device_with_pci("03:00.0").unwrap();
+//! let mut server = CommChannelServer::listen("dma_copy", &device, "b1:00.0").unwrap();
+//! let data = dpu_copy_once("03:00.0", &mut server).unwrap();
+//! ```
+//!
+
+use std::ffi::CString;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use crate::device::{open_rep_with_pci, DevRepContext, RepFilter};
+use crate::error::check;
+use crate::samples::Transport;
+use crate::{
+    DOCAError, DOCAResult, DevContext, DocaError, ExportDescriptor, LoadedInfo, RawPointer,
+    RawPointerMsg,
+};
+
+/// The max size of a message of the comm channel
+pub const DOCA_CC_MAX_MSG_SIZE: usize = 4080;
+
+// The number of the messages queued in each direction
+const DOCA_CC_QUEUE_SIZE: u16 = 16;
+
+// The endpoint shared by the server and the client
+struct Endpoint {
+    inner: NonNull<ffi::doca_comm_channel_ep_t>,
+    // the address of the peer, known after connecting or receiving a message
+    peer: Option<NonNull<ffi::doca_comm_channel_addr_t>>,
+
+    // the device and the representor should be closed after the endpoint is destroyed
+    #[allow(dead_code)]
+    rep: Option<Arc<DevRepContext>>,
+    #[allow(dead_code)]
+    dev: Arc<DevContext>,
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer {
+            unsafe { ffi::doca_comm_channel_ep_disconnect(self.inner.as_ptr(), peer.as_ptr()) };
+        }
+        unsafe { ffi::doca_comm_channel_ep_destroy(self.inner.as_ptr()) };
+
+        dropped!("DOCA Comm Channel endpoint is dropped!");
+    }
+}
+
+impl Endpoint {
+    fn new(dev: &Arc<DevContext>, rep: Option<Arc<DevRepContext>>) -> DOCAResult<Self> {
+        let mut ep: *mut ffi::doca_comm_channel_ep_t = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_comm_channel_ep_create(&mut ep as *mut _) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let res = Self {
+            inner: NonNull::new(ep).ok_or(DocaError::InvalidValue)?,
+            peer: None,
+            rep,
+            dev: dev.clone(),
+        };
+
+        let inner = res.inner.as_ptr();
+        check(unsafe { ffi::doca_comm_channel_ep_set_device(inner, dev.inner_ptr()) })?;
+        if let Some(rep) = res.rep.as_ref() {
+            check(unsafe { ffi::doca_comm_channel_ep_set_device_rep(inner, rep.inner_ptr()) })?;
+        }
+        check(unsafe {
+            ffi::doca_comm_channel_ep_set_max_msg_size(inner, DOCA_CC_MAX_MSG_SIZE as u16)
+        })?;
+        check(unsafe { ffi::doca_comm_channel_ep_set_send_queue_size(inner, DOCA_CC_QUEUE_SIZE) })?;
+        check(unsafe { ffi::doca_comm_channel_ep_set_recv_queue_size(inner, DOCA_CC_QUEUE_SIZE) })?;
+
+        Ok(res)
+    }
+
+    fn send(&mut self, msg: &[u8]) -> DOCAResult<()> {
+        let peer = self.peer.ok_or(DocaError::NotConnected)?;
+        if msg.len() > DOCA_CC_MAX_MSG_SIZE {
+            return Err(DocaError::InvalidValue);
+        }
+
+        loop {
+            let ret = unsafe {
+                ffi::doca_comm_channel_ep_sendto(
+                    self.inner.as_ptr(),
+                    msg.as_ptr() as *const _,
+                    msg.len(),
+                    ffi::DOCA_CC_MSG_FLAG_NONE as i32,
+                    peer.as_ptr(),
+                )
+            };
+            match ret {
+                // the connection is not established yet, or the send queue is full
+                DOCAError::DOCA_ERROR_AGAIN => std::thread::yield_now(),
+                ret => return check(ret),
+            }
+        }
+    }
+
+    fn try_recv(&mut self, buf: &mut [u8]) -> DOCAResult<usize> {
+        let mut len = buf.len();
+        let mut peer: *mut ffi::doca_comm_channel_addr_t = std::ptr::null_mut();
+        let ret = unsafe {
+            ffi::doca_comm_channel_ep_recvfrom(
+                self.inner.as_ptr(),
+                buf.as_mut_ptr() as *mut _,
+                &mut len as *mut _,
+                ffi::DOCA_CC_MSG_FLAG_NONE as i32,
+                &mut peer as *mut _,
+            )
+        };
+        check(ret)?;
+
+        // reply to the sender of the last message
+        if let Some(peer) = NonNull::new(peer) {
+            self.peer = Some(peer);
+        }
+        Ok(len)
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> DOCAResult<usize> {
+        loop {
+            match self.try_recv(buf) {
+                Err(DocaError::Again) => std::thread::yield_now(),
+                res => return res,
+            }
+        }
+    }
+
+    fn publish(&mut self, export_desc: &ExportDescriptor, region: RawPointer) -> DOCAResult<()> {
+        self.send(export_desc.as_bytes())?;
+        self.send(&RawPointerMsg::serialize(region.into()))
+    }
+
+    fn receive(&mut self) -> DOCAResult<LoadedInfo> {
+        let mut desc = vec![0u8; DOCA_CC_MAX_MSG_SIZE];
+        let len = self.recv(&mut desc)?;
+        if len == 0 {
+            return Err(DocaError::InvalidValue);
+        }
+        desc.truncate(len);
+
+        let mut msg = vec![0u8; DOCA_CC_MAX_MSG_SIZE];
+        let len = self.recv(&mut msg)?;
+        let region = decode_region(&msg[..len])?;
+
+        Ok(LoadedInfo::new(desc.into_boxed_slice(), vec![region]))
+    }
+}
+
+// Decode the region sent by `publish`, rejecting the malformed messages
+fn decode_region(msg: &[u8]) -> DOCAResult<RawPointer> {
+    let msg: RawPointerMsg = serde_json::from_slice(msg).map_err(|_e| DocaError::InvalidValue)?;
+    if msg.payload == 0 {
+        return Err(DocaError::InvalidValue);
+    }
+
+    Ok(RawPointer {
+        inner: NonNull::new(msg.inner as *mut _).ok_or(DocaError::InvalidValue)?,
+        payload: msg.payload,
+    })
+}
+
+/// The comm channel server, which runs on the DPU.
+///
+/// It replies to the client of the last received message.
+pub struct CommChannelServer {
+    ep: Endpoint,
+}
+
+impl CommChannelServer {
+    /// Listen on the service `name`, with the device and the representor
+    /// of the host PF at `rep_pci_addr`, e.g., "b1:00.0".
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_NOT_FOUND`: no network representor of the device is at `rep_pci_addr`.
+    ///  - other errors returned by DOCA, e.g., the name is already in use.
+    ///
+    pub fn listen(name: &str, dev: &Arc<DevContext>, rep_pci_addr: &str) -> DOCAResult<Self> {
+        let rep = open_rep_with_pci(dev, RepFilter::Net, rep_pci_addr)?;
+        let ep = Endpoint::new(dev, Some(rep))?;
+
+        let name = CString::new(name).map_err(|_e| DocaError::InvalidValue)?;
+        check(unsafe { ffi::doca_comm_channel_ep_listen(ep.inner.as_ptr(), name.as_ptr()) })?;
+
+        Ok(Self { ep })
+    }
+
+    /// Send the message to the client, blocking until it is queued.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_NOT_CONNECTED`: no message has been received from a client.
+    ///  - `DOCA_ERROR_INVALID_VALUE`: the message is larger than [`DOCA_CC_MAX_MSG_SIZE`].
+    ///
+    pub fn send(&mut self, msg: &[u8]) -> DOCAResult<()> {
+        self.ep.send(msg)
+    }
+
+    /// Receive a message into `buf`, blocking until one arrives.
+    /// Return the length of the message.
+    pub fn recv(&mut self, buf: &mut [u8]) -> DOCAResult<usize> {
+        self.ep.recv(buf)
+    }
+
+    /// Receive a message into `buf` if there is one,
+    /// otherwise return `DOCA_ERROR_AGAIN`.
+    pub fn try_recv(&mut self, buf: &mut [u8]) -> DOCAResult<usize> {
+        self.ep.try_recv(buf)
+    }
+}
+
+impl Transport for CommChannelServer {
+    fn publish(&mut self, export_desc: &ExportDescriptor, region: RawPointer) -> DOCAResult<()> {
+        self.ep.publish(export_desc, region)
+    }
+
+    fn receive(&mut self) -> DOCAResult<LoadedInfo> {
+        self.ep.receive()
+    }
+}
+
+/// The comm channel client, which runs on the host.
+pub struct CommChannelClient {
+    ep: Endpoint,
+}
+
+impl CommChannelClient {
+    /// Connect to the server listening on the service `name` with the device.
+    ///
+    /// The connection is established in the background,
+    /// the first [`send`](Self::send) blocks until it is done.
+    pub fn connect(name: &str, dev: &Arc<DevContext>) -> DOCAResult<Self> {
+        let mut ep = Endpoint::new(dev, None)?;
+
+        let name = CString::new(name).map_err(|_e| DocaError::InvalidValue)?;
+        let mut peer: *mut ffi::doca_comm_channel_addr_t = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_comm_channel_ep_connect(ep.inner.as_ptr(), name.as_ptr(), &mut peer as *mut _)
+        })?;
+        ep.peer = Some(NonNull::new(peer).ok_or(DocaError::InvalidValue)?);
+
+        Ok(Self { ep })
+    }
+
+    /// Send the message to the server, blocking until it is queued.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the message is larger than [`DOCA_CC_MAX_MSG_SIZE`].
+    pub fn send(&mut self, msg: &[u8]) -> DOCAResult<()> {
+        self.ep.send(msg)
+    }
+
+    /// Receive a message into `buf`, blocking until one arrives.
+    /// Return the length of the message.
+    pub fn recv(&mut self, buf: &mut [u8]) -> DOCAResult<usize> {
+        self.ep.recv(buf)
+    }
+
+    /// Receive a message into `buf` if there is one,
+    /// otherwise return `DOCA_ERROR_AGAIN`.
+    pub fn try_recv(&mut self, buf: &mut [u8]) -> DOCAResult<usize> {
+        self.ep.try_recv(buf)
+    }
+}
+
+impl Transport for CommChannelClient {
+    fn publish(&mut self, export_desc: &ExportDescriptor, region: RawPointer) -> DOCAResult<()> {
+        self.ep.publish(export_desc, region)
+    }
+
+    fn receive(&mut self) -> DOCAResult<LoadedInfo> {
+        self.ep.receive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_region() {
+        let mut buffer = vec![0u8; 64];
+        let region = RawPointer {
+            inner: NonNull::new(buffer.as_mut_ptr() as *mut _).unwrap(),
+            payload: buffer.len(),
+        };
+
+        let msg = RawPointerMsg::serialize(region.into());
+        let decoded = decode_region(&msg).unwrap();
+        assert_eq!(decoded.inner, region.inner);
+        assert_eq!(decoded.payload, 64);
+
+        assert!(decode_region(b"not a region").is_err());
+        assert!(decode_region(br#"{"inner":0,"payload":64}"#).is_err());
+        assert!(decode_region(br#"{"inner":4096,"payload":0}"#).is_err());
+    }
+}
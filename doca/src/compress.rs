@@ -0,0 +1,532 @@
+//! Wrapper for DOCA Compress. It provides
+//! the ability of offloading the deflate compression and decompression to the DPU.
+//!
+//! It follows the same context/work queue model as the [`dma`](crate::dma) module:
+//! - [`DOCACompressJob`]: The compress request of DOCA. It implements the trait [`ToBaseJob`],
+//! which makes it capable for being submitted to the work queue.
+//!
+//! - [`CompressEngine`]: The Compress Engine of DOCA. Users should create an instance of the engine
+//! and execute compress requests based on the engine.
+//!
+//! The output is appended to the tail of the destination data, as the DMA jobs do.
+//! Note that DOCA 1.5 only supports the deflate algorithm, so there is no LZ4 job.
+//!
+//! Many small payloads, e.g., records of a few KB, are better processed by
+//! [`DOCAWorkQueue::compress_batch`], which keeps the work queue filled with their jobs
+//! instead of submitting and polling them one by one.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::compress::CompressEngine;
+//! use doca::context::DOCAContext;
+//! use doca::DOCAWorkQueue;
+//!
+//! let compress = CompressEngine::new().unwrap();
+//! let device = doca::device::open_device_with_pci("03:00.0").unwrap();
+//!
+//! let ctx = DOCAContext::new(&compress, vec![device]).unwrap();
+//! let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+//! ```
+//!
+
+use std::collections::HashMap;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use crate::context::work_queue::ToBaseJob;
+use crate::context::{DOCAContext, Engine, EngineToContext};
+use crate::{DOCABuffer, DOCAError, DOCAResult, DOCAWorkQueue, Device, DocaError};
+
+/// DOCA Compress engine instance
+pub struct CompressEngine {
+    inner: NonNull<ffi::doca_compress>,
+}
+
+// The engine is only converted into its context, which is thread-safe
+unsafe impl Sync for CompressEngine {}
+unsafe impl Send for CompressEngine {}
+
+impl Drop for CompressEngine {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_compress_destroy(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            drop_failed!("Failed to destory compress engine: {:?}", ret);
+        }
+
+        dropped!("Compress Engine is dropped!");
+    }
+}
+
+/// Implementation `EngineToContext` Trait for Compress Engine
+impl EngineToContext for CompressEngine {
+    unsafe fn to_ctx(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_compress_as_ctx(self.inner_ptr())
+    }
+}
+
+/// Implementation `Engine` Trait for Compress Engine
+impl Engine for CompressEngine {
+    type Job = DOCACompressJob;
+
+    fn name() -> &'static str {
+        "compress"
+    }
+
+    /// The job compresses the data, see [`DOCACompressJob::set_op`].
+    fn new_job(ctx: &Arc<DOCAContext<Self>>) -> DOCACompressJob {
+        let mut res = DOCACompressJob {
+            inner: Default::default(),
+            ctx: ctx.clone(),
+            src_buff: None,
+            dst_buff: None,
+            checksum: Box::new(0),
+        };
+        res.inner.output_chksum = &mut *res.checksum as *mut u64;
+        res.set_ctx().set_flags().set_op(CompressOp::Compress);
+        res
+    }
+
+    fn is_supported(dev: &Device) -> bool {
+        CompressOp::Compress.is_supported(dev)
+    }
+
+    fn max_buf_size(dev: &Device) -> DOCAResult<u64> {
+        CompressOp::Compress.max_buf_size(dev)
+    }
+}
+
+impl CompressEngine {
+    /// Create a DOCA Compress instance.
+    pub fn new() -> DOCAResult<Arc<Self>> {
+        let mut compress: *mut ffi::doca_compress = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_compress_create(&mut compress as *mut _) };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(Arc::new(Self {
+            inner: unsafe { NonNull::new_unchecked(compress) },
+        }))
+    }
+
+    /// Get the inner pointer of the DOCA Compress instance.
+    ///
+    /// # Safety
+    ///
+    /// The instance is destroyed when the engine is dropped, so the pointer should not
+    /// outlive the engine, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_compress {
+        self.inner.as_ptr()
+    }
+}
+
+/// The operation of a [`DOCACompressJob`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressOp {
+    /// Compress the source data with deflate
+    Compress,
+    /// Decompress the deflate-compressed source data
+    Decompress,
+}
+
+impl CompressOp {
+    /// Check whether the device supports the operation
+    pub fn is_supported(self, dev: &Device) -> bool {
+        let ret = unsafe { ffi::doca_compress_job_get_supported(dev.inner_ptr(), self.job_type()) };
+        ret == DOCAError::DOCA_SUCCESS
+    }
+
+    /// Get the maximum buffer size supported by the operation on the device
+    pub fn max_buf_size(self, dev: &Device) -> DOCAResult<u64> {
+        let mut num: u64 = 0;
+        let ret = unsafe {
+            ffi::doca_compress_get_max_buf_size(
+                dev.inner_ptr(),
+                self.job_type(),
+                &mut num as *mut _,
+            )
+        };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(num)
+    }
+
+    fn job_type(self) -> ffi::doca_compress_job_types {
+        match self {
+            CompressOp::Compress => ffi::DOCA_COMPRESS_DEFLATE_JOB,
+            CompressOp::Decompress => ffi::DOCA_DECOMPRESS_DEFLATE_JOB,
+        }
+    }
+}
+
+/// A DOCA Compress request
+pub struct DOCACompressJob {
+    pub(crate) inner: ffi::doca_compress_deflate_job,
+
+    #[allow(dead_code)]
+    ctx: Arc<DOCAContext<CompressEngine>>,
+
+    src_buff: Option<DOCABuffer>,
+    dst_buff: Option<DOCABuffer>,
+
+    // the checksum written by the hardware, boxed so that its address is stable
+    checksum: Box<u64>,
+}
+
+/// Implementation of `ToBaseJob` Trait
+impl ToBaseJob for DOCACompressJob {
+    fn to_base(&self) -> &ffi::doca_job {
+        &self.inner.base
+    }
+
+    fn to_base_mut(&mut self) -> &mut ffi::doca_job {
+        &mut self.inner.base
+    }
+}
+
+impl DOCACompressJob {
+    /// Set request's destination buffer
+    pub fn set_dst(&mut self, buf: DOCABuffer) -> &mut Self {
+        debug_assert!(
+            unsafe { !buf.inner_ptr().is_null() },
+            "the dst buffer is null"
+        );
+        unsafe { self.inner.dst_buff = buf.inner_ptr() };
+        self.dst_buff = Some(buf);
+        self
+    }
+
+    /// Set request's source buffer
+    pub fn set_src(&mut self, buf: DOCABuffer) -> &mut Self {
+        debug_assert!(
+            unsafe { !buf.inner_ptr().is_null() },
+            "the src buffer is null"
+        );
+        unsafe { self.inner.src_buff = buf.inner_ptr() };
+        self.src_buff = Some(buf);
+        self
+    }
+
+    /// Take the source buffer back from the request.
+    ///
+    /// The request can't be submitted again until a new source buffer is set.
+    /// Return `None` if the buffer has already been taken.
+    pub fn take_src(&mut self) -> Option<DOCABuffer> {
+        self.inner.src_buff = std::ptr::null_mut();
+        self.src_buff.take()
+    }
+
+    /// Take the destination buffer back from the request, e.g., to read the output.
+    ///
+    /// The request can't be submitted again until a new destination buffer is set.
+    /// Return `None` if the buffer has already been taken.
+    pub fn take_dst(&mut self) -> Option<DOCABuffer> {
+        self.inner.dst_buff = std::ptr::null_mut();
+        self.dst_buff.take()
+    }
+
+    /// Set the data pointer of the src buffer,
+    /// i.e., the job processes `payload` bytes starting at `offset` of the src region.
+    #[inline]
+    pub fn set_src_data(&mut self, offset: usize, payload: usize) -> &mut Self {
+        if let Some(f) = self.src_buff.as_mut() {
+            unsafe {
+                f.set_data(offset, payload)
+                    .expect("doca fail to set src data!")
+            };
+        }
+        self
+    }
+
+    /// Set the data pointer of the dst buffer,
+    /// the output lands at `offset + payload` of the dst region.
+    #[inline]
+    pub fn set_dst_data(&mut self, offset: usize, payload: usize) -> &mut Self {
+        if let Some(f) = self.dst_buff.as_mut() {
+            unsafe {
+                f.set_data(offset, payload)
+                    .expect("doca fail to set dst data!")
+            };
+        }
+        self
+    }
+
+    /// Set whether the request compresses or decompresses the source data
+    pub fn set_op(&mut self, op: CompressOp) -> &mut Self {
+        self.inner.base.type_ = op.job_type() as i32;
+        self
+    }
+
+    /// Get the operation of the request
+    pub fn op(&self) -> CompressOp {
+        if self.inner.base.type_ == ffi::DOCA_DECOMPRESS_DEFLATE_JOB as i32 {
+            CompressOp::Decompress
+        } else {
+            CompressOp::Compress
+        }
+    }
+
+    /// Get the length of the destination data, including the output of the finished job.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the destination buffer has been taken.
+    pub fn dst_data_len(&self) -> DOCAResult<usize> {
+        self.dst_buff
+            .as_ref()
+            .ok_or(DocaError::InvalidValue)?
+            .data_len()
+    }
+
+    /// The CRC32 checksum of the uncompressed data, written by the finished job
+    pub fn crc32(&self) -> u32 {
+        *self.checksum as u32
+    }
+
+    /// The Adler32 checksum of the uncompressed data, written by the finished job
+    pub fn adler32(&self) -> u32 {
+        (*self.checksum >> 32) as u32
+    }
+
+    /// Set request's based context
+    fn set_ctx(&mut self) -> &mut Self {
+        unsafe { self.inner.base.ctx = self.ctx.inner_ptr() };
+        self
+    }
+
+    /// Set request's flags
+    fn set_flags(&mut self) -> &mut Self {
+        self.inner.base.flags = ffi::DOCA_JOB_FLAGS_NONE as i32;
+        self
+    }
+}
+
+impl DOCAWorkQueue<CompressEngine> {
+    /// Create a job compressing the source data into the destination buffer
+    pub fn create_compress_job(&self, src_buf: DOCABuffer, dst_buf: DOCABuffer) -> DOCACompressJob {
+        let mut res = self.new_job();
+        res.set_src(src_buf).set_dst(dst_buf);
+        res
+    }
+
+    /// Create a job decompressing the source data into the destination buffer
+    pub fn create_decompress_job(
+        &self,
+        src_buf: DOCABuffer,
+        dst_buf: DOCABuffer,
+    ) -> DOCACompressJob {
+        let mut res = self.create_compress_job(src_buf, dst_buf);
+        res.set_op(CompressOp::Decompress);
+        res
+    }
+
+    /// Process the payloads, each given by its source and destination buffers, with `op`,
+    /// and return their outputs in the same order.
+    ///
+    /// The jobs of the payloads are submitted in batches that fill the available slots of
+    /// the work queue, and the finished ones are retrieved until all the payloads are done.
+    /// A payload failed to be submitted or processed only fails its own output.
+    ///
+    /// Return the error of polling the work queue, e.g., `DOCA_ERROR_BAD_STATE` if the
+    /// context is poisoned, which drops the payloads.
+    pub fn compress_batch(
+        &mut self,
+        op: CompressOp,
+        payloads: Vec<(DOCABuffer, DOCABuffer)>,
+    ) -> DOCAResult<Vec<CompressOutput>> {
+        let total = payloads.len();
+        let mut jobs = Vec::with_capacity(total);
+        let mut dst_lens = Vec::with_capacity(total);
+        for (src, dst) in payloads {
+            // the output is appended to the data of the destination
+            dst_lens.push(dst.data_len()?);
+            let mut job = self.create_compress_job(src, dst);
+            job.set_op(op);
+            jobs.push(job);
+        }
+
+        let mut results: Vec<Option<DOCAResult<()>>> = vec![None; total];
+        let mut inflight = HashMap::new();
+        let mut handles = Vec::new();
+        let (mut submitted, mut finished) = (0, 0);
+        while finished < total {
+            let room = (self.available() as usize).min(total - submitted);
+            if room > 0 {
+                handles.clear();
+                let res = self.submit_batch(&mut jobs[submitted..submitted + room], &mut handles);
+                for (i, handle) in handles.iter().enumerate() {
+                    inflight.insert(handle.id(), submitted + i);
+                }
+                submitted += handles.len();
+
+                // the job failed to be submitted is done with the error
+                if let Err(e) = res {
+                    results[submitted] = Some(Err(e.into()));
+                    submitted += 1;
+                    finished += 1;
+                }
+            }
+
+            for completion in self.poll_completions(inflight.len())? {
+                if let Some(i) = inflight.remove(&completion.handle.id()) {
+                    results[i] = Some(completion.result());
+                    finished += 1;
+                }
+            }
+        }
+
+        Ok(jobs
+            .into_iter()
+            .zip(results)
+            .zip(dst_lens)
+            .map(|((mut job, result), dst_len)| {
+                let crc32 = job.crc32();
+                let src = job.take_src().unwrap();
+                let dst = job.take_dst().unwrap();
+                let result = result
+                    .unwrap_or(Err(DocaError::Unexpected))
+                    .and_then(|_| Ok(dst.data_len()? - dst_len));
+                CompressOutput {
+                    src,
+                    dst,
+                    result,
+                    crc32,
+                }
+            })
+            .collect())
+    }
+}
+
+/// The output of a payload processed by [`DOCAWorkQueue::compress_batch`]
+pub struct CompressOutput {
+    /// The source buffer of the payload
+    pub src: DOCABuffer,
+    /// The destination buffer, whose data ends with the output
+    pub dst: DOCABuffer,
+    /// The length of the output, or the error of the job
+    pub result: DOCAResult<usize>,
+    /// The CRC32 checksum of the uncompressed data, written by the succeeded job
+    pub crc32: u32,
+}
+
+mod tests {
+    #[test]
+    fn test_compress_engine_trait() {
+        use super::*;
+        use crate::*;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !CompressEngine::is_supported(&device) {
+            return;
+        }
+        assert!(CompressEngine::max_buf_size(&device).unwrap() > 0);
+
+        let ctx = DOCAContext::new(
+            &CompressEngine::new().unwrap(),
+            vec![device.open().unwrap()],
+        )
+        .unwrap();
+        let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        let job = workq.new_job();
+        assert_eq!(job.op(), CompressOp::Compress);
+    }
+
+    #[test]
+    fn test_compress_roundtrip() {
+        use super::*;
+        use crate::*;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !CompressOp::Compress.is_supported(&device)
+            || !CompressOp::Decompress.is_supported(&device)
+        {
+            return;
+        }
+        let device = device.open().unwrap();
+
+        let ctx = DOCAContext::new(&CompressEngine::new().unwrap(), vec![device.clone()]).unwrap();
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        doca_mmap.add_device(&device).unwrap();
+        let inv = BufferInventory::new(1024).unwrap();
+
+        let src_buffer = b"hello hello hello hello hello hello"
+            .repeat(32)
+            .into_boxed_slice();
+        let compressed = vec![0u8; 4096].into_boxed_slice();
+        let output = vec![0u8; src_buffer.len()].into_boxed_slice();
+
+        let to_buffer = |b: &Box<[u8]>| {
+            DOCARegisteredMemory::new(&doca_mmap, unsafe { RawPointer::from_box(b) })
+                .unwrap()
+                .to_buffer(&inv)
+                .unwrap()
+        };
+
+        let mut job = workq.create_compress_job(to_buffer(&src_buffer), to_buffer(&compressed));
+        job.set_src_data(0, src_buffer.len());
+        workq.submit(&mut job).unwrap();
+        workq.spin_completion().unwrap();
+        let compressed_len = job.dst_data_len().unwrap();
+        assert!(compressed_len > 0 && compressed_len < src_buffer.len());
+
+        let mut job = workq.create_decompress_job(to_buffer(&compressed), to_buffer(&output));
+        job.set_src_data(0, compressed_len);
+        workq.submit(&mut job).unwrap();
+        workq.spin_completion().unwrap();
+        assert_eq!(src_buffer, output);
+    }
+
+    #[test]
+    fn test_compress_batch() {
+        use super::*;
+        use crate::*;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !CompressOp::Compress.is_supported(&device) {
+            return;
+        }
+        let device = device.open().unwrap();
+
+        let ctx = DOCAContext::new(&CompressEngine::new().unwrap(), vec![device.clone()]).unwrap();
+        let mut workq = DOCAWorkQueue::new(4, &ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        doca_mmap.add_device(&device).unwrap();
+        let inv = BufferInventory::new(1024).unwrap();
+
+        // more records than the depth of the work queue
+        let records: Vec<_> = (0..10u8)
+            .map(|i| [i; 16].repeat(64).into_boxed_slice())
+            .collect();
+        let outputs: Vec<_> = (0..10)
+            .map(|_| vec![0u8; 4096].into_boxed_slice())
+            .collect();
+        let to_buffer = |b: &Box<[u8]>, len: usize| {
+            let mut buf = DOCARegisteredMemory::new(&doca_mmap, unsafe { RawPointer::from_box(b) })
+                .unwrap()
+                .to_buffer(&inv)
+                .unwrap();
+            unsafe { buf.set_data(0, len).unwrap() };
+            buf
+        };
+
+        let payloads = records
+            .iter()
+            .zip(&outputs)
+            .map(|(r, o)| (to_buffer(r, r.len()), to_buffer(o, 0)))
+            .collect();
+        let results = workq
+            .compress_batch(CompressOp::Compress, payloads)
+            .unwrap();
+        assert_eq!(results.len(), records.len());
+        for (output, record) in results.iter().zip(&records) {
+            let len = output.result.unwrap();
+            assert!(len > 0 && len < record.len());
+        }
+        assert_eq!(workq.inflight(), 0);
+    }
+}
@@ -0,0 +1,245 @@
+//! Exchange the export descriptor over a TCP socket, for the host and the DPU
+//! sharing a network, instead of copying the config files between them.
+//!
+//! - [`ExportServer`] listens on a socket, and publishes the descriptor to the
+//! peer that connects, usually on the host that exports its memory.
+//!
+//! - [`ExportClient`] connects to the server and receives the descriptor,
+//! usually on the DPU.
+//!
+//! Each message is a frame of a 4-byte big-endian length followed by the payload.
+//! The export descriptor is sent in a frame, and then the remote regions in
+//! the same text format as the buffer information file of [`save_config`](crate::save_config).
+//!
+//! Both implement [`Transport`], so they plug into the sample flows directly.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::config_exchange::ExportClient;
+//! use doca::samples::dpu_copy_once;
+//!
+//! // on the DPU, the host runs an `ExportServer` bound to port 7473
+//! let mut client = ExportClient::connect("192.168.100.1:7473").unwrap();
+//! let data = dpu_copy_once("03:00.0", &mut client).unwrap();
+//! ```
+//!
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::samples::Transport;
+use crate::{
+    buffer_info, check_desc_size, parse_buffer_info, ConfigError, DOCAResult, DocaError,
+    ExportDescriptor, LoadedInfo, RawPointer, DEFAULT_MAX_EXPORT_LENGTH,
+};
+
+// The max length of the frame of the remote regions, far more than 64 regions need
+const MAX_REGIONS_FRAME_LENGTH: usize = 1 << 16;
+
+// Send the export descriptor and the regions in two frames
+fn send_info<W: Write>(
+    stream: &mut W,
+    export_desc: &ExportDescriptor,
+    regions: &[RawPointer],
+) -> DOCAResult<()> {
+    if regions.is_empty() {
+        return Err(DocaError::InvalidValue);
+    }
+
+    write_frame(stream, export_desc.as_bytes())?;
+    write_frame(stream, buffer_info(regions).as_bytes())?;
+    stream.flush().map_err(DocaError::from)
+}
+
+// Receive the frames sent by `send_info`, rejecting the malformed ones
+fn recv_info<R: Read>(stream: &mut R) -> Result<LoadedInfo, ConfigError> {
+    let export_desc = read_frame(stream, DEFAULT_MAX_EXPORT_LENGTH)?;
+    check_desc_size(export_desc.len(), DEFAULT_MAX_EXPORT_LENGTH)?;
+
+    let regions = read_frame(stream, MAX_REGIONS_FRAME_LENGTH)?;
+    let regions = std::str::from_utf8(&regions).map_err(|_e| ConfigError::InvalidAddress)?;
+
+    Ok(LoadedInfo::new(
+        export_desc.into_boxed_slice(),
+        parse_buffer_info(regions)?,
+    ))
+}
+
+fn write_frame<W: Write>(stream: &mut W, payload: &[u8]) -> DOCAResult<()> {
+    let len = u32::try_from(payload.len()).map_err(|_e| DocaError::InvalidValue)?;
+    stream
+        .write_all(&len.to_be_bytes())
+        .and_then(|_| stream.write_all(payload))
+        .map_err(DocaError::from)
+}
+
+fn read_frame<R: Read>(stream: &mut R, max: usize) -> Result<Vec<u8>, ConfigError> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+
+    // check the length before allocating, in case the peer is not speaking the protocol
+    let size = u32::from_be_bytes(len) as usize;
+    if size > max {
+        return Err(ConfigError::DescriptorTooLarge { size, max });
+    }
+
+    let mut payload = vec![0u8; size];
+    stream.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// The server side of the exchange, see the [module](self) documentation.
+pub struct ExportServer {
+    listener: TcpListener,
+}
+
+impl ExportServer {
+    /// Listen on the address, e.g., "0.0.0.0:7473".
+    ///
+    /// Return `DOCA_ERROR_IO_FAILED` if the address can't be bound.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> DOCAResult<Self> {
+        let listener = TcpListener::bind(addr)?;
+        Ok(Self { listener })
+    }
+
+    /// The address the server listens on, e.g., to find the port picked for port 0.
+    pub fn local_addr(&self) -> DOCAResult<SocketAddr> {
+        self.listener.local_addr().map_err(DocaError::from)
+    }
+
+    /// Wait for a peer to connect, and send it the export descriptor and the regions
+    /// populated in the exported mmap.
+    ///
+    /// It can be called again to serve the next peer.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `regions` is empty.
+    ///  - `DOCA_ERROR_IO_FAILED`: failed to accept the peer or to send the frames.
+    ///
+    pub fn publish_regions(
+        &mut self,
+        export_desc: &ExportDescriptor,
+        regions: &[RawPointer],
+    ) -> DOCAResult<()> {
+        if regions.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let (mut stream, _) = self.listener.accept()?;
+        send_info(&mut stream, export_desc, regions)
+    }
+}
+
+impl Transport for ExportServer {
+    fn publish(&mut self, export_desc: &ExportDescriptor, region: RawPointer) -> DOCAResult<()> {
+        self.publish_regions(export_desc, &[region])
+    }
+
+    fn receive(&mut self) -> DOCAResult<LoadedInfo> {
+        let (mut stream, _) = self.listener.accept()?;
+        Ok(recv_info(&mut stream)?)
+    }
+}
+
+/// The client side of the exchange, see the [module](self) documentation.
+pub struct ExportClient {
+    stream: TcpStream,
+}
+
+impl ExportClient {
+    /// Connect to the [`ExportServer`] at the address.
+    ///
+    /// Return `DOCA_ERROR_IO_FAILED` if the connection fails, e.g., the server is not up yet.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> DOCAResult<Self> {
+        let stream = TcpStream::connect(addr)?;
+        Ok(Self { stream })
+    }
+
+    /// Send the export descriptor and the regions populated in the exported mmap to the server.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `regions` is empty.
+    pub fn publish_regions(
+        &mut self,
+        export_desc: &ExportDescriptor,
+        regions: &[RawPointer],
+    ) -> DOCAResult<()> {
+        send_info(&mut self.stream, export_desc, regions)
+    }
+}
+
+impl Transport for ExportClient {
+    fn publish(&mut self, export_desc: &ExportDescriptor, region: RawPointer) -> DOCAResult<()> {
+        self.publish_regions(export_desc, &[region])
+    }
+
+    fn receive(&mut self) -> DOCAResult<LoadedInfo> {
+        Ok(recv_info(&mut self.stream)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exchange_over_loopback() {
+        let mut server = ExportServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let publisher = std::thread::spawn(move || {
+            let desc = ExportDescriptor::from(b"export descriptor".to_vec());
+            let region = vec![0u8; 64].into_boxed_slice();
+            let regions = [unsafe { RawPointer::from_box(&region) }; 2];
+            server.publish_regions(&desc, &regions).unwrap();
+            region.as_ptr() as usize
+        });
+
+        let mut client = ExportClient::connect(addr).unwrap();
+        let info = client.receive().unwrap();
+        let region_addr = publisher.join().unwrap();
+
+        assert_eq!(info.export_desc.as_bytes(), b"export descriptor");
+        assert_eq!(info.remote_addrs.len(), 2);
+        assert_eq!(info.remote_addr().inner.as_ptr() as usize, region_addr);
+        assert_eq!(info.remote_addr().payload, 64);
+    }
+
+    #[test]
+    fn test_malformed_frames() {
+        // the length exceeds the max export length
+        let mut frame = ((DEFAULT_MAX_EXPORT_LENGTH + 1) as u32)
+            .to_be_bytes()
+            .to_vec();
+        frame.extend_from_slice(b"garbage");
+        assert!(matches!(
+            recv_info(&mut frame.as_slice()),
+            Err(ConfigError::DescriptorTooLarge { .. })
+        ));
+
+        // the stream ends in the middle of a frame
+        let mut frame = 16u32.to_be_bytes().to_vec();
+        frame.extend_from_slice(b"short");
+        match recv_info(&mut frame.as_slice()) {
+            Err(ConfigError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+            res => panic!("unexpected result: {:?}", res.err()),
+        }
+
+        // an empty descriptor
+        let frame = 0u32.to_be_bytes();
+        assert_eq!(
+            recv_info(&mut frame.as_slice()).err(),
+            Some(ConfigError::EmptyDescriptor)
+        );
+
+        // a zero address in the regions
+        let mut frame = Vec::new();
+        write_frame(&mut frame, b"desc").unwrap();
+        write_frame(&mut frame, b"v1\n0 64\n").unwrap();
+        assert_eq!(
+            recv_info(&mut frame.as_slice()).err(),
+            Some(ConfigError::NullAddress)
+        );
+    }
+}
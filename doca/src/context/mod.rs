@@ -10,14 +10,29 @@
 //! own function to transfer an data-path engine into a Context instance.  For example, to submit DMA jobs,
 //! a DMA context can be acquired from [`DMAEngine`], whereas SHA context can be obtained using another implementation.
 //!
+//! The trait [`Engine`] further covers the job construction and capability queries of an engine,
+//! so generic infrastructure (pools, sessions, etc.) can be written once for all engines.
+//!
+//! A context is started on creation, and can be stopped, reconfigured and started again
+//! while its work queues still hold it, see [`DOCAContext::reconfigure`].
+//!
+//! A context keeps a registry of the work queues added into it. Each work queue is removed
+//! from the context exactly once, by whichever of the two is torn down first,
+//! see [`DOCAContext::num_workqs`].
+//!
 //! - [`DOCAWorkQueue`]  is a per-thread object used to queue jobs to
 //! offload to DOCA and eventually receive their completion status.
+//! [`WorkQueuePool`](pool::WorkQueuePool) gives each thread its own queue of a shared context.
 //!
 
-use crate::{DOCAError, DOCAResult, DevContext};
+use crate::drop_order::TrackId;
+use crate::error::error_from_code;
+use crate::{DOCAError, DOCAResult, DevContext, Device, DocaError};
+use work_queue::{DOCAWorkQueue, ToBaseJob};
 
 use std::ptr::NonNull;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
 
 /// Each DOCA Engine should implement their trait to
 /// transfer the engine instance into a DOCA CTX instance
@@ -26,18 +41,105 @@ pub trait EngineToContext {
     unsafe fn to_ctx(&self) -> *mut ffi::doca_ctx;
 }
 
+/// A DOCA data-path engine, e.g., [`DMAEngine`](crate::DMAEngine).
+pub trait Engine: EngineToContext + Sized {
+    /// The job type of the engine
+    type Job: ToBaseJob;
+
+    /// Name of the engine
+    fn name() -> &'static str;
+
+    /// Create a job for the context of the engine, with its type and flags set
+    fn new_job(ctx: &Arc<DOCAContext<Self>>) -> Self::Job;
+
+    /// Check whether the device supports the jobs of the engine
+    fn is_supported(dev: &Device) -> bool;
+
+    /// Get the maximum buffer size supported by the jobs of the engine on the device
+    fn max_buf_size(dev: &Device) -> DOCAResult<u64>;
+}
+
+/// The state of a [`DOCAContext`], reported to the callback registered by
+/// [`DOCAContext::on_state_change`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContextState {
+    /// The context is stopped, and can be reconfigured.
+    Idle,
+    /// The context is started, jobs can be submitted.
+    Running,
+    /// The context is being stopped.
+    Stopping,
+    /// The context is broken by a fatal error, see [`DOCAContext::poisoned`].
+    Error(DOCAError),
+}
+
+type StateChangeHandler = Box<dyn FnMut(ContextState) + Send>;
+
+// A work queue added into a context, shared by the work queue and the registry of the context,
+// so it is removed from the context by whichever of them is torn down first
+pub(crate) struct WorkqRegistration {
+    ctx: NonNull<ffi::doca_ctx>,
+    workq: NonNull<ffi::doca_workq>,
+    // whether the work queue has been removed from the context
+    removed: AtomicBool,
+}
+
+// The pointers are only passed to `doca_ctx_workq_rm`, which is called at most once
+// as guarded by `removed`, so the registration can be shared by the work queue and
+// the registry of the context in different threads
+unsafe impl Send for WorkqRegistration {}
+unsafe impl Sync for WorkqRegistration {}
+
+impl WorkqRegistration {
+    // Remove the work queue from the context, unless it is already removed
+    pub(crate) fn remove(&self) -> DOCAResult<()> {
+        if self.removed.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        let ret = unsafe { ffi::doca_ctx_workq_rm(self.ctx.as_ptr(), self.workq.as_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            // the work queue is still in the context, so the removal can be retried
+            self.removed.store(false, Ordering::Release);
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
+    fn is_removed(&self) -> bool {
+        self.removed.load(Ordering::Acquire)
+    }
+}
+
 /// DOCA context
-/// DOCAContext is a thread-safe object.
+/// DOCAContext is a thread-safe object, which can be shared among the threads
+/// submitting to their own work queues.
 pub struct DOCAContext<T: EngineToContext> {
     inner: NonNull<ffi::doca_ctx>,
 
     // Ensure that the engine should be dropped after the context is dropped
     #[allow(dead_code)]
     pub(crate) engine: Arc<T>,
-    #[allow(dead_code)]
-    added_devs: Vec<Arc<DevContext>>,
+    // the devices added into the context, whose lock also serializes the state transitions
+    added_devs: Mutex<Vec<Arc<DevContext>>>,
+    // whether the context has been started, which is read without the lock
+    started: AtomicBool,
+    // the code of the fatal error that broke the context, or zero, i.e., `DOCA_SUCCESS`,
+    // which is never fatal; it is an atomic since it is checked on every submission and poll
+    poison: AtomicU32,
+    // the user callback notified on state changes
+    state_cb: Mutex<Option<StateChangeHandler>>,
+    // the work queues added into the context, which are owned by themselves
+    workqs: Mutex<Vec<Weak<WorkqRegistration>>>,
+    // whether the context is stopped by `close`
+    closed: bool,
+    pub(crate) track: TrackId,
 }
 
+// The SDK context is thread-safe, and it is only reconfigured under the lock of its devices
+unsafe impl<T: EngineToContext + Send + Sync> Sync for DOCAContext<T> {}
+unsafe impl<T: EngineToContext + Send + Sync> Send for DOCAContext<T> {}
+
 impl<T: EngineToContext> DOCAContext<T> {
     /// Create a new DOCA context based on the Engine instance.
     pub fn new(engine: &Arc<T>, added_devs: Vec<Arc<DevContext>>) -> DOCAResult<Arc<Self>> {
@@ -46,14 +148,20 @@ impl<T: EngineToContext> DOCAContext<T> {
         let mut res = Self {
             inner: unsafe { NonNull::new_unchecked(engine.to_ctx()) },
             engine: engine.clone(),
-            added_devs: Vec::new(),
+            added_devs: Mutex::new(Vec::new()),
+            started: AtomicBool::new(false),
+            poison: AtomicU32::new(0),
+            state_cb: Mutex::new(None),
+            workqs: Mutex::new(Vec::new()),
+            closed: false,
+            track: TrackId::new("DOCAContext"),
         };
 
         // add device to it
         for dev in &added_devs {
             res.add_device(dev)?;
         }
-        res.added_devs = added_devs;
+        *res.added_devs.get_mut().unwrap_or_else(|e| e.into_inner()) = added_devs;
 
         // start the context
         res.start()?;
@@ -64,60 +172,352 @@ impl<T: EngineToContext> DOCAContext<T> {
 
 impl<T: EngineToContext> Drop for DOCAContext<T> {
     fn drop(&mut self) {
-        let _ = self.stop().map_err(|e| {
-            panic!("Failed to stop the Context: {:?}", e);
-        });
+        self.track.release();
 
-        for dev in &self.added_devs {
-            let ret = unsafe { ffi::doca_ctx_dev_rm(self.inner_ptr(), dev.inner_ptr()) };
-            if ret != DOCAError::DOCA_SUCCESS {
-                panic!("Failed to remove device from the context: {:?}", ret);
+        if !self.closed {
+            if let Err(e) = self.teardown() {
+                drop_failed!("Failed to stop the Context: {:?}", e);
             }
         }
 
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("DOCA Context is dropped!");
+        dropped!("DOCA Context is dropped!");
     }
 }
 
 impl<T: EngineToContext> DOCAContext<T> {
     /// Finalizes all configurations, and starts the DOCA CTX.
-    pub fn start(&mut self) -> DOCAResult<()> {
-        let ret = unsafe { ffi::doca_ctx_start(self.inner_ptr()) };
-        if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is already started.
+    ///
+    pub fn start(&self) -> DOCAResult<()> {
+        {
+            let _devs = self.lock_devs();
+            if self.is_started() {
+                return Err(DocaError::BadState);
+            }
+
+            let ret = unsafe { ffi::doca_ctx_start(self.inner_ptr()) };
+            if ret != DOCAError::DOCA_SUCCESS {
+                return Err(ret.into());
+            }
+            self.started.store(true, Ordering::Release);
         }
+        debug_event!(ctx = ?self.inner, "context started");
+        self.notify(ContextState::Running);
         Ok(())
     }
 
     /// Stops the context allowing reconfiguration.
-    pub fn stop(&mut self) -> DOCAResult<()> {
-        let ret = unsafe { ffi::doca_ctx_stop(self.inner_ptr()) };
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is not started.
+    ///
+    pub fn stop(&self) -> DOCAResult<()> {
+        if !self.is_started() {
+            return Err(DocaError::BadState);
+        }
+
+        // notified out of the lock, so the callback can still query the context
+        self.notify(ContextState::Stopping);
+        {
+            let _devs = self.lock_devs();
+            if !self.is_started() {
+                return Err(DocaError::BadState);
+            }
+
+            let ret = unsafe { ffi::doca_ctx_stop(self.inner_ptr()) };
+            if ret != DOCAError::DOCA_SUCCESS {
+                return Err(ret.into());
+            }
+            self.started.store(false, Ordering::Release);
+        }
+        debug_event!(ctx = ?self.inner, "context stopped");
+        self.notify(ContextState::Idle);
+        Ok(())
+    }
+
+    /// Stop the context and remove its devices,
+    /// reporting the failure that dropping it would only log.
+    ///
+    /// On failure, the remaining resources of the context are leaked.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_IN_USE`: the context is still referenced, e.g., by its work queues
+    ///  or jobs. Only this reference is dropped.
+    ///
+    pub fn close(self: Arc<Self>) -> DOCAResult<()> {
+        let mut this = Arc::try_unwrap(self).map_err(|_| DocaError::InUse)?;
+        this.closed = true;
+        this.teardown()
+    }
+
+    fn teardown(&mut self) -> DOCAResult<()> {
+        // remove the work queues still added, so they don't refer to the destroyed context
+        let workqs = std::mem::take(self.workqs.get_mut().unwrap_or_else(|e| e.into_inner()));
+        for workq in workqs.iter().filter_map(Weak::upgrade) {
+            workq.remove()?;
+        }
+
+        if self.is_started() {
+            self.stop()?;
+        }
+
+        for dev in self.lock_devs().iter() {
+            let ret = unsafe { ffi::doca_ctx_dev_rm(self.inner_ptr(), dev.inner_ptr()) };
+            if ret != DOCAError::DOCA_SUCCESS {
+                return Err(ret.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop and start the context again, e.g., to recover it after
+    /// the devices are reconfigured by the DOCA SDK.
+    ///
+    /// Return `DOCA_ERROR_BAD_STATE` if the context is not started.
+    pub fn restart(&self) -> DOCAResult<()> {
+        self.stop()?;
+        self.start()
+    }
+
+    /// Stop the context, run `f` to reconfigure it, e.g., with [`add_dev`](Self::add_dev)
+    /// and [`rm_dev`](Self::rm_dev), and start it again, without destroying the engine.
+    ///
+    /// The context is left stopped if `f` fails, and started otherwise,
+    /// even if it was stopped before the call.
+    pub fn reconfigure<F>(&self, f: F) -> DOCAResult<()>
+    where
+        F: FnOnce(&Self) -> DOCAResult<()>,
+    {
+        if self.is_started() {
+            self.stop()?;
+        }
+        f(self)?;
+        self.start()
+    }
+
+    /// Add a device to the stopped context.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is started.
+    ///  - `DOCA_ERROR_IN_USE`: the device is already added.
+    ///
+    pub fn add_dev(&self, dev: &Arc<DevContext>) -> DOCAResult<()> {
+        let mut devs = self.lock_devs();
+        if self.is_started() {
+            return Err(DocaError::BadState);
+        }
+        if devs.iter().any(|d| Arc::ptr_eq(d, dev)) {
+            return Err(DocaError::InUse);
+        }
+
+        self.add_device(dev)?;
+        devs.push(dev.clone());
+        Ok(())
+    }
+
+    /// Remove a device from the stopped context.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is started.
+    ///  - `DOCA_ERROR_NOT_FOUND`: the device is not added to the context.
+    ///
+    pub fn rm_dev(&self, dev: &Arc<DevContext>) -> DOCAResult<()> {
+        let mut devs = self.lock_devs();
+        if self.is_started() {
+            return Err(DocaError::BadState);
+        }
+        let idx = devs
+            .iter()
+            .position(|d| Arc::ptr_eq(d, dev))
+            .ok_or(DocaError::NotFound)?;
+
+        let ret = unsafe { ffi::doca_ctx_dev_rm(self.inner_ptr(), dev.inner_ptr()) };
         if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
+        }
+        devs.remove(idx);
+        Ok(())
+    }
+
+    /// Return whether the context is started.
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    /// Get the current state of the context.
+    ///
+    /// The DOCA 1.5 SDK can't be queried for the state, so it is tracked by the wrapper,
+    /// like the transitions reported to [`on_state_change`](Self::on_state_change).
+    pub fn state(&self) -> ContextState {
+        match self.poisoned() {
+            Some(err) => ContextState::Error(err),
+            None if self.is_started() => ContextState::Running,
+            None => ContextState::Idle,
+        }
+    }
+
+    /// Return the fatal error that poisoned the context, if any.
+    ///
+    /// Once a work queue of the context hits a fatal error, e.g., a driver error,
+    /// the context is considered broken, and later submissions and polls on its
+    /// work queues fail fast with `DOCA_ERROR_BAD_STATE`.
+    pub fn poisoned(&self) -> Option<DOCAError> {
+        match self.poison.load(Ordering::Acquire) {
+            0 => None,
+            code => Some(error_from_code(u64::from(code))),
+        }
+    }
+
+    /// Poison the context if the error indicates that it is broken.
+    pub(crate) fn check_fatal(&self, err: DOCAError) {
+        if !is_fatal(err) {
+            return;
+        }
+
+        // only the first fatal error poisons the context and is notified
+        if self
+            .poison
+            .compare_exchange(0, err as u32, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            debug_event!(ctx = ?self.inner, error = ?err, "context poisoned");
+            self.notify(ContextState::Error(err));
+        }
+    }
+
+    /// Register the callback notified when the context is started, stopped or poisoned,
+    /// which replaces the previously registered one.
+    ///
+    /// The DOCA 1.5 SDK has no state-change callback of its own, so the transitions are
+    /// reported by the wrapper, i.e., [`start`](Self::start), [`stop`](Self::stop) and
+    /// the first fatal error of a work queue.
+    /// A panic inside the callback is caught, see [`crate::callback`].
+    pub fn on_state_change<F>(&self, cb: F)
+    where
+        F: FnMut(ContextState) + Send + 'static,
+    {
+        *self.state_cb.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(cb));
+    }
+
+    fn notify(&self, state: ContextState) {
+        let mut cb = self.state_cb.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cb) = cb.as_mut() {
+            crate::callback::guard("context state callback", || cb(state));
+        }
+    }
+
+    /// Fail fast if the context is poisoned by an earlier error.
+    pub(crate) fn check_poison(&self) -> DOCAResult<()> {
+        if let Some(_cause) = self.poisoned() {
+            trace_event!(ctx = ?self.inner, cause = ?_cause, "context poisoned by an earlier error");
+            return Err(DocaError::BadState);
         }
         Ok(())
     }
 
+    /// Get the devices added into the context.
+    pub fn devices(&self) -> Vec<Arc<DevContext>> {
+        self.lock_devs().clone()
+    }
+
+    // Lock the devices, which also serializes the start and stop of the context
+    fn lock_devs(&self) -> MutexGuard<'_, Vec<Arc<DevContext>>> {
+        self.added_devs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the number of the work queues added into the context,
+    /// either created on it or attached to it.
+    pub fn num_workqs(&self) -> usize {
+        let workqs = self.workqs.lock().unwrap_or_else(|e| e.into_inner());
+        workqs
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|w| !w.is_removed())
+            .count()
+    }
+
+    // Add the work queue into the context, and record it in the registry
+    pub(crate) fn register_workq(
+        &self,
+        workq: NonNull<ffi::doca_workq>,
+    ) -> DOCAResult<Arc<WorkqRegistration>> {
+        let ret = unsafe { ffi::doca_ctx_workq_add(self.inner_ptr(), workq.as_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let registration = Arc::new(WorkqRegistration {
+            ctx: self.inner,
+            workq,
+            removed: AtomicBool::new(false),
+        });
+        let mut workqs = self.workqs.lock().unwrap_or_else(|e| e.into_inner());
+        // drop the entries of the work queues gone
+        workqs.retain(|w| w.strong_count() > 0);
+        workqs.push(Arc::downgrade(&registration));
+        Ok(registration)
+    }
+
     /// Get the inner pointer of the DOCA context.
     pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_ctx {
         self.inner.as_ptr()
     }
 }
 
+impl<T: EngineToContext + 'static> DOCAContext<T> {
+    /// Add the work queue into the context, so that the jobs of the context can be submitted to it.
+    ///
+    /// A work queue is added into its own context on creation, and removed when it is dropped.
+    /// It is for moving a queue between contexts or controlling the order of attaching,
+    /// see [`DOCAWorkQueue::attach`].
+    pub fn add_workq<U: EngineToContext>(
+        self: &Arc<Self>,
+        workq: &mut DOCAWorkQueue<U>,
+    ) -> DOCAResult<()> {
+        workq.attach(self)
+    }
+
+    /// Remove the work queue from the context, see [`DOCAWorkQueue::detach`].
+    pub fn rm_workq<U: EngineToContext>(
+        self: &Arc<Self>,
+        workq: &mut DOCAWorkQueue<U>,
+    ) -> DOCAResult<()> {
+        workq.detach(self)
+    }
+}
+
 impl<T: EngineToContext> DOCAContext<T> {
     /// Add a device to a DOCA CTX.
     #[inline]
-    fn add_device(&mut self, dev: &Arc<DevContext>) -> DOCAResult<()> {
+    fn add_device(&self, dev: &Arc<DevContext>) -> DOCAResult<()> {
         let ret = unsafe { ffi::doca_ctx_dev_add(self.inner_ptr(), dev.inner_ptr()) };
         if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(())
     }
 }
 
+/// Whether the error returned from the data path indicates the context/queue is broken
+fn is_fatal(err: DOCAError) -> bool {
+    matches!(
+        err,
+        DOCAError::DOCA_ERROR_DRIVER
+            | DOCAError::DOCA_ERROR_SHUTDOWN
+            | DOCAError::DOCA_ERROR_OPERATING_SYSTEM
+            | DOCAError::DOCA_ERROR_UNEXPECTED
+            | DOCAError::DOCA_ERROR_UNKNOWN
+    )
+}
+
 /// WorkQueue
+pub mod pool;
 pub mod work_queue;
@@ -0,0 +1,235 @@
+//! Per-thread work queues of a shared context.
+//!
+//! A [`DOCAWorkQueue`] is a per-thread object, so a multi-threaded service needs
+//! one queue per worker thread. [`WorkQueuePool`] creates the queue of a thread
+//! on its first [`with_local_queue`](WorkQueuePool::with_local_queue) call,
+//! keeps it in the thread-local storage, and destroys it when the thread exits.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use std::sync::Arc;
+//! use doca::context::pool::WorkQueuePool;
+//! use doca::context::DOCAContext;
+//! use doca::DMAEngine;
+//!
+//! let device = doca::open_device_with_pci("03:00.0").unwrap();
+//! let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device]).unwrap();
+//! let pool = Arc::new(WorkQueuePool::new(&ctx, 64));
+//!
+//! let workers: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         let pool = pool.clone();
+//!         std::thread::spawn(move || {
+//!             pool.with_local_queue(|workq| workq.inflight()).unwrap();
+//!         })
+//!     })
+//!     .collect();
+//! for w in workers {
+//!     w.join().unwrap();
+//! }
+//! ```
+//!
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::work_queue::DOCAWorkQueue;
+use super::{DOCAContext, EngineToContext};
+use crate::{DOCAResult, DocaError};
+
+static NEXT_POOL_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // The work queues of the calling thread, keyed by the id of their pools.
+    // A queue is taken out while it is lent to the user.
+    static LOCAL_QUEUES: RefCell<HashMap<u64, Option<Box<dyn Any>>>> =
+        RefCell::new(HashMap::new());
+}
+
+// A work queue in the thread-local storage
+struct LocalQueue<T: EngineToContext> {
+    workq: Option<DOCAWorkQueue<T>>,
+    lock: Arc<Mutex<()>>,
+    queues: Arc<AtomicUsize>,
+}
+
+impl<T: EngineToContext> Drop for LocalQueue<T> {
+    fn drop(&mut self) {
+        // remove the queue from the context with the other threads excluded
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.workq.take();
+        self.queues.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// Put the lent queue back, even if the user closure panics
+struct Restore {
+    id: u64,
+    local: Option<Box<dyn Any>>,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let local = self.local.take();
+        let _ = LOCAL_QUEUES.try_with(|q| q.borrow_mut().insert(self.id, local));
+    }
+}
+
+/// A pool creating one [`DOCAWorkQueue`] per thread from a shared context.
+///
+/// The queue of a thread is destroyed when the thread exits, or by
+/// [`release_local_queue`](Self::release_local_queue). The creation and the
+/// destruction of the queues are serialized, since they modify the shared context.
+pub struct WorkQueuePool<T: EngineToContext> {
+    id: u64,
+    ctx: Arc<DOCAContext<T>>,
+    depth: u32,
+    // serializes adding and removing the work queues of the context
+    lock: Arc<Mutex<()>>,
+    // number of the threads holding a queue of the pool
+    queues: Arc<AtomicUsize>,
+}
+
+// The context is only touched under `lock`, and each queue stays in its own thread,
+// while the engine held by the context is shared by the threads
+unsafe impl<T: EngineToContext + Send + Sync> Send for WorkQueuePool<T> {}
+unsafe impl<T: EngineToContext + Send + Sync> Sync for WorkQueuePool<T> {}
+
+impl<T: EngineToContext> Drop for WorkQueuePool<T> {
+    fn drop(&mut self) {
+        // the queues of the other threads are destroyed when they exit
+        let _ = LOCAL_QUEUES.try_with(|q| q.borrow_mut().remove(&self.id));
+    }
+}
+
+impl<T: EngineToContext + 'static> WorkQueuePool<T> {
+    /// Create a pool whose queues are of the `depth` on the context
+    pub fn new(ctx: &Arc<DOCAContext<T>>, depth: u32) -> Self {
+        Self {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed),
+            ctx: ctx.clone(),
+            depth,
+            lock: Arc::new(Mutex::new(())),
+            queues: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Run `f` with the work queue of the calling thread, creating it on the first call.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_IN_USE`: `f` calls it on the same pool again.
+    ///  - other errors returned by [`DOCAWorkQueue::new`].
+    ///
+    pub fn with_local_queue<R, F>(&self, f: F) -> DOCAResult<R>
+    where
+        F: FnOnce(&mut DOCAWorkQueue<T>) -> R,
+    {
+        let local = LOCAL_QUEUES.with(|q| q.borrow_mut().get_mut(&self.id).map(Option::take));
+        let local = match local {
+            Some(Some(local)) => local,
+            Some(None) => return Err(DocaError::InUse),
+            None => self.new_local_queue()?,
+        };
+
+        // mark the queue as lent until it is restored
+        LOCAL_QUEUES.with(|q| q.borrow_mut().insert(self.id, None));
+        let mut restore = Restore {
+            id: self.id,
+            local: Some(local),
+        };
+
+        let workq = restore
+            .local
+            .as_mut()
+            .and_then(|l| l.downcast_mut::<LocalQueue<T>>())
+            .and_then(|l| l.workq.as_mut())
+            .expect("the local queue has the engine of its pool");
+        Ok(f(workq))
+    }
+
+    /// Destroy the work queue of the calling thread, e.g., before the context is stopped.
+    ///
+    /// Return `false` if the thread has no queue of the pool.
+    pub fn release_local_queue(&self) -> bool {
+        LOCAL_QUEUES
+            .with(|q| q.borrow_mut().remove(&self.id))
+            .flatten()
+            .is_some()
+    }
+
+    /// Get the number of the threads holding a work queue of the pool
+    pub fn num_queues(&self) -> usize {
+        self.queues.load(Ordering::Relaxed)
+    }
+
+    /// Get the context shared by the work queues
+    pub fn context(&self) -> &Arc<DOCAContext<T>> {
+        &self.ctx
+    }
+
+    fn new_local_queue(&self) -> DOCAResult<Box<dyn Any>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let workq = DOCAWorkQueue::new(self.depth, &self.ctx)?;
+
+        self.queues.fetch_add(1, Ordering::Relaxed);
+        Ok(Box::new(LocalQueue {
+            workq: Some(workq),
+            lock: self.lock.clone(),
+            queues: self.queues.clone(),
+        }))
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_work_queue_pool() {
+        use super::WorkQueuePool;
+        use crate::context::DOCAContext;
+        use crate::dma::DMAEngine;
+        use crate::DocaError;
+        use std::sync::Arc;
+
+        let device = crate::device::devices()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .open()
+            .unwrap();
+        let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device]).unwrap();
+        let pool = Arc::new(WorkQueuePool::new(&ctx, 8));
+
+        // the same thread gets the same queue
+        let first = pool
+            .with_local_queue(|workq| unsafe { workq.inner_ptr() } as usize)
+            .unwrap();
+        let second = pool
+            .with_local_queue(|workq| unsafe { workq.inner_ptr() } as usize)
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(pool.num_queues(), 1);
+
+        let nested = pool.with_local_queue(|_| pool.with_local_queue(|_| ()).err());
+        assert_eq!(nested, Ok(Some(DocaError::InUse)));
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let pool = pool.clone();
+                std::thread::spawn(move || pool.with_local_queue(|workq| workq.depth()).unwrap())
+            })
+            .collect();
+        for w in workers {
+            assert_eq!(w.join().unwrap(), 8);
+        }
+
+        // the queues are destroyed when their threads exit
+        assert_eq!(pool.num_queues(), 1);
+        assert!(pool.release_local_queue());
+        assert!(!pool.release_local_queue());
+        assert_eq!(pool.num_queues(), 0);
+    }
+}
@@ -9,21 +9,123 @@
 //! The trait [`ToBaseJob`] is designed to receive requests from all data-path
 //! libraries since each one has its own work request.
 //!
+//! Each submitted job is identified by the [`JobHandle`] returned on submission,
+//! and reported back by its [`Completion`], together with its status and user data.
+//! The user data can be any [`UserData`] type, e.g., a pointer to the application state.
+//!
+//! Jobs can also be handed over to the work queue with [`DOCAWorkQueue::submit_owned`],
+//! which gives them back on completion, so that the application can't touch
+//! the buffers while the hardware is still using them.
+//!
+//! A work queue can be attached to the contexts of other engines as well
+//! ([`DOCAWorkQueue::attach`]), so a single polling loop drives mixed offloads:
+//! the finished jobs are dispatched to the handler registered for their type
+//! ([`DOCAWorkQueue::on_completion`] and [`DOCAWorkQueue::dispatch_completion`]).
+//!
+//! An event loop can register a handler for all the completions with
+//! [`DOCAWorkQueue::on_complete`], and drive it with [`DOCAWorkQueue::progress`]
+//! instead of matching the completions to the jobs at every poll site.
+//!
+//! Before shutting down, [`DOCAWorkQueue::drain`] stops the submissions and waits for
+//! the in-flight jobs, so the work queue isn't destroyed under the hardware.
+//!
+//! The work queue counts its submissions, completions and polls in [`WorkQueueStats`],
+//! see [`DOCAWorkQueue::stats`], e.g., to export the health of a datapath to the monitoring.
+//!
+//! Instead of busy polling, a work queue created by [`DOCAWorkQueue::new_event_driven`]
+//! can block in [`DOCAWorkQueue::wait_for_completion`], or expose its
+//! [`event_handle`](DOCAWorkQueue::event_handle) to an epoll/mio loop.
+//!
 //! - [`DOCAEvent`] is an activity completion event. It is used to keep track of which
-//! the submitted job has finished.
+//! the submitted job has finished. The work queue turns it into a [`Completion`].
 
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::os::unix::io::RawFd;
+use std::time::{Duration, Instant};
 use std::{ptr::NonNull, sync::Arc};
 
 use ffi::{doca_event, doca_job};
 
-use crate::{DOCAError, DOCAResult};
+use crate::drop_order::TrackId;
+use crate::error::error_from_code;
+use crate::{DOCAError, DOCAResult, DocaError};
 
-use super::{DOCAContext, EngineToContext};
+use super::{DOCAContext, Engine, EngineToContext, WorkqRegistration};
 
 /// The trait makes WorkQueue capable for various DOCA requests
 pub trait ToBaseJob {
     /// Get the base `doca_job` from a specific job
     fn to_base(&self) -> &doca_job;
+
+    /// Get the mutable base `doca_job` from a specific job,
+    /// which the work queue tags with the handle of the job on submission.
+    ///
+    /// It has no default, since the base job can't be borrowed mutably from
+    /// [`to_base`](Self::to_base), so the jobs implementing the trait outside the crate
+    /// should implement it as well, e.g., `&mut self.inner.base`.
+    fn to_base_mut(&mut self) -> &mut doca_job;
+}
+
+/// The types that can be carried by the 64-bit user data of a job,
+/// e.g., a request id or a pointer to the application state.
+pub trait UserData: Copy {
+    /// Convert the value into the user data
+    fn into_user_data(self) -> u64;
+
+    /// Convert the user data back into the value
+    fn from_user_data(data: u64) -> Self;
+}
+
+impl UserData for u64 {
+    fn into_user_data(self) -> u64 {
+        self
+    }
+
+    fn from_user_data(data: u64) -> Self {
+        data
+    }
+}
+
+impl UserData for u32 {
+    fn into_user_data(self) -> u64 {
+        self as u64
+    }
+
+    fn from_user_data(data: u64) -> Self {
+        data as u32
+    }
+}
+
+impl UserData for usize {
+    fn into_user_data(self) -> u64 {
+        self as u64
+    }
+
+    fn from_user_data(data: u64) -> Self {
+        data as usize
+    }
+}
+
+impl<T> UserData for *const T {
+    fn into_user_data(self) -> u64 {
+        self as usize as u64
+    }
+
+    fn from_user_data(data: u64) -> Self {
+        data as usize as *const T
+    }
+}
+
+impl<T> UserData for *mut T {
+    fn into_user_data(self) -> u64 {
+        self as usize as u64
+    }
+
+    fn from_user_data(data: u64) -> Self {
+        data as usize as *mut T
+    }
 }
 
 ///Event structure defines activity completion of:
@@ -45,11 +147,150 @@ impl DOCAEvent {
 
     /// Get the return value of the event
     pub fn result(&self) -> DOCAError {
-        unsafe {
-            // FIXME: what if DOCAError is not u32?
-            let res: DOCAError = std::mem::transmute(self.inner.result.u64 as u32);
-            res
+        error_from_code(unsafe { self.inner.result.u64 })
+    }
+
+    /// Get the user data of the job that the event belongs to.
+    ///
+    /// For a job submitted by [`DOCAWorkQueue::submit`], it is the id of its [`JobHandle`],
+    /// and the user data set by the application is reported by its [`Completion`].
+    pub fn user_data(&self) -> u64 {
+        unsafe { self.inner.user_data.u64 }
+    }
+}
+
+/// The token of a submitted job, returned by [`DOCAWorkQueue::submit`]
+/// and reported by the [`Completion`] of the job.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct JobHandle(u64);
+
+impl JobHandle {
+    /// Get the id of the job, which is unique in its work queue
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The completion of a job submitted to a [`DOCAWorkQueue`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Completion {
+    /// The handle returned when the job was submitted
+    pub handle: JobHandle,
+    /// The result of the job
+    pub status: DOCAError,
+    /// The user data of the job when it was submitted
+    pub user_data: u64,
+    /// The time from the submission to the retrieval of the job,
+    /// recorded if [`DOCAWorkQueue::enable_timestamps`] is on
+    pub latency: Option<Duration>,
+}
+
+impl Completion {
+    /// Get the user data of the job as the type it was set with,
+    /// e.g., by [`DOCADMAJob::set_user_data`](crate::dma::DOCADMAJob::set_user_data).
+    pub fn user_data_as<U: UserData>(&self) -> U {
+        U::from_user_data(self.user_data)
+    }
+
+    /// Return `Err` with the status if the job failed
+    pub fn result(&self) -> DOCAResult<()> {
+        match self.status {
+            DOCAError::DOCA_SUCCESS => Ok(()),
+            e => Err(e.into()),
+        }
+    }
+}
+
+// A context the work queue is attached to, regardless of its engine
+trait AttachedContext {
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx;
+}
+
+impl<U: EngineToContext> AttachedContext for DOCAContext<U> {
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx {
+        self.inner_ptr()
+    }
+}
+
+type CompletionHandler = Box<dyn FnMut(Completion, Box<dyn Any>) -> DOCAResult<()>>;
+
+type CompleteCallback = Box<dyn FnMut(Completion)>;
+
+/// Error type of [`DOCAWorkQueue::submit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitError {
+    /// The work queue already holds as many in-flight jobs as its depth.
+    /// Poll the finished jobs before submitting more.
+    QueueFull {
+        /// The depth of the work queue
+        depth: u32,
+    },
+    /// The work queue is being drained by [`DOCAWorkQueue::drain`],
+    /// and accepts no more jobs.
+    Draining,
+    /// The error returned by DOCA
+    Doca(DocaError),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitError::QueueFull { depth } => {
+                write!(f, "the work queue is full with {} in-flight jobs", depth)
+            }
+            SubmitError::Draining => write!(f, "the work queue is being drained"),
+            SubmitError::Doca(e) => write!(f, "failed to submit the job: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+impl From<DocaError> for SubmitError {
+    fn from(e: DocaError) -> Self {
+        SubmitError::Doca(e)
+    }
+}
+
+impl From<SubmitError> for DocaError {
+    fn from(e: SubmitError) -> Self {
+        match e {
+            // the same error the SDK returns on an overflowed work queue
+            SubmitError::QueueFull { .. } => DocaError::NoMemory,
+            SubmitError::Draining => DocaError::Shutdown,
+            SubmitError::Doca(e) => e,
+        }
+    }
+}
+
+/// The counters of a [`DOCAWorkQueue`], see [`DOCAWorkQueue::stats`].
+///
+/// They are counted since the work queue is created, or since the last
+/// [`reset_stats`](DOCAWorkQueue::reset_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorkQueueStats {
+    /// The number of the submitted jobs
+    pub submitted: u64,
+    /// The number of the retrieved jobs, either succeeded or failed
+    pub completed: u64,
+    /// The number of the failed jobs, and of the submissions and polls
+    /// failed with an error other than `DOCA_ERROR_AGAIN`
+    pub errors: u64,
+    /// The number of the submissions and polls returning `DOCA_ERROR_AGAIN`,
+    /// e.g., polling before any job has finished
+    pub retries: u64,
+    /// The number of the calls retrieving a finished job
+    pub polls: u64,
+}
+
+impl WorkQueueStats {
+    /// Get the average number of the polls for each retrieved job,
+    /// which is 1 if the work queue is never polled in vain, and 0 before any job finishes.
+    pub fn avg_polls_per_completion(&self) -> f64 {
+        if self.completed == 0 {
+            return 0.0;
         }
+        self.polls as f64 / self.completed as f64
     }
 }
 
@@ -61,66 +302,281 @@ impl DOCAEvent {
 pub struct DOCAWorkQueue<T: EngineToContext> {
     inner: NonNull<ffi::doca_workq>,
     depth: u32,
+    // number of the submitted jobs that haven't been retrieved
+    inflight: u32,
     #[allow(dead_code)]
     pub(crate) ctx: Arc<DOCAContext<T>>,
+    // the registration in `ctx`, `None` if the work queue is not added into it
+    in_ctx: Option<Arc<WorkqRegistration>>,
+    // the user data and submission time of the in-flight jobs, keyed by their job id
+    pending: HashMap<u64, (u64, Option<Instant>)>,
+    // whether to timestamp the submissions
+    timestamps: bool,
+    // the jobs submitted by `submit_owned`, keyed by their job id
+    owned: HashMap<u64, Box<dyn Any>>,
+    next_id: u64,
+    // the contexts of other engines the work queue is attached to, with the registrations
+    attached: Vec<(Arc<dyn AttachedContext>, Arc<WorkqRegistration>)>,
+    // the completion handlers, keyed by the job type
+    handlers: HashMap<TypeId, CompletionHandler>,
+    // the handler of all the completions retrieved by `progress`
+    on_complete: Option<CompleteCallback>,
+    // whether the completions are notified through the event handle
+    event_driven: bool,
+    stats: WorkQueueStats,
+    // whether the work queue is drained, so it accepts no more jobs
+    draining: bool,
+    // whether the work queue is destroyed by `close`
+    closed: bool,
+    pub(crate) track: TrackId,
 }
 
 impl<T: EngineToContext> Drop for DOCAWorkQueue<T> {
     fn drop(&mut self) {
-        // remove the worker queue from the context
-        let ret = unsafe { ffi::doca_ctx_workq_rm(self.ctx.inner_ptr(), self.inner_ptr()) };
-        assert_eq!(
-            ret,
-            DOCAError::DOCA_SUCCESS,
-            "failed to remove workq from context"
-        );
-        unsafe { ffi::doca_workq_destroy(self.inner_ptr()) };
+        self.track.release();
+
+        if !self.closed {
+            if let Err(e) = self.teardown() {
+                drop_failed!("failed to destroy the workq: {:?}", e);
+            }
+        }
 
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("DOCA WorkQ is dropped!");
+        dropped!("DOCA WorkQ is dropped!");
     }
 }
 
 impl<T: EngineToContext> DOCAWorkQueue<T> {
     /// Creates empty DOCA WorkQ object with default attributes.
     pub fn new(depth: u32, ctx: &Arc<DOCAContext<T>>) -> DOCAResult<Self> {
+        Self::create(depth, ctx, false)
+    }
+
+    /// Creates an event-driven DOCA WorkQ, so the finished jobs can be waited for
+    /// by [`wait_for_completion`](Self::wait_for_completion) or through the
+    /// [`event_handle`](Self::event_handle), instead of busy polling.
+    pub fn new_event_driven(depth: u32, ctx: &Arc<DOCAContext<T>>) -> DOCAResult<Self> {
+        Self::create(depth, ctx, true)
+    }
+
+    fn create(depth: u32, ctx: &Arc<DOCAContext<T>>, event_driven: bool) -> DOCAResult<Self> {
         let mut workq: *mut ffi::doca_workq = std::ptr::null_mut();
         let ret = unsafe { ffi::doca_workq_create(depth, &mut workq as *mut _) };
 
         if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
-        let res = Self {
+        let mut res = Self {
             inner: unsafe { NonNull::new_unchecked(workq) },
             depth: depth,
+            inflight: 0,
             ctx: ctx.clone(),
+            in_ctx: None,
+            pending: HashMap::new(),
+            timestamps: false,
+            owned: HashMap::new(),
+            next_id: 1,
+            attached: Vec::new(),
+            handlers: HashMap::new(),
+            on_complete: None,
+            event_driven,
+            stats: WorkQueueStats::default(),
+            draining: false,
+            closed: false,
+            track: TrackId::new("DOCAWorkQueue"),
         };
 
+        // the mode can only be set before the work queue is added to a context
+        if event_driven {
+            let ret = unsafe { ffi::doca_workq_set_event_driven_enable(res.inner_ptr(), 1) };
+            if ret != DOCAError::DOCA_SUCCESS {
+                return Err(ret.into());
+            }
+        }
+
         // add the myself to the context
-        let ret = unsafe { ffi::doca_ctx_workq_add(ctx.inner_ptr(), res.inner_ptr()) };
+        res.in_ctx = Some(ctx.register_workq(res.inner)?);
 
-        if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+        Ok(res)
+    }
+
+    /// Remove the work queue from its contexts and destroy it,
+    /// reporting the failure that dropping it would only log.
+    ///
+    /// On failure, the work queue is leaked, since it may still be used by a context.
+    pub fn close(mut self) -> DOCAResult<()> {
+        self.closed = true;
+        self.teardown()
+    }
+
+    fn teardown(&mut self) -> DOCAResult<()> {
+        // remove the worker queue from the attached contexts first,
+        // unless a context has already removed it when torn down
+        for (_, registration) in &self.attached {
+            registration.remove()?;
         }
 
-        Ok(res)
+        // remove the worker queue from the context
+        if let Some(registration) = &self.in_ctx {
+            registration.remove()?;
+        }
+
+        let ret = unsafe { ffi::doca_workq_destroy(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(())
     }
 
-    /// Add the job into the work queue
-    pub fn submit<Job: ToBaseJob>(&mut self, job: &Job) -> DOCAResult<()> {
+    /// Add the job into the work queue, and return the handle identifying it.
+    ///
+    /// The user data of the job is replaced by the id of the handle, the original one
+    /// is reported by the [`Completion`] of the job. So set it again before resubmitting the job.
+    ///
+    /// # Errors
+    ///
+    ///  - [`SubmitError::QueueFull`]: the number of [`inflight`](Self::inflight) jobs
+    ///  reaches the depth of the work queue.
+    ///  - [`SubmitError::Draining`]: the work queue is being [`drain`](Self::drain)ed.
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is poisoned by an earlier fatal error.
+    ///  - other errors returned by `doca_workq_submit`.
+    pub fn submit<Job: ToBaseJob>(&mut self, job: &mut Job) -> Result<JobHandle, SubmitError> {
+        self.ctx.check_poison()?;
+
+        if self.draining {
+            return Err(SubmitError::Draining);
+        }
+
+        if self.inflight >= self.depth {
+            return Err(SubmitError::QueueFull { depth: self.depth });
+        }
+        debug_assert!(
+            unsafe { self.accepts(job.to_base().ctx) },
+            "the job is not created for a context of the work queue"
+        );
+
+        let handle = JobHandle(self.next_id);
+        let base = job.to_base_mut();
+        let user_data = unsafe { base.user_data.u64 };
+        base.user_data.u64 = handle.0;
+
         let ret = unsafe { ffi::doca_workq_submit(self.inner_ptr(), job.to_base() as *const _) };
         if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+            job.to_base_mut().user_data.u64 = user_data;
+            self.count_error(ret);
+            self.ctx.check_fatal(ret);
+            return Err(DocaError::from(ret).into());
         }
 
+        self.next_id += 1;
+        self.stats.submitted += 1;
+        let submitted = if self.timestamps {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        self.pending.insert(handle.0, (user_data, submitted));
+        self.inflight += 1;
+        trace_event!(job = handle.0, inflight = self.inflight, "job submitted");
+        Ok(handle)
+    }
+
+    /// Add the jobs into the work queue in order, and push their handles into `handles`,
+    /// so the vector can be reused across the batches.
+    ///
+    /// The whole batch is rejected with [`SubmitError::QueueFull`] before any job
+    /// is submitted if the work queue can't hold all of them.
+    /// If submitting a job fails, the jobs before it stay submitted,
+    /// i.e., the handles pushed so far are the in-flight ones.
+    ///
+    /// It fails in the same cases as [`submit`](Self::submit).
+    pub fn submit_batch<Job: ToBaseJob>(
+        &mut self,
+        jobs: &mut [Job],
+        handles: &mut Vec<JobHandle>,
+    ) -> Result<(), SubmitError> {
+        self.ctx.check_poison()?;
+
+        if self.draining {
+            return Err(SubmitError::Draining);
+        }
+        if (self.inflight as usize).saturating_add(jobs.len()) > self.depth as usize {
+            return Err(SubmitError::QueueFull { depth: self.depth });
+        }
+
+        handles.reserve(jobs.len());
+        for job in jobs.iter_mut() {
+            handles.push(self.submit(job)?);
+        }
         Ok(())
     }
 
-    /// Check whether there's a job finished in the work queue
+    /// Retrieve up to `max` finished jobs in the work queue.
+    ///
+    /// Unlike [`poll_completion`](Self::poll_completion), it returns an empty vector
+    /// if no job has finished yet. If an error occurs after some jobs are retrieved,
+    /// they are returned and the error is left to the next poll.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is poisoned by an earlier fatal error.
+    ///  - other errors returned by `doca_workq_progress_retrieve`.
+    pub fn poll_completions(&mut self, max: usize) -> DOCAResult<Vec<Completion>> {
+        self.ctx.check_poison()?;
+
+        let mut completions = Vec::with_capacity(max.min(self.inflight as usize));
+        while completions.len() < max {
+            match self.retrieve_completion() {
+                Ok(completion) => completions.push(completion),
+                Err(DocaError::Again) => break,
+                Err(e) if completions.is_empty() => return Err(e),
+                Err(_) => break,
+            }
+        }
+        Ok(completions)
+    }
+
+    /// Check whether there's a job finished in the work queue.
+    ///
+    /// A failed job is reported as a completion as well, see [`Completion::result`].
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_AGAIN`: no job has finished yet.
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is poisoned by an earlier fatal error.
+    ///  - other errors returned by `doca_workq_progress_retrieve`.
     #[inline]
-    pub fn poll_completion(&mut self) -> DOCAResult<DOCAEvent> {
+    pub fn poll_completion(&mut self) -> DOCAResult<Completion> {
+        self.ctx.check_poison()?;
+        self.retrieve_completion()
+    }
+
+    // Retrieve a finished job, and match it with its submission
+    fn retrieve_completion(&mut self) -> DOCAResult<Completion> {
+        let (ret, event) = self.retrieve();
+        let status = match ret {
+            DOCAError::DOCA_SUCCESS => DOCAError::DOCA_SUCCESS,
+            // the job failed, and the event holds its error
+            DOCAError::DOCA_ERROR_IO_FAILED => {
+                self.ctx.check_fatal(event.result());
+                event.result()
+            }
+            ret => return Err(ret.into()),
+        };
+
+        let id = event.user_data();
+        let (user_data, submitted) = self.pending.remove(&id).unwrap_or_default();
+        trace_event!(job = id, status = ?status, "job completed");
+        Ok(Completion {
+            handle: JobHandle(id),
+            status,
+            user_data,
+            latency: submitted.map(|t| t.elapsed()),
+        })
+    }
+
+    // Retrieve a finished job, returning its event even if the job failed
+    fn retrieve(&mut self) -> (DOCAError, DOCAEvent) {
         let mut event = DOCAEvent::new();
         let ret = unsafe {
             ffi::doca_workq_progress_retrieve(
@@ -129,10 +585,420 @@ impl<T: EngineToContext> DOCAWorkQueue<T> {
                 ffi::DOCA_WORKQ_RETRIEVE_FLAGS_NONE as i32,
             )
         };
+
+        // a job is retrieved, either succeeded or failed,
+        // while the other errors don't return any job
+        self.stats.polls += 1;
+        if ret == DOCAError::DOCA_SUCCESS || ret == DOCAError::DOCA_ERROR_IO_FAILED {
+            self.inflight = self.inflight.saturating_sub(1);
+            self.stats.completed += 1;
+        }
+
         if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+            self.count_error(ret);
+            self.ctx.check_fatal(ret);
+        }
+        (ret, event)
+    }
+
+    // Count the error returned by DOCA, where `DOCA_ERROR_AGAIN` only asks to retry
+    fn count_error(&mut self, ret: DOCAError) {
+        if ret == DOCAError::DOCA_ERROR_AGAIN {
+            self.stats.retries += 1;
+        } else {
+            self.stats.errors += 1;
+        }
+    }
+
+    /// Get the file descriptor notified when a job finishes, e.g., to be registered into epoll.
+    ///
+    /// The handle is only notified once [`arm_event`](Self::arm_event) is called,
+    /// and it should be cleared by [`clear_event`](Self::clear_event) after the notification.
+    ///
+    /// Return `DOCA_ERROR_BAD_STATE` if the work queue is not created by
+    /// [`new_event_driven`](Self::new_event_driven).
+    pub fn event_handle(&self) -> DOCAResult<RawFd> {
+        if !self.event_driven {
+            return Err(DocaError::BadState);
+        }
+
+        let mut handle: ffi::doca_event_handle_t = -1;
+        let ret =
+            unsafe { ffi::doca_workq_get_event_handle(self.inner_ptr(), &mut handle as *mut _) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(handle as RawFd)
+    }
+
+    /// Request a notification on the [`event_handle`](Self::event_handle) when a job finishes.
+    ///
+    /// Poll the work queue once more after arming it, since the jobs finished before
+    /// are not notified.
+    pub fn arm_event(&mut self) -> DOCAResult<()> {
+        let ret = unsafe { ffi::doca_workq_event_handle_arm(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
+    /// Acknowledge the notification on the [`event_handle`](Self::event_handle).
+    pub fn clear_event(&mut self) -> DOCAResult<()> {
+        let ret = unsafe { ffi::doca_workq_event_handle_clear(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
+    /// Block until a job finishes, or the `timeout` expires if it is set.
+    ///
+    /// A failed job is reported as a completion as well, see [`Completion::result`].
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the work queue is not event-driven, or the context
+    ///  is poisoned by an earlier fatal error.
+    ///  - `DOCA_ERROR_NOT_FOUND`: no job is in flight, so none will finish.
+    ///  - `DOCA_ERROR_TIME_OUT`: no job finished before the `timeout`.
+    ///  - `DOCA_ERROR_OPERATING_SYSTEM`: failed to wait on the event handle.
+    ///  - other errors returned by [`poll_completion`](Self::poll_completion).
+    pub fn wait_for_completion(&mut self, timeout: Option<Duration>) -> DOCAResult<Completion> {
+        let fd = self.event_handle()?;
+        let deadline = timeout.map(|t| Instant::now() + t);
+
+        loop {
+            if self.inflight == 0 {
+                return Err(DocaError::NotFound);
+            }
+            match self.poll_completion() {
+                Err(DocaError::Again) => {}
+                res => return res,
+            }
+
+            // check again after arming, for the jobs finished in between
+            self.arm_event()?;
+            match self.poll_completion() {
+                Err(DocaError::Again) => {}
+                res => return res,
+            }
+
+            let wait_ms = match deadline {
+                None => -1,
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return Err(DocaError::TimeOut);
+                    }
+                    // round up, so it doesn't spin on the last sub-millisecond
+                    left.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32
+                }
+            };
+
+            let mut pfd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let n = unsafe { libc::poll(&mut pfd as *mut _, 1, wait_ms) };
+            if n < 0 {
+                if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(DocaError::OperatingSystem);
+            }
+            if n > 0 {
+                self.clear_event()?;
+            }
+        }
+    }
+
+    /// Poll the work queue until a job finishes, or the `timeout` expires,
+    /// so a wedged device or an invalidated remote mmap doesn't hang the caller.
+    ///
+    /// It fails in the same cases as [`wait_completion_deadline`](Self::wait_completion_deadline).
+    pub fn poll_completion_timeout(&mut self, timeout: Duration) -> DOCAResult<Completion> {
+        self.wait_completion_deadline(Instant::now() + timeout)
+    }
+
+    /// Poll the work queue until a job finishes, or the `deadline` passes.
+    /// An event-driven work queue sleeps on its event handle in between,
+    /// see [`wait_for_completion`](Self::wait_for_completion), and the others busy poll.
+    ///
+    /// A failed job is reported as a completion as well, see [`Completion::result`].
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_NOT_FOUND`: no job is in flight, so none will finish.
+    ///  - `DOCA_ERROR_TIME_OUT`: no job finished before the `deadline`.
+    ///  - other errors returned by [`poll_completion`](Self::poll_completion).
+    pub fn wait_completion_deadline(&mut self, deadline: Instant) -> DOCAResult<Completion> {
+        if self.event_driven {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            return self.wait_for_completion(Some(timeout));
+        }
+
+        loop {
+            if self.inflight == 0 {
+                return Err(DocaError::NotFound);
+            }
+            match self.poll_completion() {
+                Err(DocaError::Again) => {}
+                res => return res,
+            }
+            if Instant::now() >= deadline {
+                return Err(DocaError::TimeOut);
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// Hand the job over to the work queue.
+    /// It is given back by [`poll_completion_owned`](Self::poll_completion_owned) when it finishes,
+    /// so its buffers can't be modified while the hardware is accessing them.
+    ///
+    /// It fails in the same cases as [`submit`](Self::submit).
+    pub fn submit_owned<Job: ToBaseJob + 'static>(
+        &mut self,
+        mut job: Job,
+    ) -> Result<JobHandle, SubmitError> {
+        let handle = self.submit(&mut job)?;
+        self.owned.insert(handle.0, Box::new(job));
+        Ok(handle)
+    }
+
+    /// Check whether a job submitted by [`submit_owned`](Self::submit_owned) finished,
+    /// and take it back together with its completion.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_AGAIN`: no job has finished yet.
+    ///  - `DOCA_ERROR_NOT_FOUND`: the finished job is not submitted by `submit_owned`.
+    ///  - `DOCA_ERROR_INVALID_VALUE`: the finished job is not of type `Job`, it is dropped.
+    ///  - the status of the job if it failed. The job is dropped as well, since the hardware
+    ///  no longer accesses its buffers.
+    ///  - other errors returned by [`poll_completion`](Self::poll_completion).
+    pub fn poll_completion_owned<Job: ToBaseJob + 'static>(
+        &mut self,
+    ) -> DOCAResult<(Completion, Job)> {
+        let (completion, job) = self.retrieve_owned()?;
+        let job = job.downcast::<Job>().map_err(|_| DocaError::InvalidValue)?;
+
+        Ok((completion, *job))
+    }
+
+    // Retrieve a finished job submitted by `submit_owned`
+    fn retrieve_owned(&mut self) -> DOCAResult<(Completion, Box<dyn Any>)> {
+        self.ctx.check_poison()?;
+
+        let completion = self.retrieve_completion()?;
+        let job = self
+            .owned
+            .remove(&completion.handle.0)
+            .ok_or(DocaError::NotFound)?;
+
+        // the failed job is done as well
+        completion.result()?;
+        Ok((completion, job))
+    }
+
+    /// Attach the work queue to the context of another engine,
+    /// so that the jobs of that context can be submitted to it as well.
+    /// The work queue is removed from the attached contexts when it is dropped.
+    ///
+    /// It can also re-attach the work queue to its own context after [`detach`](Self::detach).
+    ///
+    /// Return `DOCA_ERROR_IN_USE` if the work queue is already attached to the context.
+    pub fn attach<U: EngineToContext + 'static>(
+        &mut self,
+        ctx: &Arc<DOCAContext<U>>,
+    ) -> DOCAResult<()> {
+        let ctx_ptr = unsafe { ctx.inner_ptr() };
+        let own = ctx_ptr == unsafe { self.ctx.inner_ptr() };
+        if (own && self.in_ctx.is_some()) || self.attached_index(ctx_ptr).is_some() {
+            return Err(DocaError::InUse);
+        }
+
+        let registration = ctx.register_workq(self.inner)?;
+        if own {
+            self.in_ctx = Some(registration);
+        } else {
+            self.attached.push((ctx.clone(), registration));
+        }
+        Ok(())
+    }
+
+    /// Detach the work queue from the context, either its own context or an attached one.
+    /// Jobs of the context can't be submitted to the work queue afterwards.
+    ///
+    /// Return `DOCA_ERROR_NOT_FOUND` if the work queue is not attached to the context.
+    pub fn detach<U: EngineToContext>(&mut self, ctx: &Arc<DOCAContext<U>>) -> DOCAResult<()> {
+        let ctx_ptr = unsafe { ctx.inner_ptr() };
+        let own = ctx_ptr == unsafe { self.ctx.inner_ptr() };
+        let index = self.attached_index(ctx_ptr);
+        let registration = match index {
+            Some(i) => &self.attached[i].1,
+            None if own => self.in_ctx.as_ref().ok_or(DocaError::NotFound)?,
+            None => return Err(DocaError::NotFound),
+        };
+        registration.remove()?;
+
+        match index {
+            Some(i) => {
+                self.attached.remove(i);
+            }
+            None => self.in_ctx = None,
+        }
+        Ok(())
+    }
+
+    fn attached_index(&self, ctx: *mut ffi::doca_ctx) -> Option<usize> {
+        self.attached
+            .iter()
+            .position(|(c, _)| unsafe { c.ctx_ptr() } == ctx)
+    }
+
+    /// Register the handler of the finished jobs of type `Job`,
+    /// which replaces the previously registered one of the same type.
+    pub fn on_completion<Job, F>(&mut self, mut handler: F)
+    where
+        Job: ToBaseJob + 'static,
+        F: FnMut(Completion, Job) + 'static,
+    {
+        let handler = move |completion, job: Box<dyn Any>| {
+            // the handler is looked up with the type of the job, so the downcast never fails
+            let job = job
+                .downcast::<Job>()
+                .map_err(|_job| DocaError::Unexpected)?;
+            handler(completion, *job);
+            Ok(())
+        };
+        self.handlers.insert(TypeId::of::<Job>(), Box::new(handler));
+    }
+
+    /// Check whether a job submitted by [`submit_owned`](Self::submit_owned) finished,
+    /// and pass it to the handler registered by [`on_completion`](Self::on_completion) for its type.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_AGAIN`: no job has finished yet.
+    ///  - `DOCA_ERROR_NOT_FOUND`: the finished job is not submitted by `submit_owned`,
+    ///  or no handler is registered for its type, the job is dropped in the latter case.
+    ///  - `DOCA_ERROR_UNEXPECTED`: the job doesn't match the type of its handler.
+    ///  - other errors returned by [`poll_completion`](Self::poll_completion).
+    pub fn dispatch_completion(&mut self) -> DOCAResult<()> {
+        let (completion, job) = self.retrieve_owned()?;
+
+        let handler = self
+            .handlers
+            .get_mut(&(*job).type_id())
+            .ok_or(DocaError::NotFound)?;
+        handler(completion, job)
+    }
+
+    /// Register the handler of all the finished jobs retrieved by [`progress`](Self::progress),
+    /// which replaces the previously registered one.
+    ///
+    /// The job is identified by the [`handle`](Completion::handle) of the completion,
+    /// i.e., the one returned when it was submitted.
+    pub fn on_complete<F>(&mut self, handler: F)
+    where
+        F: FnMut(Completion) + 'static,
+    {
+        self.on_complete = Some(Box::new(handler));
+    }
+
+    /// Retrieve all the jobs finished so far, and pass each completion,
+    /// either succeeded or failed, to the handler registered by [`on_complete`](Self::on_complete).
+    /// Return the number of the retrieved jobs, which is 0 if none has finished.
+    ///
+    /// A job submitted by [`submit_owned`](Self::submit_owned) is passed to the handler
+    /// registered by [`on_completion`](Self::on_completion) for its type if it succeeded,
+    /// and dropped otherwise.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the context is poisoned by an earlier fatal error.
+    ///  - `DOCA_ERROR_UNEXPECTED`: a job doesn't match the type of its handler.
+    ///  - other errors returned by [`poll_completion`](Self::poll_completion).
+    pub fn progress(&mut self) -> DOCAResult<usize> {
+        self.ctx.check_poison()?;
+
+        let mut finished = 0;
+        loop {
+            let completion = match self.retrieve_completion() {
+                Err(DocaError::Again) => return Ok(finished),
+                res => res?,
+            };
+            finished += 1;
+
+            let mut dispatched = Ok(());
+            if let Some(job) = self.owned.remove(&completion.handle.0) {
+                if completion.result().is_ok() {
+                    if let Some(handler) = self.handlers.get_mut(&(*job).type_id()) {
+                        dispatched = handler(completion, job);
+                    }
+                }
+            }
+            if let Some(handler) = self.on_complete.as_mut() {
+                handler(completion);
+            }
+            dispatched?;
+        }
+    }
+
+    /// Stop accepting jobs, and retrieve the in-flight ones until all of them
+    /// have finished, either succeeded or failed, or the `timeout` expires.
+    /// Return the number of the jobs still in flight, which is 0 if the work queue is drained.
+    ///
+    /// It is meant for shutting down a service, so the work queue isn't dropped
+    /// while the hardware still accesses the buffers of its jobs.
+    /// The retrieved jobs are handled as by [`progress`](Self::progress),
+    /// and [`submit`](Self::submit) fails with [`SubmitError::Draining`] afterwards.
+    ///
+    /// It fails in the same cases as [`progress`](Self::progress).
+    pub fn drain(&mut self, timeout: Duration) -> DOCAResult<u32> {
+        self.draining = true;
+        let deadline = Instant::now() + timeout;
+
+        while self.inflight > 0 {
+            if self.progress()? == 0 {
+                if Instant::now() >= deadline {
+                    break;
+                }
+                std::hint::spin_loop();
+            }
+        }
+        trace_event!(inflight = self.inflight, "work queue drained");
+        Ok(self.inflight)
+    }
+
+    /// Whether the work queue is being [`drain`](Self::drain)ed, so it accepts no more jobs
+    pub fn is_draining(&self) -> bool {
+        self.draining
+    }
+
+    // Check whether jobs of the context can be submitted to the work queue
+    unsafe fn accepts(&self, ctx: *mut ffi::doca_ctx) -> bool {
+        (self.in_ctx.is_some() && ctx == self.ctx.inner_ptr()) || self.attached_index(ctx).is_some()
+    }
+
+    /// Number of the jobs submitted by [`submit_owned`](Self::submit_owned)
+    /// that haven't been taken back.
+    pub fn owned_jobs(&self) -> usize {
+        self.owned.len()
+    }
+
+    /// Busy poll the work queue until a job finishes, failing if the job failed
+    pub(crate) fn spin_completion(&mut self) -> DOCAResult<Completion> {
+        loop {
+            match self.poll_completion() {
+                Err(DocaError::Again) => continue,
+                Ok(completion) => return completion.result().map(|_| completion),
+                res => return res,
+            }
         }
-        Ok(event)
     }
 
     /// Get the inner pointer of the DOCA WorkQ.
@@ -144,14 +1010,87 @@ impl<T: EngineToContext> DOCAWorkQueue<T> {
     pub fn depth(&self) -> u32 {
         self.depth
     }
+
+    /// Get the number of the submitted jobs that haven't been retrieved,
+    /// either succeeded or failed
+    pub fn inflight(&self) -> u32 {
+        self.inflight
+    }
+
+    /// Get the number of the jobs that can be submitted before
+    /// [`submit`](Self::submit) fails with [`SubmitError::QueueFull`]
+    pub fn available(&self) -> u32 {
+        self.depth.saturating_sub(self.inflight)
+    }
+
+    /// Get a snapshot of the counters of the work queue
+    pub fn stats(&self) -> WorkQueueStats {
+        self.stats
+    }
+
+    /// Reset the counters of the work queue to zero, e.g., at the start of each monitoring interval
+    pub fn reset_stats(&mut self) {
+        self.stats = WorkQueueStats::default();
+    }
+
+    /// Record the submission time of the jobs, so their [`Completion::latency`] is reported.
+    /// It is off by default, since reading the clock on every submission costs.
+    ///
+    /// The jobs submitted before it is turned on report no latency.
+    pub fn enable_timestamps(&mut self, on: bool) {
+        self.timestamps = on;
+    }
+}
+
+impl<T: Engine> DOCAWorkQueue<T> {
+    /// Create a job of the engine for the context of the work queue
+    pub fn new_job(&self) -> T::Job {
+        T::new_job(&self.ctx)
+    }
 }
 
 mod tests {
+    #[test]
+    fn test_typed_user_data() {
+        use super::{Completion, JobHandle, UserData};
+        use crate::DOCAError;
+
+        let state = Box::new(7u32);
+        let ptr: *const u32 = &*state;
+        let completion = Completion {
+            handle: JobHandle(0),
+            status: DOCAError::DOCA_SUCCESS,
+            user_data: ptr.into_user_data(),
+            latency: None,
+        };
+        assert_eq!(completion.user_data_as::<*const u32>(), ptr);
+        assert_eq!(unsafe { *completion.user_data_as::<*const u32>() }, 7);
+
+        assert_eq!(u32::from_user_data(42u32.into_user_data()), 42);
+        assert_eq!(
+            usize::from_user_data(usize::MAX.into_user_data()),
+            usize::MAX
+        );
+    }
+
+    #[test]
+    fn test_stats_average() {
+        use super::WorkQueueStats;
+
+        let mut stats = WorkQueueStats::default();
+        assert_eq!(stats.avg_polls_per_completion(), 0.0);
+
+        stats.completed = 4;
+        stats.retries = 6;
+        stats.polls = 10;
+        assert_eq!(stats.avg_polls_per_completion(), 2.5);
+    }
+
     #[test]
     fn test_worker_queue_create() {
         use crate::context::DOCAContext;
         use crate::dma::DMAEngine;
-        use crate::DOCAWorkQueue;
+        use crate::{DOCAWorkQueue, DocaError};
 
         let device = crate::device::devices()
             .unwrap()
@@ -164,8 +1103,114 @@ mod tests {
 
         let ctx = DOCAContext::new(&dma, vec![device]).unwrap();
 
-        let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
 
         assert_eq!(workq.depth(), 1);
+        assert_eq!(workq.inflight(), 0);
+        assert_eq!(workq.available(), 1);
+        assert_eq!(
+            workq.poll_completion_timeout(std::time::Duration::from_millis(1)),
+            Err(DocaError::NotFound)
+        );
+        assert_eq!(workq.poll_completion(), Err(DocaError::Again));
+
+        let stats = workq.stats();
+        assert_eq!((stats.submitted, stats.completed), (0, 0));
+        assert_eq!((stats.retries, stats.polls), (1, 1));
+        workq.reset_stats();
+        assert_eq!(workq.stats(), Default::default());
+    }
+
+    #[test]
+    fn test_context_workq_registry() {
+        use crate::context::DOCAContext;
+        use crate::dma::DMAEngine;
+        use crate::DOCAWorkQueue;
+
+        let device = crate::device::devices()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .open()
+            .unwrap();
+
+        let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device.clone()]).unwrap();
+        let other = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device]).unwrap();
+        assert_eq!(ctx.num_workqs(), 0);
+
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        let another = DOCAWorkQueue::new(1, &ctx).unwrap();
+        assert_eq!(ctx.num_workqs(), 2);
+
+        workq.detach(&ctx).unwrap();
+        assert_eq!(ctx.num_workqs(), 1);
+        workq.attach(&ctx).unwrap();
+        workq.attach(&other).unwrap();
+        assert_eq!((ctx.num_workqs(), other.num_workqs()), (2, 1));
+
+        // the work queues leave the registries when they are dropped
+        drop(another);
+        assert_eq!(ctx.num_workqs(), 1);
+        drop(workq);
+        assert_eq!((ctx.num_workqs(), other.num_workqs()), (0, 0));
+
+        other.close().unwrap();
+        ctx.close().unwrap();
+    }
+
+    #[test]
+    fn test_drain_worker_queue() {
+        use super::SubmitError;
+        use crate::context::DOCAContext;
+        use crate::dma::DMAEngine;
+        use crate::DOCAWorkQueue;
+
+        let device = crate::device::devices()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .open()
+            .unwrap();
+
+        let dma = DMAEngine::new().unwrap();
+
+        let ctx = DOCAContext::new(&dma, vec![device]).unwrap();
+
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        assert!(!workq.is_draining());
+        assert_eq!(workq.drain(std::time::Duration::from_millis(1)), Ok(0));
+        assert!(workq.is_draining());
+
+        let mut job = workq.new_job();
+        assert_eq!(workq.submit(&mut job), Err(SubmitError::Draining));
+    }
+
+    #[test]
+    fn test_event_driven_worker_queue() {
+        use crate::context::DOCAContext;
+        use crate::dma::DMAEngine;
+        use crate::{DOCAWorkQueue, DocaError};
+
+        let device = crate::device::devices()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .open()
+            .unwrap();
+
+        let dma = DMAEngine::new().unwrap();
+
+        let ctx = DOCAContext::new(&dma, vec![device]).unwrap();
+
+        let mut workq = DOCAWorkQueue::new_event_driven(1, &ctx).unwrap();
+        assert!(workq.event_handle().unwrap() >= 0);
+        assert_eq!(
+            workq.wait_for_completion(Some(std::time::Duration::from_millis(1))),
+            Err(DocaError::NotFound)
+        );
+        drop(workq);
+
+        let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        assert_eq!(workq.event_handle(), Err(DocaError::BadState));
     }
 }
@@ -0,0 +1,481 @@
+//! Wrapper for the DOCA AES-GCM library of DOCA 2.x, which offloads the authenticated
+//! encryption and decryption of the data in [`DOCABuffer`]s.
+//!
+//! - [`AesGcmEngine`] is the AES-GCM context of a device. It is connected to a
+//! [`ProgressEngine`] on creation, which drives its tasks.
+//!
+//! - [`AesGcmKey`] is a 128-bit or 256-bit key imported into the engine.
+//!
+//! - [`CryptoKey`] manages the current key of an engine, which can be rotated while
+//! the jobs using the former key are in flight.
+//!
+//! - [`CryptoJob`] encrypts or decrypts the source buffer into the destination buffer.
+//! It owns its buffers until it completes, and gives them back to the closure passed
+//! to [`CryptoJob::submit`].
+//!
+//! The source data starts with `aad_size` bytes of additional authenticated data,
+//! which are authenticated but not encrypted. The encryption appends a tag of
+//! `tag_size` bytes to the ciphertext in the destination, and the decryption verifies
+//! the tag at the end of the source: a mismatching tag fails the job with an error result.
+//!
+//! The module is only built with the `doca2` feature, since the library doesn't exist in DOCA 1.5.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::crypto::AesGcmEngine;
+//! use doca::progress_engine::ProgressEngine;
+//!
+//! # fn run(plain: doca::DOCABuffer, cipher: doca::DOCABuffer) {
+//! let device = doca::open_device_with_pci("03:00.0").unwrap();
+//! let mut pe = ProgressEngine::new().unwrap();
+//! let aes = unsafe { AesGcmEngine::new(&device, &mut pe, 16) }.unwrap();
+//!
+//! let key = aes.import_key(&[0x42; 32]).unwrap();
+//! let job = aes.encrypt(&key, plain, cipher, &[0; 12], 16, 0).unwrap();
+//! job.submit(&mut pe, |_plain, _cipher, res| res.unwrap()).unwrap();
+//!
+//! while pe.inflight().unwrap() > 0 {
+//!     pe.progress();
+//! }
+//! # }
+//! ```
+//!
+
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
+
+use ffi::doca_error;
+
+use crate::error::check;
+use crate::progress_engine::{completion_trampoline, ProgressEngine, RawTask, Task};
+use crate::{DOCABuffer, DOCAResult, DevContext, Device, DocaError};
+
+// The max length of the initialization vector accepted by the SDK
+const MAX_IV_LENGTH: usize = 12;
+
+impl RawTask for ffi::doca_aes_gcm_task_encrypt {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_aes_gcm_task_encrypt_as_task(task)
+    }
+}
+
+impl RawTask for ffi::doca_aes_gcm_task_decrypt {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_aes_gcm_task_decrypt_as_task(task)
+    }
+}
+
+/// The AES-GCM context of a device
+pub struct AesGcmEngine {
+    inner: NonNull<ffi::doca_aes_gcm>,
+    // the device should be closed after the context is destroyed
+    #[allow(dead_code)]
+    dev: Arc<DevContext>,
+}
+
+impl Drop for AesGcmEngine {
+    fn drop(&mut self) {
+        // the tasks hold the keys, which hold the engine, so none is in flight
+        let _ = unsafe { ffi::doca_ctx_stop(self.ctx_ptr()) };
+
+        let ret = unsafe { ffi::doca_aes_gcm_destroy(self.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the AES-GCM engine: {:?}", ret);
+        }
+
+        dropped!("AES-GCM Engine is dropped!");
+    }
+}
+
+impl AesGcmEngine {
+    /// Check whether the device supports the encryption tasks
+    pub fn is_supported(dev: &Device) -> bool {
+        let ret = unsafe { ffi::doca_aes_gcm_cap_task_encrypt_is_supported(dev.inner_ptr()) };
+        ret == doca_error::DOCA_SUCCESS
+    }
+
+    /// Create the context on the device, connect it to the progress engine,
+    /// and start it with room for `num_tasks` tasks of each type.
+    ///
+    /// It is unsafe because the engine must outlive the progress engine,
+    /// see [`ProgressEngine::connect_ctx`].
+    pub unsafe fn new(
+        dev: &Arc<DevContext>,
+        pe: &mut ProgressEngine,
+        num_tasks: u32,
+    ) -> DOCAResult<Arc<Self>> {
+        let mut aes: *mut ffi::doca_aes_gcm = std::ptr::null_mut();
+        let ret = ffi::doca_aes_gcm_create(dev.inner_ptr(), &mut aes as *mut _);
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let res = Self {
+            inner: NonNull::new(aes).ok_or(DocaError::InvalidValue)?,
+            dev: dev.clone(),
+        };
+
+        let encrypt_cb = Some(completion_trampoline::<ffi::doca_aes_gcm_task_encrypt> as _);
+        check(ffi::doca_aes_gcm_task_encrypt_set_conf(
+            res.inner_ptr(),
+            encrypt_cb,
+            encrypt_cb,
+            num_tasks,
+        ))?;
+        let decrypt_cb = Some(completion_trampoline::<ffi::doca_aes_gcm_task_decrypt> as _);
+        check(ffi::doca_aes_gcm_task_decrypt_set_conf(
+            res.inner_ptr(),
+            decrypt_cb,
+            decrypt_cb,
+            num_tasks,
+        ))?;
+
+        pe.connect_ctx(res.ctx_ptr())?;
+        check(ffi::doca_ctx_start(res.ctx_ptr()))?;
+        Ok(Arc::new(res))
+    }
+
+    /// Import a raw key, whose length selects AES-128 (16 bytes) or AES-256 (32 bytes).
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the key is of another length.
+    pub fn import_key(self: &Arc<Self>, raw: &[u8]) -> DOCAResult<Arc<AesGcmKey>> {
+        let ty = match raw.len() {
+            16 => ffi::DOCA_AES_GCM_KEY_128,
+            32 => ffi::DOCA_AES_GCM_KEY_256,
+            _ => return Err(DocaError::InvalidValue),
+        };
+
+        let mut key: *mut ffi::doca_aes_gcm_key = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_aes_gcm_key_create(
+                self.inner_ptr(),
+                raw.as_ptr() as *const _,
+                ty,
+                &mut key as *mut _,
+            )
+        })?;
+
+        Ok(Arc::new(AesGcmKey {
+            inner: NonNull::new(key).ok_or(DocaError::InvalidValue)?,
+            aes: self.clone(),
+        }))
+    }
+
+    /// Create a job encrypting the source data into `dst`, followed by a tag of `tag_size` bytes.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `iv` is empty or longer than 12 bytes.
+    ///  - other errors returned by the task allocation of the SDK.
+    ///
+    pub fn encrypt(
+        &self,
+        key: &Arc<AesGcmKey>,
+        src: DOCABuffer,
+        dst: DOCABuffer,
+        iv: &[u8],
+        tag_size: u32,
+        aad_size: u32,
+    ) -> DOCAResult<CryptoJob> {
+        let iv = check_iv(iv)?;
+        let mut task: *mut ffi::doca_aes_gcm_task_encrypt = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_aes_gcm_task_encrypt_alloc_init(
+                self.inner_ptr(),
+                src.inner_ptr(),
+                dst.inner_ptr(),
+                key.inner_ptr(),
+                iv.as_ptr(),
+                iv.len() as u32,
+                tag_size,
+                aad_size,
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        Ok(CryptoJob {
+            task: unsafe { Task::from_raw(task)? },
+            key: key.clone(),
+            iv,
+            src,
+            dst,
+        })
+    }
+
+    /// Create a job verifying the tag of `tag_size` bytes at the end of the source data,
+    /// and decrypting the rest into `dst`.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `iv` is empty or longer than 12 bytes.
+    ///  - other errors returned by the task allocation of the SDK.
+    ///
+    pub fn decrypt(
+        &self,
+        key: &Arc<AesGcmKey>,
+        src: DOCABuffer,
+        dst: DOCABuffer,
+        iv: &[u8],
+        tag_size: u32,
+        aad_size: u32,
+    ) -> DOCAResult<CryptoJob> {
+        let iv = check_iv(iv)?;
+        let mut task: *mut ffi::doca_aes_gcm_task_decrypt = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_aes_gcm_task_decrypt_alloc_init(
+                self.inner_ptr(),
+                src.inner_ptr(),
+                dst.inner_ptr(),
+                key.inner_ptr(),
+                iv.as_ptr(),
+                iv.len() as u32,
+                tag_size,
+                aad_size,
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        Ok(CryptoJob {
+            task: unsafe { Task::from_raw(task)? },
+            key: key.clone(),
+            iv,
+            src,
+            dst,
+        })
+    }
+
+    /// Return the inner pointer of the AES-GCM context
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_aes_gcm {
+        self.inner.as_ptr()
+    }
+
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_aes_gcm_as_ctx(self.inner_ptr())
+    }
+}
+
+// Copy the initialization vector, which should live as long as the task
+fn check_iv(iv: &[u8]) -> DOCAResult<Box<[u8]>> {
+    if iv.is_empty() || iv.len() > MAX_IV_LENGTH {
+        return Err(DocaError::InvalidValue);
+    }
+    Ok(iv.into())
+}
+
+/// A key imported into an [`AesGcmEngine`], destroyed when it is dropped
+pub struct AesGcmKey {
+    inner: NonNull<ffi::doca_aes_gcm_key>,
+    // the key should be destroyed before the engine
+    #[allow(dead_code)]
+    aes: Arc<AesGcmEngine>,
+}
+
+impl Drop for AesGcmKey {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_aes_gcm_key_destroy(self.inner.as_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the AES-GCM key: {:?}", ret);
+        }
+    }
+}
+
+impl AesGcmKey {
+    /// Return the inner pointer of the key
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_aes_gcm_key {
+        self.inner.as_ptr()
+    }
+}
+
+/// The managed key of an [`AesGcmEngine`], which can be rotated.
+///
+/// Each job holds the key it is created with, so the key retired by a rotation
+/// is destroyed only after the last job using it finishes and the retired key is dropped.
+pub struct CryptoKey {
+    aes: Arc<AesGcmEngine>,
+    // the current key and the number of the rotations so far
+    current: Mutex<(Arc<AesGcmKey>, u64)>,
+}
+
+impl CryptoKey {
+    /// Import the raw key into the engine, see [`AesGcmEngine::import_key`].
+    pub fn new(aes: &Arc<AesGcmEngine>, raw: &[u8]) -> DOCAResult<Self> {
+        Ok(Self {
+            aes: aes.clone(),
+            current: Mutex::new((aes.import_key(raw)?, 0)),
+        })
+    }
+
+    /// Get the current key, e.g., to create a job with
+    pub fn current(&self) -> Arc<AesGcmKey> {
+        self.lock().0.clone()
+    }
+
+    /// Get the number of the rotations of the key
+    pub fn generation(&self) -> u64 {
+        self.lock().1
+    }
+
+    /// Replace the current key with the raw one, and return the retired key.
+    ///
+    /// The current key is kept if the raw key fails to be imported,
+    /// e.g., with `DOCA_ERROR_INVALID_VALUE` if it is of an unsupported length.
+    pub fn rotate(&self, raw: &[u8]) -> DOCAResult<Arc<AesGcmKey>> {
+        let key = self.aes.import_key(raw)?;
+
+        let mut current = self.lock();
+        current.1 += 1;
+        Ok(std::mem::replace(&mut current.0, key))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, (Arc<AesGcmKey>, u64)> {
+        self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// An encryption or decryption job, see [`AesGcmEngine::encrypt`] and [`AesGcmEngine::decrypt`]
+pub struct CryptoJob {
+    task: Task,
+    key: Arc<AesGcmKey>,
+    iv: Box<[u8]>,
+    src: DOCABuffer,
+    dst: DOCABuffer,
+}
+
+impl CryptoJob {
+    /// Submit the job to the progress engine the engine is connected to,
+    /// and call `on_done` with the source and the destination buffers and the result
+    /// when it finishes. The result of a decryption is an error if the tag mismatches.
+    ///
+    /// The buffers are dropped with the job if the submission fails.
+    pub fn submit<F>(self, pe: &mut ProgressEngine, on_done: F) -> DOCAResult<()>
+    where
+        F: FnOnce(DOCABuffer, DOCABuffer, DOCAResult<()>) + 'static,
+    {
+        let CryptoJob {
+            task,
+            key,
+            iv,
+            src,
+            dst,
+        } = self;
+
+        pe.submit(task, move |task, res| {
+            // free the task before the key and the iv it uses
+            drop(task);
+            drop((key, iv));
+            on_done(src, dst, res);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::progress_engine::tests::{new_buf, setup};
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        use super::*;
+        use crate::*;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !AesGcmEngine::is_supported(&device) {
+            return;
+        }
+        let device = device.open().unwrap();
+        let mut pe = ProgressEngine::new().unwrap();
+        let aes = unsafe { AesGcmEngine::new(&device, &mut pe, 4) }.unwrap();
+
+        let (doca_mmap, inv) = setup(&device);
+
+        // keep the finished buffers until the memory is checked
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let on_done = |results: &Rc<RefCell<Vec<_>>>| {
+            let results = results.clone();
+            move |src, dst, res| results.borrow_mut().push((src, dst, res))
+        };
+        let mut run = |job: CryptoJob| {
+            job.submit(&mut pe, on_done(&results)).unwrap();
+            while pe.inflight().unwrap() > 0 {
+                pe.progress();
+            }
+            results.borrow().last().unwrap().2
+        };
+
+        let key = aes.import_key(&[0x42; 16]).unwrap();
+        let iv = [7u8; 12];
+        let plain = vec![0x5au8; 64].into_boxed_slice();
+        let mut cipher = vec![0u8; 80].into_boxed_slice();
+        let decrypted = vec![0u8; 64].into_boxed_slice();
+
+        let job = aes.encrypt(
+            &key,
+            new_buf(&doca_mmap, &inv, &plain, 64),
+            new_buf(&doca_mmap, &inv, &cipher, 0),
+            &iv,
+            16,
+            0,
+        );
+        assert!(run(job.unwrap()).is_ok());
+        assert_ne!(cipher[..64], plain[..]);
+
+        let job = aes.decrypt(
+            &key,
+            new_buf(&doca_mmap, &inv, &cipher, 80),
+            new_buf(&doca_mmap, &inv, &decrypted, 0),
+            &iv,
+            16,
+            0,
+        );
+        assert!(run(job.unwrap()).is_ok());
+        assert_eq!(decrypted, plain);
+
+        // a corrupted tag fails the verification
+        cipher[79] ^= 0xff;
+        let job = aes.decrypt(
+            &key,
+            new_buf(&doca_mmap, &inv, &cipher, 80),
+            new_buf(&doca_mmap, &inv, &decrypted, 0),
+            &iv,
+            16,
+            0,
+        );
+        assert!(run(job.unwrap()).is_err());
+
+        // a job keeps using the key it is created with after the rotation
+        let managed = CryptoKey::new(&aes, &[0x42; 16]).unwrap();
+        let job = aes.encrypt(
+            &managed.current(),
+            new_buf(&doca_mmap, &inv, &plain, 64),
+            new_buf(&doca_mmap, &inv, &cipher, 0),
+            &iv,
+            16,
+            0,
+        );
+        let retired = managed.rotate(&[0x24; 32]).unwrap();
+        assert_eq!(managed.generation(), 1);
+        assert!(!Arc::ptr_eq(&retired, &managed.current()));
+        drop(retired);
+        assert!(run(job.unwrap()).is_ok());
+
+        assert!(managed.rotate(&[0; 24]).is_err());
+        assert_eq!(managed.generation(), 1);
+
+        assert!(aes.import_key(&[0; 24]).is_err());
+        assert!(aes
+            .encrypt(
+                &key,
+                new_buf(&doca_mmap, &inv, &plain, 64),
+                new_buf(&doca_mmap, &inv, &cipher, 0),
+                &[0; 13],
+                16,
+                0
+            )
+            .is_err());
+    }
+}
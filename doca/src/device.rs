@@ -23,11 +23,62 @@
 //! let device_ctx = devices().unwrap().get(0).unwrap().open().unwrap();
 //! ```
 //!
+//! If a device is not found as expected, [`probe_devices`] reports why each
+//! unusable device is rejected:
+//!
+//! ```
+//! let (_usable, unusable) = doca::device::probe_devices().unwrap();
+//! for dev in unusable {
+//!     println!("{}", dev);
+//! }
+//! ```
+//!
+//! On the DPU, the representors of the host functions are listed by
+//! [`DevContext::representors`] and opened in the same way:
+//!
+//! ```no_run
+//! use doca::{open_device_with_pci, RepFilter};
+//! let device_ctx = open_device_with_pci("03:00.0").unwrap();
+//! let reps = device_ctx.representors(RepFilter::Net).unwrap();
+//! let rep_ctx = reps.get(0).unwrap().open().unwrap();
+//! ```
+//!
+//! [`runtime_role`] tells whether the process runs on the host or on the DPU,
+//! since the two sides use the devices differently.
+//!
+//! [`devices`] is a snapshot of the devices. A long-running daemon can rescan them with
+//! a [`DeviceMonitor`], e.g., to reopen a device after it is reset:
+//!
+//! ```
+//! let mut monitor = doca::device::DeviceMonitor::new().unwrap();
+//! let changes = monitor.rescan().unwrap();
+//! for pci_addr in &changes.removed {
+//!     println!("device {} is gone", pci_addr);
+//! }
+//! for dev in &changes.added {
+//!     println!("device {} is found", dev.name().unwrap());
+//! }
+//! ```
+//!
 
 use ffi::doca_error;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::os::raw::c_char;
 use std::{ptr::NonNull, sync::Arc};
 
-use crate::DOCAResult;
+#[cfg(not(feature = "doca2"))]
+use crate::context::Engine;
+use crate::drop_order::TrackId;
+#[cfg(not(feature = "doca2"))]
+use crate::DMAEngine;
+use crate::{DOCAResult, DocaError};
+
+// Buffer sizes of the device attributes, defined in `doca_dev.h`
+const DOCA_DEVINFO_IBDEV_NAME_SIZE: usize = 64;
+const DOCA_DEVINFO_IFACE_NAME_SIZE: usize = 256;
+const DOCA_DEVINFO_MAC_ADDR_SIZE: usize = 6;
+const DOCA_DEVINFO_FW_VERSION_SIZE: usize = 64;
 
 /// DOCA Device list
 pub struct DeviceList(&'static mut [*mut ffi::doca_devinfo]);
@@ -39,9 +90,7 @@ impl Drop for DeviceList {
     fn drop(&mut self) {
         unsafe { ffi::doca_devinfo_list_destroy(self.0.as_mut_ptr()) };
 
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("DeviceList is dropped!");
+        dropped!("DeviceList is dropped!");
     }
 }
 
@@ -59,7 +108,7 @@ pub fn devices() -> DOCAResult<Arc<DeviceList>> {
     let ret = unsafe { ffi::doca_devinfo_list_create(&mut dev_list as *mut _, &mut n as *mut _) };
 
     if dev_list.is_null() || ret != doca_error::DOCA_SUCCESS {
-        return Err(ret);
+        return Err(ret.into());
     }
 
     let devices = unsafe { std::slice::from_raw_parts_mut(dev_list, n as usize) };
@@ -67,7 +116,117 @@ pub fn devices() -> DOCAResult<Arc<DeviceList>> {
     Ok(Arc::new(DeviceList(devices)))
 }
 
+/// Why a device found by [`probe_devices`] can't be used
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeFailure {
+    /// Failed to query the PCIe address
+    PciAddress(DocaError),
+    /// The device doesn't support DMA memcpy jobs
+    NoDmaCapability,
+    /// Failed to open the device
+    Open(DocaError),
+}
+
+impl fmt::Display for ProbeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProbeFailure::PciAddress(e) => write!(f, "failed to query the PCIe address: {}", e),
+            ProbeFailure::NoDmaCapability => write!(f, "missing the DMA capability"),
+            ProbeFailure::Open(DocaError::NotPermitted) => {
+                write!(f, "permission denied when opening the device")
+            }
+            ProbeFailure::Open(DocaError::Driver) => write!(
+                f,
+                "driver error when opening the device, check the driver and firmware versions"
+            ),
+            ProbeFailure::Open(e) => write!(f, "failed to open the device: {}", e),
+        }
+    }
+}
+
+/// A device found by [`probe_devices`] but unusable
+#[derive(Clone, Debug)]
+pub struct UnusableDevice {
+    /// The index in the device list
+    pub index: usize,
+    /// The PCIe address, if it can be queried
+    pub pci_addr: Option<String>,
+    /// Why the device is unusable
+    pub reason: ProbeFailure,
+}
+
+impl fmt::Display for UnusableDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "device #{} ({}): {}",
+            self.index,
+            self.pci_addr.as_deref().unwrap_or("unknown"),
+            self.reason
+        )
+    }
+}
+
+/// Like [`devices`], but check whether each device is usable for DMA,
+/// i.e., its PCIe address can be queried, it supports DMA and it can be opened.
+///
+/// Return the usable devices, and the report of the unusable ones.
+pub fn probe_devices() -> DOCAResult<(Vec<Arc<Device>>, Vec<UnusableDevice>)> {
+    let list = devices()?;
+    let mut usable = Vec::new();
+    let mut unusable = Vec::new();
+
+    for index in 0..list.len() {
+        let dev = match list.get(index) {
+            Some(dev) => dev,
+            None => continue,
+        };
+
+        let (pci_addr, res) = match dev.name() {
+            Ok(name) => (Some(name), probe(&dev)),
+            Err(e) => (None, Err(ProbeFailure::PciAddress(e))),
+        };
+
+        match res {
+            Ok(()) => usable.push(dev),
+            Err(reason) => unusable.push(UnusableDevice {
+                index,
+                pci_addr,
+                reason,
+            }),
+        }
+    }
+
+    Ok((usable, unusable))
+}
+
+/// Get the devices on the NUMA `node`, see [`Device::numa_node`],
+/// e.g., to avoid the DMA across the sockets of a multi-socket host.
+pub fn devices_on_numa(node: u32) -> DOCAResult<Vec<Arc<Device>>> {
+    let list = devices()?;
+    Ok((0..list.len())
+        .filter_map(|index| list.get(index))
+        .filter(|dev| dev.numa_node() == Some(node))
+        .collect())
+}
+
+fn probe(dev: &Arc<Device>) -> Result<(), ProbeFailure> {
+    if !dev.supports_dma() {
+        return Err(ProbeFailure::NoDmaCapability);
+    }
+    dev.open().map_err(ProbeFailure::Open)?;
+    Ok(())
+}
+
 impl DeviceList {
+    /// Take a new snapshot of the available devices, like [`devices`].
+    ///
+    /// The current list stays valid until the devices got from it are dropped,
+    /// but it may hold the devices that have been removed since.
+    pub fn refresh(&self) -> DOCAResult<Arc<DeviceList>> {
+        devices()
+    }
+
     /// Returns the number of devices.
     pub fn len(&self) -> usize {
         self.0.len()
@@ -104,6 +263,143 @@ impl DeviceList {
     }
 }
 
+/// The devices added and removed between two scans of a [`DeviceMonitor`]
+#[derive(Clone, Default)]
+pub struct DeviceChanges {
+    /// The devices found by the scan but not by the previous one
+    pub added: Vec<Arc<Device>>,
+    /// The PCIe addresses of the devices found by the previous scan but not by this one
+    pub removed: Vec<String>,
+}
+
+impl DeviceChanges {
+    /// Returns `true` if no device is added or removed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Track the available devices across rescans, so a long-running process can react
+/// to the devices removed and added, e.g., by a device reset, without restarting.
+///
+/// The devices are identified by their PCIe addresses.
+/// The ones whose address can't be queried are ignored.
+pub struct DeviceMonitor {
+    list: Arc<DeviceList>,
+    // the PCIe addresses of the devices in `list`, with their indexes
+    known: Vec<(usize, String)>,
+}
+
+impl DeviceMonitor {
+    /// Take the first snapshot of the devices.
+    pub fn new() -> DOCAResult<Self> {
+        let list = devices()?;
+        let known = pci_addrs(&list);
+        Ok(Self { list, known })
+    }
+
+    /// Get the devices found by the last scan
+    pub fn devices(&self) -> &Arc<DeviceList> {
+        &self.list
+    }
+
+    /// Scan the devices again, and report the ones added and removed since the last scan.
+    ///
+    /// A device that is reset and back between two scans isn't reported,
+    /// so rescan frequently enough to catch the resets.
+    pub fn rescan(&mut self) -> DOCAResult<DeviceChanges> {
+        let list = self.list.refresh()?;
+        let known = pci_addrs(&list);
+
+        let (added, removed) = diff_devices(&self.known, &known);
+        let changes = DeviceChanges {
+            added: added.into_iter().filter_map(|i| list.get(i)).collect(),
+            removed,
+        };
+
+        self.list = list;
+        self.known = known;
+        Ok(changes)
+    }
+}
+
+// The PCIe addresses of the devices in the list, with their indexes
+fn pci_addrs(list: &Arc<DeviceList>) -> Vec<(usize, String)> {
+    (0..list.len())
+        .filter_map(|i| Some((i, list.get(i)?.name().ok()?)))
+        .collect()
+}
+
+// Compare two scans, returning the indexes of the added devices in `new`,
+// and the addresses of the removed ones
+fn diff_devices(old: &[(usize, String)], new: &[(usize, String)]) -> (Vec<usize>, Vec<String>) {
+    let added = new
+        .iter()
+        .filter(|(_, addr)| !old.iter().any(|(_, a)| a == addr))
+        .map(|(i, _)| *i)
+        .collect();
+    let removed = old
+        .iter()
+        .filter(|(_, addr)| !new.iter().any(|(_, a)| a == addr))
+        .map(|(_, addr)| addr.clone())
+        .collect();
+    (added, removed)
+}
+
+/// A snapshot of the attributes of a [`Device`], see [`Device::info`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// The PCIe address, e.g., "17:00.1"
+    pub pci_addr: String,
+    /// The IB device name, e.g., "mlx5_0"
+    pub ibdev_name: String,
+    /// The network interface name, e.g., "p0"
+    pub iface_name: String,
+    /// The MAC address
+    pub mac_addr: [u8; DOCA_DEVINFO_MAC_ADDR_SIZE],
+    /// Whether the device supports DMA memcpy jobs
+    pub dma_supported: bool,
+    /// The maximum buffer size of DMA jobs, `None` if DMA is not supported
+    pub max_dma_buf_size: Option<u64>,
+    /// The state of the link, `None` if the device doesn't report it
+    pub link_state: Option<LinkState>,
+    /// The firmware version, `None` if the device doesn't report it
+    pub fw_version: Option<String>,
+    /// The maximum MTU, `None` if the device doesn't report it
+    pub max_mtu: Option<u32>,
+}
+
+/// The operational state of the network interface of a [`Device`], see [`Device::link_state`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkState {
+    /// The link is up, and can pass packets
+    Up,
+    /// The link is down, e.g., the cable is unplugged
+    Down,
+    /// The driver doesn't report the state, or the link is being tested
+    Unknown,
+}
+
+impl From<ffi::doca_devinfo_link_state> for LinkState {
+    fn from(state: ffi::doca_devinfo_link_state) -> Self {
+        match state {
+            ffi::DOCA_DEVINFO_LINK_STATE_UP => LinkState::Up,
+            ffi::DOCA_DEVINFO_LINK_STATE_DOWN => LinkState::Down,
+            _ => LinkState::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for LinkState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkState::Up => write!(f, "up"),
+            LinkState::Down => write!(f, "down"),
+            LinkState::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
 /// An DOCA device
 pub struct Device {
     inner: NonNull<ffi::doca_devinfo>,
@@ -135,22 +431,22 @@ impl Device {
             unsafe { ffi::doca_devinfo_get_pci_addr(self.inner_ptr(), &mut pci_bdf as *mut _) };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
-        // first check the `bus` part
-        let bus = unsafe { pci_bdf.__bindgen_anon_1.__bindgen_anon_1.bus() };
-        let device = unsafe { pci_bdf.__bindgen_anon_1.__bindgen_anon_1.device() };
-        let func = unsafe { pci_bdf.__bindgen_anon_1.__bindgen_anon_1.function() };
+        Ok(format_pci_addr(&pci_bdf))
+    }
+
+    // Whether the device supports the DMA memcpy jobs, or the memcpy tasks of DOCA 2.x
+    #[cfg(not(feature = "doca2"))]
+    fn supports_dma(&self) -> bool {
+        DMAEngine::is_supported(self)
+    }
 
-        Ok(format!(
-            "{:x}{:x}:{:x}{:x}.{:x}",
-            bus / 16,
-            bus % 16,
-            device / 16,
-            device % 16,
-            func
-        ))
+    #[cfg(feature = "doca2")]
+    fn supports_dma(&self) -> bool {
+        let ret = unsafe { ffi::doca_dma_cap_task_memcpy_is_supported(self.inner_ptr()) };
+        ret == doca_error::DOCA_SUCCESS
     }
 
     /// Open a DOCA device and store it as a context for further use.
@@ -161,35 +457,167 @@ impl Device {
     /// Get the maximum supported buffer size for DMA job.
     pub fn get_max_buf_size(&self) -> DOCAResult<u64> {
         let mut num: u64 = 0;
+        #[cfg(not(feature = "doca2"))]
         let ret = unsafe { ffi::doca_dma_get_max_buf_size(self.inner_ptr(), &mut num as *mut _) };
+        #[cfg(feature = "doca2")]
+        let ret = unsafe {
+            ffi::doca_dma_cap_task_memcpy_get_max_buf_size(self.inner_ptr(), &mut num as *mut _)
+        };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(num)
     }
 
+    /// Get the IB device name of the doca device, e.g., "mlx5_0".
+    pub fn ibdev_name(&self) -> DOCAResult<String> {
+        let mut name = [0 as c_char; DOCA_DEVINFO_IBDEV_NAME_SIZE];
+        let ret = unsafe {
+            ffi::doca_devinfo_get_ibdev_name(self.inner_ptr(), name.as_mut_ptr(), name.len() as _)
+        };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(c_string(&name))
+    }
+
+    /// Get the network interface name of the doca device, e.g., "p0".
+    pub fn iface_name(&self) -> DOCAResult<String> {
+        let mut name = [0 as c_char; DOCA_DEVINFO_IFACE_NAME_SIZE];
+        let ret = unsafe {
+            ffi::doca_devinfo_get_iface_name(self.inner_ptr(), name.as_mut_ptr(), name.len() as _)
+        };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(c_string(&name))
+    }
+
+    /// Get the MAC address of the doca device.
+    pub fn mac_addr(&self) -> DOCAResult<[u8; DOCA_DEVINFO_MAC_ADDR_SIZE]> {
+        let mut mac = [0u8; DOCA_DEVINFO_MAC_ADDR_SIZE];
+        let ret = unsafe {
+            ffi::doca_devinfo_get_mac_addr(self.inner_ptr(), mac.as_mut_ptr(), mac.len() as _)
+        };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(mac)
+    }
+
+    /// Get the state of the link of the device's network interface.
+    pub fn link_state(&self) -> DOCAResult<LinkState> {
+        let mut state: ffi::doca_devinfo_link_state = ffi::DOCA_DEVINFO_LINK_STATE_UNKNOWN;
+        let ret = unsafe { ffi::doca_devinfo_get_link_state(self.inner_ptr(), &mut state) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(state.into())
+    }
+
+    /// Get the firmware version of the device, e.g., "24.35.2000".
+    pub fn fw_version(&self) -> DOCAResult<String> {
+        let mut version = [0 as c_char; DOCA_DEVINFO_FW_VERSION_SIZE];
+        let ret = unsafe {
+            ffi::doca_devinfo_get_fw_version(
+                self.inner_ptr(),
+                version.as_mut_ptr(),
+                version.len() as _,
+            )
+        };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(c_string(&version))
+    }
+
+    /// Get the maximum MTU of the device's network interface, e.g., 9978.
+    pub fn max_mtu(&self) -> DOCAResult<u32> {
+        let mut mtu = 0u32;
+        let ret = unsafe { ffi::doca_devinfo_get_max_mtu(self.inner_ptr(), &mut mtu) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(mtu)
+    }
+
+    /// Gather the attributes of the device in one call, e.g., for logging.
+    pub fn info(&self) -> DOCAResult<DeviceInfo> {
+        let dma_supported = self.supports_dma();
+        let max_dma_buf_size = if dma_supported {
+            Some(self.get_max_buf_size()?)
+        } else {
+            None
+        };
+
+        Ok(DeviceInfo {
+            pci_addr: self.name()?,
+            ibdev_name: self.ibdev_name()?,
+            iface_name: self.iface_name()?,
+            mac_addr: self.mac_addr()?,
+            dma_supported,
+            max_dma_buf_size,
+            link_state: self.link_state().ok(),
+            fw_version: self.fw_version().ok(),
+            max_mtu: self.max_mtu().ok(),
+        })
+    }
+
+    /// Get the NUMA node of the device from sysfs, `None` if it is unknown.
+    pub fn numa_node(&self) -> Option<u32> {
+        // the PCIe address of DOCA omits the domain
+        let path = format!("/sys/bus/pci/devices/0000:{}/numa_node", self.name().ok()?);
+        // the node is -1 if the platform doesn't report it
+        std::fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
     /// Return the device
     pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_devinfo {
         self.inner.as_ptr()
     }
 }
 
+// Convert a C string filled by DOCA, which is truncated at the buffer size
+fn c_string(buf: &[c_char]) -> String {
+    let bytes: Vec<u8> = buf
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
 /// An opened Doca Device
 pub struct DevContext {
     ctx: NonNull<ffi::doca_dev>,
-    #[allow(dead_code)]
     parent: Arc<Device>,
+    pub(crate) track: TrackId,
 }
 
+// The device is only read after it is opened
+unsafe impl Sync for DevContext {}
+unsafe impl Send for DevContext {}
+
 impl Drop for DevContext {
     fn drop(&mut self) {
+        self.track.release();
         unsafe { ffi::doca_dev_close(self.ctx.as_ptr()) };
 
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("Device Context is dropped!");
+        dropped!("Device Context is dropped!");
     }
 }
 
@@ -200,12 +628,53 @@ impl DevContext {
         let ret = unsafe { ffi::doca_dev_open(dev.inner_ptr(), &mut ctx as *mut _) };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(Arc::new(DevContext {
-            ctx: NonNull::new(ctx).ok_or(doca_error::DOCA_ERROR_INVALID_VALUE)?,
+            ctx: NonNull::new(ctx).ok_or(DocaError::InvalidValue)?,
             parent: dev,
+            track: TrackId::new("DevContext"),
+        }))
+    }
+
+    /// Get the device the context is opened on
+    pub fn device(&self) -> &Arc<Device> {
+        &self.parent
+    }
+
+    /// List the representors of the device matched by the `filter`.
+    ///
+    /// It is used on the DPU to enumerate the representors of the host functions.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: received invalid input.
+    ///  - `DOCA_ERROR_NOT_SUPPORTED`: the device doesn't support representors,
+    ///    e.g., on the host.
+    ///  - `DOCA_ERROR_NO_MEMORY`: failed to allocate enough space.
+    ///
+    pub fn representors(self: &Arc<Self>, filter: RepFilter) -> DOCAResult<Arc<DeviceRepList>> {
+        let mut n = 0u32;
+        let mut rep_list: *mut *mut ffi::doca_devinfo_rep = std::ptr::null_mut();
+        let ret = unsafe {
+            ffi::doca_devinfo_rep_list_create(
+                self.inner_ptr(),
+                filter.as_raw() as i32,
+                &mut rep_list as *mut _,
+                &mut n as *mut _,
+            )
+        };
+
+        if rep_list.is_null() || ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let reps = unsafe { std::slice::from_raw_parts_mut(rep_list, n as usize) };
+
+        Ok(Arc::new(DeviceRepList {
+            reps,
+            parent: self.clone(),
         }))
     }
 
@@ -216,6 +685,185 @@ impl DevContext {
     }
 }
 
+/// The kinds of the representors listed by [`DevContext::representors`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepFilter {
+    /// All the representors
+    All,
+    /// The representors of the network functions
+    Net,
+}
+
+impl RepFilter {
+    fn as_raw(&self) -> ffi::doca_dev_rep_filter {
+        match self {
+            RepFilter::All => ffi::DOCA_DEV_REP_FILTER_ALL,
+            RepFilter::Net => ffi::DOCA_DEV_REP_FILTER_NET,
+        }
+    }
+}
+
+/// DOCA Device representor list
+pub struct DeviceRepList {
+    reps: &'static mut [*mut ffi::doca_devinfo_rep],
+
+    // the representors are listed from an opened device
+    #[allow(dead_code)]
+    parent: Arc<DevContext>,
+}
+
+unsafe impl Sync for DeviceRepList {}
+unsafe impl Send for DeviceRepList {}
+
+impl Drop for DeviceRepList {
+    fn drop(&mut self) {
+        unsafe { ffi::doca_devinfo_rep_list_destroy(self.reps.as_mut_ptr()) };
+
+        dropped!("DeviceRepList is dropped!");
+    }
+}
+
+impl DeviceRepList {
+    /// Returns the number of representors.
+    pub fn len(&self) -> usize {
+        self.reps.len()
+    }
+
+    /// Returns `true` if there are any representors.
+    pub fn is_empty(&self) -> bool {
+        self.reps.is_empty()
+    }
+
+    /// Returns the representor at the given `index`, or `None` if out of bounds.
+    pub fn get(self: &Arc<Self>, index: usize) -> Option<Arc<DeviceRep>> {
+        let inner = NonNull::new(*self.reps.get(index)?)?;
+        Some(Arc::new(DeviceRep {
+            inner,
+            parent_replist: self.clone(),
+        }))
+    }
+}
+
+/// A representor of a DOCA device, e.g., of a host PF seen from the DPU
+pub struct DeviceRep {
+    inner: NonNull<ffi::doca_devinfo_rep>,
+
+    // ensure the representor list is not freed before the representor
+    #[allow(dead_code)]
+    parent_replist: Arc<DeviceRepList>,
+}
+
+unsafe impl Sync for DeviceRep {}
+unsafe impl Send for DeviceRep {}
+
+impl DeviceRep {
+    /// Return the PCIe address of the representor, e.g "b1:00.0",
+    /// in the same format as [`Device::name`].
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: received invalid input.
+    ///
+    pub fn pci_addr(&self) -> DOCAResult<String> {
+        let mut pci_bdf: ffi::doca_pci_bdf = Default::default();
+        let ret =
+            unsafe { ffi::doca_devinfo_rep_get_pci_addr(self.inner_ptr(), &mut pci_bdf as *mut _) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(format_pci_addr(&pci_bdf))
+    }
+
+    /// Open the representor and store it as a context for further use.
+    pub fn open(self: &Arc<Self>) -> DOCAResult<Arc<DevRepContext>> {
+        let mut ctx: *mut ffi::doca_dev_rep = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_dev_rep_open(self.inner_ptr(), &mut ctx as *mut _) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(Arc::new(DevRepContext {
+            ctx: NonNull::new(ctx).ok_or(DocaError::InvalidValue)?,
+            parent: self.clone(),
+        }))
+    }
+
+    /// Return the representor
+    ///
+    /// # Safety
+    ///
+    /// The pointer lives in the [`DeviceRepList`] held by the representor,
+    /// so it should not be used after the representor is dropped.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_devinfo_rep {
+        self.inner.as_ptr()
+    }
+}
+
+/// An opened DOCA device representor
+pub struct DevRepContext {
+    ctx: NonNull<ffi::doca_dev_rep>,
+    #[allow(dead_code)]
+    parent: Arc<DeviceRep>,
+}
+
+unsafe impl Sync for DevRepContext {}
+unsafe impl Send for DevRepContext {}
+
+impl Drop for DevRepContext {
+    fn drop(&mut self) {
+        unsafe { ffi::doca_dev_rep_close(self.ctx.as_ptr()) };
+
+        dropped!("Device Representor Context is dropped!");
+    }
+}
+
+impl DevRepContext {
+    /// Return the DOCA Device representor context raw pointer
+    ///
+    /// # Safety
+    ///
+    /// The pointer is owned by the context, so it should not be used after the context
+    /// is dropped, or be closed by the caller.
+    #[inline]
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_dev_rep {
+        self.ctx.as_ptr()
+    }
+}
+
+/// Open the representor of the device `dev` with the given PCI address,
+/// among the ones matched by the `filter`.
+///
+/// Examples
+/// ```
+/// use doca::{open_device_with_pci, open_rep_with_pci, RepFilter};
+/// let device = open_device_with_pci("03:00.0").unwrap();
+/// let rep = open_rep_with_pci(&device, RepFilter::Net, "b1:00.0");
+/// ```
+///
+/// # Errors
+///
+///  - `DOCA_ERROR_NOT_FOUND`: no representor matched by the `filter` is at `pci`.
+///
+pub fn open_rep_with_pci(
+    dev: &Arc<DevContext>,
+    filter: RepFilter,
+    pci: &str,
+) -> DOCAResult<Arc<DevRepContext>> {
+    let rep_list = dev.representors(filter)?;
+
+    for i in 0..rep_list.len() {
+        let rep = rep_list.get(i).unwrap();
+        if rep.pci_addr()? == pci {
+            return rep.open();
+        }
+    }
+
+    Err(DocaError::NotFound)
+}
+
 /// Open a DOCA Device with the given PCI address
 ///
 /// Examples
@@ -225,22 +873,228 @@ impl DevContext {
 /// ```
 ///
 pub fn open_device_with_pci(pci: &str) -> DOCAResult<Arc<DevContext>> {
+    open_device_by(|device| Ok(device.name()? == pci))
+}
+
+/// Open a DOCA Device with the given IB device name, e.g., "mlx5_0"
+///
+/// Return `DOCA_ERROR_INVALID_VALUE` if no device has the name.
+pub fn open_device_with_ibdev_name(ibdev_name: &str) -> DOCAResult<Arc<DevContext>> {
+    open_device_by(|device| Ok(device.ibdev_name()? == ibdev_name))
+}
+
+/// Open a DOCA Device with the given network interface name, e.g., "p0"
+///
+/// Return `DOCA_ERROR_INVALID_VALUE` if no device has the name.
+pub fn open_device_with_iface_name(iface_name: &str) -> DOCAResult<Arc<DevContext>> {
+    open_device_by(|device| Ok(device.iface_name()? == iface_name))
+}
+
+// Open the first device matched by `pred`
+fn open_device_by<F>(mut pred: F) -> DOCAResult<Arc<DevContext>>
+where
+    F: FnMut(&Device) -> DOCAResult<bool>,
+{
     let dev_list = devices()?;
 
     for i in 0..dev_list.num_devices() {
         let device = dev_list.get(i).unwrap();
-        let pci_addr = device.name()?;
-        if pci_addr.eq(pci) {
+        if pred(&device)? {
             // open the device
             return device.open();
         }
     }
 
-    Err(doca_error::DOCA_ERROR_INVALID_VALUE)
+    Err(DocaError::InvalidValue)
+}
+
+/// The policy to pick a device by [`select_device`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectPolicy {
+    /// The first device supporting DMA
+    FirstDmaCapable,
+    /// The device with the largest max DMA buffer size
+    LargestMaxBufSize,
+    /// The device on the NUMA node of the calling thread
+    LocalNumaNode,
+}
+
+/// Pick a usable device (see [`probe_devices`]) according to the `policy`.
+///
+/// With [`SelectPolicy::LocalNumaNode`], it falls back to the first usable device
+/// if the NUMA topology is unknown or no device is local to the calling thread.
+///
+/// # Errors
+///
+///  - `DOCA_ERROR_NOT_FOUND`: no usable device is found.
+///
+pub fn select_device(policy: SelectPolicy) -> DOCAResult<Arc<Device>> {
+    let (mut usable, _) = probe_devices()?;
+    if usable.is_empty() {
+        return Err(DocaError::NotFound);
+    }
+
+    let index = match policy {
+        SelectPolicy::FirstDmaCapable => 0,
+        SelectPolicy::LargestMaxBufSize => {
+            let mut best = (0, 0);
+            for (i, dev) in usable.iter().enumerate() {
+                let size = dev.get_max_buf_size()?;
+                if size > best.1 {
+                    best = (i, size);
+                }
+            }
+            best.0
+        }
+        SelectPolicy::LocalNumaNode => {
+            let node = current_numa_node();
+            usable
+                .iter()
+                .position(|dev| node.is_some() && dev.numa_node() == node)
+                .unwrap_or(0)
+        }
+    };
+
+    Ok(usable.swap_remove(index))
+}
+
+// Format the PCIe address as "BUS:DEVICE.FUNCTION", see `Device::name`
+pub(crate) fn format_pci_addr(pci_bdf: &ffi::doca_pci_bdf) -> String {
+    // first check the `bus` part
+    let bus = unsafe { pci_bdf.__bindgen_anon_1.__bindgen_anon_1.bus() };
+    let device = unsafe { pci_bdf.__bindgen_anon_1.__bindgen_anon_1.device() };
+    let func = unsafe { pci_bdf.__bindgen_anon_1.__bindgen_anon_1.function() };
+
+    format!(
+        "{:x}{:x}:{:x}{:x}.{:x}",
+        bus / 16,
+        bus % 16,
+        device / 16,
+        device % 16,
+        func
+    )
+}
+
+/// Where the process runs, see [`runtime_role`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// The host server, which exports its memory to the DPU
+    Host,
+    /// The BlueField Arm cores, which import the memory exported by the host
+    Dpu,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Host => write!(f, "host"),
+            Role::Dpu => write!(f, "DPU"),
+        }
+    }
+}
+
+/// Detect whether the process runs on the BlueField Arm cores or on the host.
+///
+/// It is a heuristic: the DPU runs on `aarch64`, and either has the representors
+/// of the host PFs (e.g., `pf0hpf`) or the BlueField release file `/etc/mlnx-release`.
+/// Everything else, including an Arm server without the DPU topology, is the host.
+pub fn runtime_role() -> Role {
+    if !cfg!(target_arch = "aarch64") {
+        return Role::Host;
+    }
+
+    let has_host_representor = std::fs::read_dir("/sys/class/net")
+        .map(|dir| {
+            dir.filter_map(|e| e.ok())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .any(|name| is_host_representor(&name))
+        })
+        .unwrap_or(false);
+
+    if has_host_representor || std::path::Path::new("/etc/mlnx-release").exists() {
+        Role::Dpu
+    } else {
+        Role::Host
+    }
+}
+
+/// Fail with `DOCA_ERROR_NOT_PERMITTED` if the process doesn't run on the `expected` side.
+pub fn expect_role(expected: Role) -> DOCAResult<()> {
+    let role = runtime_role();
+    if role != expected {
+        debug_event!(expected = %expected, role = %role, "the operation runs on the wrong side");
+        return Err(DocaError::NotPermitted);
+    }
+    Ok(())
+}
+
+// The representor of a host PF on the DPU is named as `pf<N>hpf`
+fn is_host_representor(iface: &str) -> bool {
+    iface
+        .strip_prefix("pf")
+        .and_then(|s| s.strip_suffix("hpf"))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+// The NUMA node of the CPU the calling thread is running on, read from procfs and sysfs
+fn current_numa_node() -> Option<u32> {
+    let stat = std::fs::read_to_string("/proc/thread-self/stat").ok()?;
+    // skip the command name, which may contain spaces
+    let fields: Vec<&str> = stat.rsplit(')').next()?.split_whitespace().collect();
+    // `processor` is the 39th field, the first two are before the command name
+    let cpu: u32 = fields.get(39 - 3)?.parse().ok()?;
+
+    let dir = std::fs::read_dir(format!("/sys/devices/system/cpu/cpu{}", cpu)).ok()?;
+    dir.filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .find_map(|name| name.strip_prefix("node")?.parse().ok())
 }
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn test_link_state() {
+        use super::*;
+
+        assert_eq!(
+            LinkState::from(ffi::DOCA_DEVINFO_LINK_STATE_UP),
+            LinkState::Up
+        );
+        assert_eq!(
+            LinkState::from(ffi::DOCA_DEVINFO_LINK_STATE_DOWN),
+            LinkState::Down
+        );
+        assert_eq!(
+            LinkState::from(ffi::DOCA_DEVINFO_LINK_STATE_UNKNOWN),
+            LinkState::Unknown
+        );
+        assert_eq!(LinkState::Up.to_string(), "up");
+    }
+
+    #[test]
+    fn test_diff_devices() {
+        use super::*;
+
+        let scan = |addrs: &[&str]| -> Vec<(usize, String)> {
+            addrs
+                .iter()
+                .enumerate()
+                .map(|(i, a)| (i, a.to_string()))
+                .collect()
+        };
+
+        let old = scan(&["03:00.0", "03:00.1"]);
+        assert_eq!(diff_devices(&old, &old), (vec![], vec![]));
+
+        // the second port is gone, and another device shows up
+        let new = scan(&["03:00.0", "04:00.0"]);
+        assert_eq!(
+            diff_devices(&old, &new),
+            (vec![1], vec!["03:00.1".to_string()])
+        );
+        assert_eq!(diff_devices(&[], &new), (vec![0, 1], vec![]));
+    }
+
     #[test]
     fn test_get_device_and_check() {
         let ret = crate::device::devices();
@@ -260,12 +1114,112 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_open_device_by_name() {
+        use crate::device::{open_device_with_ibdev_name, open_device_with_iface_name};
+
+        let device = crate::device::devices().unwrap().get(0).unwrap();
+        let ibdev_name = device.ibdev_name().unwrap();
+        let iface_name = device.iface_name().unwrap();
+
+        let ctx = open_device_with_ibdev_name(&ibdev_name).unwrap();
+        assert_eq!(ctx.parent.name().unwrap(), device.name().unwrap());
+        let ctx = open_device_with_iface_name(&iface_name).unwrap();
+        assert_eq!(ctx.parent.name().unwrap(), device.name().unwrap());
+
+        assert!(open_device_with_ibdev_name("no_such_dev").is_err());
+    }
+
+    #[test]
+    fn test_list_and_open_representors() {
+        use crate::device::{open_rep_with_pci, RepFilter};
+
+        let device = crate::device::devices().unwrap().get(0).unwrap();
+        let device = device.open().unwrap();
+        // only the DPU has representors
+        let reps = match device.representors(RepFilter::Net) {
+            Ok(reps) => reps,
+            Err(_) => return,
+        };
+        assert!(reps.len() <= device.representors(RepFilter::All).unwrap().len());
+
+        for i in 0..reps.len() {
+            let rep = reps.get(i).unwrap();
+            let pci_addr = rep.pci_addr().unwrap();
+            println!("representor pci addr {}", pci_addr);
+
+            let rep_ctx = open_rep_with_pci(&device, RepFilter::Net, &pci_addr);
+            assert!(rep_ctx.is_ok());
+        }
+        assert!(reps.get(reps.len()).is_none());
+    }
+
+    #[test]
+    fn test_probe_devices() {
+        let (usable, unusable) = crate::device::probe_devices().unwrap();
+        for dev in &unusable {
+            println!("{}", dev);
+        }
+
+        let total = crate::device::devices().unwrap().len();
+        assert_eq!(usable.len() + unusable.len(), total);
+    }
+
+    #[test]
+    fn test_runtime_role() {
+        use crate::device::{is_host_representor, runtime_role, Role};
+
+        assert!(is_host_representor("pf0hpf"));
+        assert!(is_host_representor("pf12hpf"));
+        assert!(!is_host_representor("pfhpf"));
+        assert!(!is_host_representor("p0"));
+        assert!(!is_host_representor("ens1f0np0"));
+
+        if !cfg!(target_arch = "aarch64") {
+            assert_eq!(runtime_role(), Role::Host);
+        }
+    }
+
     #[test]
     fn test_get_and_open_a_device() {
         let device = crate::device::devices().unwrap().get(0).unwrap().open();
         assert!(device.is_ok());
     }
 
+    #[test]
+    fn test_device_info() {
+        let device = crate::device::devices().unwrap().get(0).unwrap();
+        let info = device.info().unwrap();
+        println!("device info: {:?}", info);
+
+        assert_eq!(info.pci_addr, device.name().unwrap());
+        assert_eq!(info.dma_supported, info.max_dma_buf_size.is_some());
+    }
+
+    #[test]
+    fn test_select_device() {
+        use crate::device::{select_device, SelectPolicy};
+
+        let first = select_device(SelectPolicy::FirstDmaCapable).unwrap();
+        let largest = select_device(SelectPolicy::LargestMaxBufSize).unwrap();
+        assert!(largest.get_max_buf_size().unwrap() >= first.get_max_buf_size().unwrap());
+
+        let local = select_device(SelectPolicy::LocalNumaNode).unwrap();
+        println!(
+            "local device {} on node {:?}",
+            local.name().unwrap(),
+            local.numa_node()
+        );
+    }
+
+    #[test]
+    fn test_current_numa_node() {
+        // the node is known on linux hosts with sysfs
+        if std::path::Path::new("/sys/devices/system/node").exists() {
+            assert!(super::current_numa_node().is_some());
+        }
+    }
+
     #[test]
     fn test_dev_max_buf() {
         let device = crate::device::devices().unwrap().get(0).unwrap();
@@ -6,6 +6,11 @@
 //! It basically contains two core structs:
 //! - [`DOCADMAJob`]: The DMA request of DOCA. It implements the trait [`ToBaseJob`],
 //! which makes it capable for being submitted to the work queue.
+//! [`DMAJobBuilder`] creates a job and validates its data range against the buffers.
+//!
+//! - [`DmaChannel`]: Reads and writes a remote region with two calls, for the common
+//! host-DPU copy without building the buffers and the jobs by hand.
+//! [`DmaChannel::copy_large`] splits the copies exceeding the device limit into chunks.
 //!
 //! - [`DMAEngine`]: The DMA Engine of DOCA. Users should create an instance of the engine and
 //! execute DMA requests based on the engine.
@@ -25,31 +30,44 @@
 //! ```
 //!
 
+use std::collections::HashMap;
 use std::ptr::NonNull;
 use std::sync::Arc;
 
 use crate::context::work_queue::ToBaseJob;
-use crate::context::EngineToContext;
-use crate::{DOCABuffer, DOCAError, DOCAResult};
+use crate::context::{Engine, EngineToContext};
+use crate::memory::buffer::{BufferInventory, DOCABufferList};
+use crate::memory::registered_memory::DOCARegisteredMemory;
+use crate::{
+    DOCABuffer, DOCAError, DOCAResult, Device, DocaError, LocalMmap, MmapState, RawPointer,
+    RemoteMmap,
+};
 
-pub use crate::context::work_queue::{DOCAEvent, DOCAWorkQueue};
+pub use crate::context::work_queue::{
+    Completion, DOCAEvent, DOCAWorkQueue, JobHandle, UserData, WorkQueueStats,
+};
 pub use crate::context::DOCAContext;
 
 /// DOCA DMA engine instance
 pub struct DMAEngine {
     inner: NonNull<ffi::doca_dma>,
+    // whether the engine is destroyed by `close`
+    closed: bool,
 }
 
+// The engine is only converted into its context, which is thread-safe
+unsafe impl Sync for DMAEngine {}
+unsafe impl Send for DMAEngine {}
+
 impl Drop for DMAEngine {
     fn drop(&mut self) {
-        let ret = unsafe { ffi::doca_dma_destroy(self.inner_ptr()) };
-        if ret != DOCAError::DOCA_SUCCESS {
-            panic!("Failed to destory dma engine!");
+        if !self.closed {
+            if let Err(e) = self.teardown() {
+                drop_failed!("Failed to destory dma engine: {:?}", e);
+            }
         }
 
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("DMA Engine is dropped!");
+        dropped!("DMA Engine is dropped!");
     }
 }
 
@@ -60,21 +78,110 @@ impl EngineToContext for DMAEngine {
     }
 }
 
+/// Implementation `Engine` Trait for DMA Engine
+impl Engine for DMAEngine {
+    type Job = DOCADMAJob;
+
+    fn name() -> &'static str {
+        "dma"
+    }
+
+    fn new_job(ctx: &Arc<DOCAContext<Self>>) -> DOCADMAJob {
+        let mut res = DOCADMAJob {
+            inner: Default::default(),
+            ctx: ctx.clone(),
+            src_buff: None,
+            dst_buff: None,
+            src_list: None,
+            dst_list: None,
+            verify: None,
+        };
+        res.set_ctx().set_flags().set_type();
+        res
+    }
+
+    fn is_supported(dev: &Device) -> bool {
+        let ret =
+            unsafe { ffi::doca_dma_job_get_supported(dev.inner_ptr(), ffi::DOCA_DMA_JOB_MEMCPY) };
+        ret == DOCAError::DOCA_SUCCESS
+    }
+
+    fn max_buf_size(dev: &Device) -> DOCAResult<u64> {
+        dev.get_max_buf_size()
+    }
+}
+
+/// The hardware limits of the DMA engine on a device, see [`DMAEngine::capabilities`].
+///
+/// The limits the DOCA 1.5 SDK doesn't report are `None`, i.e., unknown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DMACapabilities {
+    /// The max size of the buffer of a DMA job, in bytes
+    pub max_buf_size: u64,
+    /// The max depth of a work queue
+    pub max_workq_depth: Option<u32>,
+    /// The max number of the in-flight jobs on the device
+    pub max_inflight_jobs: Option<u32>,
+}
+
 impl DMAEngine {
+    /// Query the hardware limits of the DMA engine on the device,
+    /// so the work queues and buffer pools can be sized accordingly.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_NOT_SUPPORTED`: the device doesn't support the DMA memcpy job.
+    ///  - other errors returned by the capability queries of the SDK.
+    ///
+    pub fn capabilities(dev: &Device) -> DOCAResult<DMACapabilities> {
+        if !Self::is_supported(dev) {
+            return Err(DocaError::NotSupported);
+        }
+
+        Ok(DMACapabilities {
+            max_buf_size: Self::max_buf_size(dev)?,
+            // no query in DOCA 1.5, the work queue creation fails if the depth is too large
+            max_workq_depth: None,
+            max_inflight_jobs: None,
+        })
+    }
+
     /// Create a DOCA DMA instance.
     pub fn new() -> DOCAResult<Arc<Self>> {
         let mut dma: *mut ffi::doca_dma = std::ptr::null_mut();
         let ret = unsafe { ffi::doca_dma_create(&mut dma as *mut _) };
 
         if ret != DOCAError::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(Arc::new(Self {
             inner: unsafe { NonNull::new_unchecked(dma) },
+            closed: false,
         }))
     }
 
+    /// Destroy the engine, reporting the failure that dropping it would only log.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_IN_USE`: the engine is still referenced, e.g., by its contexts.
+    ///  Only this reference is dropped.
+    ///
+    pub fn close(self: Arc<Self>) -> DOCAResult<()> {
+        let mut this = Arc::try_unwrap(self).map_err(|_| DocaError::InUse)?;
+        this.closed = true;
+        this.teardown()
+    }
+
+    fn teardown(&mut self) -> DOCAResult<()> {
+        let ret = unsafe { ffi::doca_dma_destroy(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
     /// Get the inner pointer of the DOCA DMA instance.
     pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_dma {
         self.inner.as_ptr()
@@ -91,6 +198,12 @@ pub struct DOCADMAJob {
 
     src_buff: Option<DOCABuffer>,
     dst_buff: Option<DOCABuffer>,
+    // the scatter-gather lists, set instead of the single buffers
+    src_list: Option<DOCABufferList>,
+    dst_list: Option<DOCABufferList>,
+
+    // checksum and length of the source data, recorded in the verified-copy mode
+    verify: Option<(u32, usize)>,
 }
 
 /// Implementation of `ToBaseJob` Trait
@@ -98,37 +211,288 @@ impl ToBaseJob for DOCADMAJob {
     fn to_base(&self) -> &ffi::doca_job {
         &self.inner.base
     }
+
+    fn to_base_mut(&mut self) -> &mut ffi::doca_job {
+        &mut self.inner.base
+    }
 }
 
 impl DOCADMAJob {
     /// Set request's destination buffer
     pub fn set_dst(&mut self, buf: DOCABuffer) -> &mut Self {
+        debug_assert!(
+            unsafe { !buf.inner_ptr().is_null() },
+            "the dst buffer is null"
+        );
         unsafe { self.inner.dst_buff = buf.inner_ptr() };
         self.dst_buff = Some(buf);
+        self.dst_list = None;
         self
     }
 
     /// Set request's source buffer
     pub fn set_src(&mut self, buf: DOCABuffer) -> &mut Self {
+        debug_assert!(
+            unsafe { !buf.inner_ptr().is_null() },
+            "the src buffer is null"
+        );
         unsafe { self.inner.src_buff = buf.inner_ptr() };
         self.src_buff = Some(buf);
+        self.src_list = None;
+        self
+    }
+
+    /// Set request's source to a scatter-gather list,
+    /// i.e., the job gathers the data of all buffers in the list.
+    ///
+    /// It replaces the source buffer set by [`set_src`](Self::set_src), and vice versa.
+    /// The device must support the buffer list, otherwise the submission fails.
+    pub fn set_src_list(&mut self, list: DOCABufferList) -> &mut Self {
+        unsafe { self.inner.src_buff = list.head().inner_ptr() };
+        self.src_list = Some(list);
+        self.src_buff = None;
+        self
+    }
+
+    /// Set request's destination to a scatter-gather list,
+    /// i.e., the job scatters the data into the buffers in the list.
+    ///
+    /// It replaces the destination buffer set by [`set_dst`](Self::set_dst), and vice versa.
+    pub fn set_dst_list(&mut self, list: DOCABufferList) -> &mut Self {
+        unsafe { self.inner.dst_buff = list.head().inner_ptr() };
+        self.dst_list = Some(list);
+        self.dst_buff = None;
+        self
+    }
+
+    /// Take the source list back from the request, see [`take_src`](Self::take_src).
+    pub fn take_src_list(&mut self) -> Option<DOCABufferList> {
+        if self.src_list.is_some() {
+            self.inner.src_buff = std::ptr::null_mut();
+        }
+        self.src_list.take()
+    }
+
+    /// Take the destination list back from the request, see [`take_dst`](Self::take_dst).
+    pub fn take_dst_list(&mut self) -> Option<DOCABufferList> {
+        if self.dst_list.is_some() {
+            self.inner.dst_buff = std::ptr::null_mut();
+        }
+        self.dst_list.take()
+    }
+
+    /// Take the source buffer back from the request, e.g., to recycle it after the job completes.
+    ///
+    /// The request can't be submitted again until a new source buffer is set.
+    /// Return `None` if the buffer has already been taken.
+    pub fn take_src(&mut self) -> Option<DOCABuffer> {
+        if self.src_buff.is_some() {
+            self.inner.src_buff = std::ptr::null_mut();
+        }
+        self.src_buff.take()
+    }
+
+    /// Take the destination buffer back from the request, e.g., to recycle it after the job completes.
+    ///
+    /// The request can't be submitted again until a new destination buffer is set.
+    /// Return `None` if the buffer has already been taken.
+    pub fn take_dst(&mut self) -> Option<DOCABuffer> {
+        if self.dst_buff.is_some() {
+            self.inner.dst_buff = std::ptr::null_mut();
+        }
+        self.dst_buff.take()
+    }
+
+    /// Replace the source buffer, returning the previous one,
+    /// e.g., to point a reused request at the next region.
+    ///
+    /// A source list set by [`set_src_list`](Self::set_src_list) is dropped.
+    pub fn replace_src(&mut self, buf: DOCABuffer) -> Option<DOCABuffer> {
+        let prev = self.src_buff.take();
+        self.set_src(buf);
+        prev
+    }
+
+    /// Replace the destination buffer, returning the previous one.
+    ///
+    /// A destination list set by [`set_dst_list`](Self::set_dst_list) is dropped.
+    pub fn replace_dst(&mut self, buf: DOCABuffer) -> Option<DOCABuffer> {
+        let prev = self.dst_buff.take();
+        self.set_dst(buf);
+        prev
+    }
+
+    /// Reset the request after its completion, so it can be submitted again
+    /// with the same buffers, instead of allocating new ones from the inventory.
+    ///
+    /// The source data is rewound to the whole source region, the destination
+    /// data to the head of the destination region, i.e., the next copy lands at offset 0.
+    /// The user data and the verified-copy mode are cleared.
+    pub fn reset(&mut self) -> DOCAResult<&mut Self> {
+        if let Some(src) = self.src_buff.as_mut() {
+            let len = src.head.payload;
+            unsafe { src.set_data(0, len)? };
+        }
+        if let Some(dst) = self.dst_buff.as_mut() {
+            unsafe { dst.set_data(0, 0)? };
+        }
+
+        self.inner.base.user_data.u64 = 0;
+        self.verify = None;
+        Ok(self.set_ctx().set_flags().set_type())
+    }
+
+    /// Set the user data of the request, which is reported by its [`Completion`],
+    /// e.g., a request id or a pointer to the application state.
+    #[inline]
+    pub fn set_user_data<U: UserData>(&mut self, data: U) -> &mut Self {
+        self.inner.base.user_data.u64 = data.into_user_data();
         self
     }
 
-    /// Set the data pointer of the src buffer
+    /// Get the user data of the request as the type it was set with
+    #[inline]
+    pub fn user_data<U: UserData>(&self) -> U {
+        U::from_user_data(unsafe { self.inner.base.user_data.u64 })
+    }
+
+    /// Set the data pointer of the src buffer,
+    /// i.e., the job copies `payload` bytes starting at `offset` of the src region.
     #[inline]
-    pub fn set_src_data(&mut self, offset: usize, payload: usize) {
+    pub fn set_src_data(&mut self, offset: usize, payload: usize) -> &mut Self {
         if let Some(f) = self.src_buff.as_mut() {
-             unsafe { f.set_data(offset, payload).expect("doca fail to set src data!") };
+            unsafe {
+                f.set_data(offset, payload)
+                    .expect("doca fail to set src data!")
+            };
         }
+        self
     }
 
-    /// Set the data pointer of the dst buffer
+    /// Set the data pointer of the dst buffer.
+    ///
+    /// Note that the copied data is appended to the tail of the dst data,
+    /// i.e., it lands at `offset + payload` of the dst region.
     #[inline]
-    pub fn set_dst_data(&mut self, offset: usize, payload: usize) {
+    pub fn set_dst_data(&mut self, offset: usize, payload: usize) -> &mut Self {
         if let Some(f) = self.dst_buff.as_mut() {
-            unsafe { f.set_data(offset, payload).expect("doca fail to set dst data!") };
-       }
+            unsafe {
+                f.set_data(offset, payload)
+                    .expect("doca fail to set dst data!")
+            };
+        }
+        self
+    }
+
+    /// Make the copy land at `offset` of the dst region,
+    /// e.g., to scatter records into a ring buffer with a single dst buffer.
+    ///
+    /// It is a shorthand of `set_dst_data(offset, 0)`.
+    #[inline]
+    pub fn set_dst_offset(&mut self, offset: usize) -> &mut Self {
+        self.set_dst_data(offset, 0)
+    }
+
+    /// Turn on the verified-copy mode of the request.
+    ///
+    /// It records the CRC32 checksum of the current source data, so that
+    /// [`verify`](Self::verify) can check the destination after the job completes.
+    /// Call it after the source data has been set and before submitting the job.
+    ///
+    /// Return `DOCA_ERROR_NOT_PERMITTED` if the source buffer is from a remote mmap.
+    ///
+    /// # Safety
+    ///
+    /// The checksum is computed on the CPU over the source memory, which should be
+    /// initialized and not be written by the device or another thread during the call.
+    pub unsafe fn enable_verify(&mut self) -> DOCAResult<()> {
+        let src = self.src_buff.as_ref().ok_or(DocaError::InvalidValue)?;
+        if !src.is_local() {
+            return Err(DocaError::NotPermitted);
+        }
+
+        let len = src.data_len()?;
+        self.verify = Some((src.data_checksum(0, len)?, len));
+        Ok(())
+    }
+
+    /// Validate the destination data against the checksum recorded by
+    /// [`enable_verify`](Self::enable_verify). It should be called after the job completes.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_BAD_STATE`: the verified-copy mode is not enabled.
+    ///  - `DOCA_ERROR_NOT_PERMITTED`: the destination buffer is from a remote mmap.
+    ///  - `DOCA_ERROR_UNEXPECTED`: the destination doesn't match the source.
+    ///
+    /// # Safety
+    ///
+    /// The checksum is computed on the CPU over the destination memory, so the job should
+    /// have completed, and the memory should not be written by another thread during the call.
+    pub unsafe fn verify(&self) -> DOCAResult<()> {
+        let (expected, len) = self.verify.ok_or(DocaError::BadState)?;
+        let dst = self.dst_buff.as_ref().ok_or(DocaError::InvalidValue)?;
+        if !dst.is_local() {
+            return Err(DocaError::NotPermitted);
+        }
+
+        // the copied data is appended to the tail of the destination data
+        let dst_len = dst.data_len()?;
+        if dst_len < len {
+            return Err(DocaError::Unexpected);
+        }
+
+        let actual = dst.data_checksum(dst_len - len, len)?;
+        if actual != expected {
+            debug_event!(expected, actual, "DMA verify mismatch");
+            return Err(DocaError::Unexpected);
+        }
+
+        Ok(())
+    }
+
+    /// Decode the `completion` of the request into a [`DmaResult`],
+    /// with the number of bytes copied if the job succeeded.
+    pub fn dma_result(&self, completion: &Completion) -> DmaResult {
+        let status = DmaStatus::from(completion.status);
+        let bytes_copied = match status {
+            DmaStatus::Success => self.src_data_len().unwrap_or(0),
+            _ => 0,
+        };
+        DmaResult {
+            status,
+            bytes_copied,
+        }
+    }
+
+    /// Like [`dma_result`](Self::dma_result), but also validate the destination data
+    /// in the verified-copy mode, reporting a mismatch as [`DmaStatus::ChecksumMismatch`].
+    ///
+    /// # Safety
+    ///
+    /// The `completion` should be the one of the request, so its destination memory is
+    /// no longer written by the device, see [`verify`](Self::verify).
+    pub unsafe fn verified_result(&self, completion: &Completion) -> DmaResult {
+        let mut res = self.dma_result(completion);
+        if res.status == DmaStatus::Success && self.verify.is_some() {
+            if let Err(e) = self.verify() {
+                res.status = match e {
+                    DocaError::Unexpected => DmaStatus::ChecksumMismatch,
+                    e => DmaStatus::from(DOCAError::from(e)),
+                };
+                res.bytes_copied = 0;
+            }
+        }
+        res
+    }
+
+    // The length of the source data, summed over the source list
+    fn src_data_len(&self) -> DOCAResult<usize> {
+        match (&self.src_buff, &self.src_list) {
+            (Some(src), _) => src.data_len(),
+            (None, Some(list)) => list.iter().map(DOCABuffer::data_len).sum(),
+            (None, None) => Err(DocaError::InvalidValue),
+        }
     }
 
     /// Set request's based context
@@ -150,31 +514,579 @@ impl DOCADMAJob {
     }
 }
 
+/// The status of a DMA job, decoded from the error code reported by the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DmaStatus {
+    /// The data is copied
+    Success,
+    /// The device is not permitted to access a buffer,
+    /// e.g., writing a region exported with [`MmapPermissions::PCI_READ_ONLY`](crate::MmapPermissions::PCI_READ_ONLY)
+    PermissionDenied,
+    /// The destination data doesn't match the source in the verified-copy mode,
+    /// see [`DOCADMAJob::verified_result`]
+    ChecksumMismatch,
+    /// The job is malformed, e.g., the data exceeds the destination region
+    InvalidJob,
+    /// The device or its driver failed to transfer the data
+    HardwareError(DOCAError),
+    /// Any other failure
+    Failed(DOCAError),
+}
+
+impl From<DOCAError> for DmaStatus {
+    fn from(e: DOCAError) -> Self {
+        match e {
+            DOCAError::DOCA_SUCCESS => DmaStatus::Success,
+            DOCAError::DOCA_ERROR_NOT_PERMITTED => DmaStatus::PermissionDenied,
+            DOCAError::DOCA_ERROR_INVALID_VALUE => DmaStatus::InvalidJob,
+            DOCAError::DOCA_ERROR_IO_FAILED | DOCAError::DOCA_ERROR_DRIVER => {
+                DmaStatus::HardwareError(e)
+            }
+            e => DmaStatus::Failed(e),
+        }
+    }
+}
+
+impl DOCAEvent {
+    /// Decode the result of the event of a DMA job
+    pub fn dma_status(&self) -> DmaStatus {
+        DmaStatus::from(self.result())
+    }
+}
+
+/// The result of a DMA job, see [`DOCADMAJob::dma_result`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DmaResult {
+    /// The decoded status of the job
+    pub status: DmaStatus,
+    /// The number of bytes copied, 0 if the job failed
+    pub bytes_copied: usize,
+}
+
+impl DmaResult {
+    /// Check whether the job succeeded
+    pub fn is_success(&self) -> bool {
+        self.status == DmaStatus::Success
+    }
+}
+
+/// Builder of a [`DOCADMAJob`], created by [`DOCAWorkQueue::dma_job_builder`].
+///
+/// The job copies `len` bytes starting at `offset` of the source region
+/// to `dst_offset` of the destination region.
+///
+/// ``` rust, no_run
+/// # fn build(
+/// #     workq: &doca::DOCAWorkQueue<doca::DMAEngine>,
+/// #     src_buf: doca::DOCABuffer,
+/// #     dst_buf: doca::DOCABuffer,
+/// # ) -> doca::DOCAResult<()> {
+/// let job = workq
+///     .dma_job_builder()
+///     .src(src_buf)
+///     .dst(dst_buf)
+///     .offset(64)
+///     .len(128)
+///     .user_data(42)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DMAJobBuilder {
+    ctx: Arc<DOCAContext<DMAEngine>>,
+    src: Option<DOCABuffer>,
+    dst: Option<DOCABuffer>,
+    offset: usize,
+    dst_offset: usize,
+    len: Option<usize>,
+    user_data: u64,
+}
+
+impl DMAJobBuilder {
+    /// Create a builder of the jobs of the context
+    pub fn new(ctx: &Arc<DOCAContext<DMAEngine>>) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            src: None,
+            dst: None,
+            offset: 0,
+            dst_offset: 0,
+            len: None,
+            user_data: 0,
+        }
+    }
+
+    /// Set the source buffer
+    pub fn src(mut self, buf: DOCABuffer) -> Self {
+        self.src = Some(buf);
+        self
+    }
+
+    /// Set the destination buffer
+    pub fn dst(mut self, buf: DOCABuffer) -> Self {
+        self.dst = Some(buf);
+        self
+    }
+
+    /// Set the offset of the copied data in the source region, 0 by default
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the offset in the destination region the data lands at, 0 by default
+    pub fn dst_offset(mut self, offset: usize) -> Self {
+        self.dst_offset = offset;
+        self
+    }
+
+    /// Set the number of bytes to copy.
+    /// By default, the job copies the rest of the source region after the `offset`.
+    pub fn len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    /// Set the user data reported by the [`Completion`] of the job
+    pub fn user_data(mut self, user_data: u64) -> Self {
+        self.user_data = user_data;
+        self
+    }
+
+    /// Create the job, checking the copied data fits in both registered regions.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: a buffer is not set, the length is 0,
+    ///  or the data exceeds the source or the destination region.
+    ///  - other errors returned by setting the data of the buffers.
+    ///
+    pub fn build(self) -> DOCAResult<DOCADMAJob> {
+        let mut src = self.src.ok_or(DocaError::InvalidValue)?;
+        let mut dst = self.dst.ok_or(DocaError::InvalidValue)?;
+
+        let src_len = src.head.payload;
+        let len = match self.len {
+            Some(len) => len,
+            None => src_len
+                .checked_sub(self.offset)
+                .ok_or(DocaError::InvalidValue)?,
+        };
+        if len == 0 {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let fits =
+            |offset: usize, region: usize| offset.checked_add(len).is_some_and(|end| end <= region);
+        if !fits(self.offset, src_len) || !fits(self.dst_offset, dst.head.payload) {
+            return Err(DocaError::InvalidValue);
+        }
+
+        // the copied data is appended to the tail of the dst data
+        unsafe {
+            src.set_data(self.offset, len)?;
+            dst.set_data(self.dst_offset, 0)?;
+        }
+
+        let mut job = DMAEngine::new_job(&self.ctx);
+        job.set_src(src).set_dst(dst);
+        job.inner.base.user_data.u64 = self.user_data;
+        Ok(job)
+    }
+}
+
 impl DOCAWorkQueue<DMAEngine> {
+    /// Create a builder of a DMA job, see [`DMAJobBuilder`]
+    pub fn dma_job_builder(&self) -> DMAJobBuilder {
+        DMAJobBuilder::new(&self.ctx)
+    }
+
     /// Create a DMA job
     pub fn create_dma_job(&self, src_buf: DOCABuffer, dst_buf: DOCABuffer) -> DOCADMAJob {
-        let mut res = DOCADMAJob {
-            inner: Default::default(),
-            ctx: self.ctx.clone(),
-            src_buff: None,
-            dst_buff: None,
-        };
-        res.set_ctx()
-            .set_flags()
-            .set_src(src_buf)
-            .set_dst(dst_buf)
-            .set_type();
+        let mut res = self.new_job();
+        res.set_src(src_buf).set_dst(dst_buf);
         res
     }
+
+    /// Create a DMA job copying between two **remote** buffers,
+    /// e.g., the DPU moves data from one host to another (or within a host)
+    /// without staging it in the DPU memory.
+    ///
+    /// Both buffers should come from mmaps created by [`RemoteMmap::from_export`](crate::RemoteMmap::from_export),
+    /// bound to a device of the work queue's context.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: a buffer is not from an imported mmap,
+    ///  or its mmap is not bound to any device of the context.
+    ///
+    pub fn create_remote_dma_job(
+        &self,
+        src_buf: DOCABuffer,
+        dst_buf: DOCABuffer,
+    ) -> DOCAResult<DOCADMAJob> {
+        for buf in [&src_buf, &dst_buf] {
+            if buf.mmap.state() != MmapState::Imported {
+                return Err(DocaError::InvalidValue);
+            }
+            let devs = self.ctx.devices();
+            if !devs.iter().any(|dev| buf.mmap.has_device(dev)) {
+                return Err(DocaError::InvalidValue);
+            }
+        }
+
+        Ok(self.create_dma_job(src_buf, dst_buf))
+    }
+
+    /// Copy `len` bytes from the source buffer to the destination buffer
+    /// with a sequence of DMA jobs, each of which moves at most `chunk` bytes.
+    ///
+    /// `progress(done, total)` is invoked after each chunk finishes.
+    /// Returning `false` from it cancels the transfer at the chunk boundary.
+    ///
+    /// Return the number of bytes copied, which is less than `len` if the transfer is cancelled.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `chunk` is zero, or `len` exceeds either buffer.
+    pub fn copy_chunked<F>(
+        &mut self,
+        src_buf: DOCABuffer,
+        dst_buf: DOCABuffer,
+        len: usize,
+        chunk: usize,
+        mut progress: F,
+    ) -> DOCAResult<usize>
+    where
+        F: FnMut(usize, usize) -> bool,
+    {
+        if chunk == 0 || len > src_buf.head_len()? || len > dst_buf.head_len()? {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let mut job = self.create_dma_job(src_buf, dst_buf);
+        let mut done = 0;
+
+        while done < len {
+            let sz = chunk.min(len - done);
+            job.set_src_data(done, sz).set_dst_offset(done);
+
+            self.submit(&mut job)?;
+            self.spin_completion()?;
+
+            done += sz;
+            if !progress(done, len) {
+                break;
+            }
+        }
+
+        Ok(done)
+    }
+}
+
+/// The number of the jobs a [`DmaChannel`] keeps in flight for each direction
+const CHANNEL_DEPTH: usize = 4;
+
+/// The direction of a [`DmaChannel::copy_large`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DmaDirection {
+    /// Copy from the remote region to the local region
+    Read,
+    /// Copy from the local region to the remote region
+    Write,
+}
+
+/// A DMA channel between a local region and a remote region,
+/// for the common case of copying data between the host and the DPU.
+///
+/// The buffers and the jobs are created once, so each [`read`](Self::read)
+/// or [`write`](Self::write) only sets the data range, submits the job and
+/// polls its completion.
+pub struct DmaChannel {
+    workq: DOCAWorkQueue<DMAEngine>,
+    // copies from the remote region to the local region
+    read_jobs: Vec<DOCADMAJob>,
+    // copies from the local region to the remote region
+    write_jobs: Vec<DOCADMAJob>,
+    local: RawPointer,
+    remote: RawPointer,
+    // the max bytes of a job supported by all the devices of the context
+    max_chunk: usize,
+}
+
+impl DmaChannel {
+    /// Create a channel on the context, populating `local` into `local_mmap`.
+    ///
+    /// `remote` is a region of `remote_mmap`, which is usually created by
+    /// [`RemoteMmap::from_export`](crate::RemoteMmap::from_export).
+    ///
+    /// The size of a chunk of [`copy_large`](Self::copy_large) is the smallest
+    /// [`Device::get_max_buf_size`] of the devices of the context.
+    pub fn new(
+        ctx: &Arc<DOCAContext<DMAEngine>>,
+        local_mmap: &LocalMmap,
+        local: impl Into<RawPointer>,
+        remote_mmap: &RemoteMmap,
+        remote: RawPointer,
+    ) -> DOCAResult<Self> {
+        let local = local.into();
+        if local.payload == 0 || remote.payload == 0 {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let mut max_chunk = u64::MAX;
+        for dev in ctx.devices() {
+            max_chunk = max_chunk.min(dev.device().get_max_buf_size()?);
+        }
+        if max_chunk == 0 {
+            return Err(DocaError::NotSupported);
+        }
+
+        let workq = DOCAWorkQueue::new(CHANNEL_DEPTH as u32, ctx)?;
+        let inv = BufferInventory::new(4 * CHANNEL_DEPTH)?;
+
+        // populate the local region once, the other buffers reuse it
+        local_mmap.populate(local)?;
+        let new_job = |read: bool| -> DOCAResult<DOCADMAJob> {
+            let local_buf =
+                DOCARegisteredMemory::new_populated(local_mmap, local).to_buffer(&inv)?;
+            let remote_buf =
+                DOCARegisteredMemory::new_from_remote(remote_mmap, remote)?.to_buffer(&inv)?;
+            Ok(if read {
+                workq.create_dma_job(remote_buf, local_buf)
+            } else {
+                workq.create_dma_job(local_buf, remote_buf)
+            })
+        };
+
+        let read_jobs = (0..CHANNEL_DEPTH)
+            .map(|_| new_job(true))
+            .collect::<DOCAResult<Vec<_>>>()?;
+        let write_jobs = (0..CHANNEL_DEPTH)
+            .map(|_| new_job(false))
+            .collect::<DOCAResult<Vec<_>>>()?;
+
+        Ok(Self {
+            workq,
+            read_jobs,
+            write_jobs,
+            local,
+            remote,
+            max_chunk: usize::try_from(max_chunk).unwrap_or(usize::MAX),
+        })
+    }
+
+    /// Copy `local_buf.len()` bytes at `remote_off` of the remote region into `local_buf`,
+    /// and wait for the copy to finish.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `local_buf` is empty or not inside the local region,
+    ///  or the data is out of the remote region.
+    ///  - other errors of the DMA job.
+    ///
+    pub fn read(&mut self, remote_off: usize, local_buf: &mut [u8]) -> DOCAResult<()> {
+        let local_off = self.local_offset(local_buf)?;
+        let len = local_buf.len();
+        self.check_remote(remote_off, len)?;
+
+        let job = &mut self.read_jobs[0];
+        job.set_src_data(remote_off, len).set_dst_offset(local_off);
+        self.workq.submit(job)?;
+        self.workq.spin_completion()?;
+        Ok(())
+    }
+
+    /// Copy `local_buf` to `remote_off` of the remote region, and wait for the copy to finish.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `local_buf` is empty or not inside the local region,
+    ///  or the data is out of the remote region.
+    ///  - other errors of the DMA job.
+    ///
+    pub fn write(&mut self, local_buf: &[u8], remote_off: usize) -> DOCAResult<()> {
+        let local_off = self.local_offset(local_buf)?;
+        let len = local_buf.len();
+        self.check_remote(remote_off, len)?;
+
+        let job = &mut self.write_jobs[0];
+        job.set_src_data(local_off, len).set_dst_offset(remote_off);
+        self.workq.submit(job)?;
+        self.workq.spin_completion()?;
+        Ok(())
+    }
+
+    /// Copy `len` bytes between `local_off` of the local region and `remote_off` of the
+    /// remote region in the `direction`, however large it is.
+    ///
+    /// The copy is split into chunks of at most [`max_chunk`](Self::max_chunk) bytes,
+    /// and up to a few of them are kept in flight on the work queue at a time.
+    /// No more chunk is submitted once one fails, and the in-flight ones are waited for.
+    ///
+    /// The result aggregates all the chunks: its status is the one of the first failed chunk
+    /// if any, and `bytes_copied` counts the bytes of the succeeded ones.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `len` is 0, or the data is out of either region.
+    ///  - other errors of submitting or polling the jobs.
+    ///
+    pub fn copy_large(
+        &mut self,
+        direction: DmaDirection,
+        local_off: usize,
+        remote_off: usize,
+        len: usize,
+    ) -> DOCAResult<DmaResult> {
+        if len == 0 || !matches!(local_off.checked_add(len), Some(end) if end <= self.local.payload)
+        {
+            return Err(DocaError::InvalidValue);
+        }
+        self.check_remote(remote_off, len)?;
+
+        let jobs = match direction {
+            DmaDirection::Read => &mut self.read_jobs,
+            DmaDirection::Write => &mut self.write_jobs,
+        };
+        let workq = &mut self.workq;
+
+        let mut chunks = split_chunks(len, self.max_chunk);
+        let mut idle: Vec<usize> = (0..jobs.len()).collect();
+        // the index of the job of each in-flight chunk
+        let mut pending = HashMap::new();
+        let mut result = DmaResult {
+            status: DmaStatus::Success,
+            bytes_copied: 0,
+        };
+        let mut error = None;
+
+        loop {
+            // keep the pipeline full until a chunk fails
+            while result.is_success() && error.is_none() && !idle.is_empty() {
+                let (off, sz) = match chunks.next() {
+                    Some(chunk) => chunk,
+                    None => break,
+                };
+
+                let idx = idle.pop().unwrap();
+                let job = &mut jobs[idx];
+                match direction {
+                    DmaDirection::Read => job
+                        .set_src_data(remote_off + off, sz)
+                        .set_dst_offset(local_off + off),
+                    DmaDirection::Write => job
+                        .set_src_data(local_off + off, sz)
+                        .set_dst_offset(remote_off + off),
+                };
+
+                match workq.submit(job) {
+                    Ok(handle) => {
+                        pending.insert(handle, idx);
+                    }
+                    Err(e) => {
+                        idle.push(idx);
+                        error = Some(DocaError::from(e));
+                    }
+                }
+            }
+
+            if pending.is_empty() {
+                break;
+            }
+
+            let completion = match workq.poll_completion() {
+                Err(DocaError::Again) => continue,
+                res => res?,
+            };
+            let idx = pending
+                .remove(&completion.handle)
+                .ok_or(DocaError::Unexpected)?;
+            idle.push(idx);
+
+            let chunk = jobs[idx].dma_result(&completion);
+            if chunk.is_success() {
+                result.bytes_copied += chunk.bytes_copied;
+            } else if result.is_success() {
+                result.status = chunk.status;
+            }
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(result),
+        }
+    }
+
+    /// Get the max bytes of a chunk of [`copy_large`](Self::copy_large)
+    pub fn max_chunk(&self) -> usize {
+        self.max_chunk
+    }
+
+    /// Get the local region of the channel
+    pub fn local_region(&self) -> RawPointer {
+        self.local
+    }
+
+    /// Get the remote region of the channel
+    pub fn remote_region(&self) -> RawPointer {
+        self.remote
+    }
+
+    // The offset of `buf` in the local region
+    fn local_offset(&self, buf: &[u8]) -> DOCAResult<usize> {
+        let start = self.local.inner.as_ptr() as usize;
+        let addr = buf.as_ptr() as usize;
+        if buf.is_empty() || addr < start || addr - start + buf.len() > self.local.payload {
+            return Err(DocaError::InvalidValue);
+        }
+        Ok(addr - start)
+    }
+
+    fn check_remote(&self, offset: usize, len: usize) -> DOCAResult<()> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.remote.payload => Ok(()),
+            _ => Err(DocaError::InvalidValue),
+        }
+    }
 }
 
+// Split `len` bytes into the (offset, length) of chunks of at most `max` bytes
+fn split_chunks(len: usize, max: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..len)
+        .step_by(max)
+        .map(move |off| (off, max.min(len - off)))
+}
+
+#[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::*;
+
+    // Open a DMA work queue of `depth` on the first device, and a local mmap with an inventory
+    // for the buffers of the test
+    fn setup(depth: u32) -> (DOCAWorkQueue<DMAEngine>, LocalMmap, Arc<BufferInventory>) {
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+        let dma = DMAEngine::new().unwrap();
+        let ctx = DOCAContext::new(&dma, vec![device]).unwrap();
+        let workq = DOCAWorkQueue::new(depth, &ctx).unwrap();
+
+        (
+            workq,
+            LocalMmap::new().unwrap(),
+            BufferInventory::new(1024).unwrap(),
+        )
+    }
+
+    // Register `buffer` in the mmap and get a buffer over it
+    fn new_buf(mmap: &LocalMmap, inv: &Arc<BufferInventory>, buffer: &[u8]) -> DOCABuffer {
+        let raw = unsafe { RawPointer::from_raw_ptr(buffer.as_ptr() as *mut u8, buffer.len()) };
+        DOCARegisteredMemory::new(mmap, raw)
+            .unwrap()
+            .to_buffer(inv)
+            .unwrap()
+    }
 
     #[test]
     fn test_create_dma_job() {
-        use super::*;
-        use crate::dma::DMAEngine;
-        use crate::*;
         use std::ptr::NonNull;
 
         let device = devices().unwrap().get(0).unwrap().open().unwrap();
@@ -186,7 +1098,7 @@ mod tests {
         let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
 
         // create buffers
-        let doca_mmap = Arc::new(DOCAMmap::new().unwrap());
+        let doca_mmap = LocalMmap::new().unwrap();
         let inv = BufferInventory::new(1024).unwrap();
 
         let test_len = 64;
@@ -212,17 +1124,745 @@ mod tests {
         let _ = workq.create_dma_job(src_buf, dst_buf);
     }
 
+    #[test]
+    fn test_dma_engine_trait() {
+        use crate::context::Engine;
+
+        let device = devices().unwrap().get(0).unwrap();
+        assert!(DMAEngine::is_supported(&device));
+        assert!(DMAEngine::max_buf_size(&device).unwrap() > 0);
+
+        // a generic helper written once for all engines
+        fn blank_job<E: Engine>(workq: &DOCAWorkQueue<E>) -> E::Job {
+            workq.new_job()
+        }
+
+        let ctx =
+            DOCAContext::new(&DMAEngine::new().unwrap(), vec![device.open().unwrap()]).unwrap();
+        let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        let mut job = blank_job(&workq);
+        assert!(job.take_src().is_none());
+    }
+
+    #[test]
+    fn test_take_buffers() {
+        let (workq, doca_mmap, inv) = setup(1);
+
+        let src_buffer = vec![0u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+
+        let mut job = workq.create_dma_job(src_buf, dst_buf);
+        let src_buf = job.take_src().unwrap();
+        let dst_buf = job.take_dst().unwrap();
+        assert!(job.take_src().is_none());
+        assert!(job.take_dst().is_none());
+
+        // the buffers can be reused in the other direction
+        job.set_src(dst_buf).set_dst(src_buf);
+    }
+
+    #[test]
+    fn test_remote_dma_job_rejects_local_buffers() {
+        let (workq, doca_mmap, inv) = setup(1);
+
+        let src_buffer = vec![0u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+        assert!(src_buf.is_local());
+
+        assert_eq!(
+            workq.create_remote_dma_job(src_buf, dst_buf).err(),
+            Some(DocaError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_copy_chunked() {
+        let (mut workq, doca_mmap, inv) = setup(1);
+
+        let test_len = 64;
+        let src_buffer = vec![7u8; test_len].into_boxed_slice();
+        let dst_buffer = vec![0u8; test_len].into_boxed_slice();
+
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+
+        let mut reports = Vec::new();
+        let copied = workq
+            .copy_chunked(src_buf, dst_buf, test_len, 16, |done, total| {
+                reports.push((done, total));
+                true
+            })
+            .unwrap();
+
+        assert_eq!(copied, test_len);
+        assert_eq!(reports, vec![(16, 64), (32, 64), (48, 64), (64, 64)]);
+        assert_eq!(src_buffer, dst_buffer);
+
+        // more bytes than the buffers hold
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+        assert_eq!(
+            workq.copy_chunked(src_buf, dst_buf, test_len + 1, 16, |_, _| true),
+            Err(DocaError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_dst_offset() {
+        let (mut workq, doca_mmap, inv) = setup(1);
+
+        let src_buffer = vec![7u8; 16].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+
+        // land the 16 bytes in the middle of the dst region
+        let mut job = workq.create_dma_job(src_buf, dst_buf);
+        job.set_src_data(0, 16).set_dst_offset(32);
+        workq.submit(&mut job).unwrap();
+        workq.spin_completion().unwrap();
+
+        assert!(dst_buffer[..32].iter().all(|b| *b == 0));
+        assert_eq!(dst_buffer[32..48], src_buffer[..]);
+        assert!(dst_buffer[48..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_dma_job_builder() {
+        let (mut workq, doca_mmap, inv) = setup(1);
+
+        let src_buffer: Box<[u8]> = (0..64u8).collect();
+        let dst_buffer = vec![0u8; 32].into_boxed_slice();
+
+        // the data doesn't fit in the dst region
+        let res = workq
+            .dma_job_builder()
+            .src(new_buf(&doca_mmap, &inv, &src_buffer))
+            .dst(new_buf(&doca_mmap, &inv, &dst_buffer))
+            .offset(16)
+            .build();
+        assert_eq!(res.err(), Some(DocaError::InvalidValue));
+
+        // no dst buffer
+        let res = workq
+            .dma_job_builder()
+            .src(new_buf(&doca_mmap, &inv, &src_buffer))
+            .build();
+        assert_eq!(res.err(), Some(DocaError::InvalidValue));
+
+        let mut job = workq
+            .dma_job_builder()
+            .src(new_buf(&doca_mmap, &inv, &src_buffer))
+            .dst(new_buf(&doca_mmap, &inv, &dst_buffer))
+            .offset(16)
+            .dst_offset(8)
+            .len(16)
+            .user_data(42)
+            .build()
+            .unwrap();
+        workq.submit(&mut job).unwrap();
+        let completion = workq.spin_completion().unwrap();
+
+        assert_eq!(completion.user_data, 42);
+        assert_eq!(
+            job.dma_result(&completion),
+            DmaResult {
+                status: DmaStatus::Success,
+                bytes_copied: 16
+            }
+        );
+        assert!(dst_buffer[..8].iter().all(|b| *b == 0));
+        assert_eq!(dst_buffer[8..24], src_buffer[16..32]);
+        assert!(dst_buffer[24..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_failed_retrieve_keeps_inflight() {
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+        let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device]).unwrap();
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let inv = BufferInventory::new(1024).unwrap();
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let mut job = workq.create_dma_job(
+            new_buf(&doca_mmap, &inv, &src_buffer),
+            new_buf(&doca_mmap, &inv, &dst_buffer),
+        );
+        job.set_src_data(0, 64);
+        workq.submit(&mut job).unwrap();
+        assert_eq!(workq.inflight(), 1);
+
+        // no job is retrieved from a work queue out of its context
+        ctx.rm_workq(&mut workq).unwrap();
+        assert_eq!(workq.poll_completion().err(), Some(DocaError::BadState));
+        assert_eq!((workq.inflight(), workq.available()), (1, 0));
+
+        ctx.add_workq(&mut workq).unwrap();
+        workq.spin_completion().unwrap();
+        assert_eq!(workq.inflight(), 0);
+        assert_eq!(src_buffer, dst_buffer);
+    }
+
+    #[test]
+    fn test_dma_status_decoding() {
+        assert_eq!(DmaStatus::from(DOCAError::DOCA_SUCCESS), DmaStatus::Success);
+        assert_eq!(
+            DmaStatus::from(DOCAError::DOCA_ERROR_NOT_PERMITTED),
+            DmaStatus::PermissionDenied
+        );
+        assert_eq!(
+            DmaStatus::from(DOCAError::DOCA_ERROR_INVALID_VALUE),
+            DmaStatus::InvalidJob
+        );
+        assert_eq!(
+            DmaStatus::from(DOCAError::DOCA_ERROR_DRIVER),
+            DmaStatus::HardwareError(DOCAError::DOCA_ERROR_DRIVER)
+        );
+        assert_eq!(
+            DmaStatus::from(DOCAError::DOCA_ERROR_TIME_OUT),
+            DmaStatus::Failed(DOCAError::DOCA_ERROR_TIME_OUT)
+        );
+        assert_eq!(DOCAEvent::new().dma_status(), DmaStatus::Success);
+    }
+
+    #[test]
+    fn test_reuse_dma_job() {
+        let (mut workq, doca_mmap, inv) = setup(1);
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let other_buffer = vec![9u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+
+        let mut job = workq.create_dma_job(
+            new_buf(&doca_mmap, &inv, &src_buffer),
+            new_buf(&doca_mmap, &inv, &dst_buffer),
+        );
+        for _ in 0..4 {
+            job.reset().unwrap();
+            workq.submit(&mut job).unwrap();
+            workq.spin_completion().unwrap();
+            assert_eq!(dst_buffer, src_buffer);
+        }
+
+        // point the same request at another source
+        let prev = job
+            .replace_src(new_buf(&doca_mmap, &inv, &other_buffer))
+            .unwrap();
+        assert_eq!(
+            unsafe { prev.get_data().unwrap() },
+            src_buffer.as_ptr() as _
+        );
+        job.reset().unwrap();
+        workq.submit(&mut job).unwrap();
+        workq.spin_completion().unwrap();
+        assert_eq!(dst_buffer, other_buffer);
+    }
+
+    #[test]
+    fn test_dma_channel() {
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+        let dma = DMAEngine::new().unwrap();
+        let ctx = DOCAContext::new(&dma, vec![device.clone()]).unwrap();
+        let doca_mmap = LocalMmap::new().unwrap();
+
+        // import the "remote" region through the export of another local mmap
+        let remote_buffer = (0..64u8).collect::<Vec<_>>().into_boxed_slice();
+        let remote = unsafe { RawPointer::from_box(&remote_buffer) };
+        let exported_mmap = LocalMmap::new().unwrap();
+        let dev_idx = exported_mmap.add_device(&device).unwrap();
+        exported_mmap.populate(remote).unwrap();
+        let desc = exported_mmap.export(dev_idx).unwrap();
+        let remote_mmap = RemoteMmap::from_export(&desc, &device).unwrap();
+        assert_eq!(remote_mmap.state(), MmapState::Imported);
+
+        let mut local_buffer = vec![0u8; 64].into_boxed_slice();
+        let mut channel = DmaChannel::new(
+            &ctx,
+            &doca_mmap,
+            unsafe { RawPointer::from_box(&local_buffer) },
+            &remote_mmap,
+            remote,
+        )
+        .unwrap();
+
+        channel.read(16, &mut local_buffer[..8]).unwrap();
+        assert_eq!(local_buffer[..8], remote_buffer[16..24]);
+
+        local_buffer[32..40].copy_from_slice(&[0xff; 8]);
+        channel.write(&local_buffer[32..40], 56).unwrap();
+        assert_eq!(remote_buffer[56..], [0xff; 8]);
+
+        // out of the remote region
+        assert_eq!(
+            channel.read(60, &mut local_buffer[..8]),
+            Err(DocaError::InvalidValue)
+        );
+        // not inside the local region
+        let mut other = [0u8; 8];
+        assert_eq!(channel.read(0, &mut other), Err(DocaError::InvalidValue));
+        assert_eq!(channel.write(&[], 0), Err(DocaError::InvalidValue));
+
+        assert!(channel.max_chunk() > 0);
+        let res = channel.copy_large(DmaDirection::Read, 0, 0, 64).unwrap();
+        assert!(res.is_success());
+        assert_eq!(res.bytes_copied, 64);
+        assert_eq!(local_buffer[..], remote_buffer[..]);
+        assert_eq!(
+            channel.copy_large(DmaDirection::Write, 32, 0, 64),
+            Err(DocaError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let chunks = split_chunks(10, 4).collect::<Vec<_>>();
+        assert_eq!(chunks, vec![(0, 4), (4, 4), (8, 2)]);
+        assert_eq!(split_chunks(8, 4).count(), 2);
+        assert_eq!(
+            split_chunks(3, usize::MAX).collect::<Vec<_>>(),
+            vec![(0, 3)]
+        );
+        assert_eq!(split_chunks(0, 4).count(), 0);
+    }
+
+    #[test]
+    fn test_submit_owned() {
+        let (mut workq, doca_mmap, inv) = setup(1);
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+
+        let mut job = workq.create_dma_job(src_buf, dst_buf);
+        job.set_src_data(0, 64).set_user_data(42u64);
+        assert_eq!(job.user_data::<u64>(), 42);
+        let handle = workq.submit_owned(job).unwrap();
+        assert_eq!(workq.owned_jobs(), 1);
+
+        let (completion, mut job) = loop {
+            match workq.poll_completion_owned::<DOCADMAJob>() {
+                Err(DocaError::Again) => continue,
+                res => break res.unwrap(),
+            }
+        };
+        assert_eq!(completion.status, DOCAError::DOCA_SUCCESS);
+        assert_eq!(completion.handle, handle);
+        assert_eq!(completion.user_data, 42);
+        assert_eq!(workq.owned_jobs(), 0);
+        assert!(job.take_dst().is_some());
+        assert_eq!(src_buffer, dst_buffer);
+    }
+
+    #[test]
+    fn test_dma_capabilities() {
+        let device = devices().unwrap().get(0).unwrap();
+        let caps = DMAEngine::capabilities(&device).unwrap();
+        println!("dma capabilities: {:?}", caps);
+        assert_eq!(caps.max_buf_size, device.get_max_buf_size().unwrap());
+    }
+
+    #[test]
+    fn test_close() {
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+        let dma = DMAEngine::new().unwrap();
+        let ctx = DOCAContext::new(&dma, vec![device.clone()]).unwrap();
+        let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        doca_mmap.add_device(&device).unwrap();
+        let inv = BufferInventory::new(1024).unwrap();
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let buf = new_buf(&doca_mmap, &inv, &src_buffer);
+
+        // the context is still held by the work queue, and the engine by the context
+        assert_eq!(ctx.clone().close(), Err(DocaError::InUse));
+        assert_eq!(dma.clone().close(), Err(DocaError::InUse));
+        // the mmap is still held by the buffer
+        assert_eq!(doca_mmap.clone().close(), Err(DocaError::InUse));
+
+        buf.close().unwrap();
+        workq.close().unwrap();
+        ctx.close().unwrap();
+        dma.close().unwrap();
+        inv.close().unwrap();
+        doca_mmap.close().unwrap();
+    }
+
+    #[test]
+    fn test_teardown_in_any_order() {
+        use crate::drop_order::live_objects;
+        use std::any::Any;
+
+        // drop the handles in the forward, the reverse and all of the rotated orders
+        let orders = (0..6)
+            .map(|r| (0..6).map(|i| (i + r) % 6).collect::<Vec<_>>())
+            .chain((0..6).map(|r| (0..6).rev().map(|i| (i + r) % 6).collect()));
+
+        for order in orders {
+            let device = devices().unwrap().get(0).unwrap().open().unwrap();
+            let doca_mmap = LocalMmap::new().unwrap();
+            doca_mmap.add_device(&device).unwrap();
+            assert_eq!(
+                doca_mmap.populate_owned(Box::new([])).err(),
+                Some(DocaError::InvalidValue)
+            );
+
+            // the mmap owns the memory, so the region outlives every handle
+            let region = doca_mmap
+                .populate_owned(vec![0u8; 64].into_boxed_slice())
+                .unwrap();
+            let inv = BufferInventory::new(1).unwrap();
+            let buf = DOCARegisteredMemory::new_populated(&doca_mmap, region)
+                .to_buffer(&inv)
+                .unwrap();
+            let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device.clone()]).unwrap();
+            let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+            let tracks = [
+                device.track,
+                doca_mmap.track,
+                inv.track,
+                buf.track,
+                ctx.track,
+                workq.track,
+            ];
+            let mut handles: Vec<Option<Box<dyn Any>>> = vec![
+                Some(Box::new(device)),
+                Some(Box::new(doca_mmap)),
+                Some(Box::new(inv)),
+                Some(Box::new(buf)),
+                Some(Box::new(ctx)),
+                Some(Box::new(workq)),
+            ];
+            for i in order {
+                handles[i].take();
+            }
+
+            // every object is destroyed once all of the handles are dropped
+            assert!(live_objects().iter().all(|(_, id)| !tracks.contains(id)));
+        }
+    }
+
+    #[test]
+    fn test_queue_full() {
+        use crate::context::work_queue::SubmitError;
+
+        let (mut workq, doca_mmap, inv) = setup(1);
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+
+        let mut job = workq.create_dma_job(src_buf, dst_buf);
+        job.set_src_data(0, 64);
+        workq.submit(&mut job).unwrap();
+        assert_eq!(workq.inflight(), 1);
+        assert_eq!(workq.available(), 0);
+
+        // the queue of depth 1 is full until the job is retrieved
+        assert_eq!(
+            workq.submit(&mut job),
+            Err(SubmitError::QueueFull { depth: 1 })
+        );
+
+        let completion = workq
+            .poll_completion_timeout(std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(completion.result().is_ok());
+        assert_eq!(workq.inflight(), 0);
+        assert_eq!(workq.available(), 1);
+    }
+
+    #[test]
+    fn test_submit_batch() {
+        use crate::context::work_queue::SubmitError;
+
+        let (mut workq, doca_mmap, inv) = setup(4);
+
+        let mut src_buffer = vec![7u8; 256].into_boxed_slice();
+        let mut dst_buffer = vec![0u8; 256].into_boxed_slice();
+        let to_buffer = |chunk: &mut [u8]| {
+            let raw = RawPointer {
+                inner: NonNull::new(chunk.as_mut_ptr() as *mut _).unwrap(),
+                payload: chunk.len(),
+            };
+            DOCARegisteredMemory::new(&doca_mmap, raw)
+                .unwrap()
+                .to_buffer(&inv)
+                .unwrap()
+        };
+
+        // each job copies a chunk of 64 bytes
+        let mut jobs: Vec<DOCADMAJob> = src_buffer
+            .chunks_mut(64)
+            .zip(dst_buffer.chunks_mut(64))
+            .map(|(src, dst)| {
+                let mut job = workq.create_dma_job(to_buffer(src), to_buffer(dst));
+                job.set_src_data(0, 64);
+                job
+            })
+            .collect();
+
+        // nothing has finished yet
+        assert!(workq.poll_completions(4).unwrap().is_empty());
+
+        let mut handles = Vec::new();
+        workq.enable_timestamps(true);
+        workq.submit_batch(&mut jobs, &mut handles).unwrap();
+        assert_eq!(handles.len(), 4);
+
+        // the batch exceeding the queue is rejected as a whole
+        assert_eq!(
+            workq.submit_batch(&mut jobs[..1], &mut handles),
+            Err(SubmitError::QueueFull { depth: 4 })
+        );
+        assert_eq!(handles.len(), 4);
+
+        let mut completions = Vec::new();
+        while completions.len() < 4 {
+            completions.extend(workq.poll_completions(4 - completions.len()).unwrap());
+        }
+        for completion in completions.iter() {
+            assert!(handles.contains(&completion.handle));
+            assert!(completion.latency.is_some());
+            completion.result().unwrap();
+        }
+        assert_eq!(src_buffer, dst_buffer);
+    }
+
+    #[test]
+    fn test_attach_and_dispatch() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+        let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device.clone()]).unwrap();
+        let other_ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device]).unwrap();
+
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        workq.attach(&other_ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let inv = BufferInventory::new(1024).unwrap();
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let src_buf = new_buf(&doca_mmap, &inv, &src_buffer);
+        let dst_buf = new_buf(&doca_mmap, &inv, &dst_buffer);
+
+        let finished = Rc::new(Cell::new(0));
+        let counter = finished.clone();
+        workq.on_completion(move |completion: Completion, _job: DOCADMAJob| {
+            assert_eq!(completion.status, DOCAError::DOCA_SUCCESS);
+            counter.set(counter.get() + 1);
+        });
+
+        let mut job = workq.create_dma_job(src_buf, dst_buf);
+        job.set_src_data(0, 64);
+        workq.submit_owned(job).unwrap();
+
+        loop {
+            match workq.dispatch_completion() {
+                Err(DocaError::Again) => continue,
+                res => break res.unwrap(),
+            }
+        }
+        assert_eq!(finished.get(), 1);
+        assert_eq!(src_buffer, dst_buffer);
+    }
+
+    #[test]
+    fn test_progress_on_complete() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let (mut workq, doca_mmap, inv) = setup(2);
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+
+        let finished = Rc::new(RefCell::new(Vec::new()));
+        let handles = finished.clone();
+        workq.on_complete(move |completion: Completion| {
+            assert_eq!(completion.status, DOCAError::DOCA_SUCCESS);
+            handles.borrow_mut().push(completion.handle);
+        });
+        assert_eq!(workq.progress(), Ok(0));
+
+        let mut job = workq.create_dma_job(
+            new_buf(&doca_mmap, &inv, &src_buffer),
+            new_buf(&doca_mmap, &inv, &dst_buffer),
+        );
+        job.set_src_data(0, 32);
+        let first = workq.submit(&mut job).unwrap();
+        let mut owned = workq.create_dma_job(
+            new_buf(&doca_mmap, &inv, &src_buffer),
+            new_buf(&doca_mmap, &inv, &dst_buffer),
+        );
+        owned.set_src_data(32, 32).set_dst_offset(32);
+        let second = workq.submit_owned(owned).unwrap();
+
+        let mut retrieved = 0;
+        while retrieved < 2 {
+            retrieved += workq.progress().unwrap();
+        }
+        let finished = finished.borrow();
+        assert_eq!(finished.len(), 2);
+        assert!(finished.contains(&first) && finished.contains(&second));
+        assert_eq!(workq.owned_jobs(), 0);
+        assert_eq!(src_buffer, dst_buffer);
+
+        // every progress call polls once more than the jobs it retrieves
+        let stats = workq.stats();
+        assert_eq!((stats.submitted, stats.completed, stats.errors), (2, 2, 0));
+        assert_eq!(stats.polls, stats.completed + stats.retries);
+        assert!(stats.avg_polls_per_completion() >= 1.0);
+    }
+
+    #[test]
+    fn test_move_workq_between_contexts() {
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+        let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device.clone()]).unwrap();
+        let other_ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device]).unwrap();
+
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        assert_eq!(ctx.add_workq(&mut workq), Err(DocaError::InUse));
+        assert_eq!(other_ctx.rm_workq(&mut workq), Err(DocaError::NotFound));
+
+        ctx.rm_workq(&mut workq).unwrap();
+        other_ctx.add_workq(&mut workq).unwrap();
+
+        // the queue is only removed from the other context on drop
+        drop(workq);
+    }
+
+    #[test]
+    fn test_dma_context_state() {
+        use crate::dma::DOCAContext;
+        use crate::DocaError;
+
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+
+        let dma = DMAEngine::new().unwrap();
+        let ctx = DOCAContext::new(&dma, vec![device]).unwrap();
+        assert!(ctx.is_started());
+
+        // the state is tracked while a work queue shares the context
+        let _workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        assert_eq!(ctx.start(), Err(DocaError::BadState));
+        assert!(ctx.stop().is_ok());
+        assert_eq!(ctx.stop(), Err(DocaError::BadState));
+        assert_eq!(ctx.restart(), Err(DocaError::BadState));
+        assert!(ctx.start().is_ok());
+    }
+
+    #[test]
+    fn test_dma_context_reconfigure() {
+        use crate::context::ContextState;
+        use crate::dma::DOCAContext;
+        use crate::DocaError;
+
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+
+        let dma = DMAEngine::new().unwrap();
+        let ctx = DOCAContext::new(&dma, vec![device.clone()]).unwrap();
+        assert_eq!(ctx.state(), ContextState::Running);
+
+        // the devices can't be changed on a running context
+        assert_eq!(ctx.rm_dev(&device), Err(DocaError::BadState));
+
+        ctx.reconfigure(|ctx| {
+            assert_eq!(ctx.state(), ContextState::Idle);
+            assert_eq!(ctx.add_dev(&device), Err(DocaError::InUse));
+            ctx.rm_dev(&device)?;
+            assert!(ctx.devices().is_empty());
+            ctx.add_dev(&device)
+        })
+        .unwrap();
+        assert_eq!(ctx.state(), ContextState::Running);
+        assert_eq!(ctx.devices().len(), 1);
+
+        ctx.restart().unwrap();
+        assert!(ctx.is_started());
+    }
+
+    #[test]
+    fn test_restart_shared_context() {
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+        let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device.clone()]).unwrap();
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let inv = BufferInventory::new(1024).unwrap();
+
+        let src_buffer = vec![7u8; 64].into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let mut job = workq.create_dma_job(
+            new_buf(&doca_mmap, &inv, &src_buffer),
+            new_buf(&doca_mmap, &inv, &dst_buffer),
+        );
+        job.set_src_data(0, 64);
+
+        // the work queue and the job hold the context while it is reconfigured
+        assert!(Arc::strong_count(&ctx) > 1);
+        ctx.stop().unwrap();
+        assert!(workq.submit(&mut job).is_err());
+        ctx.reconfigure(|ctx| {
+            ctx.rm_dev(&device)?;
+            ctx.add_dev(&device)
+        })
+        .unwrap();
+        ctx.restart().unwrap();
+
+        workq.submit(&mut job).unwrap();
+        workq.spin_completion().unwrap();
+        assert_eq!(src_buffer, dst_buffer);
+    }
+
+    #[test]
+    fn test_dma_context_state_change() {
+        use crate::context::ContextState;
+        use crate::dma::DOCAContext;
+        use std::sync::{Arc, Mutex};
+
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
+
+        let dma = DMAEngine::new().unwrap();
+        let ctx = DOCAContext::new(&dma, vec![device]).unwrap();
+
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let recorder = states.clone();
+        ctx.on_state_change(move |state| recorder.lock().unwrap().push(state));
+
+        ctx.stop().unwrap();
+        ctx.start().unwrap();
+
+        assert_eq!(
+            *states.lock().unwrap(),
+            vec![
+                ContextState::Stopping,
+                ContextState::Idle,
+                ContextState::Running
+            ]
+        );
+    }
+
     #[test]
     fn test_dma_context() {
-        use crate::dma::DMAEngine;
         use crate::dma::DOCAContext;
 
-        let device = crate::device::devices()
-            .unwrap()
-            .get(0)
-            .unwrap()
-            .open()
-            .unwrap();
+        let device = devices().unwrap().get(0).unwrap().open().unwrap();
 
         let dma = DMAEngine::new().unwrap();
         let ctx = DOCAContext::new(&dma, vec![device]).unwrap();
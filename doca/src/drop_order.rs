@@ -0,0 +1,91 @@
+//! Runtime tracker of the live wrapper objects.
+//!
+//! Each wrapper object holds an `Arc` of the objects it depends on,
+//! i.e., buffer -> inventory & mmap, workq -> context, context -> device and mmap -> device,
+//! so a dependency is never destroyed in the SDK before its dependents,
+//! whatever order the user handles are dropped in.
+//! What the `Arc`s don't guarantee is that the objects are destroyed at all:
+//! a handle leaked by a cycle or by `mem::forget` keeps its dependencies alive as well.
+//! The tracker records each object until it is destroyed, so the leaked objects can be
+//! listed, e.g., at the end of a test.
+//!
+//! The tracker only works in `debug` mode, and costs nothing but an id per object in
+//! `release` mode.
+//!
+//! ```
+//! // after dropping the handles, check which objects are still alive
+//! for (kind, id) in doca::drop_order::live_objects() {
+//!     println!("{} {:?} is still alive", kind, id);
+//! }
+//! ```
+//!
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Identity of an object tracked by the tracker
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TrackId(u64);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static REGISTRY: Mutex<BTreeMap<TrackId, &'static str>> = Mutex::new(BTreeMap::new());
+
+impl TrackId {
+    /// Register a new object of `kind`
+    pub(crate) fn new(kind: &'static str) -> Self {
+        let id = Self(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+
+        if cfg!(debug_assertions) {
+            lock(&REGISTRY).insert(id, kind);
+            trace_event!(object = kind, id = id.0, "object tracked");
+        }
+        id
+    }
+
+    /// Unregister the object.
+    /// It should be called when the object is destroyed in the SDK.
+    pub(crate) fn release(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        if let Some(_kind) = lock(&REGISTRY).remove(self) {
+            trace_event!(object = _kind, id = self.0, "object released");
+        }
+    }
+}
+
+/// Get the tracked objects that are still alive
+pub fn live_objects() -> Vec<(&'static str, TrackId)> {
+    lock(&REGISTRY)
+        .iter()
+        .map(|(id, kind)| (*kind, *id))
+        .collect()
+}
+
+// The tracker should keep working even if a panic happened while holding the lock
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_track_live_objects() {
+        let parent = TrackId::new("Parent");
+        let child = TrackId::new("Child");
+        assert!(live_objects().contains(&("Parent", parent)));
+
+        parent.release();
+        let live = live_objects();
+        assert!(!live.contains(&("Parent", parent)));
+        assert!(live.contains(&("Child", child)));
+
+        child.release();
+        assert!(!live_objects().contains(&("Child", child)));
+    }
+}
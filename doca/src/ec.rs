@@ -0,0 +1,589 @@
+//! Wrapper for the DOCA erasure coding library of DOCA 2.x, e.g., for storage offload.
+//!
+//! - [`ECEngine`] is the erasure coding context of a device. It is connected to a
+//! [`ProgressEngine`] on creation, which drives its tasks.
+//!
+//! - [`ECMatrix`] is the coding matrix of a number of data blocks and redundancy blocks,
+//! the matrix recovering some missing blocks from the others, or the matrix updating
+//! the redundancy blocks after some data blocks change. The blocks are named by
+//! [`BlockIndex`]es.
+//!
+//! - [`ECJob`] encodes the redundancy blocks of the data blocks, recovers the missing
+//! blocks, or updates the redundancy blocks. It owns its buffers until it completes,
+//! and gives them back to the closure passed to [`ECJob::submit`].
+//!
+//! The blocks of a job are stored contiguously in a buffer, all of the same size.
+//!
+//! The module is only built with the `doca2` feature, since the library doesn't exist in DOCA 1.5.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::ec::{ECEngine, MatrixType};
+//! use doca::progress_engine::ProgressEngine;
+//!
+//! # fn run(data: doca::DOCABuffer, rdnc: doca::DOCABuffer) {
+//! let device = doca::open_device_with_pci("03:00.0").unwrap();
+//! let mut pe = ProgressEngine::new().unwrap();
+//! let ec = unsafe { ECEngine::new(&device, &mut pe, 16) }.unwrap();
+//!
+//! // 4 data blocks protected by 2 redundancy blocks
+//! let matrix = ec.matrix(MatrixType::Cauchy, 4, 2).unwrap();
+//! let job = ec.encode(&matrix, data, rdnc).unwrap();
+//! job.submit(&mut pe, |_data, _rdnc, res| res.unwrap()).unwrap();
+//!
+//! while pe.inflight().unwrap() > 0 {
+//!     pe.progress();
+//! }
+//! # }
+//! ```
+//!
+
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use ffi::doca_error;
+
+use crate::error::check;
+use crate::progress_engine::{completion_trampoline, ProgressEngine, RawTask, Task};
+use crate::{DOCABuffer, DOCAResult, DevContext, Device, DocaError};
+
+impl RawTask for ffi::doca_ec_task_create {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_ec_task_create_as_task(task)
+    }
+}
+
+impl RawTask for ffi::doca_ec_task_recover {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_ec_task_recover_as_task(task)
+    }
+}
+
+impl RawTask for ffi::doca_ec_task_update {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_ec_task_update_as_task(task)
+    }
+}
+
+/// The index of a block of a coding matrix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockIndex {
+    /// The index among the data blocks
+    Data(u32),
+    /// The index among the redundancy blocks
+    Redundancy(u32),
+}
+
+impl BlockIndex {
+    // The index in the SDK, which counts the data blocks first and then the redundancy blocks
+    fn to_raw(self, data_blocks: usize, rdnc_blocks: usize) -> DOCAResult<u32> {
+        let (index, count, first) = match self {
+            BlockIndex::Data(i) => (i as usize, data_blocks, 0),
+            BlockIndex::Redundancy(i) => (i as usize, rdnc_blocks, data_blocks),
+        };
+        if index >= count {
+            return Err(DocaError::InvalidValue);
+        }
+        u32::try_from(first + index).map_err(|_e| DocaError::InvalidValue)
+    }
+}
+
+/// The type of a coding matrix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixType {
+    /// Cauchy matrix
+    Cauchy,
+    /// Vandermonde matrix
+    Vandermonde,
+}
+
+impl MatrixType {
+    fn as_raw(&self) -> ffi::doca_ec_matrix_type {
+        match self {
+            MatrixType::Cauchy => ffi::DOCA_EC_MATRIX_TYPE_CAUCHY,
+            MatrixType::Vandermonde => ffi::DOCA_EC_MATRIX_TYPE_VANDERMONDE,
+        }
+    }
+}
+
+/// The erasure coding context of a device
+pub struct ECEngine {
+    inner: NonNull<ffi::doca_ec>,
+    // the device should be closed after the context is destroyed
+    #[allow(dead_code)]
+    dev: Arc<DevContext>,
+}
+
+impl Drop for ECEngine {
+    fn drop(&mut self) {
+        // the tasks hold the matrices, which hold the engine, so none is in flight
+        let _ = unsafe { ffi::doca_ctx_stop(self.ctx_ptr()) };
+
+        let ret = unsafe { ffi::doca_ec_destroy(self.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the erasure coding engine: {:?}", ret);
+        }
+
+        dropped!("EC Engine is dropped!");
+    }
+}
+
+impl ECEngine {
+    /// Check whether the device supports the encoding tasks
+    pub fn is_supported(dev: &Device) -> bool {
+        let ret = unsafe { ffi::doca_ec_cap_task_create_is_supported(dev.inner_ptr()) };
+        ret == doca_error::DOCA_SUCCESS
+    }
+
+    /// Create the context on the device, connect it to the progress engine,
+    /// and start it with room for `num_tasks` tasks of each type.
+    ///
+    /// It is unsafe because the engine must outlive the progress engine,
+    /// see [`ProgressEngine::connect_ctx`].
+    pub unsafe fn new(
+        dev: &Arc<DevContext>,
+        pe: &mut ProgressEngine,
+        num_tasks: u32,
+    ) -> DOCAResult<Arc<Self>> {
+        let mut ec: *mut ffi::doca_ec = std::ptr::null_mut();
+        let ret = ffi::doca_ec_create(dev.inner_ptr(), &mut ec as *mut _);
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let res = Self {
+            inner: NonNull::new(ec).ok_or(DocaError::InvalidValue)?,
+            dev: dev.clone(),
+        };
+
+        let create_cb = Some(completion_trampoline::<ffi::doca_ec_task_create> as _);
+        check(ffi::doca_ec_task_create_set_conf(
+            res.inner_ptr(),
+            create_cb,
+            create_cb,
+            num_tasks,
+        ))?;
+        let recover_cb = Some(completion_trampoline::<ffi::doca_ec_task_recover> as _);
+        check(ffi::doca_ec_task_recover_set_conf(
+            res.inner_ptr(),
+            recover_cb,
+            recover_cb,
+            num_tasks,
+        ))?;
+        let update_cb = Some(completion_trampoline::<ffi::doca_ec_task_update> as _);
+        check(ffi::doca_ec_task_update_set_conf(
+            res.inner_ptr(),
+            update_cb,
+            update_cb,
+            num_tasks,
+        ))?;
+
+        pe.connect_ctx(res.ctx_ptr())?;
+        check(ffi::doca_ctx_start(res.ctx_ptr()))?;
+        Ok(Arc::new(res))
+    }
+
+    /// Create the coding matrix of `data_blocks` data blocks and `rdnc_blocks` redundancy blocks
+    pub fn matrix(
+        self: &Arc<Self>,
+        ty: MatrixType,
+        data_blocks: usize,
+        rdnc_blocks: usize,
+    ) -> DOCAResult<Arc<ECMatrix>> {
+        let mut matrix: *mut ffi::doca_ec_matrix = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_ec_matrix_create(
+                self.inner_ptr(),
+                ty.as_raw(),
+                data_blocks,
+                rdnc_blocks,
+                &mut matrix as *mut _,
+            )
+        })?;
+
+        self.wrap_matrix(matrix, data_blocks, rdnc_blocks)
+    }
+
+    /// Create the matrix recovering the `missing` blocks of `coding`.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `missing` is empty or out of the blocks of `coding`.
+    pub fn recover_matrix(
+        self: &Arc<Self>,
+        coding: &ECMatrix,
+        missing: &[BlockIndex],
+    ) -> DOCAResult<Arc<ECMatrix>> {
+        let mut missing = coding.raw_indices(missing)?;
+        let mut matrix: *mut ffi::doca_ec_matrix = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_ec_matrix_create_recover(
+                self.inner_ptr(),
+                coding.inner_ptr(),
+                missing.as_mut_ptr(),
+                missing.len(),
+                &mut matrix as *mut _,
+            )
+        })?;
+
+        self.wrap_matrix(matrix, coding.data_blocks, coding.rdnc_blocks)
+    }
+
+    /// Create the matrix updating the redundancy blocks of `coding` after the `updated`
+    /// data blocks change, without reading the other data blocks.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `updated` is empty, out of the data blocks of
+    /// `coding`, or names a redundancy block.
+    pub fn update_matrix(
+        self: &Arc<Self>,
+        coding: &ECMatrix,
+        updated: &[BlockIndex],
+    ) -> DOCAResult<Arc<ECMatrix>> {
+        if updated
+            .iter()
+            .any(|b| matches!(b, BlockIndex::Redundancy(_)))
+        {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let mut updated = coding.raw_indices(updated)?;
+        let mut matrix: *mut ffi::doca_ec_matrix = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_ec_matrix_create_update(
+                self.inner_ptr(),
+                coding.inner_ptr(),
+                updated.as_mut_ptr(),
+                updated.len(),
+                &mut matrix as *mut _,
+            )
+        })?;
+
+        self.wrap_matrix(matrix, coding.data_blocks, coding.rdnc_blocks)
+    }
+
+    fn wrap_matrix(
+        self: &Arc<Self>,
+        matrix: *mut ffi::doca_ec_matrix,
+        data_blocks: usize,
+        rdnc_blocks: usize,
+    ) -> DOCAResult<Arc<ECMatrix>> {
+        Ok(Arc::new(ECMatrix {
+            inner: NonNull::new(matrix).ok_or(DocaError::InvalidValue)?,
+            data_blocks,
+            rdnc_blocks,
+            ec: self.clone(),
+        }))
+    }
+
+    /// Create a job encoding the redundancy blocks of the data blocks in `data`
+    /// into `rdnc`, which should have room for all the redundancy blocks of the matrix.
+    pub fn encode(
+        &self,
+        matrix: &Arc<ECMatrix>,
+        data: DOCABuffer,
+        rdnc: DOCABuffer,
+    ) -> DOCAResult<ECJob> {
+        let mut task: *mut ffi::doca_ec_task_create = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_ec_task_create_allocate_init(
+                self.inner_ptr(),
+                matrix.inner_ptr(),
+                data.inner_ptr(),
+                rdnc.inner_ptr(),
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        Ok(ECJob {
+            task: unsafe { Task::from_raw(task)? },
+            matrix: matrix.clone(),
+            src: data,
+            dst: rdnc,
+        })
+    }
+
+    /// Create a job recovering the missing blocks of the recover matrix into `recovered`,
+    /// from the remaining blocks in `available`, in the order of their indices.
+    pub fn recover(
+        &self,
+        matrix: &Arc<ECMatrix>,
+        available: DOCABuffer,
+        recovered: DOCABuffer,
+    ) -> DOCAResult<ECJob> {
+        let mut task: *mut ffi::doca_ec_task_recover = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_ec_task_recover_allocate_init(
+                self.inner_ptr(),
+                matrix.inner_ptr(),
+                available.inner_ptr(),
+                recovered.inner_ptr(),
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        Ok(ECJob {
+            task: unsafe { Task::from_raw(task)? },
+            matrix: matrix.clone(),
+            src: available,
+            dst: recovered,
+        })
+    }
+
+    /// Create a job updating the redundancy blocks with the update matrix into `updated_rdnc`.
+    ///
+    /// `src` holds the original and then the updated content of each updated data block,
+    /// in the order of their indices, followed by the original redundancy blocks.
+    pub fn update(
+        &self,
+        matrix: &Arc<ECMatrix>,
+        src: DOCABuffer,
+        updated_rdnc: DOCABuffer,
+    ) -> DOCAResult<ECJob> {
+        let mut task: *mut ffi::doca_ec_task_update = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_ec_task_update_allocate_init(
+                self.inner_ptr(),
+                matrix.inner_ptr(),
+                src.inner_ptr(),
+                updated_rdnc.inner_ptr(),
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        Ok(ECJob {
+            task: unsafe { Task::from_raw(task)? },
+            matrix: matrix.clone(),
+            src,
+            dst: updated_rdnc,
+        })
+    }
+
+    /// Return the inner pointer of the erasure coding context
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_ec {
+        self.inner.as_ptr()
+    }
+
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_ec_as_ctx(self.inner_ptr())
+    }
+}
+
+/// A coding matrix, a recover matrix or an update matrix, see [`ECEngine::matrix`]
+pub struct ECMatrix {
+    inner: NonNull<ffi::doca_ec_matrix>,
+    // the blocks of the coding matrix, which the other matrices are derived from
+    data_blocks: usize,
+    rdnc_blocks: usize,
+    // the matrix should be destroyed before the engine
+    #[allow(dead_code)]
+    ec: Arc<ECEngine>,
+}
+
+impl Drop for ECMatrix {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_ec_matrix_destroy(self.inner.as_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the coding matrix: {:?}", ret);
+        }
+    }
+}
+
+impl ECMatrix {
+    /// Get the number of the data blocks of the coding matrix
+    pub fn data_blocks(&self) -> usize {
+        self.data_blocks
+    }
+
+    /// Get the number of the redundancy blocks of the coding matrix
+    pub fn rdnc_blocks(&self) -> usize {
+        self.rdnc_blocks
+    }
+
+    // Convert the non-empty blocks into the indices of the SDK
+    fn raw_indices(&self, blocks: &[BlockIndex]) -> DOCAResult<Vec<u32>> {
+        if blocks.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+        blocks
+            .iter()
+            .map(|b| b.to_raw(self.data_blocks, self.rdnc_blocks))
+            .collect()
+    }
+
+    /// Return the inner pointer of the matrix
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_ec_matrix {
+        self.inner.as_ptr()
+    }
+}
+
+/// An encoding, recovering or updating job,
+/// see [`ECEngine::encode`], [`ECEngine::recover`] and [`ECEngine::update`]
+pub struct ECJob {
+    task: Task,
+    matrix: Arc<ECMatrix>,
+    src: DOCABuffer,
+    dst: DOCABuffer,
+}
+
+impl ECJob {
+    /// Submit the job to the progress engine the engine is connected to,
+    /// and call `on_done` with the source and the destination buffers and the result
+    /// when it finishes.
+    ///
+    /// The buffers are dropped with the job if the submission fails.
+    pub fn submit<F>(self, pe: &mut ProgressEngine, on_done: F) -> DOCAResult<()>
+    where
+        F: FnOnce(DOCABuffer, DOCABuffer, DOCAResult<()>) + 'static,
+    {
+        let ECJob {
+            task,
+            matrix,
+            src,
+            dst,
+        } = self;
+
+        pe.submit(task, move |task, res| {
+            // free the task before the matrix it uses
+            drop(task);
+            drop(matrix);
+            on_done(src, dst, res);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::progress_engine::tests::{new_buf, setup};
+
+    #[test]
+    fn test_encode_and_recover() {
+        use super::*;
+        use crate::*;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !ECEngine::is_supported(&device) {
+            return;
+        }
+        let device = device.open().unwrap();
+        let mut pe = ProgressEngine::new().unwrap();
+        let ec = unsafe { ECEngine::new(&device, &mut pe, 4) }.unwrap();
+
+        let (doca_mmap, inv) = setup(&device);
+
+        // keep the finished buffers until the memory is checked
+        let done = Rc::new(RefCell::new(Vec::new()));
+        let on_done = |done: &Rc<RefCell<Vec<_>>>| {
+            let done = done.clone();
+            move |src, dst, res: DOCAResult<()>| {
+                res.unwrap();
+                done.borrow_mut().push((src, dst));
+            }
+        };
+
+        // 2 data blocks of 64 bytes and 1 redundancy block
+        let data = (0..128u8).collect::<Vec<_>>().into_boxed_slice();
+        let rdnc = vec![0u8; 64].into_boxed_slice();
+        let matrix = ec.matrix(MatrixType::Cauchy, 2, 1).unwrap();
+        ec.encode(
+            &matrix,
+            new_buf(&doca_mmap, &inv, &data, 128),
+            new_buf(&doca_mmap, &inv, &rdnc, 0),
+        )
+        .unwrap()
+        .submit(&mut pe, on_done(&done))
+        .unwrap();
+        while pe.inflight().unwrap() > 0 {
+            pe.progress();
+        }
+
+        // lose the first data block, and recover it from the second one and the redundancy
+        let mut available = vec![0u8; 128].into_boxed_slice();
+        available[..64].copy_from_slice(&data[64..]);
+        available[64..].copy_from_slice(&rdnc);
+        let recovered = vec![0u8; 64].into_boxed_slice();
+
+        let recover = ec.recover_matrix(&matrix, &[BlockIndex::Data(0)]).unwrap();
+        ec.recover(
+            &recover,
+            new_buf(&doca_mmap, &inv, &available, 128),
+            new_buf(&doca_mmap, &inv, &recovered, 0),
+        )
+        .unwrap()
+        .submit(&mut pe, on_done(&done))
+        .unwrap();
+        while pe.inflight().unwrap() > 0 {
+            pe.progress();
+        }
+        assert_eq!(recovered[..], data[..64]);
+        assert_eq!(done.borrow().len(), 2);
+
+        // change the second data block, and update the redundancy without the first one
+        let mut src = vec![0u8; 192].into_boxed_slice();
+        src[..64].copy_from_slice(&data[64..]);
+        src[64..128].fill(0xff);
+        src[128..].copy_from_slice(&rdnc);
+        let updated = vec![0u8; 64].into_boxed_slice();
+
+        let update = ec.update_matrix(&matrix, &[BlockIndex::Data(1)]).unwrap();
+        ec.update(
+            &update,
+            new_buf(&doca_mmap, &inv, &src, 192),
+            new_buf(&doca_mmap, &inv, &updated, 0),
+        )
+        .unwrap()
+        .submit(&mut pe, on_done(&done))
+        .unwrap();
+        while pe.inflight().unwrap() > 0 {
+            pe.progress();
+        }
+
+        // the updated redundancy is the one encoded from the changed data
+        let mut changed = data.clone();
+        changed[64..].fill(0xff);
+        let encoded = vec![0u8; 64].into_boxed_slice();
+        ec.encode(
+            &matrix,
+            new_buf(&doca_mmap, &inv, &changed, 128),
+            new_buf(&doca_mmap, &inv, &encoded, 0),
+        )
+        .unwrap()
+        .submit(&mut pe, on_done(&done))
+        .unwrap();
+        while pe.inflight().unwrap() > 0 {
+            pe.progress();
+        }
+        assert_eq!(updated, encoded);
+
+        assert_eq!(
+            ec.recover_matrix(&matrix, &[]).err(),
+            Some(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            ec.update_matrix(&matrix, &[BlockIndex::Redundancy(0)])
+                .err(),
+            Some(DocaError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_block_index() {
+        use super::*;
+
+        // 4 data blocks and 2 redundancy blocks
+        assert_eq!(BlockIndex::Data(3).to_raw(4, 2), Ok(3));
+        assert_eq!(BlockIndex::Redundancy(1).to_raw(4, 2), Ok(5));
+        assert_eq!(
+            BlockIndex::Data(4).to_raw(4, 2),
+            Err(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            BlockIndex::Redundancy(2).to_raw(4, 2),
+            Err(DocaError::InvalidValue)
+        );
+    }
+}
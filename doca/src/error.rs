@@ -0,0 +1,435 @@
+//! The error type of the crate.
+//!
+//! DOCA reports the failures with the raw [`doca_error`] codes, which are
+//! converted into [`DocaError`] at the boundary of the FFI, so the errors
+//! can be displayed and composed with the other [`std::error::Error`]s.
+//!
+//! ```
+//! use doca::{DocaError, DOCAError};
+//!
+//! let e = DocaError::from(DOCAError::DOCA_ERROR_AGAIN);
+//! assert_eq!(e, DocaError::Again);
+//! assert_eq!(e.code(), DOCAError::DOCA_ERROR_AGAIN);
+//! ```
+//!
+//! The [`Display`](fmt::Display) of the error shows its code and description.
+//! The description and the name given by the SDK itself can be queried with
+//! [`DocaError::message`] and [`DocaError::name`].
+//!
+//! The failures of the file and socket IO of the crate, e.g., [`load_config`](crate::load_config),
+//! are reported as [`DocaError::Io`], which keeps the [`IoError`] of the OS,
+//! so that a missing file can be told apart from a denied permission.
+//!
+
+use std::ffi::CStr;
+use std::{fmt, io};
+
+use ffi::doca_error;
+
+/// The error returned by the DOCA APIs, i.e., the non-success [`doca_error`] codes.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocaError {
+    /// Unknown error
+    Unknown,
+    /// Operation not permitted
+    NotPermitted,
+    /// Resource already in use
+    InUse,
+    /// Operation not supported
+    NotSupported,
+    /// Resource temporarily unavailable, try again
+    Again,
+    /// Invalid input
+    InvalidValue,
+    /// Memory allocation failure
+    NoMemory,
+    /// Resource initialization failure
+    Initialization,
+    /// Timer expired waiting for resource
+    TimeOut,
+    /// Shut down in process or completed
+    Shutdown,
+    /// Connection reset by peer
+    ConnectionReset,
+    /// Connection aborted
+    ConnectionAborted,
+    /// Connection in progress
+    ConnectionInProgress,
+    /// Not connected
+    NotConnected,
+    /// Unable to acquire the required lock
+    NoLock,
+    /// Resource not found
+    NotFound,
+    /// Input/output operation failed
+    IoFailed,
+    /// Bad state
+    BadState,
+    /// Unsupported version
+    UnsupportedVersion,
+    /// Operating system call failure
+    OperatingSystem,
+    /// DOCA driver call failure
+    Driver,
+    /// Unexpected failure
+    Unexpected,
+    /// No free buffer left in the buffer inventory.
+    ///
+    /// DOCA reports it as `DOCA_ERROR_NO_MEMORY`, which it is converted back into.
+    InventoryExhausted,
+    /// The IO of the crate failed, e.g., reading a config file, with the error of the OS.
+    ///
+    /// Its code is `DOCA_ERROR_IO_FAILED`, which is converted back into [`DocaError::IoFailed`].
+    Io(IoError),
+}
+
+impl DocaError {
+    /// The raw DOCA error code, e.g., to be returned to a C caller.
+    pub fn code(&self) -> doca_error {
+        match self {
+            DocaError::Unknown => doca_error::DOCA_ERROR_UNKNOWN,
+            DocaError::NotPermitted => doca_error::DOCA_ERROR_NOT_PERMITTED,
+            DocaError::InUse => doca_error::DOCA_ERROR_IN_USE,
+            DocaError::NotSupported => doca_error::DOCA_ERROR_NOT_SUPPORTED,
+            DocaError::Again => doca_error::DOCA_ERROR_AGAIN,
+            DocaError::InvalidValue => doca_error::DOCA_ERROR_INVALID_VALUE,
+            DocaError::NoMemory => doca_error::DOCA_ERROR_NO_MEMORY,
+            DocaError::Initialization => doca_error::DOCA_ERROR_INITIALIZATION,
+            DocaError::TimeOut => doca_error::DOCA_ERROR_TIME_OUT,
+            DocaError::Shutdown => doca_error::DOCA_ERROR_SHUTDOWN,
+            DocaError::ConnectionReset => doca_error::DOCA_ERROR_CONNECTION_RESET,
+            DocaError::ConnectionAborted => doca_error::DOCA_ERROR_CONNECTION_ABORTED,
+            DocaError::ConnectionInProgress => doca_error::DOCA_ERROR_CONNECTION_INPROGRESS,
+            DocaError::NotConnected => doca_error::DOCA_ERROR_NOT_CONNECTED,
+            DocaError::NoLock => doca_error::DOCA_ERROR_NO_LOCK,
+            DocaError::NotFound => doca_error::DOCA_ERROR_NOT_FOUND,
+            DocaError::IoFailed => doca_error::DOCA_ERROR_IO_FAILED,
+            DocaError::BadState => doca_error::DOCA_ERROR_BAD_STATE,
+            DocaError::UnsupportedVersion => doca_error::DOCA_ERROR_UNSUPPORTED_VERSION,
+            DocaError::OperatingSystem => doca_error::DOCA_ERROR_OPERATING_SYSTEM,
+            DocaError::Driver => doca_error::DOCA_ERROR_DRIVER,
+            DocaError::Unexpected => doca_error::DOCA_ERROR_UNEXPECTED,
+            DocaError::InventoryExhausted => doca_error::DOCA_ERROR_NO_MEMORY,
+            DocaError::Io(_) => doca_error::DOCA_ERROR_IO_FAILED,
+        }
+    }
+
+    /// The human-readable description of the error given by the DOCA SDK,
+    /// i.e., `doca_get_error_string`.
+    pub fn message(&self) -> &'static str {
+        match self {
+            // the SDK only knows the raw code of the typed errors
+            DocaError::InventoryExhausted => self.description(),
+            _ => sdk_str(unsafe { ffi::doca_get_error_string(self.code()) })
+                .unwrap_or_else(|| self.description()),
+        }
+    }
+
+    /// The name of the error code given by the DOCA SDK, i.e., `doca_get_error_name`.
+    pub fn name(&self) -> &'static str {
+        sdk_str(unsafe { ffi::doca_get_error_name(self.code()) }).unwrap_or("DOCA_ERROR_UNKNOWN")
+    }
+
+    // The description of the error, following the ones of the DOCA SDK
+    fn description(&self) -> &'static str {
+        match self {
+            DocaError::Unknown => "unknown error",
+            DocaError::NotPermitted => "operation not permitted",
+            DocaError::InUse => "resource already in use",
+            DocaError::NotSupported => "operation not supported",
+            DocaError::Again => "resource temporarily unavailable, try again",
+            DocaError::InvalidValue => "invalid input",
+            DocaError::NoMemory => "memory allocation failure",
+            DocaError::Initialization => "resource initialization failure",
+            DocaError::TimeOut => "timer expired waiting for resource",
+            DocaError::Shutdown => "shut down in process or completed",
+            DocaError::ConnectionReset => "connection reset by peer",
+            DocaError::ConnectionAborted => "connection aborted",
+            DocaError::ConnectionInProgress => "connection in progress",
+            DocaError::NotConnected => "not connected",
+            DocaError::NoLock => "unable to acquire the required lock",
+            DocaError::NotFound => "resource not found",
+            DocaError::IoFailed => "input/output operation failed",
+            DocaError::BadState => "bad state",
+            DocaError::UnsupportedVersion => "unsupported version",
+            DocaError::OperatingSystem => "operating system call failure",
+            DocaError::Driver => "DOCA driver call failure",
+            DocaError::Unexpected => "unexpected failure",
+            DocaError::InventoryExhausted => "no free buffer left in the inventory",
+            DocaError::Io(_) => "input/output operation failed",
+        }
+    }
+}
+
+impl fmt::Display for DocaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code(), self.description())?;
+        if let DocaError::Io(e) = self {
+            write!(f, ": {}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DocaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DocaError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DocaError {
+    fn from(e: io::Error) -> Self {
+        DocaError::Io(e.into())
+    }
+}
+
+/// The error of a failed IO, i.e., the kind and the OS error code of an [`io::Error`].
+///
+/// Unlike `io::Error`, it can be copied and compared as the other errors of the crate.
+///
+/// ```
+/// use std::io;
+/// use doca::DocaError;
+///
+/// let e = DocaError::from(io::Error::from_raw_os_error(libc::ENOENT));
+/// match e {
+///     DocaError::Io(io) => assert_eq!(io.kind(), io::ErrorKind::NotFound),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IoError {
+    kind: io::ErrorKind,
+    os_error: Option<i32>,
+}
+
+impl IoError {
+    /// The kind of the error, e.g., [`io::ErrorKind::NotFound`]
+    pub fn kind(&self) -> io::ErrorKind {
+        self.kind
+    }
+
+    /// The error code of the OS, e.g., `ENOENT`, if the error is reported by the OS
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.os_error
+    }
+}
+
+impl From<io::Error> for IoError {
+    fn from(e: io::Error) -> Self {
+        Self {
+            kind: e.kind(),
+            os_error: e.raw_os_error(),
+        }
+    }
+}
+
+impl From<IoError> for io::Error {
+    fn from(e: IoError) -> Self {
+        match e.os_error {
+            Some(code) => io::Error::from_raw_os_error(code),
+            None => io::Error::from(e.kind),
+        }
+    }
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&io::Error::from(*self), f)
+    }
+}
+
+impl std::error::Error for IoError {}
+
+/// Convert the raw code returned by DOCA.
+///
+/// `DOCA_SUCCESS` is not an error, it is converted into [`DocaError::Unknown`]
+/// and fails the debug assertion, so check the code before converting it.
+impl From<doca_error> for DocaError {
+    fn from(code: doca_error) -> Self {
+        match code {
+            doca_error::DOCA_SUCCESS => {
+                debug_assert!(false, "DOCA_SUCCESS is not an error");
+                DocaError::Unknown
+            }
+            doca_error::DOCA_ERROR_UNKNOWN => DocaError::Unknown,
+            doca_error::DOCA_ERROR_NOT_PERMITTED => DocaError::NotPermitted,
+            doca_error::DOCA_ERROR_IN_USE => DocaError::InUse,
+            doca_error::DOCA_ERROR_NOT_SUPPORTED => DocaError::NotSupported,
+            doca_error::DOCA_ERROR_AGAIN => DocaError::Again,
+            doca_error::DOCA_ERROR_INVALID_VALUE => DocaError::InvalidValue,
+            doca_error::DOCA_ERROR_NO_MEMORY => DocaError::NoMemory,
+            doca_error::DOCA_ERROR_INITIALIZATION => DocaError::Initialization,
+            doca_error::DOCA_ERROR_TIME_OUT => DocaError::TimeOut,
+            doca_error::DOCA_ERROR_SHUTDOWN => DocaError::Shutdown,
+            doca_error::DOCA_ERROR_CONNECTION_RESET => DocaError::ConnectionReset,
+            doca_error::DOCA_ERROR_CONNECTION_ABORTED => DocaError::ConnectionAborted,
+            doca_error::DOCA_ERROR_CONNECTION_INPROGRESS => DocaError::ConnectionInProgress,
+            doca_error::DOCA_ERROR_NOT_CONNECTED => DocaError::NotConnected,
+            doca_error::DOCA_ERROR_NO_LOCK => DocaError::NoLock,
+            doca_error::DOCA_ERROR_NOT_FOUND => DocaError::NotFound,
+            doca_error::DOCA_ERROR_IO_FAILED => DocaError::IoFailed,
+            doca_error::DOCA_ERROR_BAD_STATE => DocaError::BadState,
+            doca_error::DOCA_ERROR_UNSUPPORTED_VERSION => DocaError::UnsupportedVersion,
+            doca_error::DOCA_ERROR_OPERATING_SYSTEM => DocaError::OperatingSystem,
+            doca_error::DOCA_ERROR_DRIVER => DocaError::Driver,
+            doca_error::DOCA_ERROR_UNEXPECTED => DocaError::Unexpected,
+            // the codes added after DOCA 1.5, e.g., by DOCA 2.x
+            #[allow(unreachable_patterns)]
+            _ => DocaError::Unknown,
+        }
+    }
+}
+
+impl From<DocaError> for doca_error {
+    fn from(e: DocaError) -> Self {
+        e.code()
+    }
+}
+
+// The strings returned by the SDK are static, so they can be borrowed forever
+fn sdk_str(s: *const std::os::raw::c_char) -> Option<&'static str> {
+    if s.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+// Convert a raw error code reported by DOCA, e.g., the result of a work queue event,
+// which may not be a valid `doca_error`
+#[cfg(not(feature = "doca2"))]
+pub(crate) fn error_from_code(code: u64) -> doca_error {
+    use doca_error::*;
+
+    // compared by the values of the codes, so the order doesn't matter
+    const CODES: [doca_error; 23] = [
+        DOCA_SUCCESS,
+        DOCA_ERROR_UNKNOWN,
+        DOCA_ERROR_NOT_PERMITTED,
+        DOCA_ERROR_IN_USE,
+        DOCA_ERROR_NOT_SUPPORTED,
+        DOCA_ERROR_AGAIN,
+        DOCA_ERROR_INVALID_VALUE,
+        DOCA_ERROR_NO_MEMORY,
+        DOCA_ERROR_INITIALIZATION,
+        DOCA_ERROR_TIME_OUT,
+        DOCA_ERROR_SHUTDOWN,
+        DOCA_ERROR_CONNECTION_RESET,
+        DOCA_ERROR_CONNECTION_ABORTED,
+        DOCA_ERROR_CONNECTION_INPROGRESS,
+        DOCA_ERROR_NOT_CONNECTED,
+        DOCA_ERROR_NO_LOCK,
+        DOCA_ERROR_NOT_FOUND,
+        DOCA_ERROR_IO_FAILED,
+        DOCA_ERROR_BAD_STATE,
+        DOCA_ERROR_UNSUPPORTED_VERSION,
+        DOCA_ERROR_OPERATING_SYSTEM,
+        DOCA_ERROR_DRIVER,
+        DOCA_ERROR_UNEXPECTED,
+    ];
+    CODES
+        .into_iter()
+        .find(|e| *e as u64 == code)
+        .unwrap_or(DOCA_ERROR_UNKNOWN)
+}
+
+// Convert the return code of a DOCA call into a result
+#[inline]
+pub(crate) fn check(ret: doca_error) -> Result<(), DocaError> {
+    if ret != doca_error::DOCA_SUCCESS {
+        return Err(ret.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code_round_trip() {
+        let codes = [
+            doca_error::DOCA_ERROR_UNKNOWN,
+            doca_error::DOCA_ERROR_AGAIN,
+            doca_error::DOCA_ERROR_IO_FAILED,
+            doca_error::DOCA_ERROR_UNEXPECTED,
+        ];
+        for code in codes {
+            assert_eq!(DocaError::from(code).code(), code);
+            assert_eq!(doca_error::from(DocaError::from(code)), code);
+        }
+
+        assert!(check(doca_error::DOCA_SUCCESS).is_ok());
+        assert_eq!(check(doca_error::DOCA_ERROR_AGAIN), Err(DocaError::Again));
+        assert_eq!(
+            DocaError::NotFound.to_string(),
+            "DOCA_ERROR_NOT_FOUND: resource not found"
+        );
+
+        // the typed errors share the codes of DOCA
+        assert_eq!(
+            DocaError::InventoryExhausted.code(),
+            doca_error::DOCA_ERROR_NO_MEMORY
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "doca2"))]
+    fn test_error_from_code() {
+        // the values of `doca_error.h`
+        let known = [
+            (0, doca_error::DOCA_SUCCESS),
+            (1, doca_error::DOCA_ERROR_UNKNOWN),
+            (5, doca_error::DOCA_ERROR_AGAIN),
+            (6, doca_error::DOCA_ERROR_INVALID_VALUE),
+            (9, doca_error::DOCA_ERROR_TIME_OUT),
+            (17, doca_error::DOCA_ERROR_IO_FAILED),
+            (18, doca_error::DOCA_ERROR_BAD_STATE),
+            (21, doca_error::DOCA_ERROR_DRIVER),
+            (22, doca_error::DOCA_ERROR_UNEXPECTED),
+        ];
+        for (code, err) in known {
+            assert_eq!(error_from_code(code), err);
+        }
+
+        assert_eq!(error_from_code(23), doca_error::DOCA_ERROR_UNKNOWN);
+        assert_eq!(error_from_code(u64::MAX), doca_error::DOCA_ERROR_UNKNOWN);
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert!(!DocaError::NotFound.message().is_empty());
+        assert_eq!(DocaError::NotFound.name(), "DOCA_ERROR_NOT_FOUND");
+        assert_eq!(
+            DocaError::InventoryExhausted.message(),
+            "no free buffer left in the inventory"
+        );
+    }
+
+    #[test]
+    fn test_io_error() {
+        let not_found = DocaError::from(io::Error::from_raw_os_error(libc::ENOENT));
+        let denied = DocaError::from(io::Error::from_raw_os_error(libc::EACCES));
+        assert_ne!(not_found, denied);
+        assert_eq!(not_found.code(), doca_error::DOCA_ERROR_IO_FAILED);
+
+        let e = match not_found {
+            DocaError::Io(e) => e,
+            _ => unreachable!(),
+        };
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        assert_eq!(e.raw_os_error(), Some(libc::ENOENT));
+        assert_eq!(io::Error::from(e).raw_os_error(), Some(libc::ENOENT));
+        assert!(not_found.to_string().starts_with("DOCA_ERROR_IO_FAILED: "));
+        assert!(std::error::Error::source(&not_found).is_some());
+
+        // the errors without an OS code keep their kind
+        let eof = IoError::from(io::Error::from(io::ErrorKind::UnexpectedEof));
+        assert_eq!(eof.raw_os_error(), None);
+        assert_eq!(io::Error::from(eof).kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -8,6 +8,10 @@
 //! - [`DOCAContext`] should be dropped before its original Engine dropped
 //! - [`DOCAMmap`] should be dropped before the [`DevContext`] registered into it
 //!
+//! Each struct holds an `Arc` of the ones it depends on, so the user handles can be
+//! dropped in any order. Only the memory registered by a [`RawPointer`] is not held,
+//! see [`LocalMmap::populate_owned`] to let the mmap own it.
+//!
 //! - The [`context`] module contains wrapper of the execution
 //! model in DOCA, including a submodule [`work_queue`].
 //!
@@ -21,6 +25,75 @@
 //! which provides the ability to copy data between memory
 //! using hardware acceleration.
 //!
+//! - The [`compress`] module provides wrapper for DOCA Compress engine,
+//! which offloads the deflate compression and decompression.
+//!
+//! - The [`regex`] module provides wrapper for DOCA RegEx engine,
+//! which offloads the matching of the rules compiled for the hardware.
+//!
+//! - The [`sha`] module provides wrapper for DOCA SHA engine,
+//! which offloads the SHA digests, and computes the HMACs with them.
+//!
+//! - The [`telemetry`] module provides wrapper for DOCA Telemetry,
+//! which reports the records of the types defined at runtime to DTS.
+//!
+//! - The [`comm_channel`] module provides wrapper for DOCA Comm Channel,
+//! which exchanges the export descriptors between the host and the DPU.
+//!
+//! - The [`config_exchange`] module exchanges the export descriptors over a TCP
+//! socket instead, for the host and the DPU sharing a network.
+//!
+//! - The [`callback`] module catches panics in the Rust callbacks
+//! called by DOCA, so that they never unwind across the C boundary.
+//!
+//! - The [`error`] module provides [`DocaError`], the error type of the crate.
+//!
+//! - The [`drop_order`] module tracks the objects still alive in `debug` mode,
+//! e.g., to find the objects leaked with their dependencies.
+//!
+//! - The [`prelude`] module re-exports the types and traits of the common DMA workflow,
+//! i.e., `use doca::prelude::*;`.
+//!
+//! - The [`samples`] module provides the workflows of the DMA examples as library
+//! functions, so users can embed them instead of copying the example code.
+//!
+//! - The [`scope`] module provides [`DocaScope`], which owns these structs
+//! and drops them in the above order, for users who don't want to memorize it.
+//!
+//! # Thread safety
+//!
+//! The devices, the engines of DOCA 1.5, the [`DOCAContext`] and the [`DOCAMmap`] are
+//! `Send` and `Sync`, so they can be shared among threads with an `Arc`.
+//! A [`DOCAWorkQueue`] is a per-thread object, so it is neither `Send` nor `Sync`.
+//! A [`BufferInventory`] is `Send` but not `Sync`, so its [`DOCABuffer`]s,
+//! which release themselves into it, stay in the thread creating them.
+//! The contexts of DOCA 2.x are driven by a non-thread-safe progress engine,
+//! so they are neither `Send` nor `Sync` either.
+//!
+//! With the optional `zeroize` feature, the memory owned by the crate, e.g., by
+//! [`DOCARegisteredMemory::new_owned`], loaded export descriptors and [`RawPointerMsg`]s
+//! are wiped when they are freed, so neither memory contents nor addresses are left behind.
+//! The memory borrowed by a [`DOCABuffer`] is left to its owner.
+//!
+//! With the optional `async-config` feature, [`load_config`] and [`save_config`] have
+//! async variants based on `tokio::fs`.
+//!
+//! With the optional `tracing` feature, the crate emits `tracing` events for the context
+//! start/stop, the mmap export and the job submission and completion,
+//! the objects tracked by [`drop_order`] and the objects dropped,
+//! instead of printing the drops in debug mode.
+//!
+//! With the optional `doca2` feature, the `progress_engine` module wraps the progress
+//! engine and the tasks of DOCA 2.x, which replace the work queue, and the `ec`, `crypto`,
+//! `rdma` and `comch` modules wrap the erasure coding, the AES-GCM, the RDMA and the Comch
+//! libraries on top of them.
+//! The modules built on the work queue of DOCA 1.5, i.e., `context`, `dma`, `compress`,
+//! `regex`, `sha`, `telemetry`, `comm_channel` and `scope`, and the DMA flows of `samples` are left out,
+//! as are the bindings of these libraries.
+//!
+//! With the optional `mock` feature, the crate runs on an in-process software implementation
+//! of DOCA instead of the SDK, e.g., to test the applications on a machine without a BlueField.
+//! It finds fake devices set by the `mock` module, and does the DMA memcpy jobs by `memcpy`.
 //!
 //!
 #![deny(
@@ -31,44 +104,247 @@
     unused_qualifications
 )]
 
+#[macro_use]
+mod trace;
+
 use ffi::doca_error;
 use std::ffi::c_void;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
 use std::ptr::NonNull;
-use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-pub use device::{devices, open_device_with_pci, DevContext, Device, DeviceList};
-pub use dma::{DMAEngine, DOCAEvent, DOCAWorkQueue};
-pub use memory::buffer::{BufferInventory, DOCABuffer, RawPointer, RawPointerMsg};
+pub use device::{
+    devices, devices_on_numa, open_device_with_ibdev_name, open_device_with_iface_name,
+    open_device_with_pci, open_rep_with_pci, runtime_role, DevContext, DevRepContext, Device,
+    DeviceChanges, DeviceList, DeviceMonitor, DeviceRep, DeviceRepList, LinkState, RepFilter, Role,
+};
+#[cfg(not(feature = "doca2"))]
+pub use dma::{DMAEngine, DOCAEvent, DOCAWorkQueue, DmaChannel, DmaDirection};
+pub use error::{DocaError, IoError};
+pub use memory::buffer::{
+    BufExtensions, BufferInventory, DOCABuffer, InventoryOptions, RawPointer, RawPointerMsg,
+};
+pub use memory::region::{MemRegion, OwnedRegion};
 pub use memory::registered_memory::DOCARegisteredMemory;
-pub use memory::DOCAMmap;
+pub use memory::{
+    DOCAMmap, ExportDescriptor, LocalMmap, MmapExport, MmapPermissions, MmapState, RegionInfo,
+    RemoteMmap,
+};
+#[cfg(not(feature = "doca2"))]
+pub use scope::DocaScope;
 
+pub mod callback;
+#[cfg(feature = "doca2")]
+pub mod comch;
+#[cfg(not(feature = "doca2"))]
+pub mod comm_channel;
+#[cfg(not(feature = "doca2"))]
+pub mod compress;
+pub mod config_exchange;
+#[cfg(not(feature = "doca2"))]
 pub mod context;
+#[cfg(feature = "doca2")]
+pub mod crypto;
 pub mod device;
+#[cfg(not(feature = "doca2"))]
 pub mod dma;
+pub mod drop_order;
+#[cfg(feature = "doca2")]
+pub mod ec;
+pub mod error;
 pub mod memory;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod prelude;
+#[cfg(feature = "doca2")]
+pub mod progress_engine;
+#[cfg(feature = "doca2")]
+pub mod rdma;
+#[cfg(not(feature = "doca2"))]
+pub mod regex;
+pub mod samples;
+#[cfg(not(feature = "doca2"))]
+pub mod scope;
+#[cfg(not(feature = "doca2"))]
+pub mod sha;
+#[cfg(not(feature = "doca2"))]
+pub mod telemetry;
 
-/// Error type
+/// The raw error code returned by the DOCA FFI,
+/// which is converted into [`DocaError`] by the Rust APIs.
 pub type DOCAError = doca_error;
 
 /// Result type
-pub type DOCAResult<T> = Result<T, DOCAError>;
+pub type DOCAResult<T> = Result<T, DocaError>;
+
+/// The default max length of the export descriptor accepted by [`load_config`].
+///
+/// The length of the descriptor depends on the SDK and the exported memory,
+/// so the limit is only a sanity check against reading a wrong file,
+/// see [`load_config_with_limit`] for a different one.
+pub const DEFAULT_MAX_EXPORT_LENGTH: usize = 1 << 20;
+
+/// Error type of [`load_config`], describing which part of the config files is bad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Failed to open or read the config files, with the error of the OS
+    Io(IoError),
+    /// The export descriptor file is empty
+    EmptyDescriptor,
+    /// The export descriptor file exceeds the max export length
+    DescriptorTooLarge {
+        /// The size of the descriptor file
+        size: usize,
+        /// The configured max length
+        max: usize,
+    },
+    /// The remote address is missing or not a number
+    InvalidAddress,
+    /// The remote address is zero
+    NullAddress,
+    /// The remote buffer length is missing or not a number
+    InvalidLength,
+    /// The remote buffer length is zero
+    ZeroLength,
+    /// The export descriptor file or the buffer information file has a version
+    /// unsupported by this library
+    UnsupportedVersion,
+    /// The buffer information file contains no remote region
+    NoRegion,
+    /// The export descriptor file doesn't start with [`EXPORT_FILE_MAGIC`],
+    /// e.g., it is saved by an older version of this library
+    BadMagic,
+    /// The export descriptor file ends in the middle of its header
+    TruncatedHeader,
+    /// The descriptor in the export descriptor file has a length different from its header
+    LengthMismatch {
+        /// The length recorded in the header
+        expected: usize,
+        /// The length of the descriptor in the file
+        actual: usize,
+    },
+    /// The descriptor in the export descriptor file doesn't match the CRC32 in its header
+    ChecksumMismatch {
+        /// The checksum recorded in the header
+        expected: u32,
+        /// The checksum of the descriptor in the file
+        actual: u32,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "failed to read the config files: {}", e),
+            ConfigError::EmptyDescriptor => write!(f, "the export descriptor is empty"),
+            ConfigError::DescriptorTooLarge { size, max } => write!(
+                f,
+                "the export descriptor has {} bytes, exceeding the max length {}",
+                size, max
+            ),
+            ConfigError::InvalidAddress => write!(f, "the remote address is not a number"),
+            ConfigError::NullAddress => write!(f, "the remote address is zero"),
+            ConfigError::InvalidLength => write!(f, "the remote length is not a number"),
+            ConfigError::ZeroLength => write!(f, "the remote length is zero"),
+            ConfigError::UnsupportedVersion => {
+                write!(f, "the config file has an unsupported version")
+            }
+            ConfigError::NoRegion => write!(f, "the buffer information file has no region"),
+            ConfigError::BadMagic => write!(f, "the export descriptor file has a bad magic"),
+            ConfigError::TruncatedHeader => {
+                write!(f, "the export descriptor file has a truncated header")
+            }
+            ConfigError::LengthMismatch { expected, actual } => write!(
+                f,
+                "the export descriptor has {} bytes, but its header says {}",
+                actual, expected
+            ),
+            ConfigError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "the export descriptor has checksum {:#010x}, but its header says {:#010x}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e.into())
+    }
+}
+
+impl From<ConfigError> for DocaError {
+    fn from(e: ConfigError) -> Self {
+        match e {
+            ConfigError::Io(e) => DocaError::Io(e),
+            _ => DocaError::InvalidValue,
+        }
+    }
+}
+
+/// The version of the buffer information file written by [`save_config`].
+///
+/// Version 1 starts with a `v1` line, followed by one `<address> <length>` line
+/// per remote region. The legacy file without the version line,
+/// i.e., the address and the length in two lines, is still accepted by [`load_config`].
+pub const CONFIG_VERSION: u32 = 1;
 
-// FIXME: Not very sure about max length of the exported information.
-// In sample of DOCA DMA, it use a buffer of size 1024.
-const DOCA_MAX_EXPORT_LENGTH: usize = 2048;
+/// The magic bytes starting the export descriptor file written by [`save_config`].
+pub const EXPORT_FILE_MAGIC: [u8; 8] = *b"DOCAEXP\0";
+
+/// The version of the export descriptor file written by [`save_config`].
+///
+/// Version 1 has a header of [`EXPORT_FILE_MAGIC`], the version, the length
+/// and the CRC32 of the descriptor, all integers in 4 little-endian bytes,
+/// followed by the descriptor itself. [`load_config`] checks each of them,
+/// so a stale or corrupted file fails before the remote mmap is created from it.
+pub const EXPORT_FILE_VERSION: u32 = 1;
+
+// The length of the header of the export descriptor file
+const EXPORT_HEADER_LENGTH: usize = EXPORT_FILE_MAGIC.len() + 12;
 
 /// Struct used for recording the return value for function `load_config`.
-/// It contains two RawPointers. `export_desc` indicates the exported information
-/// of the remote memory map. `remote_addr` indicates the buffer address in the remote
-/// memory map.
+/// `export_desc` indicates the exported information of the remote memory map.
+/// `remote_addrs` indicates the buffers in the remote memory map, which contains
+/// at least one region.
+///
+/// The loaded export descriptor is freed when the struct is dropped,
+/// so it should outlive the remote mmap creation.
 pub struct LoadedInfo {
     /// The metadata for the remote mmap
-    pub export_desc: RawPointer,
-    /// The remote address for the mmap
-    // TODO: support multiple remote address transfer
-    pub remote_addr: RawPointer,
+    pub export_desc: ExportDescriptor,
+    /// The remote regions in the mmap, in the order they are saved
+    pub remote_addrs: Vec<RawPointer>,
+}
+
+impl LoadedInfo {
+    // Build the loaded info from the received descriptor and remote regions
+    pub(crate) fn new(export_desc: Box<[u8]>, remote_addrs: Vec<RawPointer>) -> Self {
+        debug_assert!(!remote_addrs.is_empty());
+        Self {
+            export_desc: ExportDescriptor::from(export_desc),
+            remote_addrs,
+        }
+    }
+
+    /// The first remote region, which is the only one if the other side
+    /// saved the config with [`save_config`].
+    pub fn remote_addr(&self) -> RawPointer {
+        self.remote_addrs[0]
+    }
 }
 
 /// Helper function that load the exported descriptor file
@@ -79,79 +355,233 @@ pub struct LoadedInfo {
 /// # Examples
 ///
 /// ``` rust, no_run
-/// use doca::DOCAMmap;
+/// use doca::RemoteMmap;
 ///
 /// // Create the device according to the pci address
 /// let device = doca::device::open_device_with_pci("17:00.0").unwrap();
 ///
 /// // Load the config from the files and create the remote memory map object
 /// let remote_configs = doca::load_config("/tmp/export.txt", "/tmp/buffer.txt").unwrap();
-/// let mut remote_mmap = DOCAMmap::new_from_export(remote_configs.export_desc, &device).unwrap();
+/// let remote_mmap = RemoteMmap::from_export(&remote_configs.export_desc, &device).unwrap();
 /// ```
+///
+/// # Errors
+///
+/// The returned [`ConfigError`] tells which part of the files is malformed,
+/// e.g., an oversized descriptor, a checksum mismatch or a zero remote address.
+///
 pub fn load_config(
     export_desc_file_path: &str,
     buffer_info_file_path: &str,
-) -> DOCAResult<LoadedInfo> {
+) -> Result<LoadedInfo, ConfigError> {
+    load_config_with_limit(
+        export_desc_file_path,
+        buffer_info_file_path,
+        DEFAULT_MAX_EXPORT_LENGTH,
+    )
+}
+
+/// Same as [`load_config`], but reject the export descriptor longer than `max_desc_len`
+/// with [`ConfigError::DescriptorTooLarge`].
+pub fn load_config_with_limit(
+    export_desc_file_path: &str,
+    buffer_info_file_path: &str,
+    max_desc_len: usize,
+) -> Result<LoadedInfo, ConfigError> {
     // Open the file for exported information
-    let export_desc_file =
-        File::open(export_desc_file_path).map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
+    let export_desc_file = File::open(export_desc_file_path)?;
 
-    // Get the file size for reading the whole file
-    let export_desc_file_size = export_desc_file
-        .metadata()
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?
-        .len() as usize;
+    // Get the file size for sizing the buffer
+    let export_desc_file_size = export_desc_file.metadata()?.len() as usize;
+    check_file_size(export_desc_file_size, max_desc_len)?;
 
-    // Prepare the buffer for reading content
-    let mut export_desc_buffer = vec![0u8; DOCA_MAX_EXPORT_LENGTH].into_boxed_slice();
+    // Read the whole file, in case it is changed after the metadata is fetched
+    let mut export_file = Vec::with_capacity(export_desc_file_size);
+    BufReader::new(export_desc_file)
+        .take((EXPORT_HEADER_LENGTH + max_desc_len) as u64 + 1)
+        .read_to_end(&mut export_file)?;
+    let export_desc = decode_export_file(export_file, max_desc_len)?;
 
-    // Read the whole file
-    let mut export_desc_reader = BufReader::new(export_desc_file);
+    // Fetch the remote address information
+    let mut buffer_info = String::new();
+    File::open(buffer_info_file_path)
+        .and_then(|f| BufReader::new(f).read_to_string(&mut buffer_info))?;
 
-    export_desc_reader
-        .read_exact(&mut export_desc_buffer[..export_desc_file_size])
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
+    Ok(LoadedInfo::new(
+        export_desc,
+        parse_buffer_info(&buffer_info)?,
+    ))
+}
 
-    // Fetch the remote address information
-    let buffer_info_file =
-        File::open(buffer_info_file_path).map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
-    let mut buffer_info_reader = BufReader::new(buffer_info_file);
+/// The async version of [`load_config`] based on `tokio::fs`,
+/// so that the runtime threads are not blocked by the file IO.
+#[cfg(feature = "async-config")]
+pub async fn load_config_async(
+    export_desc_file_path: &str,
+    buffer_info_file_path: &str,
+) -> Result<LoadedInfo, ConfigError> {
+    load_config_async_with_limit(
+        export_desc_file_path,
+        buffer_info_file_path,
+        DEFAULT_MAX_EXPORT_LENGTH,
+    )
+    .await
+}
+
+/// The async version of [`load_config_with_limit`].
+#[cfg(feature = "async-config")]
+pub async fn load_config_async_with_limit(
+    export_desc_file_path: &str,
+    buffer_info_file_path: &str,
+    max_desc_len: usize,
+) -> Result<LoadedInfo, ConfigError> {
+    // Check the size first, so a wrong file is not read into memory
+    let export_desc_file_size = tokio::fs::metadata(export_desc_file_path).await?.len() as usize;
+    check_file_size(export_desc_file_size, max_desc_len)?;
+
+    let export_file = tokio::fs::read(export_desc_file_path).await?;
+    let export_desc = decode_export_file(export_file, max_desc_len)?;
+
+    let buffer_info = tokio::fs::read_to_string(buffer_info_file_path).await?;
+
+    Ok(LoadedInfo::new(
+        export_desc,
+        parse_buffer_info(&buffer_info)?,
+    ))
+}
+
+fn check_desc_size(size: usize, max: usize) -> Result<(), ConfigError> {
+    if size == 0 {
+        return Err(ConfigError::EmptyDescriptor);
+    }
+    if size > max {
+        return Err(ConfigError::DescriptorTooLarge { size, max });
+    }
+    Ok(())
+}
+
+// Check the size of the export descriptor file before reading it
+fn check_file_size(size: usize, max: usize) -> Result<(), ConfigError> {
+    if size == 0 {
+        return Err(ConfigError::EmptyDescriptor);
+    }
+    if size > EXPORT_HEADER_LENGTH + max {
+        return Err(ConfigError::DescriptorTooLarge {
+            size: size - EXPORT_HEADER_LENGTH,
+            max,
+        });
+    }
+    Ok(())
+}
 
-    // Read the first line, which contains the remote address
-    let mut remote_addr_buf = String::new();
-    buffer_info_reader
-        .read_line(&mut remote_addr_buf)
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
+// The content of the export descriptor file: the header and then the descriptor
+fn encode_export_file(export_desc: &[u8]) -> DOCAResult<Vec<u8>> {
+    let len = u32::try_from(export_desc.len()).map_err(|_e| DocaError::InvalidValue)?;
+
+    let mut content = Vec::with_capacity(EXPORT_HEADER_LENGTH + export_desc.len());
+    content.extend_from_slice(&EXPORT_FILE_MAGIC);
+    content.extend_from_slice(&EXPORT_FILE_VERSION.to_le_bytes());
+    content.extend_from_slice(&len.to_le_bytes());
+    content.extend_from_slice(&crc32fast::hash(export_desc).to_le_bytes());
+    content.extend_from_slice(export_desc);
+    Ok(content)
+}
 
+// Validate the content of the export descriptor file, and strip the header
+fn decode_export_file(mut content: Vec<u8>, max: usize) -> Result<Box<[u8]>, ConfigError> {
+    if content.is_empty() {
+        return Err(ConfigError::EmptyDescriptor);
+    }
+
+    let magic_len = EXPORT_FILE_MAGIC.len().min(content.len());
+    if content[..magic_len] != EXPORT_FILE_MAGIC[..magic_len] {
+        return Err(ConfigError::BadMagic);
+    }
+    if content.len() < EXPORT_HEADER_LENGTH {
+        return Err(ConfigError::TruncatedHeader);
+    }
+
+    let field = |i: usize| {
+        let off = EXPORT_FILE_MAGIC.len() + 4 * i;
+        u32::from_le_bytes(content[off..off + 4].try_into().unwrap())
+    };
+    let (version, expected_len, expected_crc) = (field(0), field(1) as usize, field(2));
+    if version != EXPORT_FILE_VERSION {
+        return Err(ConfigError::UnsupportedVersion);
+    }
+    check_desc_size(expected_len, max)?;
+
+    let export_desc = &content[EXPORT_HEADER_LENGTH..];
+    if export_desc.len() != expected_len {
+        return Err(ConfigError::LengthMismatch {
+            expected: expected_len,
+            actual: export_desc.len(),
+        });
+    }
+    let crc = crc32fast::hash(export_desc);
+    if crc != expected_crc {
+        return Err(ConfigError::ChecksumMismatch {
+            expected: expected_crc,
+            actual: crc,
+        });
+    }
+
+    content.drain(..EXPORT_HEADER_LENGTH);
+    Ok(content.into_boxed_slice())
+}
+
+// Parse the remote regions from the content of the buffer information file
+fn parse_buffer_info(buffer_info: &str) -> Result<Vec<RawPointer>, ConfigError> {
+    let mut lines = buffer_info.lines().map(str::trim);
+
+    match lines.next() {
+        Some(version) if version.starts_with('v') => {
+            let version: u32 = version[1..]
+                .parse()
+                .map_err(|_e| ConfigError::UnsupportedVersion)?;
+            if version != CONFIG_VERSION {
+                return Err(ConfigError::UnsupportedVersion);
+            }
+
+            // one region per line, skipping the trailing empty lines
+            let regions = lines
+                .filter(|line| !line.is_empty())
+                .map(|line| {
+                    let mut fields = line.split_whitespace();
+                    parse_region(fields.next(), fields.next())
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if regions.is_empty() {
+                return Err(ConfigError::NoRegion);
+            }
+            Ok(regions)
+        }
+        // the legacy format: the address and then the length of a single region
+        first => Ok(vec![parse_region(first, lines.next())?]),
+    }
+}
+
+fn parse_region(addr: Option<&str>, len: Option<&str>) -> Result<RawPointer, ConfigError> {
     // Parse and get the address
-    let remote_addr_usize: u64 = remote_addr_buf
-        .trim()
+    let remote_addr: u64 = addr
+        .unwrap_or_default()
         .parse()
-        .map_err(|_e| DOCAError::DOCA_ERROR_INVALID_VALUE)?;
-    let remote_addr = remote_addr_usize as *mut c_void;
+        .map_err(|_e| ConfigError::InvalidAddress)?;
+    let remote_addr = NonNull::new(remote_addr as *mut c_void).ok_or(ConfigError::NullAddress)?;
 
     // Read the remote memory region's size
-    let mut remote_addr_len_buf = String::new();
-
-    buffer_info_reader
-        .read_line(&mut remote_addr_len_buf)
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
-    let remote_addr_len: usize = remote_addr_len_buf
-        .trim()
+    let remote_addr_len: usize = len
+        .unwrap_or_default()
         .parse()
-        .map_err(|_e| DOCAError::DOCA_ERROR_INVALID_VALUE)?;
-
-    Ok(LoadedInfo {
-        export_desc: RawPointer {
-            // use the clone to keep the boxed memory keep alive even the function ends.
-            // The memory could be dropped after the program ends automatically.
-            inner: NonNull::new(Box::into_raw(export_desc_buffer) as *mut _).unwrap(),
-            payload: export_desc_file_size,
-        },
-        remote_addr: RawPointer {
-            inner: NonNull::new(remote_addr).unwrap(),
-            payload: remote_addr_len,
-        },
+        .map_err(|_e| ConfigError::InvalidLength)?;
+
+    if remote_addr_len == 0 {
+        return Err(ConfigError::ZeroLength);
+    }
+
+    Ok(RawPointer {
+        inner: remote_addr,
+        payload: remote_addr_len,
     })
 }
 
@@ -161,7 +591,7 @@ pub fn load_config(
 /// # Examples
 ///
 /// ``` rust, no_run
-/// use doca::DOCAMmap;
+/// use doca::LocalMmap;
 /// use doca::RawPointer;
 /// use std::ptr::NonNull;
 ///
@@ -174,7 +604,7 @@ pub fn load_config(
 /// };
 ///
 /// // Create the memory map object and add device into it.
-/// let mut local_mmap =DOCAMmap::new().unwrap();
+/// let local_mmap = LocalMmap::new().unwrap();
 /// let device = doca::device::open_device_with_pci("17:00.0").unwrap();
 /// let dev_idx = local_mmap.add_device(&device).unwrap();
 ///
@@ -183,98 +613,446 @@ pub fn load_config(
 ///
 /// // Generate the exported information and save it into files
 /// let export = local_mmap.export(dev_idx).unwrap();
-/// doca::save_config(export, src_raw, "/tmp/export.txt", "/tmp/buffer.txt").unwrap();
+/// doca::save_config(&export, src_raw, "/tmp/export.txt", "/tmp/buffer.txt").unwrap();
 /// ```
+///
+/// Each file is written to a temporary file and then renamed into place,
+/// so the reader never observes a half-written file.
 pub fn save_config(
-    export_desc: RawPointer,
+    export_desc: &ExportDescriptor,
     src_buffer: RawPointer,
     export_desc_file_path: &str,
     buffer_info_file_path: &str,
 ) -> DOCAResult<()> {
-    // Write export descriptor into file
-    let mut export_desc_file =
-        File::create(export_desc_file_path).map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
+    save_config_regions(
+        export_desc,
+        &[src_buffer],
+        export_desc_file_path,
+        buffer_info_file_path,
+    )
+}
 
-    let export_slice = unsafe {
-        slice::from_raw_parts_mut(export_desc.inner.as_ptr() as *mut u8, export_desc.payload)
-    };
+/// Like [`save_config`], but save several regions populated in the same mmap,
+/// so the other side gets all of them in [`LoadedInfo::remote_addrs`] from one exchange.
+///
+/// Return `DOCA_ERROR_INVALID_VALUE` if `src_buffers` is empty.
+pub fn save_config_regions(
+    export_desc: &ExportDescriptor,
+    src_buffers: &[RawPointer],
+    export_desc_file_path: &str,
+    buffer_info_file_path: &str,
+) -> DOCAResult<()> {
+    if src_buffers.is_empty() {
+        return Err(DocaError::InvalidValue);
+    }
+
+    write_atomic(
+        export_desc_file_path,
+        &encode_export_file(export_desc.as_bytes())?,
+        false,
+    )?;
+    write_atomic(
+        buffer_info_file_path,
+        buffer_info(src_buffers).as_bytes(),
+        false,
+    )
+}
+
+/// Like [`save_config`], but also flush the files and their directories to the disk,
+/// so the saved config survives a crash of the machine.
+pub fn save_config_durable(
+    export_desc: &ExportDescriptor,
+    src_buffer: RawPointer,
+    export_desc_file_path: &str,
+    buffer_info_file_path: &str,
+) -> DOCAResult<()> {
+    write_atomic(
+        export_desc_file_path,
+        &encode_export_file(export_desc.as_bytes())?,
+        true,
+    )?;
+    write_atomic(
+        buffer_info_file_path,
+        buffer_info(&[src_buffer]).as_bytes(),
+        true,
+    )
+}
+
+// Write the file to a temporary path in the same directory, and rename it into place
+fn write_atomic(path: &str, content: &[u8], sync: bool) -> DOCAResult<()> {
+    let tmp_path = tmp_path(path);
+
+    let res = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.flush()?;
+        if sync {
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, path)?;
+
+        if sync {
+            // persist the rename as well
+            let dir = Path::new(path)
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new("."));
+            File::open(dir)?.sync_all()?;
+        }
+        Ok::<_, std::io::Error>(())
+    })();
+
+    res.map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        e.into()
+    })
+}
+
+// The temporary file of a single write, so the threads saving the same path never share it
+fn tmp_path(path: &str) -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let seq = NEXT.fetch_add(1, Ordering::Relaxed);
+    format!("{}.{}.{}.tmp", path, std::process::id(), seq)
+}
 
-    export_desc_file
-        .write_all(export_slice)
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
-    export_desc_file
-        .flush()
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
-
-    // Write local buffer info into file
-    let mut buffer_info_file =
-        File::create(buffer_info_file_path).map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
-
-    writeln!(buffer_info_file, "{}", src_buffer.inner.as_ptr() as u64)
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
-    writeln!(buffer_info_file, "{}", src_buffer.payload)
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
-    buffer_info_file
-        .flush()
-        .map_err(|_e| DOCAError::DOCA_ERROR_IO_FAILED)?;
+/// The async version of [`save_config`] based on `tokio::fs`,
+/// so that the runtime threads are not blocked by the file IO.
+#[cfg(feature = "async-config")]
+pub async fn save_config_async(
+    export_desc: &ExportDescriptor,
+    src_buffer: RawPointer,
+    export_desc_file_path: &str,
+    buffer_info_file_path: &str,
+) -> DOCAResult<()> {
+    let export_desc = encode_export_file(export_desc.as_bytes())?;
+
+    write_atomic_async(export_desc_file_path, export_desc).await?;
+    write_atomic_async(
+        buffer_info_file_path,
+        buffer_info(&[src_buffer]).into_bytes(),
+    )
+    .await
+}
+
+// The async version of `write_atomic`, without flushing to the disk
+#[cfg(feature = "async-config")]
+async fn write_atomic_async(path: &str, content: Vec<u8>) -> DOCAResult<()> {
+    let tmp_path = tmp_path(path);
 
+    let res = match tokio::fs::write(&tmp_path, content).await {
+        Ok(()) => tokio::fs::rename(&tmp_path, path).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = res {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(e.into());
+    }
     Ok(())
 }
 
+// The content of the buffer information file: the version,
+// and then the address and the length of each region
+fn buffer_info(src_buffers: &[RawPointer]) -> String {
+    let mut content = format!("v{}\n", CONFIG_VERSION);
+    for src_buffer in src_buffers {
+        content.push_str(&format!(
+            "{} {}\n",
+            src_buffer.inner.as_ptr() as u64,
+            src_buffer.payload
+        ));
+    }
+    content
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn bindgen_test_save_config() {
-        let mut desc_string = String::from("Hello!");
+        let desc = ExportDescriptor::from(b"Hello!".to_vec());
         let mut src_buffer_string = String::from("1234567890");
 
-        let desc_raw = RawPointer {
-            inner: NonNull::new(desc_string.as_mut_ptr() as *mut _).unwrap(),
-            payload: desc_string.as_bytes().len(),
-        };
-
         let src_raw = RawPointer {
             inner: NonNull::new(src_buffer_string.as_mut_ptr() as *mut _).unwrap(),
             payload: src_buffer_string.as_bytes().len(),
         };
 
         let src_buffer = src_buffer_string.as_bytes();
-        save_config(
-            desc_raw,
-            src_raw,
-            "/tmp/desc_test.txt",
-            "/tmp/buffer_test.txt",
-        )
-        .unwrap();
+        save_config(&desc, src_raw, "/tmp/desc_test.txt", "/tmp/buffer_test.txt").unwrap();
 
         let configs = load_config("/tmp/desc_test.txt", "/tmp/buffer_test.txt").unwrap();
 
         // alright check all these
-        assert_eq!(configs.remote_addr.payload, src_buffer.len());
-        unsafe {
-            assert_eq!(
-                configs.export_desc.payload,
-                desc_string.as_bytes_mut().len()
-            )
+        assert_eq!(configs.remote_addr().payload, src_buffer.len());
+        assert_eq!(configs.export_desc.len(), desc.len());
+        assert_eq!(
+            String::from_utf8(configs.export_desc.as_bytes().to_vec()).unwrap(),
+            String::from("Hello!")
+        );
+        assert_eq!(
+            configs.remote_addr().inner.as_ptr() as u64,
+            src_buffer.as_ptr() as u64
+        );
+    }
+
+    #[test]
+    fn test_save_config_atomic() {
+        let desc = ExportDescriptor::from(b"Hello!".to_vec());
+        let mut src = vec![0u8; 64];
+        let src_raw = RawPointer {
+            inner: NonNull::new(src.as_mut_ptr() as *mut _).unwrap(),
+            payload: src.len(),
         };
-        unsafe {
-            assert_eq!(
-                String::from_utf8(
-                    slice::from_raw_parts(
-                        configs.export_desc.inner.as_ptr() as *mut u8,
-                        configs.export_desc.payload
-                    )
-                    .to_vec()
-                )
-                .unwrap(),
-                String::from("Hello!")
-            )
+
+        let desc_path = "/tmp/desc_durable_test.txt";
+        let buffer_path = "/tmp/buffer_durable_test.txt";
+        save_config_durable(&desc, src_raw, desc_path, buffer_path).unwrap();
+
+        // no temporary file is left behind
+        let leftover = std::fs::read_dir("/tmp").unwrap().any(|entry| {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            name.starts_with("desc_durable_test.txt.")
+                || name.starts_with("buffer_durable_test.txt.")
+        });
+        assert!(!leftover);
+        assert_ne!(tmp_path(desc_path), tmp_path(desc_path));
+
+        let configs = load_config(desc_path, buffer_path).unwrap();
+        assert_eq!(configs.export_desc.len(), desc.len());
+        assert_eq!(configs.remote_addr().payload, 64);
+
+        // fail without touching the target if the directory doesn't exist
+        match save_config(&desc, src_raw, "/tmp/no_such_dir/desc.txt", buffer_path) {
+            Err(DocaError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_load_config_malformed() {
+        use std::fs;
+
+        let desc_path = "/tmp/desc_malformed_test.txt";
+        let buffer_path = "/tmp/buffer_malformed_test.txt";
+
+        // an empty descriptor
+        fs::write(desc_path, "").unwrap();
+        fs::write(buffer_path, "4096\n64\n").unwrap();
+        assert_eq!(
+            load_config(desc_path, buffer_path).err(),
+            Some(ConfigError::EmptyDescriptor)
+        );
+
+        // an oversized descriptor
+        let oversized = encode_export_file(&vec![1u8; DEFAULT_MAX_EXPORT_LENGTH + 1]).unwrap();
+        fs::write(desc_path, oversized).unwrap();
+        assert_eq!(
+            load_config(desc_path, buffer_path).err(),
+            Some(ConfigError::DescriptorTooLarge {
+                size: DEFAULT_MAX_EXPORT_LENGTH + 1,
+                max: DEFAULT_MAX_EXPORT_LENGTH
+            })
+        );
+
+        // a descriptor longer than the old fixed buffer, but within the limit
+        fs::write(desc_path, encode_export_file(&[1u8; 4096]).unwrap()).unwrap();
+        let info = load_config(desc_path, buffer_path).unwrap();
+        assert_eq!(info.export_desc.len(), 4096);
+        assert_eq!(
+            load_config_with_limit(desc_path, buffer_path, 1024).err(),
+            Some(ConfigError::DescriptorTooLarge {
+                size: 4096,
+                max: 1024
+            })
+        );
+
+        // bad remote address and length
+        fs::write(desc_path, encode_export_file(b"Hello!").unwrap()).unwrap();
+        let cases = [
+            ("0\n64\n", ConfigError::NullAddress),
+            ("abc\n64\n", ConfigError::InvalidAddress),
+            ("4096\n", ConfigError::InvalidLength),
+            ("4096\n0\n", ConfigError::ZeroLength),
+        ];
+        for (content, err) in cases {
+            fs::write(buffer_path, content).unwrap();
+            assert_eq!(load_config(desc_path, buffer_path).err(), Some(err));
+        }
+
+        fs::write(buffer_path, "4096\n64\n").unwrap();
+        assert!(load_config(desc_path, buffer_path).is_ok());
+
+        // bad versioned files
+        let cases = [
+            ("v2\n4096 64\n", ConfigError::UnsupportedVersion),
+            ("vx\n4096 64\n", ConfigError::UnsupportedVersion),
+            ("v1\n", ConfigError::NoRegion),
+            ("v1\n4096 64\n0 64\n", ConfigError::NullAddress),
+            ("v1\n4096\n", ConfigError::InvalidLength),
+        ];
+        for (content, err) in cases {
+            fs::write(buffer_path, content).unwrap();
+            assert_eq!(load_config(desc_path, buffer_path).err(), Some(err));
+        }
+    }
+
+    #[test]
+    fn test_load_config_corrupted_descriptor() {
+        use std::fs;
+
+        let desc_path = "/tmp/desc_corrupted_test.txt";
+        let buffer_path = "/tmp/buffer_corrupted_test.txt";
+        fs::write(buffer_path, "v1\n4096 64\n").unwrap();
+
+        let valid = encode_export_file(b"Hello!").unwrap();
+        fs::write(desc_path, &valid).unwrap();
+        let info = load_config(desc_path, buffer_path).unwrap();
+        assert_eq!(info.export_desc.as_bytes(), b"Hello!");
+
+        let crc = crc32fast::hash(b"Hello!");
+        let mut flipped = valid.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        let mut old_version = valid.clone();
+        old_version[8] = 2;
+
+        let cases = [
+            // a raw descriptor saved without the header
+            (b"Hello!".to_vec(), ConfigError::BadMagic),
+            (valid[..4].to_vec(), ConfigError::TruncatedHeader),
+            (
+                valid[..EXPORT_HEADER_LENGTH - 1].to_vec(),
+                ConfigError::TruncatedHeader,
+            ),
+            (old_version, ConfigError::UnsupportedVersion),
+            (
+                valid[..valid.len() - 1].to_vec(),
+                ConfigError::LengthMismatch {
+                    expected: 6,
+                    actual: 5,
+                },
+            ),
+            (
+                [valid.as_slice(), b"!"].concat(),
+                ConfigError::LengthMismatch {
+                    expected: 6,
+                    actual: 7,
+                },
+            ),
+            (
+                flipped,
+                ConfigError::ChecksumMismatch {
+                    expected: crc,
+                    actual: crc32fast::hash(b"Hello "),
+                },
+            ),
+            (
+                valid[..EXPORT_HEADER_LENGTH].to_vec(),
+                ConfigError::LengthMismatch {
+                    expected: 6,
+                    actual: 0,
+                },
+            ),
+        ];
+        for (content, err) in cases {
+            fs::write(desc_path, content).unwrap();
+            assert_eq!(load_config(desc_path, buffer_path).err(), Some(err));
+        }
+    }
+
+    #[test]
+    fn test_save_config_regions() {
+        let desc = ExportDescriptor::from(b"Hello!".to_vec());
+        let mut src = vec![0u8; 96];
+        let regions: Vec<RawPointer> = src
+            .chunks_mut(32)
+            .map(|chunk| RawPointer {
+                inner: NonNull::new(chunk.as_mut_ptr() as *mut _).unwrap(),
+                payload: chunk.len(),
+            })
+            .collect();
+
+        let desc_path = "/tmp/desc_regions_test.txt";
+        let buffer_path = "/tmp/buffer_regions_test.txt";
+        assert_eq!(
+            save_config_regions(&desc, &[], desc_path, buffer_path),
+            Err(DocaError::InvalidValue)
+        );
+        save_config_regions(&desc, &regions, desc_path, buffer_path).unwrap();
+
+        let configs = load_config(desc_path, buffer_path).unwrap();
+        assert_eq!(configs.remote_addrs.len(), 3);
+        for (loaded, saved) in configs.remote_addrs.iter().zip(regions.iter()) {
+            assert_eq!(loaded.inner, saved.inner);
+            assert_eq!(loaded.payload, 32);
+        }
+        assert_eq!(configs.remote_addr().inner, regions[0].inner);
+    }
+
+    #[test]
+    #[cfg(feature = "async-config")]
+    fn test_async_config() {
+        let desc = ExportDescriptor::from(b"Hello!".to_vec());
+        let mut src = vec![0u8; 64];
+        let src_raw = RawPointer {
+            inner: NonNull::new(src.as_mut_ptr() as *mut _).unwrap(),
+            payload: src.len(),
         };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let configs = rt.block_on(async {
+            save_config_async(
+                &desc,
+                src_raw,
+                "/tmp/desc_async_test.txt",
+                "/tmp/buffer_async_test.txt",
+            )
+            .await
+            .unwrap();
+            load_config_async("/tmp/desc_async_test.txt", "/tmp/buffer_async_test.txt")
+                .await
+                .unwrap()
+        });
+
+        assert_eq!(configs.export_desc.len(), desc.len());
         assert_eq!(
-            configs.remote_addr.inner.as_ptr() as u64,
-            src_buffer.as_ptr() as u64
+            configs.remote_addr().inner.as_ptr() as *mut u8,
+            src.as_mut_ptr()
         );
+        assert_eq!(configs.remote_addr().payload, 64);
+    }
+
+    #[test]
+    fn test_thread_safety_markers() {
+        use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+        assert_impl_all!(Device: Send, Sync);
+        assert_impl_all!(DevContext: Send, Sync);
+        assert_impl_all!(DeviceMonitor: Send, Sync);
+        assert_impl_all!(DOCAMmap: Send, Sync);
+        assert_impl_all!(LocalMmap: Send, Sync, Clone);
+        assert_impl_all!(RemoteMmap: Send, Sync, Clone);
+
+        assert_impl_all!(BufferInventory: Send);
+        assert_not_impl_any!(BufferInventory: Sync);
+        assert_not_impl_any!(DOCABuffer: Send, Sync);
+
+        #[cfg(not(feature = "doca2"))]
+        {
+            use crate::compress::CompressEngine;
+            use crate::context::DOCAContext;
+            use crate::regex::RegexEngine;
+            use crate::sha::ShaEngine;
+
+            assert_impl_all!(DMAEngine: Send, Sync);
+            assert_impl_all!(CompressEngine: Send, Sync);
+            assert_impl_all!(RegexEngine: Send, Sync);
+            assert_impl_all!(ShaEngine: Send, Sync);
+            assert_impl_all!(DOCAContext<DMAEngine>: Send, Sync);
+            assert_not_impl_any!(DOCAWorkQueue<DMAEngine>: Send, Sync);
+        }
     }
 }
@@ -0,0 +1,282 @@
+//! Page-aligned host memory for the DMA, backed by hugepages.
+//!
+//! A region allocated from the heap spans many 4KB pages, each of which takes
+//! an entry in the translation of the device, which limits the DMA performance.
+//! [`HugePageBuffer`] maps the region from the hugepages reserved by the system
+//! (e.g., with `vm.nr_hugepages`), which are never swapped out, so the registered
+//! memory stays pinned.
+//!
+//! The buffer converts into a [`RawPointer`] to be registered in a [`LocalMmap`],
+//! or is handed to the mmap with [`DOCARegisteredMemory::new_huge_page`].
+//!
+//! On a multi-socket host, the DMA across the sockets is slower, so the buffer can be
+//! placed on the NUMA node of the device with [`HugePageBuffer::new_on_node`] or
+//! [`DOCARegisteredMemory::new_huge_page_near`].
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::memory::allocator::HugePageBuffer;
+//! use doca::{DOCARegisteredMemory, LocalMmap, RawPointer};
+//!
+//! let mmap = LocalMmap::new().unwrap();
+//! let buffer = HugePageBuffer::new(4 << 20).unwrap();
+//!
+//! let mem = DOCARegisteredMemory::new(&mmap, RawPointer::from(&buffer)).unwrap();
+//! ```
+//!
+
+use std::ptr::NonNull;
+
+use crate::memory::registered_memory::{DOCARegisteredMemory, OwnedMemory};
+use crate::memory::LocalMmap;
+use crate::{DOCAResult, Device, DocaError, RawPointer};
+
+/// The size of the default hugepages on x86_64 and aarch64
+pub const HUGE_PAGE_SIZE: usize = 2 << 20;
+
+// The memory policy binding the pages to the nodes, see `mbind(2)`
+const MPOL_BIND: libc::c_int = 2;
+
+/// A zeroed page-aligned memory region, unmapped when dropped.
+pub struct HugePageBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    huge: bool,
+}
+
+// The buffer owns its mapping exclusively
+unsafe impl Send for HugePageBuffer {}
+unsafe impl Sync for HugePageBuffer {}
+
+impl Drop for HugePageBuffer {
+    fn drop(&mut self) {
+        let ret = unsafe { libc::munmap(self.ptr.as_ptr() as _, self.len) };
+        if ret != 0 {
+            drop_failed!("Failed to unmap the hugepage buffer!");
+        }
+    }
+}
+
+impl HugePageBuffer {
+    /// Map at least `len` bytes from the hugepages,
+    /// rounded up to a multiple of [`HUGE_PAGE_SIZE`].
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `len` is zero.
+    ///  - `DOCA_ERROR_NO_MEMORY`: not enough hugepages are reserved.
+    ///
+    pub fn new(len: usize) -> DOCAResult<Self> {
+        Self::map(len, HUGE_PAGE_SIZE, libc::MAP_HUGETLB, None)
+    }
+
+    /// Map at least `len` bytes from the hugepages like [`new`](Self::new),
+    /// falling back to the ordinary pages locked in memory if there are no hugepages,
+    /// see [`is_huge`](Self::is_huge).
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `len` is zero.
+    ///  - `DOCA_ERROR_NO_MEMORY`: failed to map or lock the ordinary pages,
+    ///  e.g., the region exceeds `RLIMIT_MEMLOCK`.
+    ///
+    pub fn new_or_pages(len: usize) -> DOCAResult<Self> {
+        Self::map_or_pages(len, None)
+    }
+
+    /// Map at least `len` bytes like [`new_or_pages`](Self::new_or_pages),
+    /// with the pages allocated on the NUMA `node`, e.g., [`Device::numa_node`].
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `len` is zero, or the `node` doesn't exist.
+    ///  - `DOCA_ERROR_NO_MEMORY`: failed to map or lock the pages.
+    ///
+    pub fn new_on_node(len: usize, node: u32) -> DOCAResult<Self> {
+        Self::map_or_pages(len, Some(node))
+    }
+
+    fn map_or_pages(len: usize, node: Option<u32>) -> DOCAResult<Self> {
+        match Self::map(len, HUGE_PAGE_SIZE, libc::MAP_HUGETLB, node) {
+            Err(DocaError::NoMemory) => {}
+            res => return res,
+        }
+
+        let res = Self::map(len, page_size::get(), 0, node)?;
+        // the hugepages can't be swapped out, but the ordinary pages should be locked
+        let ret = unsafe { libc::mlock(res.ptr.as_ptr() as _, res.len) };
+        if ret != 0 {
+            return Err(DocaError::NoMemory);
+        }
+        Ok(res)
+    }
+
+    fn map(len: usize, page: usize, flags: libc::c_int, node: Option<u32>) -> DOCAResult<Self> {
+        if len == 0 {
+            return Err(DocaError::InvalidValue);
+        }
+        let len = len.checked_add(page - 1).ok_or(DocaError::InvalidValue)? / page * page;
+
+        // the pages are populated after they are bound to the node
+        let populate = if node.is_none() {
+            libc::MAP_POPULATE
+        } else {
+            0
+        };
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | populate | flags,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(DocaError::NoMemory);
+        }
+
+        let res = Self {
+            ptr: NonNull::new(ptr as *mut u8).ok_or(DocaError::NoMemory)?,
+            len,
+            huge: flags & libc::MAP_HUGETLB != 0,
+        };
+        if let Some(node) = node {
+            res.bind(node)?;
+            // fault the pages in, which fails instead of raising SIGBUS on touching them
+            // if the node runs out of the hugepages
+            let ret = unsafe { libc::mlock(res.ptr.as_ptr() as _, res.len) };
+            if ret != 0 {
+                return Err(DocaError::NoMemory);
+            }
+        }
+        Ok(res)
+    }
+
+    // Bind the pages of the region to the NUMA node, before they are faulted in
+    fn bind(&self, node: u32) -> DOCAResult<()> {
+        let bits = libc::c_ulong::BITS;
+        let mut mask = vec![0 as libc::c_ulong; (node / bits + 1) as usize];
+        mask[(node / bits) as usize] |= 1 << (node % bits);
+
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_mbind,
+                self.ptr.as_ptr(),
+                self.len,
+                MPOL_BIND,
+                mask.as_ptr(),
+                mask.len() as libc::c_ulong * bits as libc::c_ulong + 1,
+                0,
+            )
+        };
+        if ret != 0 {
+            return Err(DocaError::InvalidValue);
+        }
+        Ok(())
+    }
+
+    /// Get the length of the region, which is rounded up to the page size
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the region is empty, which never happens
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check whether the region is backed by the hugepages
+    pub fn is_huge(&self) -> bool {
+        self.huge
+    }
+
+    /// Get the memory of the region
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Get the memory of the region mutably, e.g., to fill the source data before the transfer
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl From<&HugePageBuffer> for RawPointer {
+    /// The pointer doesn't own the memory, so the buffer should outlive its use
+    fn from(buf: &HugePageBuffer) -> Self {
+        RawPointer {
+            inner: buf.ptr.cast(),
+            payload: buf.len,
+        }
+    }
+}
+
+impl DOCARegisteredMemory {
+    /// Map at least `len` bytes from the hugepages with [`HugePageBuffer::new_or_pages`],
+    /// and register them as [`new_owned`](Self::new_owned) does.
+    pub fn new_huge_page(mmap: &LocalMmap, len: usize) -> DOCAResult<Self> {
+        Self::new_with(
+            mmap,
+            OwnedMemory::HugePage(HugePageBuffer::new_or_pages(len)?),
+        )
+    }
+
+    /// Like [`new_huge_page`](Self::new_huge_page), but with the pages allocated on
+    /// the NUMA node of the device, or anywhere if the node of the device is unknown.
+    pub fn new_huge_page_near(mmap: &LocalMmap, len: usize, dev: &Device) -> DOCAResult<Self> {
+        let buf = match dev.numa_node() {
+            Some(node) => HugePageBuffer::new_on_node(len, node)?,
+            None => HugePageBuffer::new_or_pages(len)?,
+        };
+        Self::new_with(mmap, OwnedMemory::HugePage(buf))
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_huge_page_buffer() {
+        use super::HugePageBuffer;
+        use crate::*;
+
+        assert_eq!(
+            HugePageBuffer::new_or_pages(0).err(),
+            Some(DocaError::InvalidValue)
+        );
+
+        let mut buf = HugePageBuffer::new_or_pages(100).unwrap();
+        assert!(buf.len() >= 100);
+        assert_eq!(buf.len() % page_size::get(), 0);
+        assert!(buf.as_slice().iter().all(|b| *b == 0));
+
+        buf.as_mut_slice()[99] = 42;
+        let ptr = RawPointer::from(&buf);
+        assert_eq!(ptr.get_payload(), buf.len());
+        assert_eq!(
+            unsafe { *(ptr.get_inner().as_ptr() as *const u8).add(99) },
+            42
+        );
+        assert_eq!(
+            unsafe { ptr.get_inner().as_ptr() } as usize % page_size::get(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_huge_page_buffer_on_node() {
+        use super::HugePageBuffer;
+        use crate::*;
+
+        // the kernel may be built without NUMA, where no node can be bound
+        if let Ok(buf) = HugePageBuffer::new_on_node(100, 0) {
+            assert!(buf.len() >= 100);
+            assert!(buf.as_slice().iter().all(|b| *b == 0));
+        }
+        assert_eq!(
+            HugePageBuffer::new_on_node(100, 1023).err(),
+            Some(DocaError::InvalidValue)
+        );
+    }
+}
@@ -5,17 +5,21 @@
 //! It holds the information on a memory region that belongs to a DOCA memory map,
 //! and its descriptor is allocated from DOCA Buffer Inventory.
 //!
+//! - [`DOCABufferList`] chains several [`DOCABuffer`]s into a scatter-gather list,
+//! so a single job can operate on all of them.
+//!
 //! - [`BufferInventory`] manages a pool of doca_buf objects.
 //! Each buffer obtained from an inventory is a descriptor that
 //! points to a memory region from a doca_mmap memory range of the user's choice.
+//! [`InventoryOptions`] creates an inventory with extended buffers,
+//! e.g., the ones that can be chained into a [`DOCABufferList`].
 //!
 //! The module also provides an abstraction of the data stored in a memory map [`RawPointer`].
 //!
 //! The usage of this module is to create a specific mmap buffer which points to a piece of
 //! memory in the memory map:
 //! ```
-//! use std::sync::Arc;
-//! use doca::memory::DOCAMmap;
+//! use doca::memory::LocalMmap;
 //! use doca::memory::buffer::BufferInventory;
 //! use doca::DOCARegisteredMemory;
 //! use doca::RawPointer;
@@ -23,7 +27,7 @@
 //! // The memory region we want to register into the memory map
 //! let mut mem_buffer = vec![0u8; 1024].into_boxed_slice();
 //! // Create the memory map object
-//! let mmap = Arc::new(DOCAMmap::new().unwrap());
+//! let mmap = LocalMmap::new().unwrap();
 //! // Create the buffer inventory for buffer allocation
 //! let inv = BufferInventory::new(1024).unwrap();
 //!
@@ -40,9 +44,11 @@ use ffi::doca_error;
 use std::ptr::NonNull;
 use std::sync::Arc;
 use std::convert::From;
+use std::ops::BitOr;
 
+use crate::drop_order::TrackId;
 use crate::memory::DOCAMmap;
-use crate::DOCAResult;
+use crate::{DOCAResult, DocaError};
 
 use serde_derive::{Deserialize, Serialize};
 
@@ -60,8 +66,18 @@ pub struct RawPointer {
 /// a (de)serializable struct for passing RawPointer between nodes
 #[derive(Serialize, Deserialize)]
 pub struct RawPointerMsg {
-    inner: u64,
-    payload: usize,
+    pub(crate) inner: u64,
+    pub(crate) payload: usize,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for RawPointerMsg {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+
+        self.inner.zeroize();
+        self.payload.zeroize();
+    }
 }
 
 impl RawPointer {
@@ -92,6 +108,48 @@ impl RawPointer {
             payload: len,
         }
     }
+
+    /// Get the sub-region of `len` bytes starting at `offset`,
+    /// e.g., to partition a registered region into many DMA targets.
+    ///
+    /// Only the address is computed, so it works on the remote regions as well.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `len` is zero, or the sub-region is out of the region.
+    pub fn slice(&self, offset: usize, len: usize) -> DOCAResult<Self> {
+        match offset.checked_add(len) {
+            Some(end) if len > 0 && end <= self.payload => Ok(Self {
+                inner: self.offset_ptr(offset),
+                payload: len,
+            }),
+            _ => Err(DocaError::InvalidValue),
+        }
+    }
+
+    /// Split the region into `[0, mid)` and `[mid, payload)`.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if either part is empty.
+    pub fn split_at(&self, mid: usize) -> DOCAResult<(Self, Self)> {
+        if mid == 0 || mid >= self.payload {
+            return Err(DocaError::InvalidValue);
+        }
+        Ok((self.slice(0, mid)?, self.slice(mid, self.payload - mid)?))
+    }
+
+    // The address at `offset`, which is never dereferenced here
+    fn offset_ptr(&self, offset: usize) -> NonNull<c_void> {
+        let ptr = (self.inner.as_ptr() as *mut u8).wrapping_add(offset);
+        NonNull::new(ptr as *mut c_void).expect("the region doesn't wrap around")
+    }
+
+    // The local memory of the region, which should be alive for the returned lifetime
+    pub(crate) unsafe fn as_slice<'a>(&self) -> &'a [u8] {
+        std::slice::from_raw_parts(self.inner.as_ptr() as *const u8, self.payload)
+    }
+
+    // Like `as_slice`, and no one else should access the memory for the returned lifetime
+    pub(crate) unsafe fn as_mut_slice<'a>(&self) -> &'a mut [u8] {
+        std::slice::from_raw_parts_mut(self.inner.as_ptr() as *mut u8, self.payload)
+    }
 }
 
 impl From<RawPointerMsg> for RawPointer {
@@ -134,6 +192,8 @@ impl From<RawPointer> for RawPointerMsg {
 pub struct DOCABuffer {
     pub(crate) inner: NonNull<ffi::doca_buf>,
     pub(crate) head: RawPointer,
+    // whether the head region is backed by local memory
+    pub(crate) local: bool,
 
     // FIXME: it would be safe to record references to the creators
     // However, it may add extra overhead to the structures.
@@ -141,18 +201,41 @@ pub struct DOCABuffer {
     pub(crate) inv: Arc<BufferInventory>,
     #[allow(dead_code)]
     pub(crate) mmap: Arc<DOCAMmap>,
+    // whether the buffer is released by `close`
+    pub(crate) closed: bool,
+    pub(crate) track: TrackId,
+    // whether the head region is owned by the mmap, so it can be accessed as a slice
+    pub(crate) owned: bool,
 }
 
 impl Drop for DOCABuffer {
     fn drop(&mut self) {
+        self.track.release();
+
+        if !self.closed {
+            if let Err(e) = self.teardown() {
+                drop_failed!("Failed to remove refcount of doca buffer: {:?}", e);
+            }
+        }
+
+        dropped!("DOCA Buffer is dropped!");
+    }
+}
+
+impl DOCABuffer {
+    /// Release the buffer back to its inventory,
+    /// reporting the failure that dropping it would only log.
+    pub fn close(mut self) -> DOCAResult<()> {
+        self.closed = true;
+        self.teardown()
+    }
+
+    fn teardown(&mut self) -> DOCAResult<()> {
         let ret = unsafe { ffi::doca_buf_refcount_rm(self.inner_ptr(), std::ptr::null_mut()) };
         if ret != doca_error::DOCA_SUCCESS {
-            panic!("Failed to remove refcount of doca buffer");
+            return Err(ret.into());
         }
-
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("DOCA Buffer is dropped!");
+        Ok(())
     }
 }
 
@@ -166,7 +249,7 @@ impl DOCABuffer {
         let ret = unsafe { ffi::doca_buf_get_data(self.inner_ptr(), &mut data as *mut _) };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(data)
@@ -176,6 +259,14 @@ impl DOCABuffer {
     /// The data pointer and length should fix in the head region.
     /// Therefore, we adopt usize (in offset), instead of passing the raw pointers
     pub unsafe fn set_data(&mut self, off: usize, sz: usize) -> DOCAResult<()> {
+        debug_assert!(
+            off + sz <= self.head.payload,
+            "data [{}, {}) exceeds the registered region of {} bytes",
+            off,
+            off + sz,
+            self.head.payload
+        );
+
         let ret = unsafe {
             ffi::doca_buf_set_data(
                 self.inner_ptr(),
@@ -185,31 +276,359 @@ impl DOCABuffer {
         };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(())
     }
 
+    /// Extend the buffer's data by `len` bytes into the free room after it,
+    /// e.g., after the producer has written them, so the data accumulates
+    /// without recomputing the offsets as [`set_data`](Self::set_data) does.
+    ///
+    /// Note that a DMA job appends to the data of its destination buffer by itself,
+    /// so call it for the bytes written otherwise.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the data would exceed the region of the buffer.
+    pub fn advance(&mut self, len: usize) -> DOCAResult<()> {
+        let data = unsafe { self.get_data()? };
+        let data_len = self.data_len()?;
+
+        // the data is in the region, so its offset doesn't underflow
+        let off = data as usize - self.head.inner.as_ptr() as usize;
+        let new_len = data_len.checked_add(len).ok_or(DocaError::InvalidValue)?;
+        if off.saturating_add(new_len) > self.head.payload {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let ret = unsafe { ffi::doca_buf_set_data(self.inner_ptr(), data, new_len) };
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
+    /// Empty the buffer's data, and move it back to the start of the region,
+    /// so the buffer accumulates the data of the next round from the start.
+    pub fn reset_data(&mut self) -> DOCAResult<()> {
+        unsafe { self.set_data(0, 0) }
+    }
+
+    /// Check whether the buffer points to local memory,
+    /// rather than to a region of a remote mmap.
+    #[inline]
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    /// Get the memory owned by the buffer, e.g., to read the data copied into it.
+    ///
+    /// Return `None` if the buffer is not allocated from a memory created by
+    /// [`DOCARegisteredMemory::new_owned`](crate::DOCARegisteredMemory::new_owned).
+    pub fn owned_bytes(&self) -> Option<&[u8]> {
+        // the memory is kept by the mmap held by the buffer
+        self.owned.then(|| unsafe { self.head.as_slice() })
+    }
+
     /// Return the pointer
     pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_buf {
         self.inner.as_ptr()
     }
+
+    /// Get the length of the buffer's data, i.e., the valid bytes starting at [`get_data`](Self::get_data).
+    ///
+    /// A DMA job appends the copied bytes to the data of its destination buffer,
+    /// so after the job completes, it tells how many bytes have landed.
+    pub fn data_len(&self) -> DOCAResult<usize> {
+        let mut len: usize = 0;
+
+        let ret = unsafe { ffi::doca_buf_get_data_len(self.inner_ptr(), &mut len as *mut _) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(len)
+    }
+
+    /// Get the length of the whole region the buffer points to, which bounds its data.
+    pub fn head_len(&self) -> DOCAResult<usize> {
+        let mut len: usize = 0;
+
+        let ret = unsafe { ffi::doca_buf_get_len(self.inner_ptr(), &mut len as *mut _) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(len)
+    }
+
+    /// The number of buffers in the list this buffer heads,
+    /// which is 1 if the buffer is not chained.
+    pub fn list_len(&self) -> DOCAResult<u32> {
+        let mut len: u32 = 0;
+
+        let ret = unsafe { ffi::doca_buf_get_list_len(self.inner_ptr(), &mut len as *mut _) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(len)
+    }
+
+    /// Check whether the buffer is the last one of its list.
+    pub fn is_last_in_list(&self) -> DOCAResult<bool> {
+        let mut is_last: u8 = 0;
+
+        let ret =
+            unsafe { ffi::doca_buf_is_last_in_list(self.inner_ptr(), &mut is_last as *mut _) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(is_last != 0)
+    }
+
+    // Get the next buffer of the list, `None` if it is the last one
+    pub(crate) fn next_in_list(&self) -> DOCAResult<Option<NonNull<ffi::doca_buf>>> {
+        if self.is_last_in_list()? {
+            return Ok(None);
+        }
+
+        let mut next: *mut ffi::doca_buf = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_buf_get_next_in_list(self.inner_ptr(), &mut next as *mut _) };
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(NonNull::new(next))
+    }
+
+    // Get the last buffer of the list this buffer heads
+    pub(crate) fn last_in_list(&self) -> DOCAResult<NonNull<ffi::doca_buf>> {
+        let mut last: *mut ffi::doca_buf = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_buf_get_last_in_list(self.inner_ptr(), &mut last as *mut _) };
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        NonNull::new(last).ok_or(DocaError::Unexpected)
+    }
+
+    /// Compute the CRC32 checksum of `len` bytes starting at `off` of the buffer's data.
+    /// It is unsafe because the data must be backed by local memory.
+    #[cfg(not(feature = "doca2"))]
+    pub(crate) unsafe fn data_checksum(&self, off: usize, len: usize) -> DOCAResult<u32> {
+        let data = self.get_data()? as *const u8;
+        debug_assert!(!data.is_null(), "the buffer has no data pointer");
+        let bytes = std::slice::from_raw_parts(data.add(off), len);
+
+        Ok(crc32fast::hash(bytes))
+    }
+}
+
+/// A scatter-gather list of [`DOCABuffer`]s chained by `doca_buf_list_chain`.
+///
+/// The list owns its buffers and always contains the head buffer.
+/// Passing the list to a job, e.g., [`DOCADMAJob::set_src_list`](crate::dma::DOCADMAJob::set_src_list),
+/// makes the job operate on the data of all buffers in order.
+///
+/// The buffers are unchained before they are released,
+/// so dropping the list is the same as dropping each buffer.
+///
+/// The buffers should be allocated from an inventory with [`BufExtensions::LINKED_LIST`].
+pub struct DOCABufferList {
+    bufs: Vec<DOCABuffer>,
+}
+
+impl Drop for DOCABufferList {
+    fn drop(&mut self) {
+        while self.bufs.len() > 1 {
+            if let Err(e) = self.pop() {
+                drop_failed!("Failed to unchain doca buffer list: {:?}", e);
+                break;
+            }
+        }
+    }
+}
+
+impl DOCABufferList {
+    /// Create a list with a single buffer as its head.
+    pub fn new(head: DOCABuffer) -> Self {
+        Self { bufs: vec![head] }
+    }
+
+    /// Chain the buffer to the tail of the list.
+    ///
+    /// Return `DOCA_ERROR_NOT_PERMITTED` if the buffer is already in another list.
+    pub fn push(&mut self, buf: DOCABuffer) -> DOCAResult<()> {
+        let ret = unsafe { ffi::doca_buf_list_chain(self.head().inner_ptr(), buf.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        self.bufs.push(buf);
+        Ok(())
+    }
+
+    /// Unchain the last buffer from the list and return it.
+    ///
+    /// Return `None` if only the head is left, which can be taken by [`into_buffers`](Self::into_buffers).
+    pub fn pop(&mut self) -> DOCAResult<Option<DOCABuffer>> {
+        if self.bufs.len() == 1 {
+            return Ok(None);
+        }
+
+        let last = self.bufs.last().unwrap();
+        let ret = unsafe { ffi::doca_buf_list_unchain(self.head().inner_ptr(), last.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(self.bufs.pop())
+    }
+
+    /// Unchain all buffers and return them in order.
+    pub fn into_buffers(mut self) -> DOCAResult<Vec<DOCABuffer>> {
+        let mut tail = Vec::with_capacity(self.bufs.len());
+        while let Some(buf) = self.pop()? {
+            tail.push(buf);
+        }
+        tail.push(self.bufs.pop().unwrap());
+        tail.reverse();
+        Ok(tail)
+    }
+
+    /// The number of buffers in the list.
+    #[inline]
+    pub fn num_buffers(&self) -> usize {
+        self.bufs.len()
+    }
+
+    /// The head buffer, which represents the whole list in a job.
+    #[inline]
+    pub fn head(&self) -> &DOCABuffer {
+        &self.bufs[0]
+    }
+
+    /// The last buffer of the list.
+    #[inline]
+    pub fn last(&self) -> &DOCABuffer {
+        self.bufs.last().unwrap()
+    }
+
+    /// Get the `idx`-th buffer, e.g., to set its data with [`DOCABuffer::set_data`].
+    #[inline]
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut DOCABuffer> {
+        self.bufs.get_mut(idx)
+    }
+
+    /// Iterate over the buffers in order.
+    pub fn iter(&self) -> impl Iterator<Item = &DOCABuffer> {
+        self.bufs.iter()
+    }
+
+    /// Check the chain recorded by DOCA against the buffers of the list,
+    /// walking the next pointers from the head.
+    ///
+    /// Return `DOCA_ERROR_BAD_STATE` if they differ.
+    pub fn validate(&self) -> DOCAResult<()> {
+        if self.head().list_len()? as usize != self.bufs.len() {
+            return Err(DocaError::BadState);
+        }
+
+        let mut cur = Some(self.head().inner);
+        for buf in self.bufs.iter() {
+            if cur != Some(buf.inner) {
+                return Err(DocaError::BadState);
+            }
+            cur = buf.next_in_list()?;
+        }
+        if cur.is_some() || self.head().last_in_list()? != self.last().inner {
+            return Err(DocaError::BadState);
+        }
+        Ok(())
+    }
+}
+
+/// The extensions of the buffers in a [`BufferInventory`], which can be combined with `|`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufExtensions(u32);
+
+impl BufExtensions {
+    /// Plain buffers, the default of [`BufferInventory::new`]
+    pub const NONE: Self = Self(0);
+    /// The buffers can be chained into a [`DOCABufferList`]
+    pub const LINKED_LIST: Self = Self(1 << 0);
+
+    /// Get the extension mask passed to `doca_buf_inventory_create`
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Check whether all the extensions in `other` are enabled
+    #[inline]
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for BufExtensions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// The attributes of a [`BufferInventory`] set on its creation,
+/// see [`BufferInventory::with_options`].
+///
+/// ```
+/// use doca::memory::buffer::{BufExtensions, BufferInventory, InventoryOptions};
+///
+/// // an inventory ready for scatter-gather lists
+/// let options = InventoryOptions {
+///     extensions: BufExtensions::LINKED_LIST,
+///     ..Default::default()
+/// };
+/// let inv = BufferInventory::with_options(64, options).unwrap();
+/// assert!(inv.extensions().contains(BufExtensions::LINKED_LIST));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InventoryOptions {
+    /// The extensions of the buffers
+    pub extensions: BufExtensions,
+    /// The user data attached to the inventory
+    pub user_data: u64,
 }
 
 /// The DOCA buffer inventory manages a pool of doca_buf objects.
 /// Each buffer obtained from an inventory is a descriptor that points to a memory region from a doca_mmap memory range of the user's choice.
 pub struct BufferInventory {
     inner: NonNull<ffi::doca_buf_inventory>,
+    options: InventoryOptions,
+    // whether the inventory is destroyed by `close`
+    closed: bool,
+    pub(crate) track: TrackId,
 }
 
+// The inventory isn't thread-safe, so it can be moved to another thread but not shared.
+// The buffers hold it, and so are neither `Send` nor `Sync`.
+unsafe impl Send for BufferInventory {}
+
 impl Drop for BufferInventory {
     fn drop(&mut self) {
-        unsafe { ffi::doca_buf_inventory_destroy(self.inner.as_ptr()) };
+        self.track.release();
+        if !self.closed {
+            if let Err(e) = self.teardown() {
+                drop_failed!("Failed to destroy the buffer inventory: {:?}", e);
+            }
+        }
 
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("Buffer Inventory is dropped!");
+        dropped!("Buffer Inventory is dropped!");
     }
 }
 
@@ -219,28 +638,103 @@ impl BufferInventory {
     /// # Input:
     /// - `num` - number of elements in the inventory.
     ///
-    /// FIXME: currently we omit setting other attributes of the inventory.
-    ///
     pub fn new(num: usize) -> DOCAResult<Arc<Self>> {
-        // currently we don't use `user_data` field
+        Self::with_options(num, InventoryOptions::default())
+    }
+
+    /// Allocates buffer inventory of `num` elements with the extensions
+    /// and the user data in `options`.
+    pub fn with_options(num: usize, options: InventoryOptions) -> DOCAResult<Arc<Self>> {
         let mut buf_inv: *mut ffi::doca_buf_inventory = std::ptr::null_mut();
-        // DOCA_BUF_EXTENSION_NONE = 0;
+        let user_data = ffi::doca_data {
+            u64: options.user_data,
+        };
         let ret = unsafe {
-            ffi::doca_buf_inventory_create(std::ptr::null(), num, 0, &mut buf_inv as *mut _)
+            ffi::doca_buf_inventory_create(
+                &user_data as *const _,
+                num,
+                options.extensions.bits(),
+                &mut buf_inv as *mut _,
+            )
         };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         let mut res = Self {
             inner: unsafe { NonNull::new_unchecked(buf_inv) },
+            options,
+            closed: false,
+            track: TrackId::new("BufferInventory"),
         };
         res.start()?;
 
         Ok(Arc::new(res))
     }
 
+    /// Destroy the inventory, reporting the failure that dropping it would ignore.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_IN_USE`: the inventory is still referenced, e.g., by its buffers.
+    ///  Only this reference is dropped.
+    ///
+    pub fn close(self: Arc<Self>) -> DOCAResult<()> {
+        let mut this = Arc::try_unwrap(self).map_err(|_| DocaError::InUse)?;
+        this.closed = true;
+        this.teardown()
+    }
+
+    fn teardown(&mut self) -> DOCAResult<()> {
+        let ret = unsafe { ffi::doca_buf_inventory_destroy(self.inner.as_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
+    /// Get the total number of the buffers in the inventory
+    pub fn num_elements(&self) -> DOCAResult<usize> {
+        let mut num: u32 = 0;
+        let ret = unsafe {
+            ffi::doca_buf_inventory_get_num_elements(self.inner_ptr(), &mut num as *mut _)
+        };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(num as usize)
+    }
+
+    /// Get the number of the buffers that can still be allocated from the inventory
+    pub fn num_free(&self) -> DOCAResult<usize> {
+        let mut num: u32 = 0;
+        let ret = unsafe {
+            ffi::doca_buf_inventory_get_num_free_elements(self.inner_ptr(), &mut num as *mut _)
+        };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(num as usize)
+    }
+
+    /// Get the number of the buffers allocated from the inventory and not yet freed
+    pub fn num_used(&self) -> DOCAResult<usize> {
+        Ok(self.num_elements()? - self.num_free()?)
+    }
+
+    /// Get the extensions of the buffers in the inventory
+    pub fn extensions(&self) -> BufExtensions {
+        self.options.extensions
+    }
+
+    /// Get the user data attached to the inventory on its creation
+    pub fn user_data(&self) -> u64 {
+        self.options.user_data
+    }
+
     /// Return the pointer
     pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_buf_inventory {
         self.inner.as_ptr()
@@ -251,7 +745,7 @@ impl BufferInventory {
         let ret = unsafe { ffi::doca_buf_inventory_start(self.inner_ptr()) };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(())
@@ -265,9 +759,9 @@ mod tests {
     #[test]
     fn test_basic_buffer_inv() {
         use super::*;
-        use crate::memory::DOCAMmap;
+        use crate::memory::LocalMmap;
 
-        let doca_mmap = Arc::new(DOCAMmap::new().unwrap());
+        let doca_mmap = LocalMmap::new().unwrap();
         let inv = BufferInventory::new(1024).unwrap();
 
         let test_len = 64;
@@ -279,9 +773,154 @@ mod tests {
         };
 
         let registered_memory = DOCARegisteredMemory::new(&doca_mmap, raw_pointer).unwrap();
-        let buf = registered_memory.to_buffer(&inv).unwrap();
+        let mut buf = registered_memory.to_buffer(&inv).unwrap();
 
         let data = unsafe { buf.get_data().unwrap() };
         assert_eq!(data, dpu_buffer.as_ptr() as *mut c_void);
+
+        // the buffer covers the whole region, with no data yet
+        assert_eq!(buf.head_len().unwrap(), test_len);
+        assert_eq!(buf.data_len().unwrap(), 0);
+
+        unsafe { buf.set_data(8, 16).unwrap() };
+        assert_eq!(buf.data_len().unwrap(), 16);
+        assert_eq!(buf.head_len().unwrap(), test_len);
+
+        // the data grows after its start, up to the end of the region
+        buf.advance(32).unwrap();
+        assert_eq!(buf.data_len().unwrap(), 48);
+        assert_eq!(buf.advance(9), Err(DocaError::InvalidValue));
+        assert_eq!(buf.advance(usize::MAX), Err(DocaError::InvalidValue));
+        buf.advance(8).unwrap();
+        assert_eq!(buf.data_len().unwrap(), 56);
+
+        buf.reset_data().unwrap();
+        assert_eq!(buf.data_len().unwrap(), 0);
+        let data = unsafe { buf.get_data().unwrap() };
+        assert_eq!(data, dpu_buffer.as_ptr() as *mut c_void);
+    }
+
+    #[test]
+    fn test_inventory_exhausted() {
+        use super::*;
+        use crate::memory::LocalMmap;
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let inv = BufferInventory::new(2).unwrap();
+        assert_eq!(inv.num_elements().unwrap(), 2);
+
+        let buffer = vec![0u8; 64].into_boxed_slice();
+        let region = unsafe { RawPointer::from_box(&buffer) };
+        let new_buf = || DOCARegisteredMemory::new(&doca_mmap, region)?.to_buffer(&inv);
+
+        let first = new_buf().unwrap();
+        let _second = new_buf().unwrap();
+        assert_eq!(inv.num_free().unwrap(), 0);
+        assert_eq!(inv.num_used().unwrap(), 2);
+        assert_eq!(new_buf().err(), Some(DocaError::InventoryExhausted));
+
+        // the buffer goes back to the inventory when dropped
+        drop(first);
+        assert_eq!(inv.num_free().unwrap(), 1);
+        assert!(new_buf().is_ok());
+    }
+
+    #[test]
+    fn test_raw_pointer_slice() {
+        use super::*;
+
+        let buffer = vec![0u8; 64].into_boxed_slice();
+        let region = unsafe { RawPointer::from_box(&buffer) };
+
+        let part = region.slice(16, 32).unwrap();
+        assert_eq!(part.inner.as_ptr() as *const u8, buffer[16..].as_ptr());
+        assert_eq!(part.payload, 32);
+        assert!(region.slice(0, 64).is_ok());
+
+        assert_eq!(region.slice(48, 17).err(), Some(DocaError::InvalidValue));
+        assert_eq!(region.slice(8, 0).err(), Some(DocaError::InvalidValue));
+        assert_eq!(
+            region.slice(usize::MAX, 2).err(),
+            Some(DocaError::InvalidValue)
+        );
+
+        let (head, tail) = region.split_at(24).unwrap();
+        assert_eq!(head.inner, region.inner);
+        assert_eq!(head.payload, 24);
+        assert_eq!(tail.inner.as_ptr() as *const u8, buffer[24..].as_ptr());
+        assert_eq!(tail.payload, 40);
+
+        assert!(region.split_at(0).is_err());
+        assert!(region.split_at(64).is_err());
+    }
+
+    #[test]
+    fn test_owned_memory() {
+        use super::*;
+        use crate::memory::LocalMmap;
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let inv = BufferInventory::new(1024).unwrap();
+
+        let mut mr = DOCARegisteredMemory::new_owned(&doca_mmap, vec![0u8; 64]).unwrap();
+        mr.as_mut_slice().unwrap()[0] = 42;
+        let buf = mr.to_buffer(&inv).unwrap();
+        assert_eq!(buf.owned_bytes().unwrap()[0], 42);
+        let data = unsafe { buf.get_data().unwrap() };
+        assert_eq!(data as *const u8, buf.owned_bytes().unwrap().as_ptr());
+        // the memory stays populated, so it is kept by the mmap rather than the buffer
+        drop(buf);
+        assert_eq!(doca_mmap.handle.owned.lock().unwrap().len(), 1);
+
+        let mr = DOCARegisteredMemory::new_aligned(&doca_mmap, 8192, 4096).unwrap();
+        assert_eq!(mr.as_slice().unwrap().as_ptr() as usize % 4096, 0);
+        assert!(mr.as_slice().unwrap().iter().all(|b| *b == 0));
+
+        assert!(DOCARegisteredMemory::new_owned(&doca_mmap, vec![]).is_err());
+        assert!(DOCARegisteredMemory::new_aligned(&doca_mmap, 64, 3).is_err());
+    }
+
+    #[test]
+    fn test_buffer_list() {
+        use super::*;
+        use crate::memory::LocalMmap;
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let options = InventoryOptions {
+            extensions: BufExtensions::LINKED_LIST,
+            user_data: 7,
+        };
+        let inv = BufferInventory::with_options(1024, options).unwrap();
+        assert_eq!(inv.extensions(), BufExtensions::LINKED_LIST);
+        assert_eq!(inv.user_data(), 7);
+
+        let mut dpu_buffer = vec![0u8; 96].into_boxed_slice();
+        let mut bufs = dpu_buffer.chunks_mut(32).map(|chunk| {
+            let raw_pointer = RawPointer {
+                inner: NonNull::new(chunk.as_mut_ptr() as _).unwrap(),
+                payload: chunk.len(),
+            };
+            DOCARegisteredMemory::new(&doca_mmap, raw_pointer)
+                .unwrap()
+                .to_buffer(&inv)
+                .unwrap()
+        });
+
+        let mut list = DOCABufferList::new(bufs.next().unwrap());
+        for buf in bufs {
+            list.push(buf).unwrap();
+        }
+        assert_eq!(list.num_buffers(), 3);
+        list.validate().unwrap();
+        assert!(list.last().is_last_in_list().unwrap());
+        assert!(!list.head().is_last_in_list().unwrap());
+
+        let last = list.pop().unwrap().unwrap();
+        assert_eq!(last.list_len().unwrap(), 1);
+        list.validate().unwrap();
+
+        let bufs = list.into_buffers().unwrap();
+        assert_eq!(bufs.len(), 2);
+        assert!(bufs.iter().all(|buf| buf.is_last_in_list().unwrap()));
     }
 }
@@ -7,18 +7,25 @@
 //! There is also an entity called [`BufferInventory`] which serves as a pool of [`DOCABuffer`] with same characteristics.
 //!
 //! - [`DOCAMmap`] is the data buffers pool (chunks) which are pointed at by [`buffer`].
-//! The application populates this memory pool with buffers/chunks and maps them to devices that must access the data.
+//! The application populates a [`LocalMmap`] with buffers/chunks and maps them to devices that must access the data,
+//! and the other side accesses them through a [`RemoteMmap`] created from its [`ExportDescriptor`].
+//! [`MmapExport`] carries the export and its regions over any serde format, e.g., JSON or bincode.
 //!
-//! The way to use [`DOCAMmap`] is to register the memory the application might use into the object.
+//! - [`pool::BufferPool`] allocates the buffers over several inventories, adding more as they are exhausted.
+//!
+//! - [`allocator::HugePageBuffer`] maps the memory to register from the hugepages.
+//!
+//! - [`region::MemRegion`] and [`region::OwnedRegion`] hold the borrow or the ownership of
+//! the memory to register, so it can be registered without the unsafe [`RawPointer`] constructors.
+//!
+//! The way to use [`LocalMmap`] is to register the memory the application might use into the object.
 //!
 //! ```
-//! #![feature(get_mut_unchecked)]
-//! use std::sync::Arc;
-//! use doca::memory::DOCAMmap;
+//! use doca::memory::LocalMmap;
 //! use doca::RawPointer;
 //! use std::ptr::NonNull;
 //! // Create a memory map object
-//! let mut mmap = DOCAMmap::new().unwrap();
+//! let mmap = LocalMmap::new().unwrap();
 //!
 //! // Allocate a buffer we want to use
 //! let mut src_buffer = vec![0u8; 1024].into_boxed_slice();
@@ -31,77 +38,151 @@
 //! // And register the buffer into the memory map object.
 //! mmap.populate(mr).unwrap();
 //! ```
+pub mod allocator;
 pub mod buffer;
+pub mod pool;
+pub mod region;
 pub mod registered_memory;
 
 use core::ffi::c_void;
 use ffi::{doca_error, doca_mmap_populate};
 use page_size;
+use serde_derive::{Deserialize, Serialize};
+use std::ops::{BitOr, Deref};
 use std::ptr::NonNull;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
 
-use crate::device::DevContext;
-use crate::{DOCAError, DOCAResult, RawPointer};
+use crate::device::{expect_role, DevContext, Role};
+use crate::drop_order::TrackId;
+use crate::memory::registered_memory::OwnedMemory;
+use crate::{DOCAResult, DocaError, RawPointer};
 
 const DOCA_MMAP_CHUNK_SIZE: u32 = 64; // 64 registered memory regions per mmap
+
+/// The access permissions of the memory populated in a [`LocalMmap`],
+/// a bitwise combination of the `doca_access_flag` values of the SDK.
+///
+/// ```
+/// use doca::memory::MmapPermissions;
+///
+/// // the DPU can only read the exported memory with DMA
+/// let perms = MmapPermissions::LOCAL_READ_WRITE | MmapPermissions::PCI_READ_ONLY;
+/// assert!(perms.contains(MmapPermissions::PCI_READ_ONLY));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MmapPermissions(u32);
+
+impl MmapPermissions {
+    /// The local device can only read the memory
+    pub const LOCAL_READ_ONLY: Self = Self(0);
+    /// The local device can read and write the memory, the default of [`LocalMmap::new`]
+    pub const LOCAL_READ_WRITE: Self = Self(1 << 0);
+    /// The remote peer can read the memory with RDMA
+    pub const RDMA_READ: Self = Self(1 << 1);
+    /// The remote peer can write the memory with RDMA
+    pub const RDMA_WRITE: Self = Self(1 << 2);
+    /// The remote peer can access the memory with RDMA atomics
+    pub const RDMA_ATOMIC: Self = Self(1 << 3);
+    /// The other side of the PCI bus, e.g., the DPU importing the export,
+    /// can only read the memory
+    pub const PCI_READ_ONLY: Self = Self(1 << 4);
+    /// The other side of the PCI bus can read and write the memory
+    pub const PCI_READ_WRITE: Self = Self(1 << 5);
+
+    /// Get the access mask passed to `doca_mmap_set_permissions`
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Check whether all the permissions in `other` are granted
+    #[inline]
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for MmapPermissions {
+    fn default() -> Self {
+        Self::LOCAL_READ_WRITE
+    }
+}
+
+impl BitOr for MmapPermissions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// The lifecycle state of a [`DOCAMmap`].
+///
+/// A [`LocalMmap`] moves from `Created` to `Exported`, while a [`RemoteMmap`] is always `Imported`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MmapState {
+    /// The local mmap is created but not started, so it can still be configured.
+    Created,
+    /// The local mmap is started, devices and memory can be added into it.
+    Started,
+    /// The local mmap has been exported, devices can no longer be added or removed.
+    Exported,
+    /// The mmap is created from an export of the **remote** side.
+    Imported,
+}
+
 /// A wrapper for `doca_mmap` struct
 /// Since a mmap can be used by multiple device context,
 /// we use a vector to record them.
 ///
+/// It is the handle shared by a [`LocalMmap`] or a [`RemoteMmap`] and the buffers
+/// allocated from them, so it only exposes the operations valid on both sides.
+///
+/// The mmap is thread-safe: the device vector and the state are protected by locks,
+/// so it can be shared among worker threads with an `Arc`.
+///
 pub struct DOCAMmap {
     // inner pointer of the doca memory pool
     inner: NonNull<ffi::doca_mmap>,
     // the device contexts that the doca memory pool registered
-    ctx: Vec<Arc<DevContext>>,
-    // Control the drop behavior
-    ok: bool,
+    ctx: Mutex<Vec<Arc<DevContext>>>,
+    // Control which operations are permitted, including the drop behavior.
+    // It should be locked before `ctx`, which also serializes the operations on the mmap.
+    state: Mutex<MmapState>,
+    // the memory populated by `populate_owned`, freed after the mmap is destroyed
+    owned: Mutex<Vec<OwnedMemory>>,
+    // whether the mmap is destroyed by `close`
+    closed: bool,
+    pub(crate) track: TrackId,
 }
 
+// The mutable operations are serialized by the `state` lock
+unsafe impl Sync for DOCAMmap {}
+unsafe impl Send for DOCAMmap {}
+
 // The `drop` function in DOCAMmap should be considered carefully.
 // Since the operation `doca_mmap_dev_rm` is not permitted for:
 // - un-started/stopped memory map object.
 // - memory map object that have been exported or created from export.
-// So in these situation, the `drop` function shouldn't call the `dev_rm` function:
-// 1. The mmap is on the local side and exported;
-// 2. The mmap is on the remote side and created by `new_from_export` on the local side;
+// So the `drop` function only calls the `dev_rm` function in the `Started` state.
 impl Drop for DOCAMmap {
     fn drop(&mut self) {
-        // Check whether the device should be removed
-        if self.ok {
-            for dev in &self.ctx {
-                let ret = unsafe { ffi::doca_mmap_dev_rm(self.inner_ptr(), dev.inner_ptr()) };
+        self.track.release();
 
-                if ret != doca_error::DOCA_SUCCESS {
-                    panic!(
-                        "Failed to deregister the device from Memory Pool: {:?}",
-                        ret
-                    );
-                }
+        if !self.closed {
+            if let Err(e) = self.teardown() {
+                drop_failed!("Failed to deregister the device from Memory Pool: {:?}", e);
+                self.leak_owned();
             }
         }
 
-        self.ctx.clear();
-        unsafe { ffi::doca_mmap_destroy(self.inner.as_ptr()) };
-
-        // Show drop order only in `debug` mode
-        #[cfg(debug_assertions)]
-        println!("DOCA mmap is dropped!");
+        dropped!("DOCA mmap is dropped!");
     }
 }
 
 impl DOCAMmap {
-    /// Allocates a default mmap with default/unset attributes.
-    /// This function should be called at server side.
-    ///
-    /// # Note
-    ///   The default constructor will create a memory pool with maximum 64 chunks.
-    ///
-    /// Return values
-    /// - DOCA_SUCCESS - in case of success. doca_error code - in case of failure:
-    /// - DOCA_ERROR_INVALID_VALUE - if an invalid input had been received.
-    /// - DOCA_ERROR_NO_MEMORY - failed to alloc doca_mmap.
-    ///
-    pub fn new() -> DOCAResult<Self> {
+    // Allocate a local mmap with the attributes, and start it
+    fn new_local(max_chunks: u32, permissions: MmapPermissions) -> DOCAResult<Self> {
         let mut pool: *mut ffi::doca_mmap = std::ptr::null_mut();
 
         // currently we don't use any user data
@@ -110,50 +191,28 @@ impl DOCAMmap {
         let ret = unsafe { ffi::doca_mmap_create(null_ptr, &mut pool as *mut _) };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
-        let mut res = Self {
+        let res = Self {
             inner: unsafe { NonNull::new_unchecked(pool) },
-            ctx: Vec::new(),
-            ok: true,
+            ctx: Mutex::new(Vec::new()),
+            state: Mutex::new(MmapState::Created),
+            owned: Mutex::new(Vec::new()),
+            closed: false,
+            track: TrackId::new("DOCAMmap"),
         };
-        res.set_max_chunks(DOCA_MMAP_CHUNK_SIZE)?;
+        res.set_max_chunks(max_chunks)?;
+        res.set_permissions(permissions)?;
 
         res.start()?;
         Ok(res)
     }
 
-    // TBD
-    // pub fn new_with_arg() {
-    //     unimplemented!();
-    // }
-
-    /// Return the inner pointer of the memory map object.
-    #[inline]
-    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_mmap {
-        self.inner.as_ptr()
-    }
+    // Create the mmap of the remote memory from its export descriptor
+    fn new_from_export(desc: &[u8], dev: &Arc<DevContext>) -> DOCAResult<Self> {
+        debug_assert!(!desc.is_empty(), "the export descriptor is empty");
 
-    /// Creates a memory map object representing the **remote** memory.
-    /// It should be bound to a `DevContext`.
-    ///
-    /// Note that it is a remote device, so the usage should not be mixed with the local device.
-    /// The created object not backed by local memory.
-    ///
-    /// Limitation: Can only support mmap consisting of a single chunk.
-    ///
-    /// Return values
-    /// - DOCA_SUCCESS - in case of success. doca_error code - in case of failure:
-    /// - DOCA_ERROR_INVALID_VALUE - if an invalid input had been received or internal error. The following errors are internal and will occur if failed to produce new mmap from export descriptor:
-    /// - DOCA_ERROR_NO_MEMORY - if internal memory allocation failed.
-    /// - DOCA_ERROR_NOT_SUPPORTED - device missing create from export capability.
-    /// - DOCA_ERROR_NOT_PERMITTED
-    /// - DOCA_ERROR_DRIVER
-    ///
-    /// TODO: describe the input
-    ///
-    pub fn new_from_export(desc_buffer: RawPointer, dev: &Arc<DevContext>) -> DOCAResult<Self> {
         let mut pool: *mut ffi::doca_mmap = std::ptr::null_mut();
         // currently we don't use any user data
         let null_ptr: *mut ffi::doca_data = std::ptr::null_mut();
@@ -161,40 +220,97 @@ impl DOCAMmap {
         let ret = unsafe {
             ffi::doca_mmap_create_from_export(
                 null_ptr,
-                desc_buffer.inner.as_ptr(),
-                desc_buffer.payload,
+                desc.as_ptr() as *const c_void,
+                desc.len(),
                 dev.inner_ptr(),
                 &mut pool as *mut _,
             )
         };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(Self {
             inner: unsafe { NonNull::new_unchecked(pool) },
-            ctx: vec![dev.clone()],
-            ok: false,
+            ctx: Mutex::new(vec![dev.clone()]),
+            state: Mutex::new(MmapState::Imported),
+            owned: Mutex::new(Vec::new()),
+            closed: false,
+            track: TrackId::new("DOCAMmap"),
         })
     }
 
-    /// Export the **local mmap** information to a buffer.
-    /// This buffer can be used by remote to create a new mmap,
-    /// see the above `new_from_export`.
+    /// Deregister the devices and destroy the memory map object,
+    /// reporting the failure that dropping it would only log.
     ///
-    /// Input:
-    /// - dev_index: the index of the local device that the mmap is registered on.
+    /// On failure, the memory map object is leaked.
+    ///
+    /// # Errors
     ///
-    pub fn export(&mut self, dev_index: usize) -> DOCAResult<RawPointer> {
+    ///  - `DOCA_ERROR_IN_USE`: the mmap is still referenced, e.g., by its buffers.
+    ///  Only this reference is dropped.
+    ///
+    pub fn close(self: Arc<Self>) -> DOCAResult<()> {
+        let mut this = Arc::try_unwrap(self).map_err(|_| DocaError::InUse)?;
+        this.closed = true;
+        this.teardown().inspect_err(|_| this.leak_owned())
+    }
+
+    // The SDK may still access the memory of a mmap failing to be destroyed
+    fn leak_owned(&mut self) {
+        let owned = self.owned.get_mut().unwrap_or_else(|e| e.into_inner());
+        std::mem::forget(std::mem::take(owned));
+    }
+
+    fn teardown(&mut self) -> DOCAResult<()> {
+        let ctx = self.ctx.get_mut().unwrap_or_else(|e| e.into_inner());
+
+        // Check whether the device should be removed
+        if *self.state.get_mut().unwrap_or_else(|e| e.into_inner()) == MmapState::Started {
+            for dev in ctx.iter() {
+                let ret = unsafe { ffi::doca_mmap_dev_rm(self.inner.as_ptr(), dev.inner_ptr()) };
+                if ret != doca_error::DOCA_SUCCESS {
+                    return Err(ret.into());
+                }
+            }
+        }
+
+        ctx.clear();
+        let ret = unsafe { ffi::doca_mmap_destroy(self.inner.as_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
+    /// Return the lifecycle state of the memory map object.
+    #[inline]
+    pub fn state(&self) -> MmapState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Check whether the memory map object is registered on (or bound to) the device.
+    pub fn has_device(&self, dev: &Arc<DevContext>) -> bool {
+        self.ctx
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|d| Arc::ptr_eq(d, dev))
+    }
+
+    /// Return the inner pointer of the memory map object.
+    #[inline]
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_mmap {
+        self.inner.as_ptr()
+    }
+
+    // Export the mmap for the device, with the state locked by the caller
+    fn export_to(&self, dev: &DevContext) -> DOCAResult<ExportDescriptor> {
         let len: usize = 0;
         let len_ptr = &len as *const usize as *mut usize;
 
         let mut export_desc: *mut c_void = std::ptr::null_mut();
-        let dev = self
-            .ctx
-            .get(dev_index)
-            .ok_or(doca_error::DOCA_ERROR_INVALID_VALUE)?;
 
         let ret = unsafe {
             ffi::doca_mmap_export(
@@ -206,40 +322,230 @@ impl DOCAMmap {
         };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
-        self.ok = false;
-
-        Ok(RawPointer {
-            inner: NonNull::new(export_desc).ok_or(DOCAError::DOCA_ERROR_INVALID_VALUE)?,
+        debug_event!(mmap = ?self.inner, len, "mmap exported");
+        let raw = RawPointer {
+            inner: NonNull::new(export_desc).ok_or(DocaError::InvalidValue)?,
             payload: len,
-        })
+        };
+        Ok(unsafe { ExportDescriptor::from_sdk(raw) })
+    }
+
+    /// start the DOCA mmap
+    /// Allows execution of different operations on the mmap.
+    ///
+    fn start(&self) -> DOCAResult<()> {
+        let mut state = self.lock_state(&[MmapState::Created])?;
+
+        let ret = unsafe { ffi::doca_mmap_start(self.inner_ptr()) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        *state = MmapState::Started;
+        Ok(())
+    }
+
+    /// Set a new max number of chunks to populate in a DOCA Memory Map.
+    /// Note: once a memory map object has been first started this functionality will not be available.
+    ///
+    fn set_max_chunks(&self, num: u32) -> DOCAResult<()> {
+        let _state = self.lock_state(&[MmapState::Created])?;
+
+        let ret = unsafe { ffi::doca_mmap_set_max_num_chunks(self.inner_ptr(), num) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(())
+    }
+
+    /// Set the access permissions of the memory populated in the mmap.
+    /// Note: it is only available before the memory map object is started.
+    ///
+    fn set_permissions(&self, permissions: MmapPermissions) -> DOCAResult<()> {
+        let _state = self.lock_state(&[MmapState::Created])?;
+
+        let ret = unsafe { ffi::doca_mmap_set_permissions(self.inner_ptr(), permissions.bits()) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(())
+    }
+
+    /// Lock the state and check whether the operation is permitted in it.
+    /// The operation should hold the returned guard until it finishes.
+    #[inline]
+    fn lock_state(&self, permitted: &[MmapState]) -> DOCAResult<MutexGuard<'_, MmapState>> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if !permitted.contains(&*state) {
+            return Err(DocaError::BadState);
+        }
+        Ok(state)
+    }
+}
+
+/// A memory map object of the **local** memory, which is populated with the memory
+/// and exported to the remote side.
+///
+/// It is a cheap handle to the shared [`DOCAMmap`], which is dereferenced to
+/// wherever an `&Arc<DOCAMmap>` is expected.
+#[derive(Clone)]
+pub struct LocalMmap {
+    handle: Arc<DOCAMmap>,
+}
+
+impl Deref for LocalMmap {
+    type Target = Arc<DOCAMmap>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+impl LocalMmap {
+    /// Allocates a default mmap with default/unset attributes.
+    /// This function should be called at server side.
+    ///
+    /// # Note
+    ///   The default constructor will create a memory pool with maximum 64 chunks,
+    ///   readable and writable by the local device only.
+    ///   See [`builder`](Self::builder) for the other attributes.
+    ///
+    /// Return values
+    /// - DOCA_SUCCESS - in case of success. doca_error code - in case of failure:
+    /// - DOCA_ERROR_INVALID_VALUE - if an invalid input had been received.
+    /// - DOCA_ERROR_NO_MEMORY - failed to alloc doca_mmap.
+    ///
+    pub fn new() -> DOCAResult<Self> {
+        Self::builder().build()
+    }
+
+    /// Create a builder to set the attributes of the mmap before it is started.
+    pub fn builder() -> LocalMmapBuilder {
+        LocalMmapBuilder::new()
+    }
+
+    /// Deregister the devices and destroy the memory map object,
+    /// reporting the failure that dropping it would only log.
+    ///
+    /// On failure, the memory map object is leaked.
+    /// Return `DOCA_ERROR_IN_USE` if the handle is still shared, e.g., by a buffer.
+    pub fn close(self) -> DOCAResult<()> {
+        self.handle.close()
+    }
+
+    /// Export the **local mmap** information to a buffer.
+    /// This buffer can be used by remote to create a new mmap,
+    /// see [`RemoteMmap::from_export`].
+    ///
+    /// Input:
+    /// - dev_index: the index of the local device that the mmap is registered on.
+    ///
+    /// The descriptor memory is allocated by the SDK, and freed when the returned
+    /// [`ExportDescriptor`] is dropped.
+    pub fn export(&self, dev_index: usize) -> DOCAResult<ExportDescriptor> {
+        self.export_inner(dev_index)
+    }
+
+    /// Same as [`export`](Self::export), but check that the process runs on the host first,
+    /// since the DPU imports the memory exported by the host, not the other way around.
+    ///
+    /// Return `DOCA_ERROR_NOT_PERMITTED` if the process runs on the DPU, see [`runtime_role`].
+    ///
+    /// [`runtime_role`]: crate::device::runtime_role
+    pub fn export_from_host(&self, dev_index: usize) -> DOCAResult<ExportDescriptor> {
+        expect_role(Role::Host)?;
+        self.export_inner(dev_index)
+    }
+
+    /// Export the **local mmap** for every device it is registered on, in the order
+    /// of the device indexes, so one mmap can be shared through several devices with one call.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if no device is registered,
+    /// or any error of [`export`](Self::export).
+    pub fn export_all(&self) -> DOCAResult<Vec<ExportDescriptor>> {
+        let mut state = self
+            .handle
+            .lock_state(&[MmapState::Started, MmapState::Exported])?;
+        let ctx = self.handle.ctx.lock().unwrap_or_else(|e| e.into_inner());
+        if ctx.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let descs = ctx
+            .iter()
+            .map(|dev| self.handle.export_to(dev))
+            .collect::<DOCAResult<Vec<_>>>()?;
+
+        *state = MmapState::Exported;
+        Ok(descs)
+    }
+
+    /// Same as [`export`](Self::export), but copy the descriptor out of the SDK-owned memory,
+    /// so it can be sent without handling the pointer and the length, see [`MmapExport`].
+    pub fn export_bytes(&self, dev_index: usize) -> DOCAResult<Vec<u8>> {
+        Ok(self.export_inner(dev_index)?.as_bytes().to_vec())
+    }
+
+    fn export_inner(&self, dev_index: usize) -> DOCAResult<ExportDescriptor> {
+        let mut state = self
+            .handle
+            .lock_state(&[MmapState::Started, MmapState::Exported])?;
+
+        let ctx = self.handle.ctx.lock().unwrap_or_else(|e| e.into_inner());
+        let dev = ctx.get(dev_index).ok_or(DocaError::InvalidValue)?;
+        let desc = self.handle.export_to(dev)?;
+
+        *state = MmapState::Exported;
+        Ok(desc)
     }
 
     /// Register DOCA memory map on a given device.
-    pub fn add_device(&mut self, dev: &Arc<DevContext>) -> DOCAResult<usize> {
+    ///
+    /// Return `DOCA_ERROR_BAD_STATE` if the mmap is exported.
+    pub fn add_device(&self, dev: &Arc<DevContext>) -> DOCAResult<usize> {
+        let _state = self
+            .handle
+            .lock_state(&[MmapState::Created, MmapState::Started])?;
+
         let ret = unsafe { ffi::doca_mmap_dev_add(self.inner_ptr(), dev.inner_ptr()) };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
-        self.ctx.push(dev.clone());
-        Ok(self.ctx.len() - 1)
+        let mut ctx = self.handle.ctx.lock().unwrap_or_else(|e| e.into_inner());
+        ctx.push(dev.clone());
+        Ok(ctx.len() - 1)
     }
 
     /// Deregister given device from DOCA memory map.
     /// Notice that, the given index from `add_device`
     /// will change after the user calls the function.
-    pub fn rm_device(&self, _dev_idx: usize) -> DOCAResult<()> {
-        let ret =
-            unsafe { ffi::doca_mmap_dev_rm(self.inner_ptr(), self.ctx[_dev_idx].inner_ptr()) };
+    ///
+    /// Return `DOCA_ERROR_BAD_STATE` if the mmap is exported.
+    pub fn rm_device(&self, dev_idx: usize) -> DOCAResult<()> {
+        let _state = self
+            .handle
+            .lock_state(&[MmapState::Created, MmapState::Started])?;
+
+        let mut ctx = self.handle.ctx.lock().unwrap_or_else(|e| e.into_inner());
+        let dev = ctx.get(dev_idx).ok_or(DocaError::InvalidValue)?;
+
+        let ret = unsafe { ffi::doca_mmap_dev_rm(self.inner_ptr(), dev.inner_ptr()) };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
+        ctx.remove(dev_idx);
         Ok(())
     }
 
@@ -248,7 +554,16 @@ impl DOCAMmap {
     ///
     /// The memory can be used for DMA for all the contexts already in the mmap.
     ///
-    pub fn populate(&self, mr: RawPointer) -> DOCAResult<()> {
+    /// Return `DOCA_ERROR_BAD_STATE` if the mmap is exported.
+    ///
+    pub fn populate(&self, mr: impl Into<RawPointer>) -> DOCAResult<()> {
+        let mr = mr.into();
+        let _state = self
+            .handle
+            .lock_state(&[MmapState::Created, MmapState::Started])?;
+
+        debug_assert!(mr.payload > 0, "the populated memory is empty");
+
         let null_opaque: *mut c_void = std::ptr::null_mut::<c_void>();
         let ret = unsafe {
             doca_mmap_populate(
@@ -262,38 +577,443 @@ impl DOCAMmap {
         };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err(ret.into());
         }
 
         Ok(())
     }
-}
 
-impl DOCAMmap {
-    /// start the DOCA mmap
-    /// Allows execution of different operations on the mmap.
+    /// Add the memory to DOCA memory map, like [`LocalMmap::populate`], and take the
+    /// ownership of it, so it is freed only after the mmap is destroyed.
     ///
-    fn start(&self) -> DOCAResult<()> {
-        let ret = unsafe { ffi::doca_mmap_start(self.inner_ptr()) };
+    /// Return the populated range, which is valid as long as the mmap,
+    /// e.g., for [`DOCARegisteredMemory::new_populated`](crate::DOCARegisteredMemory::new_populated).
+    ///
+    /// Return `DOCA_ERROR_BAD_STATE` if the mmap is exported,
+    /// or `DOCA_ERROR_INVALID_VALUE` if `data` is empty.
+    ///
+    pub fn populate_owned(&self, data: Box<[u8]>) -> DOCAResult<RawPointer> {
+        if data.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+        self.populate_owned_memory(OwnedMemory::Boxed(data))
+    }
+
+    // Populate the memory owned by the crate, and keep it until the mmap is destroyed.
+    // The memory is freed on failure, since the mmap doesn't hold it.
+    pub(crate) fn populate_owned_memory(&self, mut owned: OwnedMemory) -> DOCAResult<RawPointer> {
+        // moving the memory doesn't move the allocation it points to
+        let mr = owned.raw_pointer();
+        self.populate(mr)?;
+        self.handle
+            .owned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(owned);
+        Ok(mr)
+    }
+
+    /// Add memory range to DOCA memory map, like [`LocalMmap::populate`],
+    /// with a callback invoked once the SDK no longer uses the memory range,
+    /// e.g., to release the memory.
+    ///
+    /// The callback is called through [`crate::callback`] shims,
+    /// so a panic inside it is caught and recorded instead of unwinding into DOCA.
+    /// If populating fails, the callback is dropped without being called.
+    ///
+    /// Return `DOCA_ERROR_BAD_STATE` if the mmap is exported.
+    ///
+    pub fn populate_with_free_cb<F>(&self, mr: RawPointer, free_cb: F) -> DOCAResult<()>
+    where
+        F: FnOnce(RawPointer) + Send + 'static,
+    {
+        let _state = self
+            .handle
+            .lock_state(&[MmapState::Created, MmapState::Started])?;
+
+        debug_assert!(mr.payload > 0, "the populated memory is empty");
+
+        let opaque = Box::into_raw(Box::new(free_cb)) as *mut c_void;
+        let ret = unsafe {
+            doca_mmap_populate(
+                self.inner_ptr(),
+                mr.inner.as_ptr(),
+                mr.payload,
+                page_size::get(),
+                Some(free_trampoline::<F>),
+                opaque,
+            )
+        };
 
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            // the SDK doesn't take the callback, reclaim it
+            drop(unsafe { Box::from_raw(opaque as *mut F) });
+            return Err(ret.into());
         }
 
         Ok(())
     }
+}
+
+/// Builder of a [`LocalMmap`], for the attributes that can't be changed after it is started.
+///
+/// ``` rust, no_run
+/// use doca::memory::{LocalMmap, MmapPermissions};
+///
+/// // export the memory to the DPU with DMA-read-only semantics
+/// let mmap = LocalMmap::builder()
+///     .permissions(MmapPermissions::LOCAL_READ_WRITE | MmapPermissions::PCI_READ_ONLY)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct LocalMmapBuilder {
+    max_chunks: u32,
+    permissions: MmapPermissions,
+}
+
+impl LocalMmapBuilder {
+    /// Create a builder with the attributes of [`LocalMmap::new`]
+    pub fn new() -> Self {
+        Self {
+            max_chunks: DOCA_MMAP_CHUNK_SIZE,
+            permissions: MmapPermissions::default(),
+        }
+    }
 
-    /// Set a new max number of chunks to populate in a DOCA Memory Map.
-    /// Note: once a memory map object has been first started this functionality will not be available.
+    /// Set the max number of chunks populated in the mmap, 64 by default
+    pub fn max_chunks(mut self, num: u32) -> Self {
+        self.max_chunks = num;
+        self
+    }
+
+    /// Set the access permissions of the populated memory,
+    /// [`MmapPermissions::LOCAL_READ_WRITE`] by default
+    pub fn permissions(mut self, permissions: MmapPermissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Create and start the mmap.
     ///
-    fn set_max_chunks(&mut self, num: u32) -> DOCAResult<()> {
-        let ret = unsafe { ffi::doca_mmap_set_max_num_chunks(self.inner_ptr(), num) };
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: the max number of chunks is 0,
+    ///  or the permissions grant both [`MmapPermissions::PCI_READ_ONLY`] and
+    ///  [`MmapPermissions::PCI_READ_WRITE`].
+    ///  - `DOCA_ERROR_NO_MEMORY`: failed to alloc doca_mmap.
+    ///  - `DOCA_ERROR_NOT_SUPPORTED`: the permissions are not supported by the SDK.
+    ///
+    pub fn build(self) -> DOCAResult<LocalMmap> {
+        let pci = MmapPermissions::PCI_READ_ONLY | MmapPermissions::PCI_READ_WRITE;
+        if self.max_chunks == 0 || self.permissions.contains(pci) {
+            return Err(DocaError::InvalidValue);
+        }
 
-        if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+        Ok(LocalMmap {
+            handle: Arc::new(DOCAMmap::new_local(self.max_chunks, self.permissions)?),
+        })
+    }
+}
+
+impl Default for LocalMmapBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Called by DOCA with the opaque created in `populate_with_free_cb`
+unsafe extern "C" fn free_trampoline<F: FnOnce(RawPointer)>(
+    addr: *mut c_void,
+    len: usize,
+    opaque: *mut c_void,
+) {
+    let free_cb = Box::from_raw(opaque as *mut F);
+    crate::callback::guard("mmap free callback", move || {
+        if let Some(inner) = NonNull::new(addr) {
+            free_cb(RawPointer {
+                inner,
+                payload: len,
+            })
         }
+    });
+}
 
-        Ok(())
+/// A memory map object representing the **remote** memory, created from
+/// the export descriptor of a [`LocalMmap`] on the other side.
+///
+/// It is not backed by local memory, so it can't be populated or exported:
+///
+/// ``` rust, compile_fail
+/// use doca::{RawPointer, RemoteMmap};
+///
+/// fn misuse(remote_mmap: &RemoteMmap, region: RawPointer) {
+///     remote_mmap.populate(region).unwrap();
+/// }
+/// ```
+///
+/// Like [`LocalMmap`], it is a cheap handle to the shared [`DOCAMmap`].
+#[derive(Clone)]
+pub struct RemoteMmap {
+    handle: Arc<DOCAMmap>,
+}
+
+impl Deref for RemoteMmap {
+    type Target = Arc<DOCAMmap>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+impl RemoteMmap {
+    /// Creates a memory map object representing the **remote** memory.
+    /// It should be bound to a `DevContext`.
+    ///
+    /// Limitation: Can only support mmap consisting of a single chunk.
+    ///
+    /// Return values
+    /// - DOCA_SUCCESS - in case of success. doca_error code - in case of failure:
+    /// - DOCA_ERROR_INVALID_VALUE - if an invalid input had been received or internal error. The following errors are internal and will occur if failed to produce new mmap from export descriptor:
+    /// - DOCA_ERROR_NO_MEMORY - if internal memory allocation failed.
+    /// - DOCA_ERROR_NOT_SUPPORTED - device missing create from export capability.
+    /// - DOCA_ERROR_NOT_PERMITTED
+    /// - DOCA_ERROR_DRIVER
+    ///
+    /// `desc_buffer` is the descriptor returned by [`LocalMmap::export`] on the other side,
+    /// e.g., [`LoadedInfo::export_desc`](crate::LoadedInfo::export_desc).
+    ///
+    pub fn from_export(desc: &ExportDescriptor, dev: &Arc<DevContext>) -> DOCAResult<Self> {
+        Ok(Self {
+            handle: Arc::new(DOCAMmap::new_from_export(desc.as_bytes(), dev)?),
+        })
+    }
+
+    /// Same as [`from_export`](Self::from_export), but check that the process runs
+    /// on the DPU first, since the export descriptor is created by the host.
+    ///
+    /// Return `DOCA_ERROR_NOT_PERMITTED` if the process runs on the host, see [`runtime_role`].
+    ///
+    /// [`runtime_role`]: crate::device::runtime_role
+    pub fn from_export_on_dpu(desc: &ExportDescriptor, dev: &Arc<DevContext>) -> DOCAResult<Self> {
+        expect_role(Role::Dpu)?;
+        Self::from_export(desc, dev)
+    }
+
+    /// Same as [`from_export`](Self::from_export), but with the descriptor returned by
+    /// [`LocalMmap::export_bytes`], e.g., [`MmapExport::desc`].
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `desc` is empty, or any error of `from_export`.
+    pub fn from_export_bytes(desc: &[u8], dev: &Arc<DevContext>) -> DOCAResult<Self> {
+        if desc.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+        Ok(Self {
+            handle: Arc::new(DOCAMmap::new_from_export(desc, dev)?),
+        })
+    }
+
+    /// Create the memory map objects from the export descriptors created by
+    /// [`LocalMmap::export_all`], the `i`-th one bound to the `i`-th device,
+    /// e.g., to reach the same host memory through several DPU ports.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the numbers of the descriptors and the devices differ,
+    /// or any error of [`from_export`](Self::from_export).
+    pub fn from_exports(
+        desc_buffers: &[ExportDescriptor],
+        devs: &[Arc<DevContext>],
+    ) -> DOCAResult<Vec<Self>> {
+        if desc_buffers.len() != devs.len() {
+            return Err(DocaError::InvalidValue);
+        }
+
+        desc_buffers
+            .iter()
+            .zip(devs)
+            .map(|(desc, dev)| Self::from_export(desc, dev))
+            .collect()
+    }
+
+    /// Destroy the memory map object, reporting the failure that dropping it would only log.
+    ///
+    /// On failure, the memory map object is leaked.
+    /// Return `DOCA_ERROR_IN_USE` if the handle is still shared, e.g., by a buffer.
+    pub fn close(self) -> DOCAResult<()> {
+        self.handle.close()
+    }
+}
+
+/// The export descriptor of a mmap, which owns its memory and frees it when dropped.
+///
+/// It is either allocated by the SDK on [`LocalMmap::export`], or received from the other side,
+/// e.g., by [`load_config`](crate::load_config), and only needs to live until
+/// the [`RemoteMmap`] is created from it.
+///
+/// With the `zeroize` feature, the memory is wiped before it is freed.
+pub struct ExportDescriptor {
+    raw: RawPointer,
+    owner: DescOwner,
+}
+
+// Who allocated the memory of the descriptor, i.e., how to free it
+enum DescOwner {
+    // `doca_mmap_export`, which allocates it with `malloc`
+    Sdk,
+    // a boxed slice leaked into the raw pointer
+    Boxed,
+}
+
+impl ExportDescriptor {
+    // Take the ownership of the descriptor returned by `doca_mmap_export`
+    unsafe fn from_sdk(raw: RawPointer) -> Self {
+        Self {
+            raw,
+            owner: DescOwner::Sdk,
+        }
+    }
+
+    /// Get the length of the descriptor
+    pub fn len(&self) -> usize {
+        self.raw.payload
+    }
+
+    /// Check whether the descriptor is empty, which can't create a [`RemoteMmap`]
+    pub fn is_empty(&self) -> bool {
+        self.raw.payload == 0
+    }
+
+    /// Get the content of the descriptor, e.g., to send it to the other side
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(self.raw.inner.as_ptr() as *const u8, self.raw.payload)
+        }
+    }
+
+    /// Get the representation passed to the SDK,
+    /// which is only valid while the descriptor is alive.
+    pub fn as_raw(&self) -> RawPointer {
+        self.raw
+    }
+}
+
+impl From<Box<[u8]>> for ExportDescriptor {
+    fn from(desc: Box<[u8]>) -> Self {
+        let payload = desc.len();
+        Self {
+            raw: RawPointer {
+                inner: NonNull::new(Box::into_raw(desc) as *mut c_void).unwrap(),
+                payload,
+            },
+            owner: DescOwner::Boxed,
+        }
+    }
+}
+
+impl From<Vec<u8>> for ExportDescriptor {
+    fn from(desc: Vec<u8>) -> Self {
+        Self::from(desc.into_boxed_slice())
+    }
+}
+
+impl Drop for ExportDescriptor {
+    fn drop(&mut self) {
+        let ptr = self.raw.inner.as_ptr() as *mut u8;
+        let desc = unsafe { std::slice::from_raw_parts_mut(ptr, self.raw.payload) };
+
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut *desc);
+
+        match self.owner {
+            DescOwner::Sdk => unsafe { libc::free(ptr as *mut c_void) },
+            DescOwner::Boxed => drop(unsafe { Box::from_raw(desc as *mut [u8]) }),
+        }
+    }
+}
+
+impl std::fmt::Debug for ExportDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExportDescriptor")
+            .field("len", &self.raw.payload)
+            .finish()
+    }
+}
+
+// The memory is owned by the struct and never written through a shared reference
+unsafe impl Send for ExportDescriptor {}
+unsafe impl Sync for ExportDescriptor {}
+
+/// A region of an exported mmap, i.e., its address and length on the exporting side
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionInfo {
+    /// The address of the region
+    pub addr: u64,
+    /// The length of the region
+    pub len: usize,
+}
+
+impl From<RawPointer> for RegionInfo {
+    fn from(ptr: RawPointer) -> Self {
+        Self {
+            addr: ptr.inner.as_ptr() as u64,
+            len: ptr.payload,
+        }
+    }
+}
+
+impl TryFrom<RegionInfo> for RawPointer {
+    type Error = DocaError;
+
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the address is null
+    fn try_from(region: RegionInfo) -> DOCAResult<Self> {
+        Ok(Self {
+            inner: NonNull::new(region.addr as *mut c_void).ok_or(DocaError::InvalidValue)?,
+            payload: region.len,
+        })
+    }
+}
+
+/// The export descriptor of a [`LocalMmap`] together with the regions of it to access,
+/// in a form that can be (de)serialized with serde, e.g., to be sent over gRPC or a socket.
+///
+/// ``` rust, no_run
+/// use doca::memory::{LocalMmap, MmapExport, RemoteMmap};
+///
+/// # fn run(mmap: &LocalMmap, region: doca::RawPointer, dev: &std::sync::Arc<doca::DevContext>) {
+/// // the exporting side
+/// let export = MmapExport::new(mmap.export_bytes(0).unwrap(), &[region]);
+/// let msg = serde_json::to_vec(&export).unwrap();
+///
+/// // the importing side
+/// let export: MmapExport = serde_json::from_slice(&msg).unwrap();
+/// let remote_mmap = RemoteMmap::from_export_bytes(&export.desc, dev).unwrap();
+/// let regions = export.raw_regions().unwrap();
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MmapExport {
+    /// The descriptor returned by [`LocalMmap::export_bytes`]
+    pub desc: Vec<u8>,
+    /// The regions in the mmap, in the order they are exported
+    pub regions: Vec<RegionInfo>,
+}
+
+impl MmapExport {
+    /// Pack the descriptor and the regions
+    pub fn new(desc: Vec<u8>, regions: &[RawPointer]) -> Self {
+        Self {
+            desc,
+            regions: regions.iter().map(|r| RegionInfo::from(*r)).collect(),
+        }
+    }
+
+    /// Convert the regions into [`RawPointer`]s, to create the buffers of the remote mmap,
+    /// see [`DOCARegisteredMemory::new_from_remote`](crate::DOCARegisteredMemory::new_from_remote).
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the address of a region is null.
+    pub fn raw_regions(&self) -> DOCAResult<Vec<RawPointer>> {
+        self.regions
+            .iter()
+            .map(|r| RawPointer::try_from(*r))
+            .collect()
     }
 }
 
@@ -308,7 +1028,7 @@ mod tests {
 
         // use the first device found
         let device_ctx = devices().unwrap().get(0).unwrap().open().unwrap();
-        let mut doca_mmap = DOCAMmap::new().unwrap();
+        let doca_mmap = LocalMmap::new().unwrap();
         doca_mmap.add_device(&device_ctx).unwrap();
 
         let test_len = 1024;
@@ -321,15 +1041,31 @@ mod tests {
         doca_mmap.populate(mr).unwrap();
     }
 
+    // Test show that a panic in the free callback doesn't escape the trampoline
+    #[test]
+    fn test_free_cb_panic_is_caught() {
+        use super::free_trampoline;
+        use crate::callback::take_panics;
+        use core::ffi::c_void;
+
+        let cb: fn(crate::RawPointer) = |mr| panic!("free {} bytes", mr.payload);
+        let opaque = Box::into_raw(Box::new(cb)) as *mut c_void;
+        let mut buffer = vec![0u8; 16];
+        unsafe { free_trampoline::<fn(crate::RawPointer)>(buffer.as_mut_ptr() as _, 16, opaque) };
+
+        assert!(take_panics().iter().any(|p| p.message == "free 16 bytes"));
+    }
+
     // Test show that the `rm_device` is forbidden on a exported mmap
     #[test]
     fn test_mmap_rm_device() {
+        use crate::memory::MmapState;
         use crate::*;
         use std::ptr::NonNull;
 
         // use the first device found
         let device_ctx = devices().unwrap().get(0).unwrap().open().unwrap();
-        let mut doca_mmap = DOCAMmap::new().unwrap();
+        let doca_mmap = LocalMmap::new().unwrap();
         let dev_idx = doca_mmap.add_device(&device_ctx).unwrap();
 
         let test_len = 1024;
@@ -343,7 +1079,122 @@ mod tests {
         doca_mmap.populate(mr).unwrap();
 
         let _ = doca_mmap.export(dev_idx).unwrap();
+        assert_eq!(doca_mmap.state(), MmapState::Exported);
 
         assert!(!doca_mmap.rm_device(dev_idx).is_ok());
+        assert_eq!(doca_mmap.add_device(&device_ctx), Err(DocaError::BadState));
+    }
+
+    // Test show that the permissions are checked before the mmap is created
+    #[test]
+    fn test_mmap_permissions() {
+        use crate::memory::{LocalMmapBuilder, MmapPermissions};
+        use crate::*;
+
+        let perms = MmapPermissions::LOCAL_READ_WRITE | MmapPermissions::PCI_READ_ONLY;
+        assert_eq!(perms.bits(), 0b10001);
+        assert!(perms.contains(MmapPermissions::PCI_READ_ONLY));
+        assert!(!perms.contains(MmapPermissions::PCI_READ_WRITE));
+        assert_eq!(
+            MmapPermissions::default(),
+            MmapPermissions::LOCAL_READ_WRITE
+        );
+
+        let conflicting = MmapPermissions::PCI_READ_ONLY | MmapPermissions::PCI_READ_WRITE;
+        assert_eq!(
+            LocalMmap::builder().permissions(conflicting).build().err(),
+            Some(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            LocalMmapBuilder::new().max_chunks(0).build().err(),
+            Some(DocaError::InvalidValue)
+        );
+    }
+
+    // Test show that a mmap is only closed by its last handle
+    #[test]
+    fn test_mmap_close_shared() {
+        use crate::*;
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let other = doca_mmap.clone();
+        assert_eq!(other.close(), Err(DocaError::InUse));
+        assert_eq!(doca_mmap.state(), MmapState::Started);
+
+        doca_mmap.close().unwrap();
+    }
+
+    // Test show that one mmap is exported for all its devices
+    #[test]
+    fn test_mmap_export_all() {
+        use crate::*;
+
+        let device_ctx = devices().unwrap().get(0).unwrap().open().unwrap();
+        let doca_mmap = LocalMmap::new().unwrap();
+        assert_eq!(doca_mmap.export_all().err(), Some(DocaError::InvalidValue));
+        doca_mmap.add_device(&device_ctx).unwrap();
+
+        let dpu_buffer = vec![0u8; 1024].into_boxed_slice();
+        doca_mmap
+            .populate(unsafe { RawPointer::from_box(&dpu_buffer) })
+            .unwrap();
+
+        let descs = doca_mmap.export_all().unwrap();
+        assert_eq!(descs.len(), 1);
+        assert_eq!(doca_mmap.state(), MmapState::Exported);
+
+        // each descriptor needs a device to bind to
+        assert_eq!(
+            RemoteMmap::from_exports(&descs, &[]).err(),
+            Some(DocaError::InvalidValue)
+        );
+
+        let desc = doca_mmap.export_bytes(0).unwrap();
+        assert!(!desc.is_empty());
+        assert_eq!(
+            RemoteMmap::from_export_bytes(&[], &device_ctx).err(),
+            Some(DocaError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_mmap_export_serde() {
+        use super::*;
+
+        let buffer = vec![0u8; 64].into_boxed_slice();
+        let region = unsafe { RawPointer::from_box(&buffer) };
+        let export = MmapExport::new(vec![1, 2, 3], &[region, region.slice(8, 16).unwrap()]);
+        assert_eq!(export.regions[1].addr, region.inner.as_ptr() as u64 + 8);
+        assert_eq!(export.regions[1].len, 16);
+
+        let msg = serde_json::to_vec(&export).unwrap();
+        let received: MmapExport = serde_json::from_slice(&msg).unwrap();
+        assert_eq!(received, export);
+
+        let regions = received.raw_regions().unwrap();
+        assert_eq!(regions[0].inner, region.inner);
+        assert_eq!(regions[0].payload, 64);
+
+        let null = RegionInfo { addr: 0, len: 8 };
+        assert!(RawPointer::try_from(null).is_err());
+    }
+
+    #[test]
+    fn test_export_descriptor() {
+        use super::*;
+
+        let desc = ExportDescriptor::from(b"descriptor".to_vec());
+        assert_eq!(desc.len(), 10);
+        assert_eq!(desc.as_bytes(), b"descriptor");
+        assert_eq!(
+            desc.as_raw().inner.as_ptr() as *const u8,
+            desc.as_bytes().as_ptr()
+        );
+
+        // the descriptor can be moved to another thread, and is freed there
+        std::thread::spawn(move || assert!(!desc.is_empty()))
+            .join()
+            .unwrap();
+        assert!(ExportDescriptor::from(Vec::new()).is_empty());
     }
 }
@@ -0,0 +1,192 @@
+//! A growable pool of buffers over several buffer inventories.
+//!
+//! The size of a [`BufferInventory`] is fixed when it is created, which is hard to pick
+//! for a long-running service whose demand is not known at startup.
+//! [`BufferPool`] allocates another inventory of the same size once all of its
+//! inventories are exhausted, and [`BufferPool::shrink`] destroys the extra ones
+//! after their buffers are returned.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::memory::pool::BufferPool;
+//! use doca::{DOCARegisteredMemory, LocalMmap, RawPointer};
+//!
+//! let mmap = LocalMmap::new().unwrap();
+//! let pool = BufferPool::new(16).unwrap();
+//!
+//! let region = vec![0u8; 1024].into_boxed_slice();
+//! let mem = DOCARegisteredMemory::new(&mmap, unsafe { RawPointer::from_box(&region) }).unwrap();
+//!
+//! // the buffer goes back to its inventory when dropped
+//! let buf = pool.alloc(mem).unwrap();
+//! ```
+//!
+
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use crate::memory::buffer::{BufferInventory, DOCABuffer};
+use crate::memory::registered_memory::DOCARegisteredMemory;
+use crate::{DOCAResult, DocaError};
+
+/// A buffer allocated from a [`BufferPool`], returned to its inventory when dropped.
+pub struct PooledBuffer {
+    buf: DOCABuffer,
+}
+
+impl Deref for PooledBuffer {
+    type Target = DOCABuffer;
+
+    fn deref(&self) -> &DOCABuffer {
+        &self.buf
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut DOCABuffer {
+        &mut self.buf
+    }
+}
+
+impl PooledBuffer {
+    /// Take the buffer out, e.g., to set it into a job
+    pub fn into_inner(self) -> DOCABuffer {
+        self.buf
+    }
+}
+
+/// A pool of buffers growing by inventories of `inv_size` buffers (non-thread-safe).
+pub struct BufferPool {
+    inv_size: usize,
+    max_inventories: usize,
+    inventories: RefCell<Vec<Arc<BufferInventory>>>,
+}
+
+impl BufferPool {
+    /// Create a pool with an inventory of `inv_size` buffers, growing without a limit.
+    pub fn new(inv_size: usize) -> DOCAResult<Self> {
+        Self::with_limit(inv_size, usize::MAX)
+    }
+
+    /// Create a pool with an inventory of `inv_size` buffers,
+    /// growing up to `max_inventories` inventories.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `inv_size` or `max_inventories` is zero.
+    pub fn with_limit(inv_size: usize, max_inventories: usize) -> DOCAResult<Self> {
+        if inv_size == 0 || max_inventories == 0 {
+            return Err(DocaError::InvalidValue);
+        }
+
+        Ok(Self {
+            inv_size,
+            max_inventories,
+            inventories: RefCell::new(vec![BufferInventory::new(inv_size)?]),
+        })
+    }
+
+    /// Allocate a buffer over the registered memory,
+    /// creating another inventory if all of the current ones are exhausted.
+    ///
+    /// # Errors
+    ///
+    ///  - `DocaError::InventoryExhausted`: the pool has reached its max number of inventories.
+    ///  - other errors of [`DOCARegisteredMemory::to_buffer`] or [`BufferInventory::new`].
+    ///
+    pub fn alloc(&self, mem: DOCARegisteredMemory) -> DOCAResult<PooledBuffer> {
+        let mut inventories = self.inventories.borrow_mut();
+
+        // try the newest inventory first, which is the most likely to have free buffers
+        let mut mem = mem;
+        for inv in inventories.iter().rev() {
+            match mem.try_to_buffer(inv) {
+                Ok(buf) => return Ok(PooledBuffer { buf }),
+                Err((DocaError::InventoryExhausted, m)) => mem = m,
+                Err((e, _)) => return Err(e),
+            }
+        }
+
+        if inventories.len() >= self.max_inventories {
+            return Err(DocaError::InventoryExhausted);
+        }
+        let inv = BufferInventory::new(self.inv_size)?;
+        inventories.push(inv.clone());
+
+        Ok(PooledBuffer {
+            buf: mem.to_buffer(&inv)?,
+        })
+    }
+
+    /// Destroy the extra inventories whose buffers are all returned.
+    /// The first inventory is always kept.
+    ///
+    /// Return the number of the destroyed inventories.
+    pub fn shrink(&self) -> usize {
+        let mut inventories = self.inventories.borrow_mut();
+        let before = inventories.len();
+
+        // an inventory is only referenced by the pool once its buffers are dropped
+        let mut idx = 0;
+        inventories.retain(|inv| {
+            idx += 1;
+            idx == 1 || Arc::strong_count(inv) > 1
+        });
+        before - inventories.len()
+    }
+
+    /// Get the number of the inventories of the pool
+    pub fn num_inventories(&self) -> usize {
+        self.inventories.borrow().len()
+    }
+
+    /// Get the number of the buffers allocated from the pool and not yet freed
+    pub fn num_used(&self) -> DOCAResult<usize> {
+        self.inventories
+            .borrow()
+            .iter()
+            .map(|inv| inv.num_used())
+            .sum()
+    }
+
+    /// Get the number of the buffers that can be allocated without growing the pool
+    pub fn num_free(&self) -> DOCAResult<usize> {
+        self.inventories
+            .borrow()
+            .iter()
+            .map(|inv| inv.num_free())
+            .sum()
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_buffer_pool_grows() {
+        use super::BufferPool;
+        use crate::*;
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let pool = BufferPool::with_limit(2, 2).unwrap();
+
+        let buffer = vec![0u8; 64].into_boxed_slice();
+        let region = unsafe { RawPointer::from_box(&buffer) };
+        let alloc = || pool.alloc(DOCARegisteredMemory::new(&doca_mmap, region)?);
+
+        let mut bufs: Vec<_> = (0..4).map(|_| alloc().unwrap()).collect();
+        assert_eq!(pool.num_inventories(), 2);
+        assert_eq!(pool.num_used().unwrap(), 4);
+        assert_eq!(alloc().err(), Some(DocaError::InventoryExhausted));
+
+        // the freed buffer is reused before growing
+        bufs.pop();
+        assert!(alloc().is_ok());
+        assert_eq!(pool.num_inventories(), 2);
+
+        // the inventories in use are kept
+        assert_eq!(pool.shrink(), 0);
+        bufs.clear();
+        assert_eq!(pool.shrink(), 1);
+        assert_eq!(pool.num_inventories(), 1);
+        assert_eq!(pool.num_free().unwrap(), 2);
+    }
+}
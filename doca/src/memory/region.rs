@@ -0,0 +1,212 @@
+//! Safe handles of the memory registered into a [`LocalMmap`](crate::LocalMmap).
+//!
+//! A [`RawPointer`] is `Copy` and doesn't borrow the memory it points to, so it is created
+//! with unsafe constructors and can easily outlive the memory. The regions here hold the
+//! borrow or the ownership of the memory instead, and only turn into a [`RawPointer`]
+//! when they are passed to the SDK:
+//!
+//! - [`MemRegion`] borrows a slice, so it can't be created over freed memory.
+//! - [`OwnedRegion`] owns its allocation, and is handed to the mmap when it is registered,
+//! see [`DOCARegisteredMemory::from_owned`], so the memory lives as long as the mmap.
+//!
+//! The functions registering memory, e.g., [`LocalMmap::populate`] and
+//! [`DOCARegisteredMemory::new`], accept a [`MemRegion`] wherever they accept a [`RawPointer`]:
+//!
+//! ```
+//! use doca::memory::region::MemRegion;
+//! use doca::{DOCARegisteredMemory, LocalMmap};
+//!
+//! let mmap = LocalMmap::new().unwrap();
+//! let mut buffer = vec![0u8; 1024];
+//! let mem = DOCARegisteredMemory::new(&mmap, MemRegion::new(&mut buffer).unwrap()).unwrap();
+//! ```
+//!
+//! The borrow of a [`MemRegion`] is only checked when it is created: the registered memory
+//! and the buffers over it don't carry its lifetime, so freeing the memory right after
+//! registering it still compiles. The device still accesses the memory through the mmap,
+//! so the borrowed memory should outlive the buffers over it.
+//!
+//! [`LocalMmap::populate`]: crate::LocalMmap::populate
+//! [`DOCARegisteredMemory::new`]: crate::DOCARegisteredMemory::new
+//! [`DOCARegisteredMemory::from_owned`]: crate::DOCARegisteredMemory::from_owned
+
+use std::alloc::{self, Layout};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use crate::memory::allocator::HugePageBuffer;
+use crate::memory::registered_memory::OwnedMemory;
+use crate::{DOCAResult, DocaError, RawPointer};
+
+/// A non-empty region of the memory borrowed for `'a`,
+/// which is checked when the region is created, not when it is registered
+pub struct MemRegion<'a> {
+    raw: RawPointer,
+    _memory: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> MemRegion<'a> {
+    /// Borrow the `memory`, which the device may write.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `memory` is empty, since DOCA can't register it.
+    pub fn new(memory: &'a mut [u8]) -> DOCAResult<Self> {
+        if memory.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+
+        Ok(Self {
+            raw: RawPointer {
+                inner: NonNull::new(memory.as_mut_ptr() as _).unwrap(),
+                payload: memory.len(),
+            },
+            _memory: PhantomData,
+        })
+    }
+
+    /// Get the length of the region
+    pub fn len(&self) -> usize {
+        self.raw.payload
+    }
+
+    /// Always `false`, since the region is never empty
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Get the sub-region of `len` bytes at `offset`, which reborrows the region,
+    /// so two sub-regions of it can't be held at once.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the sub-region is empty or out of the region.
+    pub fn slice(&mut self, offset: usize, len: usize) -> DOCAResult<MemRegion<'_>> {
+        Ok(MemRegion {
+            raw: self.raw.slice(offset, len)?,
+            _memory: PhantomData,
+        })
+    }
+
+    /// Get the representation passed to the SDK, which no longer tracks the borrow
+    pub fn as_raw(&self) -> RawPointer {
+        self.raw
+    }
+}
+
+impl From<MemRegion<'_>> for RawPointer {
+    fn from(region: MemRegion<'_>) -> Self {
+        region.raw
+    }
+}
+
+/// A non-empty region of the memory owned by the struct,
+/// which is freed only after the mmap it is registered into is destroyed.
+pub struct OwnedRegion(pub(crate) OwnedMemory);
+
+impl OwnedRegion {
+    /// Allocate `len` zeroed bytes from the heap.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `len` is zero.
+    pub fn zeroed(len: usize) -> DOCAResult<Self> {
+        Self::try_from(vec![0u8; len])
+    }
+
+    /// Allocate `len` zeroed bytes aligned to `align`, e.g., the page size.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `len` is zero, or `align` is not a power of two.
+    ///  - `DOCA_ERROR_NO_MEMORY`: failed to allocate the memory.
+    ///
+    pub fn aligned(len: usize, align: usize) -> DOCAResult<Self> {
+        if len == 0 {
+            return Err(DocaError::InvalidValue);
+        }
+        let layout = Layout::from_size_align(len, align).map_err(|_e| DocaError::InvalidValue)?;
+        let ptr =
+            NonNull::new(unsafe { alloc::alloc_zeroed(layout) }).ok_or(DocaError::NoMemory)?;
+
+        Ok(Self(OwnedMemory::Aligned { ptr, layout }))
+    }
+
+    /// Get the length of the region
+    pub fn len(&self) -> usize {
+        self.0.as_slice().len()
+    }
+
+    /// Always `false`, since the region is never empty
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Get the memory of the region
+    pub fn as_slice(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+
+    /// Get the memory of the region mutably, e.g., to fill the source data
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.0.as_mut_slice()
+    }
+
+    /// Borrow the region, e.g., to populate it in another mmap
+    pub fn region(&mut self) -> MemRegion<'_> {
+        MemRegion::new(self.0.as_mut_slice()).expect("the owned region is never empty")
+    }
+}
+
+impl TryFrom<Vec<u8>> for OwnedRegion {
+    type Error = DocaError;
+
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `data` is empty
+    fn try_from(data: Vec<u8>) -> DOCAResult<Self> {
+        if data.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+        Ok(Self(OwnedMemory::Boxed(data.into_boxed_slice())))
+    }
+}
+
+impl From<HugePageBuffer> for OwnedRegion {
+    fn from(buf: HugePageBuffer) -> Self {
+        Self(OwnedMemory::HugePage(buf))
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_mem_region() {
+        use super::*;
+
+        let mut buffer = vec![0u8; 64];
+        let start = buffer.as_ptr();
+        let mut region = MemRegion::new(&mut buffer).unwrap();
+        assert_eq!(region.len(), 64);
+
+        let sub = region.slice(16, 8).unwrap();
+        assert_eq!(
+            sub.as_raw().inner.as_ptr() as *const u8,
+            start.wrapping_add(16)
+        );
+        assert_eq!(RawPointer::from(sub).payload, 8);
+        assert!(region.slice(60, 8).is_err());
+
+        assert!(MemRegion::new(&mut []).is_err());
+    }
+
+    #[test]
+    fn test_owned_region() {
+        use super::*;
+
+        let mut region = OwnedRegion::zeroed(64).unwrap();
+        region.as_mut_slice()[0] = 42;
+        assert_eq!(region.len(), 64);
+        assert_eq!(region.region().as_raw().payload, 64);
+        assert_eq!(region.as_slice()[0], 42);
+
+        let region = OwnedRegion::aligned(8192, 4096).unwrap();
+        assert_eq!(region.as_slice().as_ptr() as usize % 4096, 0);
+        assert!(region.as_slice().iter().all(|b| *b == 0));
+
+        assert!(OwnedRegion::zeroed(0).is_err());
+        assert!(OwnedRegion::aligned(64, 3).is_err());
+        assert!(OwnedRegion::try_from(Vec::new()).is_err());
+    }
+}
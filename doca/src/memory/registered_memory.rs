@@ -5,45 +5,194 @@
 //! It holds the memory region metadata(start address and length) and
 //! the memory map it belongs to.
 //!
+//! The memory can also be owned by the crate, see [`DOCARegisteredMemory::from_owned`],
+//! so it lives as long as the mmap it is populated into.
+//!
+use crate::drop_order::TrackId;
+use crate::memory::allocator::HugePageBuffer;
 use crate::memory::buffer::{BufferInventory, DOCABuffer};
-use crate::memory::DOCAMmap;
-use crate::{DOCAResult, RawPointer};
+use crate::memory::region::OwnedRegion;
+use crate::memory::{DOCAMmap, LocalMmap, RemoteMmap};
+use crate::{DOCAResult, DocaError, RawPointer};
 
 use ffi::doca_error;
+use std::alloc::{self, Layout};
 use std::ptr::NonNull;
 use std::sync::Arc;
 
+// The memory owned by the crate, which is handed to the mmap populating it
+pub(crate) enum OwnedMemory {
+    Boxed(Box<[u8]>),
+    Aligned { ptr: NonNull<u8>, layout: Layout },
+    HugePage(HugePageBuffer),
+}
+
+impl Drop for OwnedMemory {
+    fn drop(&mut self) {
+        // Wipe the memory before it is freed, since the crate is the only owner of it
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(self.as_mut_slice());
+
+        if let OwnedMemory::Aligned { ptr, layout } = self {
+            unsafe { alloc::dealloc(ptr.as_ptr(), *layout) };
+        }
+    }
+}
+
+impl OwnedMemory {
+    pub(crate) fn raw_pointer(&mut self) -> RawPointer {
+        let (ptr, len) = match self {
+            OwnedMemory::Boxed(mem) => (mem.as_mut_ptr(), mem.len()),
+            OwnedMemory::Aligned { ptr, layout } => (ptr.as_ptr(), layout.size()),
+            OwnedMemory::HugePage(buf) => return RawPointer::from(&*buf),
+        };
+        RawPointer {
+            inner: NonNull::new(ptr as *mut _).unwrap(),
+            payload: len,
+        }
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        match self {
+            OwnedMemory::Boxed(mem) => mem,
+            OwnedMemory::Aligned { ptr, layout } => unsafe {
+                std::slice::from_raw_parts(ptr.as_ptr(), layout.size())
+            },
+            OwnedMemory::HugePage(buf) => buf.as_slice(),
+        }
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+        match self {
+            OwnedMemory::Boxed(mem) => mem,
+            OwnedMemory::Aligned { ptr, layout } => unsafe {
+                std::slice::from_raw_parts_mut(ptr.as_ptr(), layout.size())
+            },
+            OwnedMemory::HugePage(buf) => buf.as_mut_slice(),
+        }
+    }
+}
+
 /// Using DOCA memory is a two step process:
-/// 1. populate it with `DOCAMmap::populate`(Note that the remote address in a remote mmap has already been exported)
+/// 1. populate it with [`LocalMmap::populate`] (Note that the remote address in a [`RemoteMmap`] has already been exported)
 /// 2. allocate buffer with a `BufferInventory`.
 ///
 pub struct DOCARegisteredMemory {
     mmap: Arc<DOCAMmap>,
     register_memory: RawPointer,
+    local: bool,
+    // whether the memory is owned by the mmap, so it can be accessed as a slice
+    owned: bool,
 }
 
 impl DOCARegisteredMemory {
-    /// Create a new DOCARegisteredMemory
-    pub fn new(mmap: &Arc<DOCAMmap>, register_memory: RawPointer) -> DOCAResult<Self> {
-        let mmap = mmap.clone();
+    /// Create a new DOCARegisteredMemory, populating the memory,
+    /// e.g., a [`MemRegion`](crate::memory::region::MemRegion), into the mmap.
+    ///
+    /// The registered memory doesn't hold the borrow of a `MemRegion`,
+    /// so the memory should outlive it and the buffers over it.
+    pub fn new(mmap: &LocalMmap, register_memory: impl Into<RawPointer>) -> DOCAResult<Self> {
+        let register_memory = register_memory.into();
         mmap.populate(register_memory)?;
 
         Ok(Self {
-            mmap,
+            mmap: mmap.handle.clone(),
+            register_memory,
+            local: true,
+            owned: false,
+        })
+    }
+
+    /// Wrap a region already populated in the mmap, e.g., by [`LocalMmap::populate_owned`],
+    /// to allocate another buffer over it without populating it again.
+    pub fn new_populated(mmap: &LocalMmap, register_memory: impl Into<RawPointer>) -> Self {
+        Self {
+            mmap: mmap.handle.clone(),
+            register_memory: register_memory.into(),
+            local: true,
+            owned: false,
+        }
+    }
+
+    /// Register the memory of `data`, and hand the ownership of it to the mmap,
+    /// so it is freed only after the mmap is destroyed.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `data` is empty.
+    pub fn new_owned(mmap: &LocalMmap, data: Vec<u8>) -> DOCAResult<Self> {
+        Self::from_owned(mmap, OwnedRegion::try_from(data)?)
+    }
+
+    /// Allocate `len` zeroed bytes aligned to `align`, e.g., the page size,
+    /// and register them as [`new_owned`](Self::new_owned) does.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `len` is zero, or `align` is not a power of two.
+    ///  - `DOCA_ERROR_NO_MEMORY`: failed to allocate the memory.
+    ///
+    pub fn new_aligned(mmap: &LocalMmap, len: usize, align: usize) -> DOCAResult<Self> {
+        Self::from_owned(mmap, OwnedRegion::aligned(len, align)?)
+    }
+
+    /// Register the owned `region`, and hand the ownership of it to the mmap,
+    /// so it is freed only after the mmap is destroyed.
+    pub fn from_owned(mmap: &LocalMmap, region: OwnedRegion) -> DOCAResult<Self> {
+        Self::new_with(mmap, region.0)
+    }
+
+    pub(crate) fn new_with(mmap: &LocalMmap, owned: OwnedMemory) -> DOCAResult<Self> {
+        let register_memory = mmap.populate_owned_memory(owned)?;
+
+        Ok(Self {
+            mmap: mmap.handle.clone(),
             register_memory,
+            local: true,
+            owned: true,
         })
     }
 
+    /// Get the owned memory, `None` if the memory is not owned by the crate.
+    pub fn as_slice(&self) -> Option<&[u8]> {
+        // the memory is kept by the mmap held by the struct
+        self.owned
+            .then(|| unsafe { self.register_memory.as_slice() })
+    }
+
+    /// Get the owned memory mutably, e.g., to fill the source data before the transfer.
+    /// Return `None` if the memory is not owned by the crate.
+    pub fn as_mut_slice(&mut self) -> Option<&mut [u8]> {
+        // the memory is kept by the mmap, and no other struct refers to it
+        self.owned
+            .then(|| unsafe { self.register_memory.as_mut_slice() })
+    }
+
     /// Create a new DOCARegisteredMemory on the remote side
-    pub fn new_from_remote(mmap: &Arc<DOCAMmap>, register_memory: RawPointer) -> DOCAResult<Self> {
+    pub fn new_from_remote(mmap: &RemoteMmap, register_memory: RawPointer) -> DOCAResult<Self> {
         Ok(Self {
-            mmap: mmap.clone(),
+            mmap: mmap.handle.clone(),
             register_memory: register_memory,
+            local: false,
+            owned: false,
         })
     }
 
     /// Allocate a buffer from the registered memory
+    ///
+    /// Return `DocaError::InventoryExhausted` if `inv` has no free buffer left.
     pub fn to_buffer(self, inv: &Arc<BufferInventory>) -> DOCAResult<DOCABuffer> {
+        self.try_to_buffer(inv).map_err(|(e, _)| e)
+    }
+
+    // Like `to_buffer`, but give the memory back on failure, e.g., to retry with another inventory
+    pub(crate) fn try_to_buffer(
+        self,
+        inv: &Arc<BufferInventory>,
+    ) -> Result<DOCABuffer, (DocaError, Self)> {
+        debug_assert!(
+            self.register_memory.get_payload() > 0,
+            "the registered memory is empty"
+        );
+
         let mut buffer: *mut ffi::doca_buf = std::ptr::null_mut();
         let ret = unsafe {
             ffi::doca_buf_inventory_buf_by_args(
@@ -57,15 +206,22 @@ impl DOCARegisteredMemory {
             )
         };
 
+        if ret == doca_error::DOCA_ERROR_NO_MEMORY && inv.num_free() == Ok(0) {
+            return Err((DocaError::InventoryExhausted, self));
+        }
         if ret != doca_error::DOCA_SUCCESS {
-            return Err(ret);
+            return Err((ret.into(), self));
         }
 
         Ok(DOCABuffer {
             inner: unsafe { NonNull::new_unchecked(buffer) },
             head: self.register_memory,
+            local: self.local,
             inv: inv.clone(),
+            closed: false,
+            track: TrackId::new("DOCABuffer"),
             mmap: self.mmap,
+            owned: self.owned,
         })
     }
 
@@ -0,0 +1,93 @@
+//! The fake devices of the in-process implementation of DOCA,
+//! which replaces the SDK with the `mock` feature.
+//!
+//! The devices are named by their PCIe addresses, read from the comma-separated
+//! `DOCA_MOCK_DEVICES` environment variable, or set by [`set_devices`].
+//! Without both, a single device at `03:00.0` is found.
+//! Each of them supports the DMA memcpy jobs, which copy the memory of this process,
+//! and the other engines and the representors return `DOCA_ERROR_NOT_SUPPORTED`.
+//!
+//! ```
+//! use doca::mock;
+//!
+//! mock::set_devices(&["03:00.0", "03:00.1"]).unwrap();
+//! let device = doca::open_device_with_pci("03:00.1").unwrap();
+//! ```
+//!
+//! The export descriptor of a mmap holds the addresses of this process,
+//! so the "remote" mmap created from it only works in the same process.
+
+use crate::{DOCAResult, DocaError};
+use ffi::doca_error;
+
+/// Set the PCIe addresses, e.g., `"03:00.0"`, of the fake devices found by
+/// [`devices`](crate::devices) from now on.
+///
+/// Return `DOCA_ERROR_INVALID_VALUE` if an address is malformed, which keeps the former devices.
+pub fn set_devices(pci_addrs: &[&str]) -> DOCAResult<()> {
+    let ret = ffi::set_devices(pci_addrs);
+    if ret != doca_error::DOCA_SUCCESS {
+        return Err(DocaError::from(ret));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::DocaError;
+
+    #[test]
+    fn test_set_devices() {
+        super::set_devices(&["03:00.0", "b1:00.1"]).unwrap();
+        let device = open_device_with_pci("b1:00.1").unwrap();
+        assert_eq!(device.device().name().unwrap(), "b1:00.1");
+        assert_eq!(device.device().ibdev_name().unwrap(), "mlx5_1");
+        assert_eq!(
+            device.device().link_state().unwrap(),
+            crate::device::LinkState::Up
+        );
+        assert_eq!(device.device().fw_version().unwrap(), "22.35.1012");
+        assert_eq!(device.device().max_mtu().unwrap(), 9978);
+
+        // a malformed address keeps the former devices
+        assert_eq!(super::set_devices(&["b1:00"]), Err(DocaError::InvalidValue));
+        assert!(open_device_with_pci("b1:00.1").is_ok());
+    }
+
+    #[test]
+    #[cfg(not(feature = "doca2"))]
+    fn test_dma_memcpy() {
+        let device = open_device_with_pci("03:00.0").unwrap();
+        let ctx = DOCAContext::new(&DMAEngine::new().unwrap(), vec![device]).unwrap();
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        let inv = BufferInventory::new(2).unwrap();
+
+        let src_buffer = b"hello mock".to_vec().into_boxed_slice();
+        let dst_buffer = vec![0u8; 64].into_boxed_slice();
+        let new_buf = |raw: RawPointer| {
+            DOCARegisteredMemory::new(&doca_mmap, raw)
+                .unwrap()
+                .to_buffer(&inv)
+                .unwrap()
+        };
+
+        let (src, dst) = unsafe {
+            (
+                RawPointer::from_box(&src_buffer),
+                RawPointer::from_box(&dst_buffer),
+            )
+        };
+        let mut job = workq.create_dma_job(new_buf(src), new_buf(dst));
+        job.set_src_data(0, src_buffer.len()).set_dst_offset(8);
+        workq.submit(&mut job).unwrap();
+
+        // the work queue is full until the completion is retrieved
+        assert!(workq.submit(&mut job).is_err());
+        let completion = workq.poll_completion().unwrap();
+        assert!(job.dma_result(&completion).is_success());
+        assert_eq!(&dst_buffer[8..8 + src_buffer.len()], &src_buffer[..]);
+    }
+}
@@ -0,0 +1,39 @@
+//! The types and traits needed by the common DMA workflow.
+//!
+//! ``` rust, no_run
+//! use doca::prelude::*;
+//!
+//! let device = open_device_with_pci("03:00.0").unwrap();
+//! let dma = DMAEngine::new().unwrap();
+//! let ctx = DOCAContext::new(&dma, vec![device.clone()]).unwrap();
+//! let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+//!
+//! let mmap = LocalMmap::new().unwrap();
+//! mmap.add_device(&device).unwrap();
+//! let inv = BufferInventory::new(1024).unwrap();
+//! ```
+//!
+
+#[cfg(not(feature = "doca2"))]
+pub use crate::context::pool::WorkQueuePool;
+#[cfg(not(feature = "doca2"))]
+pub use crate::context::work_queue::{SubmitError, ToBaseJob, UserData, WorkQueueStats};
+#[cfg(not(feature = "doca2"))]
+pub use crate::context::{DOCAContext, Engine, EngineToContext};
+pub use crate::device::{
+    devices, open_device_with_ibdev_name, open_device_with_iface_name, open_device_with_pci,
+    DevContext, Device,
+};
+#[cfg(not(feature = "doca2"))]
+pub use crate::dma::{
+    Completion, DMACapabilities, DMAEngine, DMAJobBuilder, DOCADMAJob, DOCAEvent, DOCAWorkQueue,
+    DmaChannel, DmaDirection, DmaResult, DmaStatus, JobHandle,
+};
+pub use crate::memory::buffer::{
+    BufExtensions, BufferInventory, DOCABuffer, DOCABufferList, InventoryOptions, RawPointer,
+};
+pub use crate::memory::pool::{BufferPool, PooledBuffer};
+pub use crate::memory::region::{MemRegion, OwnedRegion};
+pub use crate::memory::registered_memory::DOCARegisteredMemory;
+pub use crate::memory::{DOCAMmap, ExportDescriptor, LocalMmap, RemoteMmap};
+pub use crate::{DOCAError, DOCAResult, DocaError};
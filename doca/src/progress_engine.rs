@@ -0,0 +1,262 @@
+//! Wrapper for the progress engine of DOCA 2.x, which replaces the work queue of DOCA 1.5.
+//!
+//! In DOCA 2.x, the jobs become tasks allocated by the contexts, and the contexts
+//! are connected to a [`ProgressEngine`]. Each call of [`ProgressEngine::progress`]
+//! invokes the completion callbacks of the finished tasks.
+//!
+//! - [`Task`] owns a task allocated by a context, and frees it when dropped.
+//!
+//! - [`RawTask`] converts the typed tasks of the libraries, e.g., `doca_dma_task_memcpy`,
+//! into the generic `doca_task`, so that [`completion_trampoline`] can be registered as
+//! their completion callbacks. The trampoline calls the closure given to
+//! [`ProgressEngine::submit`] with the task and its result.
+//!
+//! The module is only built with the `doca2` feature,
+//! which leaves out the modules built on the work queue of DOCA 1.5.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::progress_engine::{ProgressEngine, Task};
+//!
+//! # unsafe fn run(ctx: *mut ffi::doca_ctx, raw_task: *mut ffi::doca_dma_task_memcpy) {
+//! let mut pe = ProgressEngine::new().unwrap();
+//! // the memcpy tasks of the context are configured with `completion_trampoline`
+//! pe.connect_ctx(ctx).unwrap();
+//!
+//! let task = Task::from_raw(raw_task).unwrap();
+//! pe.submit(task, |_task, res| println!("task done: {:?}", res)).unwrap();
+//! while pe.inflight().unwrap() > 0 {
+//!     pe.progress();
+//! }
+//! # }
+//! ```
+//!
+
+use std::ptr::NonNull;
+
+use ffi::doca_error;
+
+use crate::callback::guard;
+use crate::error::check;
+use crate::{DOCAResult, DocaError};
+
+/// The typed tasks of the libraries, which can be converted into the generic `doca_task`
+pub trait RawTask {
+    /// Get the generic task of the typed task
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task;
+}
+
+impl RawTask for ffi::doca_task {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        task
+    }
+}
+
+impl RawTask for ffi::doca_dma_task_memcpy {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_dma_task_memcpy_as_task(task)
+    }
+}
+
+/// A task allocated by a context, freed when it is dropped
+pub struct Task {
+    inner: NonNull<ffi::doca_task>,
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        unsafe { ffi::doca_task_free(self.inner_ptr()) };
+    }
+}
+
+impl Task {
+    /// Take the ownership of a typed task allocated by a context.
+    ///
+    /// It is unsafe because the task must not be owned by another `Task` or freed elsewhere.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the task is null.
+    pub unsafe fn from_raw<T: RawTask>(task: *mut T) -> DOCAResult<Self> {
+        if task.is_null() {
+            return Err(DocaError::InvalidValue);
+        }
+        Ok(Self {
+            inner: NonNull::new(T::as_task(task)).ok_or(DocaError::InvalidValue)?,
+        })
+    }
+
+    /// Get the result of the finished task
+    pub fn status(&self) -> DOCAResult<()> {
+        check(unsafe { ffi::doca_task_get_status(self.inner_ptr()) })
+    }
+
+    /// Return the generic task
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_task {
+        self.inner.as_ptr()
+    }
+}
+
+type TaskCallback = Box<dyn FnOnce(Task, DOCAResult<()>)>;
+
+// A submitted task with its callback, carried by the user data of the task
+struct Pending {
+    task: Task,
+    on_done: TaskCallback,
+}
+
+/// The completion callback of the tasks submitted by [`ProgressEngine::submit`],
+/// to be registered as both the success and the error callback of a task type,
+/// e.g., with `doca_dma_task_memcpy_set_conf`.
+///
+/// It is unsafe because the tasks of the type must all be submitted by
+/// [`ProgressEngine::submit`], which sets their user data.
+pub unsafe extern "C" fn completion_trampoline<T: RawTask>(
+    task: *mut T,
+    task_user_data: ffi::doca_data,
+    _ctx_user_data: ffi::doca_data,
+) {
+    let pending = Box::from_raw(task_user_data.ptr as *mut Pending);
+    debug_assert_eq!(pending.task.inner_ptr(), T::as_task(task));
+
+    let Pending { task, on_done } = *pending;
+    let res = task.status();
+    guard("task completion", move || on_done(task, res));
+}
+
+/// The progress engine of DOCA 2.x, which drives the contexts connected to it (non-thread-safe).
+pub struct ProgressEngine {
+    inner: NonNull<ffi::doca_pe>,
+}
+
+impl Drop for ProgressEngine {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_pe_destroy(self.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("failed to destroy the progress engine: {:?}", ret);
+        }
+
+        dropped!("DOCA progress engine is dropped!");
+    }
+}
+
+impl ProgressEngine {
+    /// Create a progress engine
+    pub fn new() -> DOCAResult<Self> {
+        let mut pe: *mut ffi::doca_pe = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_pe_create(&mut pe as *mut _) };
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(Self {
+            inner: NonNull::new(pe).ok_or(DocaError::InvalidValue)?,
+        })
+    }
+
+    /// Connect the context, so its tasks are progressed by the engine.
+    /// It must be called before the context is started.
+    ///
+    /// It is unsafe because the context must outlive the engine,
+    /// which can't be tracked until the contexts of DOCA 2.x are wrapped.
+    pub unsafe fn connect_ctx(&mut self, ctx: *mut ffi::doca_ctx) -> DOCAResult<()> {
+        check(ffi::doca_pe_connect_ctx(self.inner_ptr(), ctx))
+    }
+
+    /// Submit the task, and call `on_done` with the task and its result when it finishes.
+    ///
+    /// The task is freed if the submission fails.
+    ///
+    /// The task type must be configured with [`completion_trampoline`] as its callbacks,
+    /// otherwise the closure is leaked.
+    pub fn submit<F>(&mut self, task: Task, on_done: F) -> DOCAResult<()>
+    where
+        F: FnOnce(Task, DOCAResult<()>) + 'static,
+    {
+        let task_ptr = unsafe { task.inner_ptr() };
+        let pending = Box::into_raw(Box::new(Pending {
+            task,
+            on_done: Box::new(on_done),
+        }));
+
+        let mut user_data = ffi::doca_data::default();
+        user_data.ptr = pending as *mut _;
+        unsafe { ffi::doca_task_set_user_data(task_ptr, user_data) };
+
+        let ret = unsafe { ffi::doca_task_submit(task_ptr) };
+        if ret != doca_error::DOCA_SUCCESS {
+            // the callback is never invoked for the rejected task
+            drop(unsafe { Box::from_raw(pending) });
+            return Err(ret.into());
+        }
+        Ok(())
+    }
+
+    /// Progress the connected contexts once, invoking the callbacks of the finished tasks.
+    ///
+    /// Return `true` if any task finished.
+    #[inline]
+    pub fn progress(&mut self) -> bool {
+        unsafe { ffi::doca_pe_progress(self.inner_ptr()) != 0 }
+    }
+
+    /// Get the number of the submitted tasks that haven't finished
+    pub fn inflight(&self) -> DOCAResult<usize> {
+        let mut num: usize = 0;
+        check(unsafe {
+            ffi::doca_pe_get_num_inflight_tasks(self.inner_ptr(), &mut num as *mut _)
+        })?;
+        Ok(num)
+    }
+
+    /// Return the inner pointer of the progress engine
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_pe {
+        self.inner.as_ptr()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        BufferInventory, DOCABuffer, DOCARegisteredMemory, DevContext, LocalMmap, RawPointer,
+    };
+
+    // Create a mmap on the device, and an inventory for the buffers of the tasks of a test
+    pub(crate) fn setup(device: &Arc<DevContext>) -> (LocalMmap, Arc<BufferInventory>) {
+        let doca_mmap = LocalMmap::new().unwrap();
+        doca_mmap.add_device(device).unwrap();
+        (doca_mmap, BufferInventory::new(16).unwrap())
+    }
+
+    // Register `buffer` in the mmap, and get a buffer over it holding its first `len` bytes
+    pub(crate) fn new_buf(
+        mmap: &LocalMmap,
+        inv: &Arc<BufferInventory>,
+        buffer: &[u8],
+        len: usize,
+    ) -> DOCABuffer {
+        let raw = unsafe { RawPointer::from_raw_ptr(buffer.as_ptr() as *mut u8, buffer.len()) };
+        let mut buf = DOCARegisteredMemory::new(mmap, raw)
+            .unwrap()
+            .to_buffer(inv)
+            .unwrap();
+        unsafe { buf.set_data(0, len).unwrap() };
+        buf
+    }
+
+    #[test]
+    fn test_progress_engine_create() {
+        use crate::progress_engine::ProgressEngine;
+        use crate::DocaError;
+
+        let mut pe = match ProgressEngine::new() {
+            // e.g., the mock has no progress engine
+            Err(DocaError::NotSupported) => return,
+            res => res.unwrap(),
+        };
+        assert_eq!(pe.inflight().unwrap(), 0);
+        assert!(!pe.progress());
+    }
+}
@@ -0,0 +1,411 @@
+//! Wrapper for the DOCA RDMA library of DOCA 2.x, which sends, receives, reads and writes
+//! [`DOCABuffer`]s over an RDMA connection.
+//!
+//! - [`RdmaEngine`] is the RDMA context of a device. It is connected to a
+//! [`ProgressEngine`] on creation, which drives its tasks.
+//! The connection is set up by exchanging the details returned by [`RdmaEngine::export`]
+//! with the peer, e.g., over [`config_exchange`](crate::config_exchange), and passing the
+//! details of the peer to [`RdmaEngine::connect`].
+//!
+//! - [`RdmaJob`] is a send, receive, read or write of the engine. It owns its buffers until
+//! it completes, and gives them back to the closure passed to [`RdmaJob::submit`].
+//!
+//! The remote buffers of the reads and writes are created from a mmap exported by the peer,
+//! see [`RemoteMmap::from_export`](crate::RemoteMmap::from_export).
+//!
+//! The module is only built with the `doca2` feature, since the library doesn't exist in DOCA 1.5.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::rdma::RdmaEngine;
+//! use doca::progress_engine::ProgressEngine;
+//!
+//! # fn run(local: doca::DOCABuffer, remote: doca::DOCABuffer, peer: &[u8]) {
+//! let device = doca::open_device_with_pci("03:00.0").unwrap();
+//! let mut pe = ProgressEngine::new().unwrap();
+//! let rdma = unsafe { RdmaEngine::new(&device, &mut pe, 16) }.unwrap();
+//!
+//! // send `details` to the peer, which sends back its own `peer`
+//! let details = rdma.export().unwrap();
+//! rdma.connect(peer).unwrap();
+//!
+//! let job = rdma.write(local, remote).unwrap();
+//! job.submit(&mut pe, |_local, _remote, res| res.unwrap()).unwrap();
+//!
+//! while pe.inflight().unwrap() > 0 {
+//!     pe.progress();
+//! }
+//! # }
+//! ```
+//!
+
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use ffi::doca_error;
+
+use crate::error::check;
+use crate::progress_engine::{completion_trampoline, ProgressEngine, RawTask, Task};
+use crate::{DOCABuffer, DOCAResult, DevContext, Device, DocaError};
+
+impl RawTask for ffi::doca_rdma_task_send {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_rdma_task_send_as_task(task)
+    }
+}
+
+impl RawTask for ffi::doca_rdma_task_receive {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_rdma_task_receive_as_task(task)
+    }
+}
+
+impl RawTask for ffi::doca_rdma_task_read {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_rdma_task_read_as_task(task)
+    }
+}
+
+impl RawTask for ffi::doca_rdma_task_write {
+    unsafe fn as_task(task: *mut Self) -> *mut ffi::doca_task {
+        ffi::doca_rdma_task_write_as_task(task)
+    }
+}
+
+/// The RDMA context of a device
+pub struct RdmaEngine {
+    inner: NonNull<ffi::doca_rdma>,
+    // the device should be closed after the context is destroyed
+    #[allow(dead_code)]
+    dev: Arc<DevContext>,
+}
+
+impl Drop for RdmaEngine {
+    fn drop(&mut self) {
+        // the jobs hold the engine, so none is in flight
+        let _ = unsafe { ffi::doca_ctx_stop(self.ctx_ptr()) };
+
+        let ret = unsafe { ffi::doca_rdma_destroy(self.inner_ptr()) };
+        if ret != doca_error::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy the RDMA engine: {:?}", ret);
+        }
+
+        dropped!("RDMA Engine is dropped!");
+    }
+}
+
+impl RdmaEngine {
+    /// Check whether the device supports the send and the receive tasks
+    pub fn is_supported(dev: &Device) -> bool {
+        let ret = unsafe { ffi::doca_rdma_cap_task_send_is_supported(dev.inner_ptr()) };
+        ret == doca_error::DOCA_SUCCESS
+    }
+
+    /// Create the context on the device, allowing the peer to read and write the local
+    /// memory, connect it to the progress engine, and start it with room for `num_tasks`
+    /// tasks of each type.
+    ///
+    /// It is unsafe because the engine must outlive the progress engine,
+    /// see [`ProgressEngine::connect_ctx`].
+    pub unsafe fn new(
+        dev: &Arc<DevContext>,
+        pe: &mut ProgressEngine,
+        num_tasks: u32,
+    ) -> DOCAResult<Arc<Self>> {
+        let mut rdma: *mut ffi::doca_rdma = std::ptr::null_mut();
+        let ret = ffi::doca_rdma_create(dev.inner_ptr(), &mut rdma as *mut _);
+
+        if ret != doca_error::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let res = Self {
+            inner: NonNull::new(rdma).ok_or(DocaError::InvalidValue)?,
+            dev: dev.clone(),
+        };
+
+        check(ffi::doca_rdma_set_permissions(
+            res.inner_ptr(),
+            ffi::DOCA_ACCESS_FLAG_LOCAL_READ_WRITE
+                | ffi::DOCA_ACCESS_FLAG_RDMA_READ
+                | ffi::DOCA_ACCESS_FLAG_RDMA_WRITE,
+        ))?;
+
+        let send_cb = Some(completion_trampoline::<ffi::doca_rdma_task_send> as _);
+        check(ffi::doca_rdma_task_send_set_conf(
+            res.inner_ptr(),
+            send_cb,
+            send_cb,
+            num_tasks,
+        ))?;
+        let receive_cb = Some(completion_trampoline::<ffi::doca_rdma_task_receive> as _);
+        check(ffi::doca_rdma_task_receive_set_conf(
+            res.inner_ptr(),
+            receive_cb,
+            receive_cb,
+            num_tasks,
+        ))?;
+        let read_cb = Some(completion_trampoline::<ffi::doca_rdma_task_read> as _);
+        check(ffi::doca_rdma_task_read_set_conf(
+            res.inner_ptr(),
+            read_cb,
+            read_cb,
+            num_tasks,
+        ))?;
+        let write_cb = Some(completion_trampoline::<ffi::doca_rdma_task_write> as _);
+        check(ffi::doca_rdma_task_write_set_conf(
+            res.inner_ptr(),
+            write_cb,
+            write_cb,
+            num_tasks,
+        ))?;
+
+        pe.connect_ctx(res.ctx_ptr())?;
+        check(ffi::doca_ctx_start(res.ctx_ptr()))?;
+        Ok(Arc::new(res))
+    }
+
+    /// Export the connection details of the engine, to be passed to
+    /// [`connect`](Self::connect) of the peer.
+    pub fn export(&self) -> DOCAResult<Vec<u8>> {
+        let mut details: *const std::os::raw::c_void = std::ptr::null();
+        let mut len: usize = 0;
+        check(unsafe {
+            ffi::doca_rdma_export(self.inner_ptr(), &mut details as *mut _, &mut len as *mut _)
+        })?;
+
+        if details.is_null() {
+            return Err(DocaError::InvalidValue);
+        }
+        // the details are owned by the engine, so they are copied out
+        Ok(unsafe { std::slice::from_raw_parts(details as *const u8, len) }.to_vec())
+    }
+
+    /// Connect the engine to the peer with its exported connection details.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `remote` is empty.
+    pub fn connect(&self, remote: &[u8]) -> DOCAResult<()> {
+        if remote.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+
+        check(unsafe {
+            ffi::doca_rdma_connect(self.inner_ptr(), remote.as_ptr() as *const _, remote.len())
+        })?;
+        debug_event!(rdma = ?self.inner, "rdma connected");
+        Ok(())
+    }
+
+    /// Create a job sending the data of `src` to a receive of the peer
+    pub fn send(self: &Arc<Self>, src: DOCABuffer) -> DOCAResult<RdmaJob> {
+        let mut task: *mut ffi::doca_rdma_task_send = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_rdma_task_send_allocate_init(
+                self.inner_ptr(),
+                src.inner_ptr(),
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        let task = unsafe { Task::from_raw(task)? };
+        Ok(self.new_job(RdmaOp::Send, task, Some(src), None))
+    }
+
+    /// Create a job receiving the data sent by the peer into `dst`
+    pub fn recv(self: &Arc<Self>, dst: DOCABuffer) -> DOCAResult<RdmaJob> {
+        let mut task: *mut ffi::doca_rdma_task_receive = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_rdma_task_receive_allocate_init(
+                self.inner_ptr(),
+                dst.inner_ptr(),
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        let task = unsafe { Task::from_raw(task)? };
+        Ok(self.new_job(RdmaOp::Recv, task, None, Some(dst)))
+    }
+
+    /// Create a job reading the data of the remote buffer `src` into the local `dst`
+    pub fn read(self: &Arc<Self>, src: DOCABuffer, dst: DOCABuffer) -> DOCAResult<RdmaJob> {
+        let mut task: *mut ffi::doca_rdma_task_read = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_rdma_task_read_allocate_init(
+                self.inner_ptr(),
+                src.inner_ptr(),
+                dst.inner_ptr(),
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        let task = unsafe { Task::from_raw(task)? };
+        Ok(self.new_job(RdmaOp::Read, task, Some(src), Some(dst)))
+    }
+
+    /// Create a job writing the data of the local `src` into the remote buffer `dst`
+    pub fn write(self: &Arc<Self>, src: DOCABuffer, dst: DOCABuffer) -> DOCAResult<RdmaJob> {
+        let mut task: *mut ffi::doca_rdma_task_write = std::ptr::null_mut();
+        check(unsafe {
+            ffi::doca_rdma_task_write_allocate_init(
+                self.inner_ptr(),
+                src.inner_ptr(),
+                dst.inner_ptr(),
+                ffi::doca_data::default(),
+                &mut task as *mut _,
+            )
+        })?;
+
+        let task = unsafe { Task::from_raw(task)? };
+        Ok(self.new_job(RdmaOp::Write, task, Some(src), Some(dst)))
+    }
+
+    /// Return the inner pointer of the RDMA context
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_rdma {
+        self.inner.as_ptr()
+    }
+
+    unsafe fn ctx_ptr(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_rdma_as_ctx(self.inner_ptr())
+    }
+
+    fn new_job(
+        self: &Arc<Self>,
+        op: RdmaOp,
+        task: Task,
+        src: Option<DOCABuffer>,
+        dst: Option<DOCABuffer>,
+    ) -> RdmaJob {
+        RdmaJob {
+            op,
+            task,
+            rdma: self.clone(),
+            src,
+            dst,
+        }
+    }
+}
+
+/// The operation of an [`RdmaJob`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RdmaOp {
+    /// Send the local data to a receive of the peer
+    Send,
+    /// Receive the data sent by the peer
+    Recv,
+    /// Read the remote data into the local buffer
+    Read,
+    /// Write the local data into the remote buffer
+    Write,
+}
+
+/// A job of an [`RdmaEngine`], see [`RdmaEngine::send`], [`RdmaEngine::recv`],
+/// [`RdmaEngine::read`] and [`RdmaEngine::write`]
+pub struct RdmaJob {
+    op: RdmaOp,
+    task: Task,
+    rdma: Arc<RdmaEngine>,
+    src: Option<DOCABuffer>,
+    dst: Option<DOCABuffer>,
+}
+
+impl RdmaJob {
+    /// Get the operation of the job
+    pub fn op(&self) -> RdmaOp {
+        self.op
+    }
+
+    /// Submit the job to the progress engine the engine is connected to,
+    /// and call `on_done` with the source and the destination buffers and the result
+    /// when it finishes. A send has no destination, and a receive has no source.
+    ///
+    /// The buffers are dropped with the job if the submission fails.
+    pub fn submit<F>(self, pe: &mut ProgressEngine, on_done: F) -> DOCAResult<()>
+    where
+        F: FnOnce(Option<DOCABuffer>, Option<DOCABuffer>, DOCAResult<()>) + 'static,
+    {
+        let RdmaJob {
+            op: _,
+            task,
+            rdma,
+            src,
+            dst,
+        } = self;
+
+        pe.submit(task, move |task, res| {
+            // free the task before the engine it is allocated from
+            drop(task);
+            drop(rdma);
+            on_done(src, dst, res);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::progress_engine::tests::{new_buf, setup};
+
+    #[test]
+    fn test_rdma_loopback() {
+        use super::*;
+        use crate::*;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !RdmaEngine::is_supported(&device) {
+            return;
+        }
+        let device = device.open().unwrap();
+
+        // connect two engines of the same device
+        let mut pe = ProgressEngine::new().unwrap();
+        let client = unsafe { RdmaEngine::new(&device, &mut pe, 4) }.unwrap();
+        let server = unsafe { RdmaEngine::new(&device, &mut pe, 4) }.unwrap();
+        let (client_details, server_details) = (client.export().unwrap(), server.export().unwrap());
+        client.connect(&server_details).unwrap();
+        server.connect(&client_details).unwrap();
+        assert_eq!(client.connect(&[]), Err(DocaError::InvalidValue));
+
+        let (doca_mmap, inv) = setup(&device);
+
+        // keep the finished buffers until the memory is checked
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let on_done = |results: &Rc<RefCell<Vec<_>>>| {
+            let results = results.clone();
+            move |src, dst, res| results.borrow_mut().push((src, dst, res))
+        };
+
+        let message = b"hello rdma".to_vec().into_boxed_slice();
+        let received = vec![0u8; 64].into_boxed_slice();
+        let written = vec![0u8; 64].into_boxed_slice();
+
+        let recv = server
+            .recv(new_buf(&doca_mmap, &inv, &received, 0))
+            .unwrap();
+        assert_eq!(recv.op(), RdmaOp::Recv);
+        recv.submit(&mut pe, on_done(&results)).unwrap();
+        let send = client
+            .send(new_buf(&doca_mmap, &inv, &message, message.len()))
+            .unwrap();
+        send.submit(&mut pe, on_done(&results)).unwrap();
+
+        let write = client
+            .write(
+                new_buf(&doca_mmap, &inv, &message, message.len()),
+                new_buf(&doca_mmap, &inv, &written, 0),
+            )
+            .unwrap();
+        write.submit(&mut pe, on_done(&results)).unwrap();
+
+        while pe.inflight().unwrap() > 0 {
+            pe.progress();
+        }
+        assert!(results.borrow().iter().all(|(_, _, res)| res.is_ok()));
+        assert_eq!(received[..message.len()], message[..]);
+        assert_eq!(written[..message.len()], message[..]);
+    }
+}
@@ -0,0 +1,410 @@
+//! Wrapper for DOCA RegEx. It provides
+//! the ability of offloading the regular expression matching to the DPU.
+//!
+//! It follows the same context/work queue model as the [`dma`](crate::dma) module:
+//! - [`DOCARegexJob`]: The search request of DOCA. It implements the trait [`ToBaseJob`],
+//! which makes it capable for being submitted to the work queue.
+//!
+//! - [`RegexEngine`]: The RegEx Engine of DOCA, loaded with the rules compiled
+//! for the hardware, e.g., by `rxpc`.
+//!
+//! The matches of a finished job are read with [`DOCARegexJob::take_matches`],
+//! which returns them to the matches memory pool of the work queue.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::regex::RegexEngine;
+//! use doca::context::DOCAContext;
+//! use doca::DOCAWorkQueue;
+//!
+//! let regex = RegexEngine::from_rules_file("rules.rof2.binary").unwrap();
+//! let device = doca::device::open_device_with_pci("03:00.0").unwrap();
+//!
+//! let ctx = DOCAContext::new(&regex, vec![device]).unwrap();
+//! let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+//! ```
+//!
+
+use std::path::Path;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use crate::context::work_queue::ToBaseJob;
+use crate::context::{DOCAContext, Engine, EngineToContext};
+use crate::{DOCABuffer, DOCAError, DOCAResult, DOCAWorkQueue, Device, DocaError};
+
+/// The default number of matches in the matches memory pool of each work queue
+pub const DEFAULT_MATCHES_POOL_SIZE: u32 = 1024;
+
+/// The max number of rule groups a search is restricted to
+pub const MAX_RULE_GROUPS: usize = 4;
+
+/// DOCA RegEx engine instance
+pub struct RegexEngine {
+    inner: NonNull<ffi::doca_regex>,
+}
+
+// The rules are loaded on creation, and then the engine is only converted into its context
+unsafe impl Sync for RegexEngine {}
+unsafe impl Send for RegexEngine {}
+
+impl Drop for RegexEngine {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_regex_destroy(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            drop_failed!("Failed to destory regex engine: {:?}", ret);
+        }
+
+        dropped!("RegEx Engine is dropped!");
+    }
+}
+
+/// Implementation `EngineToContext` Trait for RegEx Engine
+impl EngineToContext for RegexEngine {
+    unsafe fn to_ctx(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_regex_as_ctx(self.inner_ptr())
+    }
+}
+
+/// Implementation `Engine` Trait for RegEx Engine
+impl Engine for RegexEngine {
+    type Job = DOCARegexJob;
+
+    fn name() -> &'static str {
+        "regex"
+    }
+
+    fn new_job(ctx: &Arc<DOCAContext<Self>>) -> DOCARegexJob {
+        let mut res = DOCARegexJob {
+            inner: Default::default(),
+            ctx: ctx.clone(),
+            buff: None,
+            result: Box::default(),
+        };
+        res.inner.result = &mut *res.result as *mut _;
+        res.set_ctx().set_flags();
+        res.inner.base.type_ = ffi::DOCA_REGEX_JOB_SEARCH as i32;
+        res
+    }
+
+    fn is_supported(dev: &Device) -> bool {
+        let ret = unsafe {
+            ffi::doca_regex_job_get_supported(dev.inner_ptr(), ffi::DOCA_REGEX_JOB_SEARCH)
+        };
+        ret == DOCAError::DOCA_SUCCESS
+    }
+
+    fn max_buf_size(dev: &Device) -> DOCAResult<u64> {
+        let mut num: u64 = 0;
+        let ret =
+            unsafe { ffi::doca_regex_get_maximum_job_size(dev.inner_ptr(), &mut num as *mut _) };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(num)
+    }
+}
+
+impl RegexEngine {
+    /// Create a DOCA RegEx instance loaded with the compiled `rules`,
+    /// with a matches memory pool of [`DEFAULT_MATCHES_POOL_SIZE`].
+    pub fn new(rules: &[u8]) -> DOCAResult<Arc<Self>> {
+        Self::with_matches_pool(rules, DEFAULT_MATCHES_POOL_SIZE)
+    }
+
+    /// Create a DOCA RegEx instance loaded with the compiled rules of the file at `path`.
+    ///
+    /// Return [`DocaError::Io`] if the file can't be read.
+    pub fn from_rules_file<P: AsRef<Path>>(path: P) -> DOCAResult<Arc<Self>> {
+        let rules = std::fs::read(path)?;
+        Self::new(&rules)
+    }
+
+    /// Create a DOCA RegEx instance loaded with the compiled `rules`,
+    /// whose work queues can hold up to `nb_matches` matches that are not yet taken.
+    ///
+    /// The rules are copied by DOCA, so `rules` can be dropped afterwards.
+    ///
+    /// # Errors
+    ///
+    ///  - `DOCA_ERROR_INVALID_VALUE`: `rules` is empty or `nb_matches` is zero.
+    ///  - other errors returned by `doca_regex_set_hardware_compiled_rules`.
+    ///
+    pub fn with_matches_pool(rules: &[u8], nb_matches: u32) -> DOCAResult<Arc<Self>> {
+        if rules.is_empty() || nb_matches == 0 {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let mut regex: *mut ffi::doca_regex = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_regex_create(&mut regex as *mut _) };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        // the engine destroys the instance on the failures below
+        let res = Self {
+            inner: unsafe { NonNull::new_unchecked(regex) },
+        };
+
+        let ret = unsafe {
+            ffi::doca_regex_set_hardware_compiled_rules(
+                res.inner_ptr(),
+                rules.as_ptr() as _,
+                rules.len(),
+            )
+        };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let ret = unsafe {
+            ffi::doca_regex_set_workq_matches_memory_pool_size(res.inner_ptr(), nb_matches)
+        };
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(Arc::new(res))
+    }
+
+    /// Get the inner pointer of the DOCA RegEx instance.
+    ///
+    /// # Safety
+    ///
+    /// The instance is destroyed when the engine is dropped, so the pointer should not
+    /// outlive the engine, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_regex {
+        self.inner.as_ptr()
+    }
+}
+
+/// A match reported by a finished [`DOCARegexJob`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegexMatch {
+    /// The id of the matched rule
+    pub rule_id: u32,
+    /// The offset of the match in the searched data
+    pub offset: u32,
+    /// The length of the match
+    pub len: u32,
+}
+
+/// A DOCA RegEx search request
+pub struct DOCARegexJob {
+    pub(crate) inner: ffi::doca_regex_job_search,
+
+    #[allow(dead_code)]
+    ctx: Arc<DOCAContext<RegexEngine>>,
+
+    buff: Option<DOCABuffer>,
+
+    // the result written by the hardware, boxed so that its address is stable
+    result: Box<ffi::doca_regex_search_result>,
+}
+
+impl Drop for DOCARegexJob {
+    fn drop(&mut self) {
+        // give the matches not taken back to the pool of the work queue
+        self.release_matches();
+    }
+}
+
+/// Implementation of `ToBaseJob` Trait
+impl ToBaseJob for DOCARegexJob {
+    fn to_base(&self) -> &ffi::doca_job {
+        &self.inner.base
+    }
+
+    fn to_base_mut(&mut self) -> &mut ffi::doca_job {
+        &mut self.inner.base
+    }
+}
+
+impl DOCARegexJob {
+    /// Set the buffer whose data is searched
+    pub fn set_buffer(&mut self, buf: DOCABuffer) -> &mut Self {
+        debug_assert!(
+            unsafe { !buf.inner_ptr().is_null() },
+            "the searched buffer is null"
+        );
+        unsafe { self.inner.buffer = buf.inner_ptr() };
+        self.buff = Some(buf);
+        self
+    }
+
+    /// Take the searched buffer back from the request.
+    ///
+    /// The request can't be submitted again until a new buffer is set.
+    /// Return `None` if the buffer has already been taken.
+    pub fn take_buffer(&mut self) -> Option<DOCABuffer> {
+        self.inner.buffer = std::ptr::null();
+        self.buff.take()
+    }
+
+    /// Set the data pointer of the buffer,
+    /// i.e., the job searches `payload` bytes starting at `offset` of the region.
+    #[inline]
+    pub fn set_data(&mut self, offset: usize, payload: usize) -> &mut Self {
+        if let Some(f) = self.buff.as_mut() {
+            unsafe {
+                f.set_data(offset, payload)
+                    .expect("doca fail to set searched data!")
+            };
+        }
+        self
+    }
+
+    /// Restrict the search to the rules of the `groups`, or search all of the rules
+    /// if `groups` is empty.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if there are more than [`MAX_RULE_GROUPS`] groups,
+    /// or any of them is zero, which DOCA reserves for the unused slots.
+    pub fn set_rule_groups(&mut self, groups: &[u16]) -> DOCAResult<&mut Self> {
+        if groups.len() > MAX_RULE_GROUPS || groups.contains(&0) {
+            return Err(DocaError::InvalidValue);
+        }
+
+        self.inner.rule_group_ids = [0; MAX_RULE_GROUPS];
+        self.inner.rule_group_ids[..groups.len()].copy_from_slice(groups);
+        Ok(self)
+    }
+
+    /// Allow DOCA to batch the request with the following ones, which improves the
+    /// throughput at the cost of the latency.
+    pub fn set_allow_batching(&mut self, allow: bool) -> &mut Self {
+        self.inner.allow_batching = allow as u8;
+        self
+    }
+
+    /// Get the number of the matches detected by the finished job,
+    /// which can exceed the number of the reported ones if the matches pool is exhausted.
+    pub fn detected_matches(&self) -> u64 {
+        self.result.detected_matches
+    }
+
+    /// Check whether the finished job failed to search the data
+    pub fn search_failed(&self) -> bool {
+        self.result.status_flags & ffi::DOCA_REGEX_STATUS_SEARCH_FAILED as u8 != 0
+    }
+
+    /// Take the matches reported by the finished job, ordered as DOCA reports them.
+    ///
+    /// The matches are returned to the matches memory pool of the work queue,
+    /// so the next call returns an empty list until the request is submitted again.
+    pub fn take_matches(&mut self) -> Vec<RegexMatch> {
+        let mut res = Vec::with_capacity(self.result.num_matches as usize);
+        let mut cur = self.result.matches;
+        while let Some(m) = unsafe { cur.as_ref() } {
+            res.push(RegexMatch {
+                rule_id: m.rule_id,
+                offset: m.match_start,
+                len: m.length,
+            });
+            cur = m.next;
+        }
+
+        self.release_matches();
+        res
+    }
+
+    // Return the matches of the result to the memory pool
+    fn release_matches(&mut self) {
+        let mut cur = self.result.matches;
+        while !cur.is_null() {
+            let next = unsafe { (*cur).next };
+            unsafe { ffi::doca_regex_mempool_put_obj(self.result.matches_mempool, cur as _) };
+            cur = next;
+        }
+        self.result.matches = std::ptr::null_mut();
+        self.result.num_matches = 0;
+    }
+
+    /// Set request's based context
+    fn set_ctx(&mut self) -> &mut Self {
+        unsafe { self.inner.base.ctx = self.ctx.inner_ptr() };
+        self
+    }
+
+    /// Set request's flags
+    fn set_flags(&mut self) -> &mut Self {
+        self.inner.base.flags = ffi::DOCA_JOB_FLAGS_NONE as i32;
+        self
+    }
+}
+
+impl DOCAWorkQueue<RegexEngine> {
+    /// Create a job searching the data of the buffer with the rules of the engine
+    pub fn create_regex_job(&self, buf: DOCABuffer) -> DOCARegexJob {
+        let mut res = self.new_job();
+        res.set_buffer(buf);
+        res
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_regex_rejects_empty_rules() {
+        use super::*;
+
+        assert_eq!(RegexEngine::new(&[]).err(), Some(DocaError::InvalidValue));
+        assert_eq!(
+            RegexEngine::with_matches_pool(b"rules", 0).err(),
+            Some(DocaError::InvalidValue)
+        );
+        assert!(matches!(
+            RegexEngine::from_rules_file("/nonexistent/rules.rof2.binary"),
+            Err(DocaError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn test_regex_search() {
+        use super::*;
+        use crate::*;
+
+        // the rules compiled by `rxpc` for the test, e.g., from the rule `1, hello`
+        let rules = match std::env::var("DOCA_REGEX_TEST_RULES") {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+        let device = devices().unwrap().get(0).unwrap();
+        if !RegexEngine::is_supported(&device) {
+            return;
+        }
+        let device = device.open().unwrap();
+
+        let regex = RegexEngine::from_rules_file(rules).unwrap();
+        let ctx = DOCAContext::new(&regex, vec![device.clone()]).unwrap();
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+        let doca_mmap = LocalMmap::new().unwrap();
+        doca_mmap.add_device(&device).unwrap();
+        let inv = BufferInventory::new(1).unwrap();
+
+        let data = b"say hello to the world".to_vec().into_boxed_slice();
+        let buf = DOCARegisteredMemory::new(&doca_mmap, unsafe { RawPointer::from_box(&data) })
+            .unwrap()
+            .to_buffer(&inv)
+            .unwrap();
+
+        let mut job = workq.create_regex_job(buf);
+        job.set_data(0, data.len());
+        assert_eq!(
+            job.set_rule_groups(&[1, 2, 3, 4, 5]).err(),
+            Some(DocaError::InvalidValue)
+        );
+        workq.submit(&mut job).unwrap();
+        workq.spin_completion().unwrap();
+
+        assert!(!job.search_failed());
+        let matches = job.take_matches();
+        assert_eq!(matches.len() as u64, job.detected_matches());
+        assert!(matches
+            .iter()
+            .any(|m| m.rule_id == 1 && m.offset == 4 && m.len == 5));
+        assert!(job.take_matches().is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+//! Reusable versions of the DMA example workflows.
+//!
+//! - [`host_export_loop`] registers a host buffer, publishes its export descriptor
+//! and keeps the buffer registered until the application stops it (`dma_copy_host`).
+//!
+//! - [`dpu_copy_once`] receives the descriptor, and copies the remote buffer
+//! into the DPU memory with a single DMA job (`dma_copy_dpu`).
+//!
+//! - [`local_copy`] copies a buffer into another within the local memory (`local_copy_on_dpu`).
+//!
+//! - [`dma_bench`] measures the throughput and latency of the local DMA copies
+//! (`dma_bench` and `benches/dma.rs`).
+//!
+//! The descriptor exchange is abstracted by the trait [`Transport`], so the flows can run
+//! over sockets, RPC frameworks, etc. [`FileTransport`] exchanges it through files,
+//! as the examples do.
+//!
+//! With the `doca2` feature, only [`host_export_loop`] is built, since the other flows
+//! run the DMA jobs on the work queue of DOCA 1.5.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::samples::{dpu_copy_once, FileTransport};
+//!
+//! let mut transport = FileTransport::new("/tmp/export.txt", "/tmp/buffer.txt");
+//! let data = dpu_copy_once("03:00.0", &mut transport).unwrap();
+//! println!("{}", String::from_utf8_lossy(&data));
+//! ```
+//!
+
+use std::time::Duration;
+
+use crate::memory::region::MemRegion;
+use crate::{
+    load_config, open_device_with_pci, save_config, DOCAResult, ExportDescriptor, LoadedInfo,
+    LocalMmap, RawPointer,
+};
+
+// The DMA flows run on the work queue of DOCA 1.5
+#[cfg(not(feature = "doca2"))]
+use crate::context::DOCAContext;
+#[cfg(not(feature = "doca2"))]
+use crate::dma::DOCADMAJob;
+#[cfg(not(feature = "doca2"))]
+use crate::{
+    BufferInventory, DMAEngine, DOCARegisteredMemory, DOCAWorkQueue, DocaError, RemoteMmap,
+};
+#[cfg(not(feature = "doca2"))]
+use serde_derive::Serialize;
+#[cfg(not(feature = "doca2"))]
+use std::collections::HashMap;
+#[cfg(not(feature = "doca2"))]
+use std::time::Instant;
+
+/// The channel used to exchange the export descriptor between the host and the DPU.
+pub trait Transport {
+    /// Publish the export descriptor and the exported memory region to the remote side
+    fn publish(&mut self, export_desc: &ExportDescriptor, region: RawPointer) -> DOCAResult<()>;
+
+    /// Receive the export descriptor and the memory region published by the remote side
+    fn receive(&mut self) -> DOCAResult<LoadedInfo>;
+}
+
+/// A [`Transport`] through files, see [`save_config`] and [`load_config`].
+/// The user should copy the files to the remote side in between.
+pub struct FileTransport {
+    export_file: String,
+    buffer_file: String,
+}
+
+impl FileTransport {
+    /// Create a transport through the export descriptor file and buffer information file
+    pub fn new(export_file: &str, buffer_file: &str) -> Self {
+        Self {
+            export_file: export_file.to_string(),
+            buffer_file: buffer_file.to_string(),
+        }
+    }
+}
+
+impl Transport for FileTransport {
+    fn publish(&mut self, export_desc: &ExportDescriptor, region: RawPointer) -> DOCAResult<()> {
+        save_config(export_desc, region, &self.export_file, &self.buffer_file)
+    }
+
+    fn receive(&mut self) -> DOCAResult<LoadedInfo> {
+        Ok(load_config(&self.export_file, &self.buffer_file)?)
+    }
+}
+
+/// Register `src_buffer` on the device at `pci_addr`, publish its export descriptor
+/// through the `transport`, and keep it registered while `running()` returns `true`.
+///
+/// Return `DOCA_ERROR_NOT_PERMITTED` if the process runs on the DPU.
+pub fn host_export_loop<T, F>(
+    pci_addr: &str,
+    src_buffer: &mut [u8],
+    transport: &mut T,
+    mut running: F,
+) -> DOCAResult<()>
+where
+    T: Transport,
+    F: FnMut() -> bool,
+{
+    let src_raw = raw_pointer(src_buffer)?;
+
+    let device = open_device_with_pci(pci_addr)?;
+    let local_mmap = LocalMmap::new()?;
+    let dev_idx = local_mmap.add_device(&device)?;
+
+    // populate the buffer into the mmap and export it
+    local_mmap.populate(src_raw)?;
+    let export = local_mmap.export_from_host(dev_idx)?;
+    transport.publish(&export, src_raw)?;
+
+    while running() {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    Ok(())
+}
+
+/// Receive the export descriptor through the `transport`, and copy the whole remote
+/// buffer into the DPU memory with the device at `pci_addr`.
+/// It should run on the DPU, or `DOCA_ERROR_NOT_PERMITTED` is returned.
+///
+/// Return the copied data.
+#[cfg(not(feature = "doca2"))]
+pub fn dpu_copy_once<T: Transport>(pci_addr: &str, transport: &mut T) -> DOCAResult<Box<[u8]>> {
+    let remote_configs = transport.receive()?;
+    let remote_addr = remote_configs.remote_addr();
+    let mut dpu_buffer = vec![0u8; remote_addr.payload].into_boxed_slice();
+
+    let device = open_device_with_pci(pci_addr)?;
+    let dma = DMAEngine::new()?;
+    let ctx = DOCAContext::new(&dma, vec![device.clone()])?;
+    let mut workq = DOCAWorkQueue::new(1, &ctx)?;
+
+    let doca_mmap = LocalMmap::new()?;
+    doca_mmap.add_device(&device)?;
+    let remote_mmap = RemoteMmap::from_export_on_dpu(&remote_configs.export_desc, &device)?;
+
+    let inv = BufferInventory::new(1024)?;
+    let mut src_buf =
+        DOCARegisteredMemory::new_from_remote(&remote_mmap, remote_addr)?.to_buffer(&inv)?;
+    unsafe { src_buf.set_data(0, remote_addr.payload)? };
+    let dst_buf =
+        DOCARegisteredMemory::new(&doca_mmap, raw_pointer(&mut dpu_buffer)?)?.to_buffer(&inv)?;
+
+    let mut job = workq.create_dma_job(src_buf, dst_buf);
+    workq.submit(&mut job)?;
+    workq.spin_completion()?;
+
+    // release the buffers before giving the memory back
+    drop(job);
+    Ok(dpu_buffer)
+}
+
+/// Copy `src_buffer` into `dst_buffer` with the device at `pci_addr`.
+/// The buffers should have the same length.
+///
+/// With `verify`, the copied data is checked against the checksum of the source,
+/// see [`DOCADMAJob::verify`](crate::dma::DOCADMAJob::verify).
+#[cfg(not(feature = "doca2"))]
+pub fn local_copy(
+    pci_addr: &str,
+    src_buffer: &mut [u8],
+    dst_buffer: &mut [u8],
+    verify: bool,
+) -> DOCAResult<()> {
+    if src_buffer.len() != dst_buffer.len() {
+        return Err(DocaError::InvalidValue);
+    }
+    let length = src_buffer.len();
+
+    let device = open_device_with_pci(pci_addr)?;
+    let dma = DMAEngine::new()?;
+    let ctx = DOCAContext::new(&dma, vec![device.clone()])?;
+    let mut workq = DOCAWorkQueue::new(1, &ctx)?;
+
+    let doca_mmap = LocalMmap::new()?;
+    doca_mmap.add_device(&device)?;
+
+    let inv = BufferInventory::new(1024)?;
+    let mut src_buf =
+        DOCARegisteredMemory::new(&doca_mmap, raw_pointer(src_buffer)?)?.to_buffer(&inv)?;
+    unsafe { src_buf.set_data(0, length)? };
+    let dst_buf =
+        DOCARegisteredMemory::new(&doca_mmap, raw_pointer(dst_buffer)?)?.to_buffer(&inv)?;
+
+    let mut job = workq.create_dma_job(src_buf, dst_buf);
+    if verify {
+        unsafe { job.enable_verify()? };
+    }
+    workq.submit(&mut job)?;
+    workq.spin_completion()?;
+
+    if verify {
+        unsafe { job.verify()? };
+    }
+    Ok(())
+}
+
+/// The parameters of [`dma_bench`]
+#[cfg(not(feature = "doca2"))]
+#[derive(Clone, Debug)]
+pub struct BenchConfig {
+    /// The PCIe address of the device
+    pub pci_addr: String,
+    /// The bytes copied by each job
+    pub size: usize,
+    /// The depth of the work queue, at most 64 since each job has its own regions in the mmaps
+    pub depth: u32,
+    /// The number of the jobs submitted together by [`DOCAWorkQueue::submit_batch`]
+    pub batch: usize,
+    /// The number of the jobs to run
+    pub iterations: usize,
+}
+
+/// The result of [`dma_bench`], see [`to_json`](Self::to_json) for the machine-readable format
+#[cfg(not(feature = "doca2"))]
+#[derive(Clone, Debug, Serialize)]
+pub struct BenchReport {
+    /// The bytes copied by each job
+    pub size: usize,
+    /// The depth of the work queue
+    pub depth: u32,
+    /// The number of the jobs submitted together
+    pub batch: usize,
+    /// The number of the finished jobs
+    pub jobs: usize,
+    /// The time to finish all jobs, in microseconds
+    pub elapsed_us: f64,
+    /// The copy throughput, in Gbps
+    pub throughput_gbps: f64,
+    /// The average latency from the submission to the retrieval of a job, in microseconds
+    pub latency_avg_us: f64,
+    /// The median latency, in microseconds
+    pub latency_p50_us: f64,
+    /// The 99th percentile latency, in microseconds
+    pub latency_p99_us: f64,
+}
+
+#[cfg(not(feature = "doca2"))]
+impl BenchReport {
+    /// Serialize the report as a single line of JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Run `iterations` DMA copies of `size` bytes within the local memory of the device,
+/// keeping up to `depth` jobs in flight in groups of `batch`, and report the throughput
+/// and the latency.
+///
+/// Return `DOCA_ERROR_INVALID_VALUE` if a parameter is zero, `batch` exceeds `depth`,
+/// or `depth` exceeds 64.
+#[cfg(not(feature = "doca2"))]
+pub fn dma_bench(config: &BenchConfig) -> DOCAResult<BenchReport> {
+    if config.size == 0
+        || config.depth == 0
+        || config.depth > 64
+        || config.batch == 0
+        || config.batch > config.depth as usize
+        || config.iterations == 0
+    {
+        return Err(DocaError::InvalidValue);
+    }
+    let num_groups = config.depth as usize / config.batch;
+
+    let device = open_device_with_pci(&config.pci_addr)?;
+    let dma = DMAEngine::new()?;
+    let ctx = DOCAContext::new(&dma, vec![device.clone()])?;
+    let mut workq = DOCAWorkQueue::new(config.depth, &ctx)?;
+    workq.enable_timestamps(true);
+
+    // a mmap holds up to 64 regions, so the sources and destinations are in different ones
+    let src_mmap = LocalMmap::new()?;
+    src_mmap.add_device(&device)?;
+    let dst_mmap = LocalMmap::new()?;
+    dst_mmap.add_device(&device)?;
+    let inv = BufferInventory::new(2 * config.depth as usize)?;
+
+    let page = page_size::get();
+    let new_job = || -> DOCAResult<DOCADMAJob> {
+        let mut src = DOCARegisteredMemory::new_aligned(&src_mmap, config.size, page)?;
+        src.as_mut_slice().unwrap().fill(0x5a);
+        let dst = DOCARegisteredMemory::new_aligned(&dst_mmap, config.size, page)?;
+
+        let mut job = workq.create_dma_job(src.to_buffer(&inv)?, dst.to_buffer(&inv)?);
+        job.set_src_data(0, config.size);
+        Ok(job)
+    };
+    let mut groups = (0..num_groups)
+        .map(|_| (0..config.batch).map(|_| new_job()).collect())
+        .collect::<DOCAResult<Vec<Vec<_>>>>()?;
+
+    // a group is resubmitted after all of its jobs finish
+    let mut free: Vec<usize> = (0..num_groups).collect();
+    let mut remaining = vec![0; num_groups];
+    let mut group_of = HashMap::new();
+    let mut handles = Vec::with_capacity(config.batch);
+    let mut latencies = Vec::with_capacity(config.iterations);
+    let mut submitted = 0;
+
+    let start = Instant::now();
+    while latencies.len() < config.iterations {
+        while submitted < config.iterations {
+            let group = match free.pop() {
+                Some(group) => group,
+                None => break,
+            };
+            let n = config.batch.min(config.iterations - submitted);
+            let jobs = &mut groups[group][..n];
+            for job in jobs.iter_mut() {
+                // the copy is appended to the dst data, so rewind it
+                job.set_dst_offset(0);
+            }
+
+            handles.clear();
+            workq.submit_batch(jobs, &mut handles)?;
+            for handle in handles.iter() {
+                group_of.insert(*handle, group);
+            }
+            remaining[group] = n;
+            submitted += n;
+        }
+
+        for completion in workq.poll_completions(config.depth as usize)? {
+            completion.result()?;
+            latencies.push(completion.latency.unwrap_or_default());
+
+            let group = group_of
+                .remove(&completion.handle)
+                .ok_or(DocaError::Unexpected)?;
+            remaining[group] -= 1;
+            if remaining[group] == 0 {
+                free.push(group);
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+
+    latencies.sort_unstable();
+    let us = |d: Duration| d.as_secs_f64() * 1e6;
+    let percentile = |q: f64| us(latencies[((latencies.len() - 1) as f64 * q).round() as usize]);
+
+    Ok(BenchReport {
+        size: config.size,
+        depth: config.depth,
+        batch: config.batch,
+        jobs: latencies.len(),
+        elapsed_us: us(elapsed),
+        throughput_gbps: (config.size * latencies.len()) as f64 * 8.0 / elapsed.as_secs_f64() / 1e9,
+        latency_avg_us: us(latencies.iter().sum::<Duration>()) / latencies.len() as f64,
+        latency_p50_us: percentile(0.5),
+        latency_p99_us: percentile(0.99),
+    })
+}
+
+// The DOCA memory can't be empty
+fn raw_pointer(buffer: &mut [u8]) -> DOCAResult<RawPointer> {
+    Ok(MemRegion::new(buffer)?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_transport() {
+        let desc = ExportDescriptor::from(vec![1u8, 2, 3, 4]);
+        let mut region = vec![0u8; 64].into_boxed_slice();
+
+        let mut transport = FileTransport::new(
+            "/tmp/desc_transport_test.txt",
+            "/tmp/buffer_transport_test.txt",
+        );
+        transport
+            .publish(&desc, raw_pointer(&mut region).unwrap())
+            .unwrap();
+
+        let info = transport.receive().unwrap();
+        assert_eq!(info.export_desc.as_bytes(), desc.as_bytes());
+        assert_eq!(
+            info.remote_addr().inner.as_ptr() as *mut u8,
+            region.as_mut_ptr()
+        );
+        assert_eq!(info.remote_addr().payload, 64);
+    }
+
+    #[test]
+    fn test_empty_buffer_is_rejected() {
+        assert!(raw_pointer(&mut []).is_err());
+    }
+}
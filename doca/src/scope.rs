@@ -0,0 +1,177 @@
+//! RAII scope that owns the DOCA objects of an application.
+//!
+//! [`DocaScope`] creates the context, work queues, memory maps and the buffer inventory
+//! on top of an engine and a set of devices, and destroys them in the documented order
+//! when it is dropped:
+//! work queues -> context -> engine -> inventory -> memory maps -> devices.
+//!
+//! Buffers created through the scope are handed to the user, since they are usually
+//! moved into jobs. Each buffer holds its inventory and memory map,
+//! so they are always dropped before these objects.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::memory::region::MemRegion;
+//! use doca::scope::DocaScope;
+//! use doca::DMAEngine;
+//!
+//! let device = doca::device::open_device_with_pci("03:00.0").unwrap();
+//! let mut scope = DocaScope::new(&DMAEngine::new().unwrap(), vec![device], 1024).unwrap();
+//!
+//! let idx = scope.create_workq(1).unwrap();
+//! let mmap = scope.create_mmap().unwrap();
+//!
+//! let mut src_buffer = vec![0u8; 1024];
+//! let src = scope
+//!     .local_buffer(&mmap, MemRegion::new(&mut src_buffer).unwrap())
+//!     .unwrap();
+//! let workq = scope.workq(idx).unwrap();
+//! ```
+//!
+
+use std::sync::Arc;
+
+use crate::context::{DOCAContext, EngineToContext};
+use crate::{
+    BufferInventory, DOCABuffer, DOCAMmap, DOCARegisteredMemory, DOCAResult, DOCAWorkQueue,
+    DevContext, DocaError, ExportDescriptor, LocalMmap, RawPointer, RemoteMmap,
+};
+
+/// A container owning the DOCA objects created through it.
+pub struct DocaScope<T: EngineToContext> {
+    workqs: Vec<DOCAWorkQueue<T>>,
+    ctx: Option<Arc<DOCAContext<T>>>,
+    engine: Option<Arc<T>>,
+    inv: Option<Arc<BufferInventory>>,
+    mmaps: Vec<Arc<DOCAMmap>>,
+    devs: Vec<Arc<DevContext>>,
+}
+
+impl<T: EngineToContext> Drop for DocaScope<T> {
+    fn drop(&mut self) {
+        // tear down in the documented order, rather than relying on the field order
+        self.workqs.clear();
+        self.ctx = None;
+        self.engine = None;
+        self.inv = None;
+        self.mmaps.clear();
+        self.devs.clear();
+
+        dropped!("DOCA Scope is dropped!");
+    }
+}
+
+impl<T: EngineToContext> DocaScope<T> {
+    /// Create a scope which starts a context of the engine on the devices,
+    /// together with a buffer inventory of `num_bufs` elements.
+    pub fn new(engine: &Arc<T>, devs: Vec<Arc<DevContext>>, num_bufs: usize) -> DOCAResult<Self> {
+        let ctx = DOCAContext::new(engine, devs.clone())?;
+        let inv = BufferInventory::new(num_bufs)?;
+
+        Ok(Self {
+            workqs: Vec::new(),
+            ctx: Some(ctx),
+            engine: Some(engine.clone()),
+            inv: Some(inv),
+            mmaps: Vec::new(),
+            devs,
+        })
+    }
+
+    /// Get the context of the scope.
+    pub fn context(&self) -> &Arc<DOCAContext<T>> {
+        self.ctx.as_ref().unwrap()
+    }
+
+    /// Get the buffer inventory of the scope.
+    pub fn inventory(&self) -> &Arc<BufferInventory> {
+        self.inv.as_ref().unwrap()
+    }
+
+    /// Get the devices of the scope.
+    pub fn devices(&self) -> &[Arc<DevContext>] {
+        &self.devs
+    }
+
+    /// Create a work queue on the context, returning its index in the scope.
+    pub fn create_workq(&mut self, depth: u32) -> DOCAResult<usize> {
+        let workq = DOCAWorkQueue::new(depth, self.context())?;
+        self.workqs.push(workq);
+        Ok(self.workqs.len() - 1)
+    }
+
+    /// Get the work queue at the given `index`, or `None` if out of bounds.
+    pub fn workq(&mut self, index: usize) -> Option<&mut DOCAWorkQueue<T>> {
+        self.workqs.get_mut(index)
+    }
+
+    /// Create a local memory map registered on all devices of the scope.
+    pub fn create_mmap(&mut self) -> DOCAResult<LocalMmap> {
+        let mmap = LocalMmap::new()?;
+        for dev in &self.devs {
+            mmap.add_device(dev)?;
+        }
+
+        self.mmaps.push(Arc::clone(&mmap));
+        Ok(mmap)
+    }
+
+    /// Create a memory map from the export descriptor of the remote side,
+    /// bound to the device at `dev_index`.
+    pub fn import_mmap(
+        &mut self,
+        desc: &ExportDescriptor,
+        dev_index: usize,
+    ) -> DOCAResult<RemoteMmap> {
+        let dev = self.devs.get(dev_index).ok_or(DocaError::InvalidValue)?;
+
+        let mmap = RemoteMmap::from_export(desc, dev)?;
+        self.mmaps.push(Arc::clone(&mmap));
+        Ok(mmap)
+    }
+
+    /// Register a local memory region into the memory map and get a buffer pointing to it.
+    pub fn local_buffer(
+        &self,
+        mmap: &LocalMmap,
+        region: impl Into<RawPointer>,
+    ) -> DOCAResult<DOCABuffer> {
+        DOCARegisteredMemory::new(mmap, region)?.to_buffer(self.inventory())
+    }
+
+    /// Get a buffer pointing to a region of the remote memory map.
+    pub fn remote_buffer(&self, mmap: &RemoteMmap, region: RawPointer) -> DOCAResult<DOCABuffer> {
+        DOCARegisteredMemory::new_from_remote(mmap, region)?.to_buffer(self.inventory())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_scope_create_and_drop() {
+        use super::*;
+        use crate::DMAEngine;
+
+        let device = crate::device::devices()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .open()
+            .unwrap();
+
+        let mut scope = DocaScope::new(&DMAEngine::new().unwrap(), vec![device], 16).unwrap();
+        let idx = scope.create_workq(1).unwrap();
+        assert_eq!(scope.workq(idx).unwrap().depth(), 1);
+
+        let mmap = scope.create_mmap().unwrap();
+        let buffer = vec![0u8; 64].into_boxed_slice();
+        let buf = scope
+            .local_buffer(&mmap, unsafe { RawPointer::from_box(&buffer) })
+            .unwrap();
+
+        // the buffer keeps its inventory and mmap alive after the scope is gone
+        drop(scope);
+        drop(buf);
+    }
+}
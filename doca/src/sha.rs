@@ -0,0 +1,501 @@
+//! Wrapper for DOCA SHA. It provides
+//! the ability of offloading the SHA-1, SHA-256 and SHA-512 digests to the DPU.
+//!
+//! It follows the same context/work queue model as the [`dma`](crate::dma) module:
+//! - [`DOCAShaJob`]: The digest request of DOCA. It implements the trait [`ToBaseJob`],
+//! which makes it capable for being submitted to the work queue.
+//!
+//! - [`ShaEngine`]: The SHA Engine of DOCA. Users should create an instance of the engine
+//! and execute digest requests based on the engine.
+//!
+//! The digest is appended to the tail of the destination data, as the DMA jobs do.
+//!
+//! The hardware doesn't compute HMACs natively, so [`Hmac`] sequences the inner and
+//! the outer digests of an HMAC on the work queue, see [`DOCAWorkQueue::hmac`].
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::sha::{Hmac, ShaAlgo, ShaEngine};
+//! use doca::context::DOCAContext;
+//! use doca::{BufferInventory, DOCAWorkQueue, LocalMmap};
+//!
+//! let sha = ShaEngine::new().unwrap();
+//! let device = doca::device::open_device_with_pci("03:00.0").unwrap();
+//!
+//! let ctx = DOCAContext::new(&sha, vec![device.clone()]).unwrap();
+//! let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+//!
+//! let mmap = LocalMmap::new().unwrap();
+//! mmap.add_device(&device).unwrap();
+//! let inv = BufferInventory::new(16).unwrap();
+//! let mut hmac = Hmac::new(&mut workq, ShaAlgo::Sha256, b"key", &mmap, &inv, 4096).unwrap();
+//! let tag = workq.hmac(&mut hmac, b"message").unwrap();
+//! ```
+//!
+
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use crate::context::work_queue::ToBaseJob;
+use crate::context::{DOCAContext, Engine, EngineToContext};
+use crate::{
+    BufferInventory, DOCABuffer, DOCAError, DOCARegisteredMemory, DOCAResult, DOCAWorkQueue,
+    Device, DocaError, LocalMmap, RawPointer,
+};
+
+/// DOCA SHA engine instance
+pub struct ShaEngine {
+    inner: NonNull<ffi::doca_sha>,
+}
+
+// The engine is only converted into its context, which is thread-safe
+unsafe impl Sync for ShaEngine {}
+unsafe impl Send for ShaEngine {}
+
+impl Drop for ShaEngine {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_sha_destroy(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy SHA engine: {:?}", ret);
+        }
+
+        dropped!("SHA Engine is dropped!");
+    }
+}
+
+/// Implementation `EngineToContext` Trait for SHA Engine
+impl EngineToContext for ShaEngine {
+    unsafe fn to_ctx(&self) -> *mut ffi::doca_ctx {
+        ffi::doca_sha_as_ctx(self.inner_ptr())
+    }
+}
+
+/// Implementation `Engine` Trait for SHA Engine
+impl Engine for ShaEngine {
+    type Job = DOCAShaJob;
+
+    fn name() -> &'static str {
+        "sha"
+    }
+
+    /// The job computes the SHA-256 digest, see [`DOCAShaJob::set_algo`].
+    fn new_job(ctx: &Arc<DOCAContext<Self>>) -> DOCAShaJob {
+        let mut res = DOCAShaJob {
+            inner: Default::default(),
+            ctx: ctx.clone(),
+            src_buff: None,
+            dst_buff: None,
+        };
+        res.set_ctx().set_flags().set_algo(ShaAlgo::Sha256);
+        res
+    }
+
+    fn is_supported(dev: &Device) -> bool {
+        ShaAlgo::Sha256.is_supported(dev)
+    }
+
+    fn max_buf_size(dev: &Device) -> DOCAResult<u64> {
+        let mut num: u64 = 0;
+        let ret =
+            unsafe { ffi::doca_sha_get_max_src_buffer_size(dev.inner_ptr(), &mut num as *mut _) };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(num)
+    }
+}
+
+impl ShaEngine {
+    /// Create a DOCA SHA instance.
+    pub fn new() -> DOCAResult<Arc<Self>> {
+        let mut sha: *mut ffi::doca_sha = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_sha_create(&mut sha as *mut _) };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(Arc::new(Self {
+            inner: unsafe { NonNull::new_unchecked(sha) },
+        }))
+    }
+
+    /// Get the inner pointer of the DOCA SHA instance.
+    ///
+    /// # Safety
+    ///
+    /// The instance is destroyed when the engine is dropped, so the pointer should not
+    /// outlive the engine, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_sha {
+        self.inner.as_ptr()
+    }
+}
+
+/// The digest algorithm of a [`DOCAShaJob`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaAlgo {
+    /// SHA-1, with a digest of 20 bytes
+    Sha1,
+    /// SHA-256, with a digest of 32 bytes
+    Sha256,
+    /// SHA-512, with a digest of 64 bytes
+    Sha512,
+}
+
+impl ShaAlgo {
+    /// Check whether the device supports the algorithm
+    pub fn is_supported(self, dev: &Device) -> bool {
+        let ret = unsafe { ffi::doca_sha_job_get_supported(dev.inner_ptr(), self.job_type()) };
+        ret == DOCAError::DOCA_SUCCESS
+    }
+
+    /// Get the minimum size of the destination buffer of the algorithm on the device
+    pub fn min_dst_size(self, dev: &Device) -> DOCAResult<u32> {
+        let mut num: u32 = 0;
+        let ret = unsafe {
+            ffi::doca_sha_get_min_dst_buffer_size(
+                dev.inner_ptr(),
+                self.job_type(),
+                &mut num as *mut _,
+            )
+        };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+        Ok(num)
+    }
+
+    /// Get the length of the digest
+    pub fn digest_len(self) -> usize {
+        match self {
+            ShaAlgo::Sha1 => 20,
+            ShaAlgo::Sha256 => 32,
+            ShaAlgo::Sha512 => 64,
+        }
+    }
+
+    /// Get the length of the blocks the algorithm hashes, which is the length of the HMAC pads
+    pub fn block_len(self) -> usize {
+        match self {
+            ShaAlgo::Sha1 | ShaAlgo::Sha256 => 64,
+            ShaAlgo::Sha512 => 128,
+        }
+    }
+
+    fn job_type(self) -> ffi::doca_sha_job_type {
+        match self {
+            ShaAlgo::Sha1 => ffi::DOCA_SHA_JOB_SHA1,
+            ShaAlgo::Sha256 => ffi::DOCA_SHA_JOB_SHA256,
+            ShaAlgo::Sha512 => ffi::DOCA_SHA_JOB_SHA512,
+        }
+    }
+}
+
+/// A DOCA SHA request
+pub struct DOCAShaJob {
+    pub(crate) inner: ffi::doca_sha_job,
+
+    #[allow(dead_code)]
+    ctx: Arc<DOCAContext<ShaEngine>>,
+
+    src_buff: Option<DOCABuffer>,
+    dst_buff: Option<DOCABuffer>,
+}
+
+/// Implementation of `ToBaseJob` Trait
+impl ToBaseJob for DOCAShaJob {
+    fn to_base(&self) -> &ffi::doca_job {
+        &self.inner.base
+    }
+
+    fn to_base_mut(&mut self) -> &mut ffi::doca_job {
+        &mut self.inner.base
+    }
+}
+
+impl DOCAShaJob {
+    /// Set request's destination buffer, which should be at least
+    /// [`ShaAlgo::min_dst_size`] bytes
+    pub fn set_dst(&mut self, buf: DOCABuffer) -> &mut Self {
+        debug_assert!(
+            unsafe { !buf.inner_ptr().is_null() },
+            "the dst buffer is null"
+        );
+        unsafe { self.inner.resp_buf = buf.inner_ptr() };
+        self.dst_buff = Some(buf);
+        self
+    }
+
+    /// Set request's source buffer
+    pub fn set_src(&mut self, buf: DOCABuffer) -> &mut Self {
+        debug_assert!(
+            unsafe { !buf.inner_ptr().is_null() },
+            "the src buffer is null"
+        );
+        unsafe { self.inner.req_buf = buf.inner_ptr() };
+        self.src_buff = Some(buf);
+        self
+    }
+
+    /// Take the source buffer back from the request.
+    ///
+    /// The request can't be submitted again until a new source buffer is set.
+    /// Return `None` if the buffer has already been taken.
+    pub fn take_src(&mut self) -> Option<DOCABuffer> {
+        self.inner.req_buf = std::ptr::null_mut();
+        self.src_buff.take()
+    }
+
+    /// Take the destination buffer back from the request, e.g., to read the digest.
+    ///
+    /// The request can't be submitted again until a new destination buffer is set.
+    /// Return `None` if the buffer has already been taken.
+    pub fn take_dst(&mut self) -> Option<DOCABuffer> {
+        self.inner.resp_buf = std::ptr::null_mut();
+        self.dst_buff.take()
+    }
+
+    /// Set the data pointer of the src buffer,
+    /// i.e., the job hashes `payload` bytes starting at `offset` of the src region.
+    #[inline]
+    pub fn set_src_data(&mut self, offset: usize, payload: usize) -> &mut Self {
+        if let Some(f) = self.src_buff.as_mut() {
+            unsafe {
+                f.set_data(offset, payload)
+                    .expect("doca fail to set src data!")
+            };
+        }
+        self
+    }
+
+    /// Set the digest algorithm of the request
+    pub fn set_algo(&mut self, algo: ShaAlgo) -> &mut Self {
+        self.inner.base.type_ = algo.job_type() as i32;
+        self
+    }
+
+    /// Get the digest algorithm of the request
+    pub fn algo(&self) -> ShaAlgo {
+        match self.inner.base.type_ {
+            t if t == ffi::DOCA_SHA_JOB_SHA1 as i32 => ShaAlgo::Sha1,
+            t if t == ffi::DOCA_SHA_JOB_SHA512 as i32 => ShaAlgo::Sha512,
+            _ => ShaAlgo::Sha256,
+        }
+    }
+
+    /// Set request's based context
+    fn set_ctx(&mut self) -> &mut Self {
+        unsafe { self.inner.base.ctx = self.ctx.inner_ptr() };
+        self
+    }
+
+    /// Set request's flags
+    fn set_flags(&mut self) -> &mut Self {
+        self.inner.base.flags = ffi::DOCA_JOB_FLAGS_NONE as i32;
+        self.inner.flags = ffi::DOCA_SHA_JOB_FLAGS_NONE as u64;
+        self
+    }
+}
+
+impl DOCAWorkQueue<ShaEngine> {
+    /// Create a job hashing the source data into the destination buffer with `algo`
+    pub fn create_sha_job(
+        &self,
+        algo: ShaAlgo,
+        src_buf: DOCABuffer,
+        dst_buf: DOCABuffer,
+    ) -> DOCAShaJob {
+        let mut res = self.new_job();
+        res.set_algo(algo).set_src(src_buf).set_dst(dst_buf);
+        res
+    }
+
+    /// Compute the HMAC of `msg` with the key of `hmac`, by hashing the inner pad
+    /// followed by `msg`, and then the outer pad followed by the inner digest.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `msg` is longer than the maximum message length
+    /// of `hmac`, or the error of the failed digest.
+    pub fn hmac(&mut self, hmac: &mut Hmac, msg: &[u8]) -> DOCAResult<Vec<u8>> {
+        if msg.len() > hmac.max_msg_len {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let inner = hmac.digest(self, &[&hmac.ipad, msg])?;
+        hmac.digest(self, &[&hmac.opad, &inner])
+    }
+}
+
+/// An HMAC key, with the registered memory its digests are computed in,
+/// see [`DOCAWorkQueue::hmac`]
+pub struct Hmac {
+    algo: ShaAlgo,
+    // the key padded to a block, XORed with 0x36 and 0x5c
+    ipad: Vec<u8>,
+    opad: Vec<u8>,
+    max_msg_len: usize,
+
+    mmap: LocalMmap,
+    inv: Arc<BufferInventory>,
+    // the input and the output of a digest, owned by the mmap
+    src: RawPointer,
+    dst: RawPointer,
+}
+
+impl Hmac {
+    /// Prepare the HMAC of `key` with `algo` for the messages of up to `max_msg_len` bytes,
+    /// registering the memory of the digests into `mmap`, whose buffers are allocated from `inv`.
+    ///
+    /// A key longer than a block is hashed on `workq` first, as HMAC specifies.
+    pub fn new(
+        workq: &mut DOCAWorkQueue<ShaEngine>,
+        algo: ShaAlgo,
+        key: &[u8],
+        mmap: &LocalMmap,
+        inv: &Arc<BufferInventory>,
+        max_msg_len: usize,
+    ) -> DOCAResult<Self> {
+        let src_len = (algo.block_len() + max_msg_len.max(algo.digest_len())).max(key.len());
+        // large enough for the digest of any algorithm
+        let dst_len = ShaAlgo::Sha512.digest_len();
+
+        let mut res = Self {
+            algo,
+            ipad: Vec::new(),
+            opad: Vec::new(),
+            max_msg_len,
+            mmap: mmap.clone(),
+            inv: inv.clone(),
+            src: mmap.populate_owned(vec![0u8; src_len].into_boxed_slice())?,
+            dst: mmap.populate_owned(vec![0u8; dst_len].into_boxed_slice())?,
+        };
+
+        let (ipad, opad) = if key.len() > algo.block_len() {
+            pads(&res.digest(workq, &[key])?, algo.block_len())
+        } else {
+            pads(key, algo.block_len())
+        };
+        res.ipad = ipad;
+        res.opad = opad;
+        Ok(res)
+    }
+
+    /// Get the digest algorithm of the HMAC
+    pub fn algo(&self) -> ShaAlgo {
+        self.algo
+    }
+
+    /// Get the maximum length of the messages
+    pub fn max_msg_len(&self) -> usize {
+        self.max_msg_len
+    }
+
+    // Hash the concatenated `parts` on the work queue, and return the digest.
+    // The caller should hold the HMAC exclusively, since the memory of the digests is reused.
+    fn digest(&self, workq: &mut DOCAWorkQueue<ShaEngine>, parts: &[&[u8]]) -> DOCAResult<Vec<u8>> {
+        // no job is in flight on the memory, since the digests are computed one by one
+        let src = unsafe { self.src.as_mut_slice() };
+        let mut len = 0;
+        for part in parts {
+            src[len..len + part.len()].copy_from_slice(part);
+            len += part.len();
+        }
+
+        let mut src =
+            DOCARegisteredMemory::new_populated(&self.mmap, self.src).to_buffer(&self.inv)?;
+        unsafe { src.set_data(0, len)? };
+        let dst = DOCARegisteredMemory::new_populated(&self.mmap, self.dst).to_buffer(&self.inv)?;
+
+        let mut job = workq.create_sha_job(self.algo, src, dst);
+        workq.submit(&mut job)?;
+        workq.spin_completion()?;
+
+        let digest_len = self.algo.digest_len();
+        Ok(unsafe { self.dst.as_slice() }[..digest_len].to_vec())
+    }
+}
+
+// The inner and the outer pads of a key no longer than a block
+fn pads(key: &[u8], block_len: usize) -> (Vec<u8>, Vec<u8>) {
+    let mut ipad = vec![0x36u8; block_len];
+    let mut opad = vec![0x5cu8; block_len];
+    for (i, k) in key.iter().enumerate() {
+        ipad[i] ^= k;
+        opad[i] ^= k;
+    }
+    (ipad, opad)
+}
+
+mod tests {
+    #[test]
+    fn test_sha_engine_trait() {
+        use super::*;
+        use crate::*;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !ShaEngine::is_supported(&device) {
+            return;
+        }
+        assert!(ShaEngine::max_buf_size(&device).unwrap() > 0);
+
+        let ctx =
+            DOCAContext::new(&ShaEngine::new().unwrap(), vec![device.open().unwrap()]).unwrap();
+        let workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+        let job = workq.new_job();
+        assert_eq!(job.algo(), ShaAlgo::Sha256);
+    }
+
+    #[test]
+    fn test_hmac_pads() {
+        use super::*;
+
+        let (ipad, opad) = pads(b"Jefe", ShaAlgo::Sha256.block_len());
+        assert_eq!(ipad.len(), 64);
+        assert_eq!(
+            ipad[..4],
+            [b'J' ^ 0x36, b'e' ^ 0x36, b'f' ^ 0x36, b'e' ^ 0x36]
+        );
+        assert_eq!(opad[0], b'J' ^ 0x5c);
+        assert!(ipad[4..].iter().all(|b| *b == 0x36));
+        assert!(opad[4..].iter().all(|b| *b == 0x5c));
+    }
+
+    #[test]
+    fn test_hmac() {
+        use super::*;
+        use crate::*;
+
+        let device = devices().unwrap().get(0).unwrap();
+        if !ShaAlgo::Sha256.is_supported(&device) {
+            return;
+        }
+        let device = device.open().unwrap();
+
+        let ctx = DOCAContext::new(&ShaEngine::new().unwrap(), vec![device.clone()]).unwrap();
+        let mut workq = DOCAWorkQueue::new(1, &ctx).unwrap();
+
+        let mmap = LocalMmap::new().unwrap();
+        mmap.add_device(&device).unwrap();
+        let inv = BufferInventory::new(16).unwrap();
+
+        // the test case 2 of RFC 4231
+        let mut hmac = Hmac::new(&mut workq, ShaAlgo::Sha256, b"Jefe", &mmap, &inv, 64).unwrap();
+        let tag = workq
+            .hmac(&mut hmac, b"what do ya want for nothing?")
+            .unwrap();
+        assert_eq!(
+            tag,
+            [
+                0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+                0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+                0x64, 0xec, 0x38, 0x43,
+            ]
+        );
+        assert_eq!(
+            workq.hmac(&mut hmac, &[0u8; 65]).err(),
+            Some(DocaError::InvalidValue)
+        );
+    }
+}
@@ -0,0 +1,653 @@
+//! Wrapper for DOCA Telemetry, which reports the records of the types defined at runtime
+//! to the DOCA Telemetry Service (DTS).
+//!
+//! - [`RecordType`] describes the fields of a type, i.e., their names, descriptions and
+//! [`FieldType`]s, or arrays of them.
+//!
+//! - [`TelemetrySchema`] registers the types under its name, and is started once all of
+//! them are added. Each added type returns a [`RegisteredType`] creating its records.
+//!
+//! - [`TelemetrySource`] reports the [`Record`]s of the types of a started schema.
+//!
+//! A record is laid out as the packed C struct of its type, whose fields are set by name
+//! with [`FieldValue`]s of their types.
+//!
+//! # Examples
+//!
+//! ``` rust, no_run
+//! use doca::telemetry::{FieldType, FieldValue, RecordType, TelemetrySchema, TelemetrySource};
+//!
+//! let mut schema = TelemetrySchema::new("storage").unwrap();
+//! let io = schema
+//!     .add_type(
+//!         "io",
+//!         &RecordType::new()
+//!             .field("timestamp", "the time of the sample", FieldType::Timestamp)
+//!             .field("bytes", "the bytes written", FieldType::UInt64)
+//!             .array("volume", "the name of the volume", FieldType::Char, 16),
+//!     )
+//!     .unwrap();
+//! let schema = schema.start().unwrap();
+//! let mut source = TelemetrySource::new(&schema, "node-0", "storage").unwrap();
+//!
+//! let mut record = io.new_record();
+//! record.set("timestamp", FieldValue::Timestamp(doca::telemetry::timestamp().unwrap())).unwrap();
+//! record.set("bytes", FieldValue::UInt64(4096)).unwrap();
+//! record.set_str("volume", "vol0").unwrap();
+//! source.report(&record).unwrap();
+//! source.flush().unwrap();
+//! ```
+//!
+
+use std::ffi::CString;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use crate::error::check;
+use crate::{DOCAError, DOCAResult, DocaError};
+
+/// The scalar type of a field, i.e., the C type of the field in the packed record
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    /// `bool`
+    Bool,
+    /// `char`, e.g., the elements of a string
+    Char,
+    /// `int8_t`
+    Int8,
+    /// `int16_t`
+    Int16,
+    /// `int32_t`
+    Int32,
+    /// `int64_t`
+    Int64,
+    /// `uint8_t`
+    UInt8,
+    /// `uint16_t`
+    UInt16,
+    /// `uint32_t`
+    UInt32,
+    /// `uint64_t`
+    UInt64,
+    /// `float`
+    Float,
+    /// `double`
+    Double,
+    /// `doca_telemetry_timestamp_t`, see [`timestamp`]
+    Timestamp,
+}
+
+impl FieldType {
+    /// Get the size of a value of the type in the record
+    pub fn size(self) -> usize {
+        match self {
+            FieldType::Bool | FieldType::Char | FieldType::Int8 | FieldType::UInt8 => 1,
+            FieldType::Int16 | FieldType::UInt16 => 2,
+            FieldType::Int32 | FieldType::UInt32 | FieldType::Float => 4,
+            FieldType::Int64 | FieldType::UInt64 | FieldType::Double | FieldType::Timestamp => 8,
+        }
+    }
+
+    // The name of the type in the schema
+    fn type_name(self) -> &'static str {
+        match self {
+            FieldType::Bool => "bool",
+            FieldType::Char => "char",
+            FieldType::Int8 => "int8_t",
+            FieldType::Int16 => "int16_t",
+            FieldType::Int32 => "int32_t",
+            FieldType::Int64 => "int64_t",
+            FieldType::UInt8 => "uint8_t",
+            FieldType::UInt16 => "uint16_t",
+            FieldType::UInt32 => "uint32_t",
+            FieldType::UInt64 => "uint64_t",
+            FieldType::Float => "float",
+            FieldType::Double => "double",
+            FieldType::Timestamp => "doca_telemetry_timestamp_t",
+        }
+    }
+}
+
+/// A value of a field, whose variant names its [`FieldType`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldValue {
+    /// A `bool`
+    Bool(bool),
+    /// A `char`
+    Char(u8),
+    /// An `int8_t`
+    Int8(i8),
+    /// An `int16_t`
+    Int16(i16),
+    /// An `int32_t`
+    Int32(i32),
+    /// An `int64_t`
+    Int64(i64),
+    /// A `uint8_t`
+    UInt8(u8),
+    /// A `uint16_t`
+    UInt16(u16),
+    /// A `uint32_t`
+    UInt32(u32),
+    /// A `uint64_t`
+    UInt64(u64),
+    /// A `float`
+    Float(f32),
+    /// A `double`
+    Double(f64),
+    /// A `doca_telemetry_timestamp_t`
+    Timestamp(u64),
+}
+
+impl FieldValue {
+    /// Get the type of the value
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            FieldValue::Bool(_) => FieldType::Bool,
+            FieldValue::Char(_) => FieldType::Char,
+            FieldValue::Int8(_) => FieldType::Int8,
+            FieldValue::Int16(_) => FieldType::Int16,
+            FieldValue::Int32(_) => FieldType::Int32,
+            FieldValue::Int64(_) => FieldType::Int64,
+            FieldValue::UInt8(_) => FieldType::UInt8,
+            FieldValue::UInt16(_) => FieldType::UInt16,
+            FieldValue::UInt32(_) => FieldType::UInt32,
+            FieldValue::UInt64(_) => FieldType::UInt64,
+            FieldValue::Float(_) => FieldType::Float,
+            FieldValue::Double(_) => FieldType::Double,
+            FieldValue::Timestamp(_) => FieldType::Timestamp,
+        }
+    }
+
+    // Write the value into `out`, which is as long as the value
+    fn write(&self, out: &mut [u8]) {
+        match *self {
+            FieldValue::Bool(v) => out.copy_from_slice(&[v as u8]),
+            FieldValue::Char(v) | FieldValue::UInt8(v) => out.copy_from_slice(&[v]),
+            FieldValue::Int8(v) => out.copy_from_slice(&v.to_ne_bytes()),
+            FieldValue::Int16(v) => out.copy_from_slice(&v.to_ne_bytes()),
+            FieldValue::Int32(v) => out.copy_from_slice(&v.to_ne_bytes()),
+            FieldValue::Int64(v) => out.copy_from_slice(&v.to_ne_bytes()),
+            FieldValue::UInt16(v) => out.copy_from_slice(&v.to_ne_bytes()),
+            FieldValue::UInt32(v) => out.copy_from_slice(&v.to_ne_bytes()),
+            FieldValue::UInt64(v) | FieldValue::Timestamp(v) => {
+                out.copy_from_slice(&v.to_ne_bytes())
+            }
+            FieldValue::Float(v) => out.copy_from_slice(&v.to_ne_bytes()),
+            FieldValue::Double(v) => out.copy_from_slice(&v.to_ne_bytes()),
+        }
+    }
+}
+
+// A field of a type, with its offset in the packed record once the type is laid out
+#[derive(Clone, Debug)]
+struct Field {
+    name: String,
+    desc: String,
+    ty: FieldType,
+    len: u16,
+    offset: usize,
+}
+
+/// The fields of a type of records, added to a schema by [`TelemetrySchema::add_type`]
+#[derive(Clone, Debug, Default)]
+pub struct RecordType {
+    fields: Vec<Field>,
+}
+
+impl RecordType {
+    /// Create a type without fields
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field of a single value of `ty`
+    pub fn field(self, name: &str, desc: &str, ty: FieldType) -> Self {
+        self.array(name, desc, ty, 1)
+    }
+
+    /// Add a field of `len` values of `ty`, e.g., a string of [`FieldType::Char`]s
+    pub fn array(mut self, name: &str, desc: &str, ty: FieldType, len: u16) -> Self {
+        self.fields.push(Field {
+            name: name.to_string(),
+            desc: desc.to_string(),
+            ty,
+            len,
+            offset: 0,
+        });
+        self
+    }
+
+    /// Get the size of a record of the type
+    pub fn size(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|f| f.ty.size() * f.len as usize)
+            .sum()
+    }
+
+    // Lay out the fields in the packed record, checking that the type can be added
+    fn layout(&self, name: &str, index: u8) -> DOCAResult<Layout> {
+        if name.is_empty() || self.fields.is_empty() {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let mut fields = self.fields.clone();
+        let mut offset = 0;
+        for (i, f) in fields.iter_mut().enumerate() {
+            let duplicated = self.fields[..i].iter().any(|g| g.name == f.name);
+            if f.name.is_empty() || f.len == 0 || duplicated {
+                return Err(DocaError::InvalidValue);
+            }
+            f.offset = offset;
+            offset += f.ty.size() * f.len as usize;
+        }
+
+        Ok(Layout {
+            name: name.to_string(),
+            index,
+            fields,
+            size: offset,
+        })
+    }
+}
+
+// A type added to a schema
+#[derive(Debug)]
+struct Layout {
+    name: String,
+    index: ffi::doca_telemetry_type_index_t,
+    fields: Vec<Field>,
+    size: usize,
+}
+
+impl Layout {
+    fn field(&self, name: &str) -> DOCAResult<&Field> {
+        self.fields
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or(DocaError::NotFound)
+    }
+}
+
+/// A type added to a [`TelemetrySchema`], which creates the records of the type
+#[derive(Clone, Debug)]
+pub struct RegisteredType {
+    layout: Arc<Layout>,
+}
+
+impl RegisteredType {
+    /// Get the name of the type
+    pub fn name(&self) -> &str {
+        &self.layout.name
+    }
+
+    /// Create a record of the type, whose fields are all zeros
+    pub fn new_record(&self) -> Record {
+        Record {
+            data: vec![0u8; self.layout.size],
+            layout: self.layout.clone(),
+        }
+    }
+}
+
+/// A record of a [`RegisteredType`], laid out as the packed C struct of the type
+#[derive(Clone, Debug)]
+pub struct Record {
+    layout: Arc<Layout>,
+    data: Vec<u8>,
+}
+
+impl Record {
+    /// Set the field of a single value.
+    ///
+    /// Return `DOCA_ERROR_NOT_FOUND` if the type has no such field,
+    /// or `DOCA_ERROR_INVALID_VALUE` if the field is an array or of another type.
+    pub fn set(&mut self, field: &str, value: FieldValue) -> DOCAResult<()> {
+        let f = self.layout.field(field)?;
+        if f.len != 1 {
+            return Err(DocaError::InvalidValue);
+        }
+        self.set_array(field, &[value])
+    }
+
+    /// Set the leading values of an array field, leaving the others as they are.
+    ///
+    /// Return `DOCA_ERROR_NOT_FOUND` if the type has no such field,
+    /// or `DOCA_ERROR_INVALID_VALUE` if there are more values than the field holds,
+    /// or any of them is of another type.
+    pub fn set_array(&mut self, field: &str, values: &[FieldValue]) -> DOCAResult<()> {
+        let f = self.layout.field(field)?;
+        if values.len() > f.len as usize || values.iter().any(|v| v.field_type() != f.ty) {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let size = f.ty.size();
+        for (i, v) in values.iter().enumerate() {
+            let start = f.offset + i * size;
+            v.write(&mut self.data[start..start + size]);
+        }
+        Ok(())
+    }
+
+    /// Set a [`FieldType::Char`] array to `s`, padded with nul bytes.
+    ///
+    /// Return `DOCA_ERROR_NOT_FOUND` if the type has no such field,
+    /// or `DOCA_ERROR_INVALID_VALUE` if the field is not a `Char` array or `s` is longer.
+    pub fn set_str(&mut self, field: &str, s: &str) -> DOCAResult<()> {
+        let f = self.layout.field(field)?;
+        if f.ty != FieldType::Char || s.len() > f.len as usize {
+            return Err(DocaError::InvalidValue);
+        }
+
+        let out = &mut self.data[f.offset..f.offset + f.len as usize];
+        out.fill(0);
+        out[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(())
+    }
+
+    /// Get the packed bytes of the record
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// A telemetry schema, which the types of the records are added to before it starts
+pub struct TelemetrySchema {
+    inner: NonNull<ffi::doca_telemetry_schema>,
+    types: Vec<Arc<Layout>>,
+}
+
+// The schema is only reconfigured by its owner before it is started and shared
+unsafe impl Send for TelemetrySchema {}
+unsafe impl Sync for TelemetrySchema {}
+
+impl Drop for TelemetrySchema {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_telemetry_schema_destroy(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy telemetry schema: {:?}", ret);
+        }
+
+        dropped!("Telemetry Schema is dropped!");
+    }
+}
+
+impl TelemetrySchema {
+    /// Create a schema named `name`.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `name` is empty or contains a nul byte.
+    pub fn new(name: &str) -> DOCAResult<Self> {
+        let name = c_string(name)?;
+        let mut schema: *mut ffi::doca_telemetry_schema = std::ptr::null_mut();
+        let ret = unsafe { ffi::doca_telemetry_schema_init(name.as_ptr(), &mut schema as *mut _) };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        Ok(Self {
+            inner: NonNull::new(schema).ok_or(DocaError::InvalidValue)?,
+            types: Vec::new(),
+        })
+    }
+
+    /// Add the type `ty` named `name` to the schema.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `name` or a field name is empty or contains
+    /// a nul byte, `ty` has no fields, two fields of the same name or an empty array.
+    pub fn add_type(&mut self, name: &str, ty: &RecordType) -> DOCAResult<RegisteredType> {
+        // check the type before creating it in the SDK
+        ty.layout(name, 0)?;
+        let type_name = c_string(name)?;
+
+        let mut raw: *mut ffi::doca_telemetry_type = std::ptr::null_mut();
+        check(unsafe { ffi::doca_telemetry_type_create(&mut raw as *mut _) })?;
+        for f in ty.fields.iter() {
+            // the field is owned by the type once added
+            if let Err(e) = unsafe { add_field(raw, f) } {
+                unsafe { ffi::doca_telemetry_type_destroy(raw) };
+                return Err(e);
+            }
+        }
+
+        // the type is owned by the schema once added
+        let mut index: ffi::doca_telemetry_type_index_t = 0;
+        let ret = unsafe {
+            ffi::doca_telemetry_schema_add_type(
+                self.inner_ptr(),
+                type_name.as_ptr(),
+                raw,
+                &mut index as *mut _,
+            )
+        };
+        if ret != DOCAError::DOCA_SUCCESS {
+            unsafe { ffi::doca_telemetry_type_destroy(raw) };
+            return Err(ret.into());
+        }
+
+        let layout = Arc::new(ty.layout(name, index)?);
+        self.types.push(layout.clone());
+        Ok(RegisteredType { layout })
+    }
+
+    /// Start the schema, after which no type can be added and the sources can be created
+    pub fn start(self) -> DOCAResult<Arc<Self>> {
+        check(unsafe { ffi::doca_telemetry_schema_start(self.inner_ptr()) })?;
+        Ok(Arc::new(self))
+    }
+
+    /// Get the inner pointer of the DOCA Telemetry schema.
+    ///
+    /// # Safety
+    ///
+    /// The schema is destroyed when it is dropped, so the pointer should not
+    /// outlive the schema, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_telemetry_schema {
+        self.inner.as_ptr()
+    }
+}
+
+/// A telemetry source, which reports the records of the types of a started schema
+pub struct TelemetrySource {
+    inner: NonNull<ffi::doca_telemetry_source>,
+    schema: Arc<TelemetrySchema>,
+}
+
+impl Drop for TelemetrySource {
+    fn drop(&mut self) {
+        let ret = unsafe { ffi::doca_telemetry_source_destroy(self.inner_ptr()) };
+        if ret != DOCAError::DOCA_SUCCESS {
+            drop_failed!("Failed to destroy telemetry source: {:?}", ret);
+        }
+
+        dropped!("Telemetry Source is dropped!");
+    }
+}
+
+impl TelemetrySource {
+    /// Create and start a source of the started schema, identified by `id` and tagged `tag`.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if `id` or `tag` is empty or contains a nul byte.
+    pub fn new(schema: &Arc<TelemetrySchema>, id: &str, tag: &str) -> DOCAResult<Self> {
+        let (id, tag) = (c_string(id)?, c_string(tag)?);
+        let mut source: *mut ffi::doca_telemetry_source = std::ptr::null_mut();
+        let ret =
+            unsafe { ffi::doca_telemetry_source_create(schema.inner_ptr(), &mut source as *mut _) };
+
+        if ret != DOCAError::DOCA_SUCCESS {
+            return Err(ret.into());
+        }
+
+        let res = Self {
+            inner: NonNull::new(source).ok_or(DocaError::InvalidValue)?,
+            schema: schema.clone(),
+        };
+        unsafe {
+            check(ffi::doca_telemetry_source_set_id(
+                res.inner_ptr(),
+                id.as_ptr(),
+            ))?;
+            check(ffi::doca_telemetry_source_set_tag(
+                res.inner_ptr(),
+                tag.as_ptr(),
+            ))?;
+            check(ffi::doca_telemetry_source_start(res.inner_ptr()))?;
+        }
+        Ok(res)
+    }
+
+    /// Report the record, which is buffered until the buffer of the source is full
+    /// or [`flush`](Self::flush) is called.
+    ///
+    /// Return `DOCA_ERROR_INVALID_VALUE` if the type of the record is not of the schema.
+    pub fn report(&mut self, record: &Record) -> DOCAResult<()> {
+        if !self
+            .schema
+            .types
+            .iter()
+            .any(|t| Arc::ptr_eq(t, &record.layout))
+        {
+            return Err(DocaError::InvalidValue);
+        }
+
+        check(unsafe {
+            ffi::doca_telemetry_source_report(
+                self.inner_ptr(),
+                record.layout.index,
+                record.data.as_ptr() as *mut _,
+                1,
+            )
+        })
+    }
+
+    /// Send the buffered records to DTS
+    pub fn flush(&mut self) -> DOCAResult<()> {
+        check(unsafe { ffi::doca_telemetry_source_flush(self.inner_ptr()) })
+    }
+
+    /// Get the inner pointer of the DOCA Telemetry source.
+    ///
+    /// # Safety
+    ///
+    /// The source is destroyed when it is dropped, so the pointer should not
+    /// outlive the source, or be destroyed by the caller.
+    pub unsafe fn inner_ptr(&self) -> *mut ffi::doca_telemetry_source {
+        self.inner.as_ptr()
+    }
+}
+
+/// Get the current time as a [`FieldType::Timestamp`]
+pub fn timestamp() -> DOCAResult<u64> {
+    let mut ts: ffi::doca_telemetry_timestamp_t = 0;
+    check(unsafe { ffi::doca_telemetry_get_timestamp(&mut ts as *mut _) })?;
+    Ok(ts)
+}
+
+// Create the field `f` in the SDK and add it into the type `ty`
+unsafe fn add_field(ty: *mut ffi::doca_telemetry_type, f: &Field) -> DOCAResult<()> {
+    let (name, desc) = (
+        c_string(&f.name)?,
+        CString::new(f.desc.as_str()).map_err(|_e| DocaError::InvalidValue)?,
+    );
+    let type_name = c_string(f.ty.type_name())?;
+
+    let mut field: *mut ffi::doca_telemetry_field = std::ptr::null_mut();
+    check(ffi::doca_telemetry_field_create(&mut field as *mut _))?;
+
+    let res = check(ffi::doca_telemetry_field_set_name(field, name.as_ptr()))
+        .and_then(|_| {
+            check(ffi::doca_telemetry_field_set_description(
+                field,
+                desc.as_ptr(),
+            ))
+        })
+        .and_then(|_| {
+            check(ffi::doca_telemetry_field_set_type_name(
+                field,
+                type_name.as_ptr(),
+            ))
+        })
+        .and_then(|_| check(ffi::doca_telemetry_field_set_array_length(field, f.len)))
+        .and_then(|_| check(ffi::doca_telemetry_type_add_field(ty, field)));
+    if res.is_err() {
+        ffi::doca_telemetry_field_destroy(field);
+    }
+    res
+}
+
+// A name passed to the SDK, which can't be empty
+fn c_string(s: &str) -> DOCAResult<CString> {
+    if s.is_empty() {
+        return Err(DocaError::InvalidValue);
+    }
+    CString::new(s).map_err(|_e| DocaError::InvalidValue)
+}
+
+mod tests {
+    #[test]
+    fn test_record_layout() {
+        use super::*;
+
+        let ty = RecordType::new()
+            .field("flag", "a flag", FieldType::Bool)
+            .field("bytes", "a counter", FieldType::UInt64)
+            .array("name", "a string", FieldType::Char, 8);
+        assert_eq!(ty.size(), 1 + 8 + 8);
+
+        let io = RegisteredType {
+            layout: Arc::new(ty.layout("io", 3).unwrap()),
+        };
+        let mut record = io.new_record();
+        record.set("flag", FieldValue::Bool(true)).unwrap();
+        record.set("bytes", FieldValue::UInt64(4096)).unwrap();
+        record.set_str("name", "vol0").unwrap();
+
+        // packed in the order of the fields
+        let bytes = record.as_bytes();
+        assert_eq!(bytes[0], 1);
+        assert_eq!(bytes[1..9], 4096u64.to_ne_bytes());
+        assert_eq!(bytes[9..], *b"vol0\0\0\0\0");
+
+        assert_eq!(
+            record.set("bytes", FieldValue::UInt32(1)),
+            Err(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            record.set("name", FieldValue::Char(b'v')),
+            Err(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            record.set_str("name", "a long volume name"),
+            Err(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            record.set("missing", FieldValue::Bool(false)),
+            Err(DocaError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_invalid_record_type() {
+        use super::*;
+
+        let ty = RecordType::new().field("a", "", FieldType::Int32);
+        assert!(ty.layout("t", 0).is_ok());
+        assert_eq!(ty.layout("", 0).err(), Some(DocaError::InvalidValue));
+        assert_eq!(
+            RecordType::new().layout("t", 0).err(),
+            Some(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            ty.clone()
+                .field("a", "", FieldType::Int8)
+                .layout("t", 0)
+                .err(),
+            Some(DocaError::InvalidValue)
+        );
+        assert_eq!(
+            ty.array("b", "", FieldType::Char, 0).layout("t", 0).err(),
+            Some(DocaError::InvalidValue)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+// The diagnostics of the crate.
+//
+// With the `tracing` feature, the crate emits `tracing` events under the `doca` target,
+// covering the context start/stop, the mmap export, and the job submission and completion,
+// so services can observe DOCA through their own subscriber.
+// Without it, the events are compiled out, and only the dropped objects are printed in debug mode.
+// The failures to release the objects on drop are always reported.
+
+// Emit a debug event with the `tracing` feature
+macro_rules! debug_event {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "doca", $($arg)+);
+    };
+}
+
+// Emit a trace event with the `tracing` feature, for the frequent events,
+// e.g., on the data path
+macro_rules! trace_event {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(target: "doca", $($arg)+);
+    };
+}
+
+// Report a failure to release an object on drop, which leaks the object instead of
+// panicking, since a panic in drop aborts the process during unwinding.
+// It is an error event with the `tracing` feature, or printed to stderr without it
+macro_rules! drop_failed {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::error!(target: "doca::drop", $($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        eprintln!($($arg)+);
+    };
+}
+
+// Report that an object is dropped, as a trace event with the `tracing` feature,
+// or printed in debug mode without it
+macro_rules! dropped {
+    ($msg:literal) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(target: "doca::drop", $msg);
+        #[cfg(all(debug_assertions, not(feature = "tracing")))]
+        println!($msg);
+    };
+}